@@ -0,0 +1,148 @@
+//! End-to-end pipeline: grammar → blueprint → validation → export → Avian.
+//!
+//! Derives an L-System, interprets it into a `RobotBlueprint`, repairs its joint limits
+//! and anchors, writes it next to the working directory as a URDF xacro, and drops it
+//! onto the ground in an Avian scene with `backends::avian::spawn_blueprint`. Copy this file as a starting point for your own
+//! engine glue.
+//!
+//! ```text
+//! cargo run --example avian --features avian
+//! cargo run --example avian --features avian -- "B(0.4,0.3,0.3)A" "A -> J[+B(0.5,0.1,0.1)]A"
+//! ```
+//!
+//! The first argument is the axiom and every further argument a production; without
+//! arguments a small branching walker is grown.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use symbios::System;
+use symbios_robot::backends::avian::spawn_blueprint;
+use symbios_robot::bevy_spawn::shape_mesh;
+use symbios_robot::{LimitValidation, RobotBlueprint, RobotConfig, RobotInterpreter};
+
+const DEFAULT_AXIOM: &str = "B(0.6,0.2,0.3)A";
+const DEFAULT_RULES: &[&str] =
+    &["A -> [+(90)&(60)J B(0.4,0.08,0.08)A][-(90)&(60)J B(0.4,0.08,0.08)A]"];
+const DERIVATION_STEPS: usize = 2;
+
+/// Distance the lowest point of the robot is dropped from.
+const DROP_HEIGHT: f32 = 0.2;
+
+fn main() {
+    let blueprint = build_from_args();
+    App::new()
+        .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+        .insert_resource(Robot(blueprint))
+        .add_systems(Startup, (spawn_scene, spawn_robot))
+        .run();
+}
+
+#[derive(Resource)]
+struct Robot(RobotBlueprint);
+
+/// Steps 1-4: derive the grammar, interpret it, validate it and export it.
+fn build_from_args() -> RobotBlueprint {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (axiom, rules) = match args.split_first() {
+        Some((axiom, rules)) => (axiom.clone(), rules.to_vec()),
+        None => (
+            DEFAULT_AXIOM.to_owned(),
+            DEFAULT_RULES.iter().map(|r| r.to_string()).collect(),
+        ),
+    };
+
+    // 1. Derive the genotype.
+    let mut system = System::new();
+    for rule in &rules {
+        system.add_rule(rule).expect("invalid production");
+    }
+    system.set_axiom(&axiom).expect("invalid axiom");
+    system.derive(DERIVATION_STEPS).expect("derivation failed");
+
+    // 2. Interpret it into a phenotype.
+    let config = RobotConfig::default();
+    let mut interpreter = RobotInterpreter::new(config.clone());
+    interpreter.populate_standard_symbols(&system.interner);
+    let mut blueprint = interpreter.build_blueprint(&system.state);
+    println!(
+        "{} modules, {} joints",
+        blueprint.modules.len(),
+        blueprint.joints.len()
+    );
+    for warning in &blueprint.metadata.warnings {
+        println!("warning: {warning}");
+    }
+
+    // 3. Validate, repairing what a simulator would reject.
+    let fixed = blueprint
+        .validate_limits(LimitValidation::Repair)
+        .expect("repair never fails");
+    for issue in fixed {
+        println!("repaired limits: {issue:?}");
+    }
+    for issue in blueprint.snap_anchors(1e-3) {
+        println!("snapped anchor: {issue:?}");
+    }
+
+    // 4. Export. `xacro robot.urdf.xacro > robot.urdf` expands it to plain URDF.
+    std::fs::write("robot.urdf.xacro", blueprint.to_xacro("robot", &config))
+        .expect("failed to write robot.urdf.xacro");
+    println!("wrote robot.urdf.xacro");
+    blueprint
+}
+
+fn spawn_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(3.0, 2.5, 3.0).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+    ));
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 2.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(20.0, 0.1, 20.0),
+        Mesh3d(meshes.add(Cuboid::new(20.0, 0.1, 20.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+        Transform::from_xyz(0.0, -0.05, 0.0),
+    ));
+}
+
+/// Step 5: one rigid body per module and one Avian joint per blueprint joint, built by
+/// the crate's Avian backend, plus a mesh on every body.
+fn spawn_robot(
+    mut commands: Commands,
+    robot: Res<Robot>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Lift the robot so that its lowest point starts just above the ground.
+    let mut blueprint = robot.0.clone();
+    let lowest = blueprint
+        .to_trimesh()
+        .vertices
+        .iter()
+        .map(|v| v.y)
+        .fold(f32::INFINITY, f32::min);
+    if lowest.is_finite() {
+        for module in blueprint.modules.values_mut() {
+            module.transform.0.y += DROP_HEIGHT - lowest;
+        }
+    }
+
+    let material = materials.add(Color::srgb(0.8, 0.6, 0.3));
+    for (id, body) in spawn_blueprint(&mut commands, &blueprint) {
+        let mesh = meshes.add(shape_mesh(&blueprint.modules[&id].shape));
+        commands
+            .entity(body)
+            .insert((Mesh3d(mesh), MeshMaterial3d(material.clone())));
+    }
+}
@@ -0,0 +1,118 @@
+//! Engine adapters that build a [`RobotBlueprint`] in a physics engine.
+//!
+//! The anchor and axis conventions these adapters translate from are defined by the
+//! interpreter, so they are kept next to it rather than in every downstream project.
+//! [`RobotBlueprint::instantiate`] walks a blueprint once and hands every body, joint and
+//! sensor to a [`PhysicsBackend`], with the joint frames already worked out; an adapter
+//! only maps them onto its engine's types. The bundled adapters each sit behind their own
+//! feature.
+//!
+//! Every joint gets a [`JointFrame`] in both of its bodies, with [`JOINT_FRAME_AXIS`]
+//! along the joint axis. The blueprint keeps the axis in both body frames
+//! ([`axis`](JointDefinition::axis) and [`child_axis`](JointDefinition::child_axis)), and
+//! the two frames are built to coincide in world space at the rest pose: every joint then
+//! reads zero (or its [`rest_displacement`](JointDefinition::rest_displacement)) before
+//! the simulation starts, and the limits keep their meaning.
+
+#[cfg(feature = "avian")]
+pub mod avian;
+#[cfg(feature = "rapier")]
+pub mod rapier;
+
+use crate::blueprint::{
+    JointDefinition, ModuleId, RobotBlueprint, RobotModule, SensorAttachment, SensorMount,
+};
+use glam::{Quat, Vec3};
+use std::collections::HashMap;
+
+/// The axis of a joint frame that joints hinge, slide and twist about.
+pub const JOINT_FRAME_AXIS: Vec3 = Vec3::X;
+
+/// A joint's frame in the local space of one of its bodies.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointFrame {
+    /// The joint's anchor.
+    pub anchor: Vec3,
+    /// The frame's orientation; its [`JOINT_FRAME_AXIS`] is the joint axis.
+    pub basis: Quat,
+}
+
+/// An engine that [`RobotBlueprint::instantiate`] can build a robot in.
+pub trait PhysicsBackend {
+    /// The engine's handle to a rigid body.
+    type Body: Copy;
+
+    /// Creates the body of module `id` at its rest pose. `welded` modules are attached to
+    /// the world and should not move.
+    fn spawn_module(&mut self, id: ModuleId, module: &RobotModule, welded: bool) -> Self::Body;
+
+    /// Connects two bodies created by [`spawn_module`](Self::spawn_module) with `joint`,
+    /// given its frame in each.
+    fn spawn_joint(
+        &mut self,
+        joint: &JointDefinition,
+        parent: (Self::Body, JointFrame),
+        child: (Self::Body, JointFrame),
+    );
+
+    /// Attaches a sensor to `body` at `pose` (position and rotation in the body's local
+    /// space). Ignores sensors by default.
+    fn mount_sensor(&mut self, body: Self::Body, sensor: &SensorMount, pose: (Vec3, Quat)) {
+        let _ = (body, sensor, pose);
+    }
+}
+
+impl RobotBlueprint {
+    /// Builds the robot in `backend` and returns the body of every module.
+    ///
+    /// Modules are spawned in [topological order](Self::modules_topological), each
+    /// followed by its sensors; joints follow once every body exists, parents' joints
+    /// first. [`SensorAttachment::Joint`] sensors are posed relative to the module's
+    /// parent joint anchor. Joints whose modules are missing are skipped.
+    pub fn instantiate<B: PhysicsBackend>(&self, backend: &mut B) -> HashMap<ModuleId, B::Body> {
+        let mut bodies = HashMap::new();
+        for (id, module) in self.modules_topological() {
+            let body = backend.spawn_module(id, module, self.is_welded(id));
+            let joint_anchor = self.parent_joint(id).map_or(Vec3::ZERO, |j| j.anchor_child);
+            for sensor in &module.sensors {
+                let origin = match sensor.attachment {
+                    SensorAttachment::Module => Vec3::ZERO,
+                    SensorAttachment::Joint => joint_anchor,
+                };
+                let pose = (origin + sensor.local_position, sensor.local_rotation);
+                backend.mount_sensor(body, sensor, pose);
+            }
+            bodies.insert(id, body);
+        }
+
+        for joint in self.joints_topological() {
+            let (Some(&parent), Some(&child), Some((basis1, basis2))) = (
+                bodies.get(&joint.parent_id),
+                bodies.get(&joint.child_id),
+                joint_bases(self, joint),
+            ) else {
+                continue;
+            };
+            let frame = |anchor, basis| JointFrame { anchor, basis };
+            backend.spawn_joint(
+                joint,
+                (parent, frame(joint.anchor_parent, basis1)),
+                (child, frame(joint.anchor_child, basis2)),
+            );
+        }
+        bodies
+    }
+}
+
+/// Orientations of `joint`'s frame in its parent's and its child's local space, or `None`
+/// if either module is missing.
+pub(crate) fn joint_bases(
+    blueprint: &RobotBlueprint,
+    joint: &JointDefinition,
+) -> Option<(Quat, Quat)> {
+    let parent = blueprint.modules.get(&joint.parent_id)?;
+    let child = blueprint.modules.get(&joint.child_id)?;
+    let parent_basis = Quat::from_rotation_arc(JOINT_FRAME_AXIS, joint.axis.normalize_or(Vec3::X));
+    let child_basis = child.transform.1.inverse() * parent.transform.1 * parent_basis;
+    Some((parent_basis, child_basis))
+}
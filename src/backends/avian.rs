@@ -0,0 +1,136 @@
+//! Avian3D adapter (feature `avian`).
+//!
+//! [`spawn_blueprint`] creates one dynamic rigid body per module and one Avian joint per
+//! blueprint joint, through the [`AvianBackend`] adapter. Bodies get colliders, masses and
+//! centres of mass but no visuals; add a `Mesh3d` from
+//! [`shape_mesh`](crate::bevy_spawn::shape_mesh) to see them. Joint frames follow the
+//! conventions described in [`backends`](crate::backends).
+
+use super::{JOINT_FRAME_AXIS, JointFrame, PhysicsBackend};
+use crate::blueprint::{
+    JointDefinition, JointType, ModuleId, RobotBlueprint, RobotModule, ShapePrimitive,
+};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// An Avian collider matching `shape`.
+pub fn shape_collider(shape: &ShapePrimitive) -> Collider {
+    match *shape {
+        ShapePrimitive::Box(h) => Collider::cuboid(h.x * 2.0, h.y * 2.0, h.z * 2.0),
+        ShapePrimitive::Cylinder { radius, height } => Collider::cylinder(radius, height),
+        ShapePrimitive::Sphere(radius) => Collider::sphere(radius),
+        ShapePrimitive::Capsule { radius, height } => Collider::capsule(radius, height),
+    }
+}
+
+/// Spawns the robot at its rest pose and returns the body entity of every module.
+///
+/// Modules welded to the world become static bodies. Hinges become [`RevoluteJoint`]s,
+/// prismatic joints [`PrismaticJoint`]s, ball joints [`SphericalJoint`]s with their swing
+/// cone and twist limits, and fixed joints [`FixedJoint`]s. Bodies connected by a joint do
+/// not collide with each other; other
+/// [`collision_exclusions`](RobotBlueprint::collision_exclusions) are left to the caller.
+/// No motors are added: drive the joints with the effort limits from
+/// [`control_interface`](RobotBlueprint::control_interface).
+pub fn spawn_blueprint(
+    commands: &mut Commands,
+    blueprint: &RobotBlueprint,
+) -> HashMap<ModuleId, Entity> {
+    blueprint.instantiate(&mut AvianBackend(commands))
+}
+
+/// The [`PhysicsBackend`] behind [`spawn_blueprint`], spawning through `Commands`.
+/// Sensors are ignored.
+pub struct AvianBackend<'a, 'w, 's>(pub &'a mut Commands<'w, 's>);
+
+impl PhysicsBackend for AvianBackend<'_, '_, '_> {
+    type Body = Entity;
+
+    fn spawn_module(&mut self, _id: ModuleId, module: &RobotModule, welded: bool) -> Entity {
+        let (pos, rot) = module.transform;
+        let body = if welded {
+            RigidBody::Static
+        } else {
+            RigidBody::Dynamic
+        };
+        self.0
+            .spawn((
+                body,
+                shape_collider(&module.shape),
+                Mass(module.mass),
+                CenterOfMass(module.center_of_mass),
+                Transform::from_translation(pos).with_rotation(rot),
+            ))
+            .id()
+    }
+
+    fn spawn_joint(
+        &mut self,
+        joint: &JointDefinition,
+        parent: (Entity, JointFrame),
+        child: (Entity, JointFrame),
+    ) {
+        spawn_joint(self.0, joint, parent, child);
+    }
+}
+
+fn spawn_joint(
+    commands: &mut Commands,
+    joint: &JointDefinition,
+    (parent, frame1): (Entity, JointFrame),
+    (child, frame2): (Entity, JointFrame),
+) {
+    let limits = joint.limits;
+    let mut entity = match joint.joint_type {
+        JointType::Fixed => commands.spawn(
+            FixedJoint::new(parent, child)
+                .with_local_anchor1(frame1.anchor)
+                .with_local_anchor2(frame2.anchor)
+                .with_local_basis1(frame1.basis)
+                .with_local_basis2(frame2.basis),
+        ),
+        JointType::Hinge => {
+            let mut hinge = RevoluteJoint::new(parent, child)
+                .with_local_anchor1(frame1.anchor)
+                .with_local_anchor2(frame2.anchor)
+                .with_local_basis1(frame1.basis)
+                .with_local_basis2(frame2.basis)
+                .with_hinge_axis(JOINT_FRAME_AXIS);
+            if let Some(l) = limits {
+                hinge = hinge.with_angle_limits(l.min, l.max);
+            }
+            commands.spawn(hinge)
+        }
+        JointType::Prismatic => {
+            let mut slider = PrismaticJoint::new(parent, child)
+                .with_local_anchor1(frame1.anchor)
+                .with_local_anchor2(frame2.anchor)
+                .with_local_basis1(frame1.basis)
+                .with_local_basis2(frame2.basis)
+                .with_slider_axis(JOINT_FRAME_AXIS);
+            if let Some(l) = limits {
+                slider = slider.with_limits(l.min, l.max);
+            }
+            commands.spawn(slider)
+        }
+        JointType::Ball => {
+            let mut ball = SphericalJoint::new(parent, child)
+                .with_local_anchor1(frame1.anchor)
+                .with_local_anchor2(frame2.anchor)
+                .with_local_basis1(frame1.basis)
+                .with_local_basis2(frame2.basis)
+                .with_twist_axis(JOINT_FRAME_AXIS);
+            if let Some(cone) = limits.and_then(|l| l.cone) {
+                // Avian's swing limit is circular; the wider half-angle keeps every pose
+                // the elliptical cone allows reachable.
+                let swing = cone.swing1.max(cone.swing2);
+                ball = ball
+                    .with_swing_limits(-swing, swing)
+                    .with_twist_limits(-cone.twist, cone.twist);
+            }
+            commands.spawn(ball)
+        }
+    };
+    entity.insert(JointCollisionDisabled);
+}
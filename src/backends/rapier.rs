@@ -0,0 +1,174 @@
+//! Rapier3D adapter (feature `rapier`).
+//!
+//! [`build_blueprint`] inserts one rigid body and collider per module and one impulse joint
+//! per blueprint joint into the caller's Rapier sets, through the [`RapierBackend`]
+//! adapter, and returns the handles keyed by [`ModuleId`]. Joint frames follow the
+//! conventions described in [`backends`](crate::backends).
+//!
+//! Rapier's joint builders take a single axis and use it in both bodies' frames, which is
+//! only right when the child is not rotated relative to its parent. The blueprint's
+//! [`axis`](JointDefinition::axis) is parent-local, so every joint here is built from two
+//! explicit frames instead.
+
+use super::{JointFrame, PhysicsBackend};
+use crate::blueprint::{
+    JointDefinition, JointType, ModuleId, RobotBlueprint, RobotModule, ShapePrimitive,
+};
+use bevy_heavy::ComputeMassProperties3d;
+use glam::{Quat, Vec3};
+use rapier3d::na::{Quaternion, Translation3, UnitQuaternion};
+use rapier3d::prelude::*;
+use std::collections::HashMap;
+
+/// Handles of everything [`build_blueprint`] inserted.
+#[derive(Clone, Debug, Default)]
+pub struct RapierHandles {
+    /// The rigid body of every module.
+    pub bodies: HashMap<ModuleId, RigidBodyHandle>,
+    /// The collider of every module.
+    pub colliders: HashMap<ModuleId, ColliderHandle>,
+    /// The joint attaching every non-root module to its parent, keyed by the child.
+    pub joints: HashMap<ModuleId, ImpulseJointHandle>,
+}
+
+/// A Rapier collider builder matching `shape`.
+pub fn shape_collider(shape: &ShapePrimitive) -> ColliderBuilder {
+    match *shape {
+        ShapePrimitive::Box(h) => ColliderBuilder::cuboid(h.x, h.y, h.z),
+        ShapePrimitive::Cylinder { radius, height } => {
+            ColliderBuilder::cylinder(height / 2.0, radius)
+        }
+        ShapePrimitive::Sphere(radius) => ColliderBuilder::ball(radius),
+        ShapePrimitive::Capsule { radius, height } => {
+            ColliderBuilder::capsule_y(height / 2.0, radius)
+        }
+    }
+}
+
+/// Inserts the robot at its rest pose into the given sets.
+///
+/// Modules welded to the world become fixed bodies. Each body carries the module's mass
+/// and center of mass; colliders are massless. Hinges, prismatic joints and ball joints
+/// lock the matching axes of a [`GenericJoint`] and get their limits (a ball joint's swing
+/// cone limits the two swing axes separately); fixed joints lock everything. Bodies
+/// connected by a joint do not collide with each other. No motors are configured: drive
+/// the joints with the effort limits from
+/// [`control_interface`](RobotBlueprint::control_interface).
+pub fn build_blueprint(
+    blueprint: &RobotBlueprint,
+    bodies: &mut RigidBodySet,
+    colliders: &mut ColliderSet,
+    joints: &mut ImpulseJointSet,
+) -> RapierHandles {
+    let mut backend = RapierBackend {
+        bodies,
+        colliders,
+        joints,
+        handles: RapierHandles::default(),
+    };
+    blueprint.instantiate(&mut backend);
+    backend.handles
+}
+
+/// The [`PhysicsBackend`] behind [`build_blueprint`], inserting into borrowed Rapier sets
+/// and recording every handle. Sensors are ignored.
+pub struct RapierBackend<'a> {
+    /// The set bodies are inserted into.
+    pub bodies: &'a mut RigidBodySet,
+    /// The set colliders are inserted into.
+    pub colliders: &'a mut ColliderSet,
+    /// The set joints are inserted into.
+    pub joints: &'a mut ImpulseJointSet,
+    /// Everything inserted so far.
+    pub handles: RapierHandles,
+}
+
+impl PhysicsBackend for RapierBackend<'_> {
+    type Body = RigidBodyHandle;
+
+    fn spawn_module(&mut self, id: ModuleId, module: &RobotModule, welded: bool) -> Self::Body {
+        let builder = if welded {
+            RigidBodyBuilder::fixed()
+        } else {
+            RigidBodyBuilder::dynamic()
+        };
+        let (pos, rot) = module.transform;
+        let inertia = module
+            .shape
+            .to_bevy_primitive()
+            .unit_principal_angular_inertia()
+            * module.mass;
+        let body = builder
+            .position(isometry(pos, rot))
+            .additional_mass_properties(MassProperties::new(
+                point(module.center_of_mass),
+                module.mass,
+                vector(inertia),
+            ))
+            .build();
+        let body = self.bodies.insert(body);
+        let collider = shape_collider(&module.shape).density(0.0).build();
+        let collider = self
+            .colliders
+            .insert_with_parent(collider, body, self.bodies);
+        self.handles.colliders.insert(id, collider);
+        self.handles.bodies.insert(id, body);
+        body
+    }
+
+    fn spawn_joint(
+        &mut self,
+        joint: &JointDefinition,
+        (parent, frame1): (Self::Body, JointFrame),
+        (child, frame2): (Self::Body, JointFrame),
+    ) {
+        let generic = generic_joint(joint, frame1, frame2);
+        self.handles.joints.insert(
+            joint.child_id,
+            self.joints.insert(parent, child, generic, true),
+        );
+    }
+}
+
+fn generic_joint(joint: &JointDefinition, frame1: JointFrame, frame2: JointFrame) -> GenericJoint {
+    // Rapier's joint masks free the X axes of the frames, which is where the bases put
+    // the joint axis.
+    let locked = match joint.joint_type {
+        JointType::Fixed => JointAxesMask::LOCKED_FIXED_AXES,
+        JointType::Hinge => JointAxesMask::LOCKED_REVOLUTE_AXES,
+        JointType::Prismatic => JointAxesMask::LOCKED_PRISMATIC_AXES,
+        JointType::Ball => JointAxesMask::LOCKED_SPHERICAL_AXES,
+    };
+    let mut builder = GenericJointBuilder::new(locked)
+        .local_frame1(isometry(frame1.anchor, frame1.basis))
+        .local_frame2(isometry(frame2.anchor, frame2.basis))
+        .contacts_enabled(false);
+    if let Some(l) = joint.limits {
+        builder = match joint.joint_type {
+            JointType::Fixed => builder,
+            JointType::Hinge => builder.limits(JointAxis::AngX, [l.min, l.max]),
+            JointType::Prismatic => builder.limits(JointAxis::LinX, [l.min, l.max]),
+            JointType::Ball => match l.cone {
+                Some(cone) => builder
+                    .limits(JointAxis::AngX, [-cone.twist, cone.twist])
+                    .limits(JointAxis::AngY, [-cone.swing1, cone.swing1])
+                    .limits(JointAxis::AngZ, [-cone.swing2, cone.swing2]),
+                None => builder,
+            },
+        };
+    }
+    builder.build()
+}
+
+fn vector(v: Vec3) -> Vector<Real> {
+    vector![v.x, v.y, v.z]
+}
+
+fn point(v: Vec3) -> Point<Real> {
+    point![v.x, v.y, v.z]
+}
+
+fn isometry(pos: Vec3, rot: Quat) -> Isometry<Real> {
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(rot.w, rot.x, rot.y, rot.z));
+    Isometry::from_parts(Translation3::new(pos.x, pos.y, pos.z), rotation)
+}
@@ -0,0 +1,140 @@
+//! Bevy integration (feature `bevy`).
+//!
+//! [`RobotPlugin`] turns a [`SpawnRobot`] component into a hierarchy of entities: one
+//! child of the robot entity per module, with a `Transform` at its rest pose, a mesh built
+//! from its [`ShapePrimitive`] and a material coloured by its material ID, plus marker
+//! entities for joints and sensors. No physics components are added; engine adapters
+//! attach colliders and joints by querying the markers.
+//!
+//! ```rust,ignore
+//! app.add_plugins(RobotPlugin);
+//! commands.spawn(SpawnRobot(blueprint));
+//! ```
+
+use crate::blueprint::{
+    JointDefinition, ModuleId, RobotBlueprint, SensorAttachment, SensorMount, ShapePrimitive,
+};
+use crate::gltf::material_color;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Spawns every entity carrying [`SpawnRobot`] as a robot hierarchy.
+pub struct RobotPlugin;
+
+impl Plugin for RobotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, spawn_robots);
+    }
+}
+
+/// Insert on an entity to have [`RobotPlugin`] spawn the blueprint under it.
+///
+/// Once spawned, the component is replaced by [`RobotRoot`].
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct SpawnRobot(pub RobotBlueprint);
+
+/// The root entity of a spawned robot, holding the blueprint it was spawned from.
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct RobotRoot(pub RobotBlueprint);
+
+/// A module entity, a child of the [`RobotRoot`] placed at the module's rest pose.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RobotPart {
+    /// The module's ID in the blueprint.
+    pub module_id: ModuleId,
+}
+
+/// A joint entity, a child of the joint's parent module placed at its parent anchor.
+#[derive(Component, Clone, Debug)]
+pub struct RobotJoint(pub JointDefinition);
+
+/// A sensor entity, a child of the module it is mounted on, placed at its mount pose.
+#[derive(Component, Clone, Debug)]
+pub struct RobotSensor(pub SensorMount);
+
+/// A mesh for `shape`, matching its tessellation in [`mesh`](crate::mesh).
+pub fn shape_mesh(shape: &ShapePrimitive) -> Mesh {
+    match *shape {
+        ShapePrimitive::Box(h) => Mesh::from(Cuboid::from_size(h * 2.0)),
+        ShapePrimitive::Cylinder { radius, height } => Mesh::from(Cylinder::new(radius, height)),
+        ShapePrimitive::Sphere(radius) => Mesh::from(Sphere::new(radius)),
+        ShapePrimitive::Capsule { radius, height } => Mesh::from(Capsule3d::new(radius, height)),
+    }
+}
+
+fn spawn_robots(
+    mut commands: Commands,
+    robots: Query<(Entity, &SpawnRobot)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (root, SpawnRobot(blueprint)) in &robots {
+        // Modules with equal shapes or materials share one asset.
+        let mut shapes: Vec<(ShapePrimitive, Handle<Mesh>)> = Vec::new();
+        let mut colors: HashMap<u8, Handle<StandardMaterial>> = HashMap::new();
+        let mut parts = HashMap::new();
+        for (id, module) in blueprint.modules_topological() {
+            let mesh = match shapes.iter().find(|(s, _)| *s == module.shape) {
+                Some((_, handle)) => handle.clone(),
+                None => {
+                    let handle = meshes.add(shape_mesh(&module.shape));
+                    shapes.push((module.shape, handle.clone()));
+                    handle
+                }
+            };
+            let material = colors
+                .entry(module.material_id)
+                .or_insert_with(|| {
+                    let [r, g, b, a] = material_color(module.material_id);
+                    materials.add(Color::srgba(r, g, b, a))
+                })
+                .clone();
+            let (pos, rot) = module.transform;
+            let part = commands
+                .spawn((
+                    RobotPart { module_id: id },
+                    Mesh3d(mesh),
+                    MeshMaterial3d(material),
+                    Transform::from_translation(pos).with_rotation(rot),
+                    ChildOf(root),
+                ))
+                .id();
+
+            let joint_anchor = blueprint
+                .joints
+                .iter()
+                .find(|j| j.child_id == id)
+                .map_or(Vec3::ZERO, |j| j.anchor_child);
+            for mount in &module.sensors {
+                let origin = match mount.attachment {
+                    SensorAttachment::Module => Vec3::ZERO,
+                    SensorAttachment::Joint => joint_anchor,
+                };
+                commands.spawn((
+                    RobotSensor(mount.clone()),
+                    Transform::from_translation(origin + mount.local_position)
+                        .with_rotation(mount.local_rotation),
+                    ChildOf(part),
+                ));
+            }
+            parts.insert(id, part);
+        }
+
+        for joint in &blueprint.joints {
+            if let Some(&parent) = parts.get(&joint.parent_id) {
+                commands.spawn((
+                    RobotJoint(joint.clone()),
+                    Transform::from_translation(joint.anchor_parent),
+                    ChildOf(parent),
+                ));
+            }
+        }
+
+        commands
+            .entity(root)
+            .remove::<SpawnRobot>()
+            .insert(RobotRoot(blueprint.clone()));
+    }
+}
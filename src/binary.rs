@@ -0,0 +1,209 @@
+//! Compact binary serialization of blueprints.
+//!
+//! [`RobotBlueprint::to_bytes`] writes a short header — the magic bytes
+//! [`BINARY_MAGIC`] and the format version [`BINARY_FORMAT_VERSION`] as a little-endian
+//! `u16` — followed by the blueprint encoded with [postcard](https://crates.io/crates/postcard),
+//! which is several times smaller than the JSON serde produces.
+//!
+//! Postcard is not self-describing: adding, removing or reordering a field of any
+//! serialized type changes the layout. Every such change bumps [`BINARY_FORMAT_VERSION`];
+//! [`RobotBlueprint::from_bytes`] keeps decoding older versions by reading them into a
+//! frozen copy of their old types and converting, so stored populations stay readable.
+
+use crate::blueprint::{RobotBlueprint, RobotModule};
+use thiserror::Error;
+
+/// Magic bytes opening every binary blueprint.
+pub const BINARY_MAGIC: [u8; 4] = *b"SRBP";
+
+/// Version of the binary layout written by [`RobotBlueprint::to_bytes`].
+pub const BINARY_FORMAT_VERSION: u16 = 3;
+
+/// Length of the header preceding the payload.
+const HEADER_LEN: usize = BINARY_MAGIC.len() + 2;
+
+/// Errors produced by [`RobotBlueprint::from_bytes`].
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BinaryError {
+    /// The data does not start with [`BINARY_MAGIC`].
+    #[error("Not a binary blueprint")]
+    BadMagic,
+    /// The data was written by a newer version of the format.
+    #[error("Unsupported binary format version {0}")]
+    UnsupportedVersion(u16),
+    /// The payload could not be decoded.
+    #[error("Invalid binary blueprint: {0}")]
+    Decode(String),
+}
+
+impl RobotBlueprint {
+    /// Encodes the blueprint in the compact binary format.
+    ///
+    /// Modules are written in the iteration order of [`modules`](Self::modules), so two
+    /// equal blueprints can encode to different bytes; compare decoded blueprints instead.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN);
+        out.extend_from_slice(&BINARY_MAGIC);
+        out.extend_from_slice(&BINARY_FORMAT_VERSION.to_le_bytes());
+        postcard::to_extend(self, out).expect("blueprints only contain postcard-encodable types")
+    }
+
+    /// Decodes a blueprint written by [`to_bytes`](Self::to_bytes), by this or any earlier
+    /// version of the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BinaryError::BadMagic`] if the header is missing,
+    /// [`BinaryError::UnsupportedVersion`] for data from a newer format and
+    /// [`BinaryError::Decode`] if the payload is truncated or corrupt.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryError> {
+        let (header, payload) = bytes
+            .split_at_checked(HEADER_LEN)
+            .ok_or(BinaryError::BadMagic)?;
+        if header[..BINARY_MAGIC.len()] != BINARY_MAGIC {
+            return Err(BinaryError::BadMagic);
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        let decode = |e: postcard::Error| BinaryError::Decode(e.to_string());
+        match version {
+            // Future versions add an arm here decoding into the frozen types of the
+            // version they replace, then migrating the result.
+            1 => postcard::from_bytes::<legacy::RobotBlueprint<legacy::ModuleV1>>(payload)
+                .map(|old| {
+                    old.migrate::<legacy::ModuleV2>()
+                        .migrate::<RobotModule>()
+                        .into()
+                })
+                .map_err(decode),
+            2 => postcard::from_bytes::<legacy::RobotBlueprint<legacy::ModuleV2>>(payload)
+                .map(|old| old.migrate::<RobotModule>().into())
+                .map_err(decode),
+            BINARY_FORMAT_VERSION => postcard::from_bytes(payload).map_err(decode),
+            _ => Err(BinaryError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Frozen layouts of earlier versions. Only modules have changed so far, so a blueprint of
+/// any version is a [`legacy::RobotBlueprint`] over that version's module type.
+mod legacy {
+    use crate::blueprint::{
+        self, AnisotropicFriction, BlueprintMetadata, JointDefinition, Marker, MaterialId,
+        ModuleId, SensorMount, ShapePrimitive, WorldJoint,
+    };
+    use crate::collision::CollisionExclusion;
+    use crate::material::ContactTable;
+    use glam::{Quat, Vec3};
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize)]
+    pub(super) struct RobotBlueprint<M> {
+        roots: Vec<ModuleId>,
+        modules: HashMap<ModuleId, M>,
+        joints: Vec<JointDefinition>,
+        world_joints: Vec<WorldJoint>,
+        contacts: ContactTable,
+        collision_exclusions: Vec<CollisionExclusion>,
+        metadata: BlueprintMetadata,
+    }
+
+    /// Version 1, written before modules carried markers.
+    #[derive(Deserialize)]
+    pub(super) struct ModuleV1 {
+        shape: ShapePrimitive,
+        mass: f32,
+        center_of_mass: Vec3,
+        density: f32,
+        pivot_offset: Vec3,
+        material_id: MaterialId,
+        growth_stage: u32,
+        sensors: Vec<SensorMount>,
+        anisotropic_friction: Option<AnisotropicFriction>,
+        transform: (Vec3, Quat),
+    }
+
+    /// Version 2, written before modules stored their bounds.
+    #[derive(Deserialize)]
+    pub(super) struct ModuleV2 {
+        shape: ShapePrimitive,
+        mass: f32,
+        center_of_mass: Vec3,
+        density: f32,
+        pivot_offset: Vec3,
+        material_id: MaterialId,
+        growth_stage: u32,
+        sensors: Vec<SensorMount>,
+        markers: Vec<Marker>,
+        anisotropic_friction: Option<AnisotropicFriction>,
+        transform: (Vec3, Quat),
+    }
+
+    impl From<ModuleV1> for ModuleV2 {
+        fn from(m: ModuleV1) -> Self {
+            Self {
+                shape: m.shape,
+                mass: m.mass,
+                center_of_mass: m.center_of_mass,
+                density: m.density,
+                pivot_offset: m.pivot_offset,
+                material_id: m.material_id,
+                growth_stage: m.growth_stage,
+                sensors: m.sensors,
+                markers: Vec::new(),
+                anisotropic_friction: m.anisotropic_friction,
+                transform: m.transform,
+            }
+        }
+    }
+
+    impl From<ModuleV2> for blueprint::RobotModule {
+        fn from(m: ModuleV2) -> Self {
+            // `massless` derives the bounds.
+            Self {
+                mass: m.mass,
+                center_of_mass: m.center_of_mass,
+                pivot_offset: m.pivot_offset,
+                material_id: m.material_id,
+                growth_stage: m.growth_stage,
+                sensors: m.sensors,
+                markers: m.markers,
+                anisotropic_friction: m.anisotropic_friction,
+                ..Self::massless(m.shape, m.density, m.transform)
+            }
+        }
+    }
+
+    impl<M> RobotBlueprint<M> {
+        /// Converts every module to the current layout.
+        pub(super) fn migrate<N: From<M>>(self) -> RobotBlueprint<N> {
+            RobotBlueprint {
+                roots: self.roots,
+                modules: self
+                    .modules
+                    .into_iter()
+                    .map(|(id, m)| (id, m.into()))
+                    .collect(),
+                joints: self.joints,
+                world_joints: self.world_joints,
+                contacts: self.contacts,
+                collision_exclusions: self.collision_exclusions,
+                metadata: self.metadata,
+            }
+        }
+    }
+
+    impl From<RobotBlueprint<blueprint::RobotModule>> for blueprint::RobotBlueprint {
+        fn from(old: RobotBlueprint<blueprint::RobotModule>) -> Self {
+            Self {
+                roots: old.roots,
+                modules: old.modules,
+                joints: old.joints,
+                world_joints: old.world_joints,
+                contacts: old.contacts,
+                collision_exclusions: old.collision_exclusions,
+                metadata: old.metadata,
+            }
+        }
+    }
+}
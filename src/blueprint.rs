@@ -1,10 +1,41 @@
+use crate::collision::CollisionExclusion;
+use crate::material::{ContactSurface, ContactTable};
+use crate::validation::LimitIssue;
 use bevy_heavy::ComputeMassProperties3d;
 use bevy_math::Isometry3d;
 use bevy_math::bounding::{Aabb3d, Bounded3d, BoundingSphere, BoundingVolume};
-use bevy_math::primitives::{Capsule3d, Cuboid, Cylinder, Sphere};
+use bevy_math::primitives::{Capsule3d, Cuboid, Cylinder, Measured3d, Sphere};
 use glam::{Quat, Vec3};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use smol_str::SmolStr;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::PI;
+use std::fmt::{self, Write as _};
+use thiserror::Error;
+
+/// Errors produced by [`RobotBlueprint`] graph operations.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BlueprintError {
+    /// The referenced module does not exist in the blueprint.
+    #[error("Unknown module id {0}")]
+    UnknownModule(ModuleId),
+    /// The joint graph contains a cycle or a module with several parents, so
+    /// no unique path to the root exists.
+    #[error("Joint graph is not a tree at module {0}")]
+    NotATree(ModuleId),
+    /// The operation needs at least one module.
+    #[error("Blueprint has no modules")]
+    Empty,
+    /// A module ID is already taken in the destination blueprint.
+    #[error("Module id {0} is already in use")]
+    DuplicateModule(ModuleId),
+    /// Offsetting a module ID would exceed the range of [`ModuleId`].
+    #[error("Module id {0} overflows when offset")]
+    IdOverflow(ModuleId),
+    /// A joint's limits do not fit its type (see [`crate::validation`]).
+    #[error("Joint {} has invalid limits: {}", .0.joint_index, .0.kind)]
+    InvalidLimits(LimitIssue),
+}
 
 /// A unique identifier for a robot module (rigid body).
 /// Maps to L-System derivation steps or Turtle spawn indices.
@@ -13,6 +44,10 @@ pub type ModuleId = u16;
 /// A generic material identifier referencing an external palette.
 pub type MaterialId = u8;
 
+/// How far [`RobotBlueprint::ragdoll`] widens each joint limit, in radians (metres for
+/// prismatic joints).
+pub const RAGDOLL_LIMIT_SLACK: f32 = 0.1;
+
 /// The complete, engine-agnostic definition of a robot's topology.
 ///
 /// This structure represents the "Phenotype" generated from an L-System.
@@ -22,15 +57,129 @@ pub type MaterialId = u8;
 /// downstream code that matches or constructs this type.
 #[non_exhaustive]
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RobotBlueprint {
-    /// The ID of the root module (base of the robot).
-    pub root_module: Option<ModuleId>,
+    /// The root of every articulated tree in the blueprint, primary tree first.
+    ///
+    /// Most robots are a single tree, but a grammar can also grow detached parts (a robot
+    /// plus a loose tool, say); each of those is a separate tree with its own root.
+    /// Blueprints saved with the single optional `root_module` of earlier releases load
+    /// with that module as the only root.
+    #[serde(default, alias = "root_module", deserialize_with = "deserialize_roots")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<ModuleId>"))]
+    pub roots: Vec<ModuleId>,
 
     /// All rigid bodies in the robot, indexed by their unique ID.
     pub modules: HashMap<ModuleId, RobotModule>,
 
     /// All physical connections between modules.
     pub joints: Vec<JointDefinition>,
+
+    /// Welds fixing modules to the world at their rest pose.
+    #[serde(default)]
+    pub world_joints: Vec<WorldJoint>,
+
+    /// Contact parameters between material pairs and the ground.
+    #[serde(default)]
+    pub contacts: ContactTable,
+
+    /// Module pairs that should not be tested for self-collision, as computed by
+    /// [`update_collision_exclusions`](Self::update_collision_exclusions).
+    #[serde(default)]
+    pub collision_exclusions: Vec<CollisionExclusion>,
+
+    /// Optional annotations that travel with the blueprint, such as its [`Lineage`].
+    #[serde(default)]
+    pub metadata: BlueprintMetadata,
+}
+
+/// Reads [`RobotBlueprint::roots`] from either a list of roots or a legacy `root_module`.
+/// Binary data always holds the list, and postcard cannot tell the two forms apart.
+fn deserialize_roots<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<ModuleId>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Roots {
+        Roots(Vec<ModuleId>),
+        RootModule(Option<ModuleId>),
+    }
+    if !deserializer.is_human_readable() {
+        return Vec::deserialize(deserializer);
+    }
+    Ok(match Roots::deserialize(deserializer)? {
+        Roots::Roots(roots) => roots,
+        Roots::RootModule(root) => root.into_iter().collect(),
+    })
+}
+
+/// Annotations attached to a [`RobotBlueprint`] that do not affect its physics.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BlueprintMetadata {
+    /// Evolutionary provenance, if recorded.
+    #[serde(default)]
+    pub lineage: Option<Lineage>,
+    /// Problems the interpreter worked around while building the blueprint.
+    #[serde(default)]
+    pub warnings: Vec<BuildWarning>,
+    /// Factor every module density was multiplied by to reach
+    /// [`RobotConfig::target_density`](crate::RobotConfig::target_density), if applied.
+    #[serde(default)]
+    pub density_scale: Option<f32>,
+    /// Pushes (`[`) ignored because the stack already held
+    /// [`RobotConfig::max_stack_depth`](crate::RobotConfig::max_stack_depth) states.
+    #[serde(default)]
+    pub dropped_pushes: usize,
+    /// Pops (`]`) ignored because the stack was empty.
+    #[serde(default)]
+    pub unmatched_pops: usize,
+    /// How the capsule segments were measured when the blueprint was grown, as set by
+    /// [`RobotConfig::capsule_length`](crate::RobotConfig::capsule_length). Stored capsule
+    /// heights are always the cylindrical section; use [`CapsuleLength::length`] to
+    /// recover the grown lengths.
+    #[serde(default)]
+    pub capsule_length: CapsuleLength,
+}
+
+/// A problem the interpreter worked around instead of failing the build.
+#[derive(Error, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum BuildWarning {
+    /// A module reached outside [`RobotConfig::max_extent`](crate::RobotConfig::max_extent)
+    /// and was not spawned.
+    #[error("module at {center} lies outside the maximum extent and was skipped")]
+    ModuleOutOfBounds {
+        /// World-space center the module would have had.
+        #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+        center: Vec3,
+    },
+    /// A joint would have exceeded [`RobotConfig::max_dof`](crate::RobotConfig::max_dof)
+    /// and was made fixed.
+    #[error("joint to module {child_id} exceeds the degree-of-freedom budget and was fixed")]
+    DofLimitReached {
+        /// Child module of the joint.
+        child_id: ModuleId,
+    },
+}
+
+/// Where an evolved blueprint came from.
+///
+/// Every field is optional so that callers can record as much provenance as their search
+/// loop tracks. Genotype hashes are opaque; [`genotype_hash`](crate::genotype_hash) is one
+/// way to compute them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Lineage {
+    /// Hash of the parent genotype this one was derived from.
+    #[serde(default)]
+    pub parent_genotype_hash: Option<u64>,
+    /// Generation in which this genotype was produced.
+    #[serde(default)]
+    pub generation: Option<u32>,
+    /// Name of the mutation or crossover operator that produced it.
+    #[serde(default)]
+    pub mutation_operator: Option<String>,
 }
 
 impl RobotBlueprint {
@@ -39,22 +188,805 @@ impl RobotBlueprint {
         Self::default()
     }
 
+    /// The recorded provenance of this blueprint, if any.
+    pub fn lineage(&self) -> Option<&Lineage> {
+        self.metadata.lineage.as_ref()
+    }
+
+    /// Records provenance, replacing any previous lineage.
+    pub fn set_lineage(&mut self, lineage: Lineage) {
+        self.metadata.lineage = Some(lineage);
+    }
+
+    /// Records provenance (builder pattern).
+    pub fn with_lineage(mut self, lineage: Lineage) -> Self {
+        self.set_lineage(lineage);
+        self
+    }
+
+    /// Hash of the parent genotype, if recorded.
+    pub fn parent_genotype_hash(&self) -> Option<u64> {
+        self.lineage()?.parent_genotype_hash
+    }
+
+    /// Generation number, if recorded.
+    pub fn generation(&self) -> Option<u32> {
+        self.lineage()?.generation
+    }
+
+    /// Name of the operator that produced this genotype, if recorded.
+    pub fn mutation_operator(&self) -> Option<&str> {
+        self.lineage()?.mutation_operator.as_deref()
+    }
+
+    /// The root of the primary tree (the base of the robot), if any.
+    pub fn root_module(&self) -> Option<ModuleId> {
+        self.roots.first().copied()
+    }
+
     /// Inserts a module into the blueprint.
     ///
-    /// The first module inserted is automatically set as [`root_module`](Self::root_module).
+    /// The first module inserted is automatically registered as the primary root; use
+    /// [`add_root`](Self::add_root) for the roots of further detached trees.
     /// If `id` already exists it is silently overwritten.
     pub fn add_module(&mut self, id: ModuleId, module: RobotModule) {
         if self.modules.is_empty() {
-            self.root_module = Some(id);
+            self.add_root(id);
         }
         self.modules.insert(id, module);
     }
 
+    /// Registers `id` as the root of a further tree. Existing roots are left unchanged.
+    pub fn add_root(&mut self, id: ModuleId) {
+        if !self.roots.contains(&id) {
+            self.roots.push(id);
+        }
+    }
+
     /// Appends a joint to the blueprint.
+    ///
+    /// The child gains a parent, so it stops being a root if it was one.
     pub fn add_joint(&mut self, joint: JointDefinition) {
+        self.roots.retain(|&id| id != joint.child_id);
         self.joints.push(joint);
     }
 
+    /// Returns the root of the tree containing module `id`.
+    ///
+    /// Follows parent joints upwards; returns `None` if `id` is unknown or the path revisits
+    /// a module.
+    pub fn tree_root(&self, id: ModuleId) -> Option<ModuleId> {
+        if !self.modules.contains_key(&id) {
+            return None;
+        }
+        let mut visited = HashSet::from([id]);
+        let mut current = id;
+        while let Some(joint) = self.parent_joint(current) {
+            current = joint.parent_id;
+            if !visited.insert(current) {
+                return None;
+            }
+        }
+        Some(current)
+    }
+
+    /// Welds a module to the world. A module that is already welded is left unchanged.
+    pub fn add_world_joint(&mut self, weld: WorldJoint) {
+        if !self.is_welded(weld.child_id) {
+            self.world_joints.push(weld);
+        }
+    }
+
+    /// Returns `true` if module `id` is welded to the world.
+    pub fn is_welded(&self, id: ModuleId) -> bool {
+        self.world_joints.iter().any(|w| w.child_id == id)
+    }
+
+    /// Returns every module ID in topological order: each parent precedes its children.
+    ///
+    /// The traversal is breadth-first, starting at each of the [`roots`](Self::roots) in turn.
+    /// Modules that are not reachable from a declared root start their own traversal in
+    /// ascending ID order, so every module appears exactly once. Siblings are visited in the
+    /// order their joints appear in [`joints`](Self::joints), making the result deterministic.
+    pub fn topological_order(&self) -> Vec<ModuleId> {
+        let mut children: HashMap<ModuleId, Vec<ModuleId>> = HashMap::new();
+        let mut has_parent: HashSet<ModuleId> = HashSet::new();
+        for joint in &self.joints {
+            children
+                .entry(joint.parent_id)
+                .or_default()
+                .push(joint.child_id);
+            has_parent.insert(joint.child_id);
+        }
+
+        let mut ids: Vec<ModuleId> = self.modules.keys().copied().collect();
+        ids.sort_unstable();
+
+        // Seeds: the declared roots first, then parentless modules, then anything left over
+        // (only reachable through a cycle, which a well-formed blueprint never contains).
+        let mut seeds: Vec<ModuleId> = Vec::new();
+        seeds.extend(
+            self.roots
+                .iter()
+                .copied()
+                .filter(|id| self.modules.contains_key(id)),
+        );
+        seeds.extend(ids.iter().copied().filter(|id| !has_parent.contains(id)));
+        seeds.extend(ids.iter().copied());
+
+        let mut order = Vec::with_capacity(self.modules.len());
+        let mut visited: HashSet<ModuleId> = HashSet::with_capacity(self.modules.len());
+        let mut queue = VecDeque::new();
+        for seed in seeds {
+            if !visited.insert(seed) {
+                continue;
+            }
+            queue.push_back(seed);
+            while let Some(id) = queue.pop_front() {
+                order.push(id);
+                for &child in children.get(&id).into_iter().flatten() {
+                    if self.modules.contains_key(&child) && visited.insert(child) {
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// Iterates over modules in topological order (parents before children).
+    ///
+    /// See [`topological_order`](Self::topological_order) for the traversal rules.
+    pub fn modules_topological(&self) -> impl Iterator<Item = (ModuleId, &RobotModule)> + '_ {
+        self.topological_order()
+            .into_iter()
+            .filter_map(|id| self.modules.get(&id).map(|m| (id, m)))
+    }
+
+    /// Iterates over joints in topological order.
+    ///
+    /// Joints are sorted by the position of their child module in
+    /// [`topological_order`](Self::topological_order), so a joint is always yielded after the
+    /// joint that attaches its parent. Joints whose child is not a known module come last.
+    pub fn joints_topological(&self) -> impl Iterator<Item = &JointDefinition> + '_ {
+        let rank: HashMap<ModuleId, usize> = self
+            .topological_order()
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| (id, i))
+            .collect();
+        let mut joints: Vec<&JointDefinition> = self.joints.iter().collect();
+        joints.sort_by_key(|j| rank.get(&j.child_id).copied().unwrap_or(usize::MAX));
+        joints.into_iter()
+    }
+
+    /// Returns the joint attaching `id` to its parent, or `None` for roots.
+    pub fn parent_joint(&self, id: ModuleId) -> Option<&JointDefinition> {
+        self.joints.iter().find(|j| j.child_id == id)
+    }
+
+    /// Iterates over the IDs of the modules directly attached below `id`.
+    pub fn children(&self, id: ModuleId) -> impl Iterator<Item = ModuleId> + '_ {
+        self.joints
+            .iter()
+            .filter(move |j| j.parent_id == id)
+            .map(|j| j.child_id)
+    }
+
+    /// Makes `new_root` the root of its kinematic tree.
+    ///
+    /// Every joint on the path from `new_root` up to its current tree root is reversed:
+    /// parent and child are swapped, the anchors are exchanged, the axis is re-expressed in
+    /// the new parent's local frame, and limits are mirrored (`[min, max]` becomes
+    /// `[-max, -min]`) because the relative motion is now measured from the other side.
+    /// Modules on the path take the anchor of their new parent joint as
+    /// [`pivot_offset`](RobotModule::pivot_offset).
+    /// Module transforms are untouched, so the rest pose is preserved exactly. `new_root`
+    /// takes the old root's place in [`roots`](Self::roots); other trees are unaffected.
+    /// [`SensorAttachment::Joint`] sensors move to the joint's new child module, keeping
+    /// their world-space pose.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlueprintError::UnknownModule`] if `new_root` is not in the blueprint and
+    /// [`BlueprintError::NotATree`] if the path to the root revisits a module.
+    pub fn reroot(&mut self, new_root: ModuleId) -> Result<(), BlueprintError> {
+        if !self.modules.contains_key(&new_root) {
+            return Err(BlueprintError::UnknownModule(new_root));
+        }
+
+        let mut path = Vec::new();
+        let mut visited = HashSet::from([new_root]);
+        let mut current = new_root;
+        while let Some(idx) = self.joints.iter().position(|j| j.child_id == current) {
+            current = self.joints[idx].parent_id;
+            if !visited.insert(current) {
+                return Err(BlueprintError::NotATree(current));
+            }
+            path.push(idx);
+        }
+
+        // Joint-mounted sensors follow their joint to its new child. Collect them all
+        // before re-inserting, so a sensor is never moved twice along the path.
+        let mut moved_sensors = Vec::new();
+        for &idx in &path {
+            let joint = &self.joints[idx];
+            let (Some(parent), Some(child)) = (
+                self.modules.get(&joint.parent_id),
+                self.modules.get(&joint.child_id),
+            ) else {
+                continue;
+            };
+            let ((parent_pos, parent_rot), (child_pos, child_rot)) =
+                (parent.transform, child.transform);
+            let (anchor_parent, anchor_child, parent_id) =
+                (joint.anchor_parent, joint.anchor_child, joint.parent_id);
+            let child = self
+                .modules
+                .get_mut(&joint.child_id)
+                .expect("checked above");
+            let (on_joint, on_module) = std::mem::take(&mut child.sensors)
+                .into_iter()
+                .partition(|s| s.attachment == SensorAttachment::Joint);
+            child.sensors = on_module;
+            for mut sensor in on_joint {
+                let world_pos = child_pos + child_rot * (anchor_child + sensor.local_position);
+                sensor.local_position =
+                    parent_rot.inverse() * (world_pos - parent_pos) - anchor_parent;
+                sensor.local_rotation = parent_rot.inverse() * child_rot * sensor.local_rotation;
+                moved_sensors.push((parent_id, sensor));
+            }
+        }
+        for (id, sensor) in moved_sensors {
+            if let Some(module) = self.modules.get_mut(&id) {
+                module.sensors.push(sensor);
+            }
+        }
+
+        for idx in path {
+            let joint = &mut self.joints[idx];
+            std::mem::swap(&mut joint.axis, &mut joint.child_axis);
+            std::mem::swap(&mut joint.parent_id, &mut joint.child_id);
+            std::mem::swap(&mut joint.anchor_parent, &mut joint.anchor_child);
+            let (child_id, pivot) = (joint.child_id, joint.anchor_child);
+            joint.rest_displacement = -joint.rest_displacement;
+            if let Some(limits) = &mut joint.limits {
+                (limits.min, limits.max) = (-limits.max, -limits.min);
+            }
+            if let Some(child) = self.modules.get_mut(&child_id) {
+                child.pivot_offset = -pivot;
+            }
+        }
+
+        match self.roots.iter_mut().find(|id| **id == current) {
+            Some(root) => *root = new_root,
+            None => self.roots.push(new_root),
+        }
+        Ok(())
+    }
+
+    /// Copies the subtree rooted at `id` into a new, self-contained blueprint.
+    ///
+    /// Module IDs are remapped to `0..n` in breadth-first order, so `id` becomes module `0`
+    /// and the root of the result. Only joints with both ends inside the subtree are kept;
+    /// the joint attaching `id` to its former parent is dropped. Module transforms keep their
+    /// world-space rest pose, and the contact table and collision exclusions inside the
+    /// subtree are copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlueprintError::UnknownModule`] if `id` is not in the blueprint.
+    pub fn extract_subtree(&self, id: ModuleId) -> Result<RobotBlueprint, BlueprintError> {
+        if !self.modules.contains_key(&id) {
+            return Err(BlueprintError::UnknownModule(id));
+        }
+
+        let mut remap: HashMap<ModuleId, ModuleId> = HashMap::new();
+        let mut queue = VecDeque::from([id]);
+        let mut subtree = RobotBlueprint {
+            contacts: self.contacts.clone(),
+            ..RobotBlueprint::new()
+        };
+        while let Some(old) = queue.pop_front() {
+            if remap.contains_key(&old) {
+                continue;
+            }
+            let new = remap.len() as ModuleId;
+            remap.insert(old, new);
+            if let Some(module) = self.modules.get(&old) {
+                subtree.add_module(new, module.clone());
+            }
+            queue.extend(self.children(old));
+        }
+
+        for joint in &self.joints {
+            if let (Some(&parent_id), Some(&child_id)) =
+                (remap.get(&joint.parent_id), remap.get(&joint.child_id))
+                && joint.child_id != id
+            {
+                subtree.add_joint(JointDefinition {
+                    parent_id,
+                    child_id,
+                    ..joint.clone()
+                });
+            }
+        }
+        for weld in &self.world_joints {
+            if let Some(&child_id) = remap.get(&weld.child_id) {
+                subtree.world_joints.push(WorldJoint { child_id, ..*weld });
+            }
+        }
+        for pair in &self.collision_exclusions {
+            if let (Some(&a), Some(&b)) = (remap.get(&pair.a), remap.get(&pair.b)) {
+                subtree
+                    .collision_exclusions
+                    .push(CollisionExclusion::new(a, b, pair.reason));
+            }
+        }
+
+        Ok(subtree)
+    }
+
+    /// Merges `other` into this blueprint, for combining separately grown components (a body
+    /// from one grammar, a gripper from another) into one phenotype.
+    ///
+    /// Every module ID of `other` is shifted by `id_offset`, and its joints, world welds and
+    /// roots follow. Modules keep their world-space rest pose, so position `other` beforehand
+    /// if needed. If `connection` is given, its `parent_id` refers to a module of `self` and its
+    /// `child_id` to a module of `other` (before offsetting); it is added as a joint, which
+    /// turns the connected tree of `other` into a branch of `self` instead of a detached root.
+    /// This blueprint's metadata is kept. Contact pairs of `other` are added unless this
+    /// blueprint already defines the same pair, and its collision exclusions are offset like
+    /// its modules.
+    ///
+    /// On error `self` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlueprintError::IdOverflow`] if an offset ID does not fit in a [`ModuleId`],
+    /// [`BlueprintError::DuplicateModule`] if it is already used in `self`, and
+    /// [`BlueprintError::UnknownModule`] if either end of `connection` does not exist.
+    pub fn merge(
+        &mut self,
+        other: RobotBlueprint,
+        id_offset: ModuleId,
+        connection: Option<JointDefinition>,
+    ) -> Result<(), BlueprintError> {
+        let shift = |id: ModuleId| {
+            id.checked_add(id_offset)
+                .ok_or(BlueprintError::IdOverflow(id))
+        };
+        for &id in other.modules.keys() {
+            let new = shift(id)?;
+            if self.modules.contains_key(&new) {
+                return Err(BlueprintError::DuplicateModule(new));
+            }
+        }
+        let connection = match connection {
+            Some(joint) => {
+                if !self.modules.contains_key(&joint.parent_id) {
+                    return Err(BlueprintError::UnknownModule(joint.parent_id));
+                }
+                if !other.modules.contains_key(&joint.child_id) {
+                    return Err(BlueprintError::UnknownModule(joint.child_id));
+                }
+                Some(JointDefinition {
+                    child_id: shift(joint.child_id)?,
+                    ..joint
+                })
+            }
+            None => None,
+        };
+        // Every module ID was checked above; dangling references in joints, welds or roots
+        // that cannot be shifted are dropped.
+        for (id, module) in other.modules {
+            self.modules.insert(shift(id)?, module);
+        }
+        for root in other.roots.iter().filter_map(|&id| shift(id).ok()) {
+            self.add_root(root);
+        }
+        for joint in other.joints {
+            if let (Ok(parent_id), Ok(child_id)) = (shift(joint.parent_id), shift(joint.child_id)) {
+                self.add_joint(JointDefinition {
+                    parent_id,
+                    child_id,
+                    ..joint
+                });
+            }
+        }
+        for weld in other.world_joints {
+            if let Ok(child_id) = shift(weld.child_id) {
+                self.add_world_joint(WorldJoint { child_id, ..weld });
+            }
+        }
+        for pair in other.contacts.pairs {
+            if self.contacts.get(pair.a, pair.b).is_none() {
+                self.contacts.set(pair);
+            }
+        }
+        for pair in other.collision_exclusions {
+            if let (Ok(a), Ok(b)) = (shift(pair.a), shift(pair.b)) {
+                self.collision_exclusions
+                    .push(CollisionExclusion::new(a, b, pair.reason));
+            }
+        }
+        if let Some(joint) = connection {
+            self.add_joint(joint);
+        }
+        Ok(())
+    }
+
+    /// Renders the kinematic tree as indented text for terminal inspection.
+    ///
+    /// Each line shows the module ID, shape and dimensions, mass, the type of the joint
+    /// attaching it to its parent, and any mounted sensors. Detached trees are printed one
+    /// after another, in the order of [`roots`](Self::roots).
+    ///
+    /// ```text
+    /// #0 Box(0.20 x 1.00 x 0.20) 4.00 kg
+    /// ├── #1 Cylinder(r 0.10, h 0.50) 1.57 kg [Hinge] sensors: Camera
+    /// └── #2 Sphere(r 0.10) 0.42 kg [Fixed]
+    /// ```
+    pub fn print_tree(&self) -> String {
+        let mut out = String::new();
+        let order = self.topological_order();
+        for &id in order.iter().filter(|&&id| self.parent_joint(id).is_none()) {
+            self.write_tree_node(&mut out, id, "", None);
+        }
+        out
+    }
+
+    fn write_tree_node(&self, out: &mut String, id: ModuleId, prefix: &str, last: Option<bool>) {
+        let Some(module) = self.modules.get(&id) else {
+            return;
+        };
+        let (branch, indent) = match last {
+            None => ("", ""),
+            Some(false) => ("├── ", "│   "),
+            Some(true) => ("└── ", "    "),
+        };
+        let _ = write!(
+            out,
+            "{prefix}{branch}#{id} {} {:.2} kg",
+            module.shape, module.mass
+        );
+        if let Some(joint) = self.parent_joint(id) {
+            let _ = write!(out, " [{:?}]", joint.joint_type);
+        }
+        if !module.sensors.is_empty() {
+            let names: Vec<String> = module
+                .sensors
+                .iter()
+                .map(|s| format!("{:?}", s.sensor_type))
+                .collect();
+            let _ = write!(out, " sensors: {}", names.join(", "));
+        }
+        out.push('\n');
+
+        let children: Vec<ModuleId> = self.children(id).collect();
+        let child_prefix = format!("{prefix}{indent}");
+        for (i, &child) in children.iter().enumerate() {
+            self.write_tree_node(out, child, &child_prefix, Some(i + 1 == children.len()));
+        }
+    }
+
+    /// Scales every module's density by one common factor so that the robot's average
+    /// density (total mass over total volume) becomes `target` kg/m³, and returns the factor.
+    ///
+    /// Point masses such as actuators count towards the total but are not scaled. Relative
+    /// densities between modules are preserved, so an aluminum frame stays heavier than its
+    /// plastic shell; for a swimmer, `1000.0` makes the whole robot neutrally buoyant in
+    /// water. Returns `None`, leaving the blueprint unchanged, if there is no volume to scale
+    /// or the point masses alone already exceed the target mass.
+    pub fn scale_to_average_density(&mut self, target: f32) -> Option<f32> {
+        let (volume, shape_mass) = self.modules.values().fold((0.0, 0.0), |(v, m), module| {
+            let shape = module.shape.to_bevy_primitive();
+            (v + shape.volume(), m + shape.mass(module.density))
+        });
+        let point_mass = self.modules.values().map(|m| m.mass).sum::<f32>() - shape_mass;
+        let scale = density_scale(target, volume, shape_mass, point_mass)?;
+        for module in self.modules.values_mut() {
+            let shape = module.shape.to_bevy_primitive();
+            let mass = module.mass + shape.mass(module.density) * (scale - 1.0);
+            if mass > 0.0 {
+                module.center_of_mass *= module.mass / mass;
+            }
+            module.density *= scale;
+            module.mass = mass;
+        }
+        Some(scale)
+    }
+
+    /// Mass properties of everything joint `joint_index` moves: its child module and all of
+    /// that module's descendants, at the rest pose.
+    ///
+    /// Returns `None` if there is no such joint or its parent or child module is missing.
+    pub fn joint_load(&self, joint_index: usize) -> Option<JointLoad> {
+        let joint = self.joints.get(joint_index)?;
+        let parent_rot = self.modules.get(&joint.parent_id)?.transform.1;
+        let (child_pos, child_rot) = self.modules.get(&joint.child_id)?.transform;
+        let anchor = child_pos + child_rot * joint.anchor_child;
+        let axis = (parent_rot * joint.axis).normalize_or_zero();
+
+        let mut load = JointLoad::default();
+        let mut moment = Vec3::ZERO;
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([joint.child_id]);
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id) {
+                continue;
+            }
+            queue.extend(self.children(id));
+            let Some(module) = self.modules.get(&id) else {
+                continue;
+            };
+            let com = module.world_center_of_mass();
+            let local_axis = module.transform.1.inverse() * axis;
+            let own = module
+                .shape
+                .to_bevy_primitive()
+                .unit_principal_angular_inertia()
+                * module.mass;
+            let offset = com - anchor;
+            let perpendicular = offset - axis * offset.dot(axis);
+            load.mass += module.mass;
+            load.inertia +=
+                own.dot(local_axis * local_axis) + module.mass * perpendicular.length_squared();
+            moment += offset * module.mass;
+        }
+        if load.mass > 0.0 {
+            load.lever = (moment / load.mass).length();
+        }
+        Some(load)
+    }
+
+    /// The robot as it stood after derivation step `stage`: only modules whose
+    /// [`growth_stage`](RobotModule::growth_stage) is at most `stage`, with the joints,
+    /// welds and collision exclusions among them. Step through the stages to animate how
+    /// the morphology developed.
+    pub fn at_growth_stage(&self, stage: u32) -> RobotBlueprint {
+        let mut grown = self.clone();
+        grown.modules.retain(|_, m| m.growth_stage <= stage);
+        let kept = |id: &ModuleId| grown.modules.contains_key(id);
+        grown.roots.retain(kept);
+        grown
+            .joints
+            .retain(|j| kept(&j.parent_id) && kept(&j.child_id));
+        grown.world_joints.retain(|w| kept(&w.child_id));
+        grown
+            .collision_exclusions
+            .retain(|p| kept(&p.a) && kept(&p.b));
+        grown
+    }
+
+    /// A passive copy of the robot for "drop it and see if it holds together" previews.
+    ///
+    /// Every joint loses its actuator and transmission and gets zero effort, so no
+    /// exporter or engine adapter drives it, and its limits are widened by
+    /// [`RAGDOLL_LIMIT_SLACK`] on each side so that the limbs settle against them softly
+    /// instead of snapping to a stop. Joint types, modules and masses (including actuator
+    /// masses) are unchanged: the preview weighs what the powered robot weighs.
+    pub fn ragdoll(&self) -> RobotBlueprint {
+        let mut ragdoll = self.clone();
+        for joint in &mut ragdoll.joints {
+            joint.actuator = None;
+            joint.transmission = None;
+            if let Some(limits) = &mut joint.limits {
+                limits.effort = 0.0;
+                limits.min -= RAGDOLL_LIMIT_SLACK;
+                limits.max += RAGDOLL_LIMIT_SLACK;
+                if let Some(cone) = &mut limits.cone {
+                    let widen = |a: f32| (a + RAGDOLL_LIMIT_SLACK).min(PI);
+                    cone.swing1 = widen(cone.swing1);
+                    cone.swing2 = widen(cone.swing2);
+                    cone.twist = widen(cone.twist);
+                }
+            }
+        }
+        ragdoll
+    }
+
+    /// Center of mass of the whole robot in world space, or `None` if it has no mass.
+    pub fn center_of_mass(&self) -> Option<Vec3> {
+        let (weighted, total) = self
+            .modules
+            .values()
+            .fold((Vec3::ZERO, 0.0), |(sum, total), m| {
+                (sum + m.world_center_of_mass() * m.mass, total + m.mass)
+            });
+        (total > 0.0).then(|| weighted / total)
+    }
+
+    /// Friction coefficient between module `id` and the ground, if
+    /// [`contacts`](Self::contacts) defines one for its material.
+    pub fn ground_friction(&self, id: ModuleId) -> Option<f32> {
+        let module = self.modules.get(&id)?;
+        self.contacts
+            .get(
+                ContactSurface::Material(module.material_id),
+                ContactSurface::Ground,
+            )
+            .map(|pair| pair.friction)
+    }
+
+    /// Mounts an IMU at a center of mass and returns the module it was attached to.
+    ///
+    /// For [`ComTarget::Module`] the IMU goes on that module. For [`ComTarget::Robot`] it goes
+    /// on the module containing the robot's center of mass, or failing that the module whose
+    /// surface is nearest to it (the robot's COM can lie in empty space, e.g. between the
+    /// legs of a walker); ties go to the smaller ID. The IMU is aligned with its host
+    /// module's axes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlueprintError::UnknownModule`] for a missing module and
+    /// [`BlueprintError::Empty`] if the robot has no modules or no mass.
+    pub fn mount_imu_at_com(&mut self, target: ComTarget) -> Result<ModuleId, BlueprintError> {
+        let (host, com) = match target {
+            ComTarget::Module(id) => {
+                let module = self
+                    .modules
+                    .get(&id)
+                    .ok_or(BlueprintError::UnknownModule(id))?;
+                (id, module.world_center_of_mass())
+            }
+            ComTarget::Robot => {
+                let com = self.center_of_mass().ok_or(BlueprintError::Empty)?;
+                // A module containing the COM is at distance zero, so it always wins.
+                let (host, _) = self
+                    .spatial_index()
+                    .nearest(com)
+                    .ok_or(BlueprintError::Empty)?;
+                (host, com)
+            }
+        };
+
+        let module = self.modules.get_mut(&host).expect("host module exists");
+        let (pos, rot) = module.transform;
+        module.sensors.push(SensorMount {
+            sensor_type: SensorType::IMU,
+            local_position: rot.inverse() * (com - pos),
+            local_rotation: Quat::IDENTITY,
+            attachment: SensorAttachment::Module,
+            timing: SensorTiming::default(),
+        });
+        Ok(host)
+    }
+
+    /// World-space rest pose of a sensor mounted on module `id`.
+    ///
+    /// Returns `None` if the module does not exist, or if the sensor is
+    /// [`SensorAttachment::Joint`]-mounted and the module has no parent joint.
+    pub fn sensor_pose(&self, id: ModuleId, sensor: &SensorMount) -> Option<(Vec3, Quat)> {
+        let (pos, rot) = self.modules.get(&id)?.transform;
+        let origin = match sensor.attachment {
+            SensorAttachment::Module => Vec3::ZERO,
+            SensorAttachment::Joint => self.parent_joint(id)?.anchor_child,
+        };
+        Some((
+            pos + rot * (origin + sensor.local_position),
+            rot * sensor.local_rotation,
+        ))
+    }
+
+    /// Adds a marker called `name` to module `id` at `local_position` (in the module's
+    /// local space).
+    pub fn add_marker(
+        &mut self,
+        id: ModuleId,
+        name: impl Into<SmolStr>,
+        local_position: Vec3,
+    ) -> Result<(), BlueprintError> {
+        let module = self
+            .modules
+            .get_mut(&id)
+            .ok_or(BlueprintError::UnknownModule(id))?;
+        module.markers.push(Marker {
+            name: name.into(),
+            local_position,
+        });
+        Ok(())
+    }
+
+    /// Every marker with the module carrying it, in [topological](Self::modules_topological)
+    /// module order.
+    pub fn markers(&self) -> impl Iterator<Item = (ModuleId, &Marker)> {
+        self.modules_topological()
+            .flat_map(|(id, module)| module.markers.iter().map(move |m| (id, m)))
+    }
+
+    /// World-space rest position of every marker, in the order of
+    /// [`markers`](Self::markers). Feed a simulated pose of each module to
+    /// [`Marker::world_position`] instead to track the markers over a trajectory.
+    pub fn marker_positions(&self) -> Vec<(SmolStr, Vec3)> {
+        self.markers()
+            .map(|(id, m)| {
+                (
+                    m.name.clone(),
+                    m.world_position(self.modules[&id].transform),
+                )
+            })
+            .collect()
+    }
+
+    /// Refreshes the [`bounds`](RobotModule::bounds) of every module, e.g. after editing
+    /// shapes or transforms by hand.
+    pub fn update_bounds(&mut self) {
+        for module in self.modules.values_mut() {
+            module.update_bounds();
+        }
+    }
+
+    /// Restores the [`pivot_offset`](RobotModule::pivot_offset) of every module that lacks
+    /// one, as in blueprints serialized before the field existed: from the child anchor of
+    /// its parent joint, or for roots from the shape's base pivot under the recorded
+    /// [`capsule_length`](BlueprintMetadata::capsule_length) convention. A zero offset is
+    /// taken as missing, so a root sphere centred on its pivot gets the base pivot too.
+    pub fn fill_missing_pivot_offsets(&mut self) {
+        let convention = self.metadata.capsule_length;
+        let anchors: HashMap<ModuleId, Vec3> = self
+            .joints
+            .iter()
+            .map(|j| (j.child_id, j.anchor_child))
+            .collect();
+        for (id, module) in &mut self.modules {
+            if module.pivot_offset != Vec3::ZERO {
+                continue;
+            }
+            module.pivot_offset = match anchors.get(id) {
+                Some(&anchor) => -anchor,
+                None => -convention.base_pivot(&module.shape),
+            };
+        }
+    }
+
+    /// Derives the [`child_axis`](JointDefinition::child_axis) of every joint that lacks
+    /// one, as in blueprints serialized before the field existed, from its parent-frame
+    /// [`axis`](JointDefinition::axis) and the rest pose.
+    pub fn fill_missing_child_axes(&mut self) {
+        for joint in &mut self.joints {
+            if joint.child_axis != Vec3::ZERO {
+                continue;
+            }
+            let rot = |id: ModuleId| {
+                self.modules
+                    .get(&id)
+                    .map_or(Quat::IDENTITY, |m| m.transform.1)
+            };
+            joint.child_axis = rot(joint.child_id).inverse() * rot(joint.parent_id) * joint.axis;
+        }
+    }
+
+    /// A sphere enclosing every module's [`bounds`](RobotModule::bounds), for placing
+    /// robots in an arena without overlap, or `None` for an empty blueprint. Not the
+    /// smallest such sphere: it is centred on the box around the module spheres.
+    pub fn bounding_sphere(&self) -> Option<ModuleBounds> {
+        let spheres = self.modules.values().map(|m| m.bounds);
+        let (min, max) = spheres.clone().fold(None, |acc: Option<(Vec3, Vec3)>, s| {
+            let (lo, hi) = (s.center - s.radius, s.center + s.radius);
+            Some(acc.map_or((lo, hi), |(min, max)| (min.min(lo), max.max(hi))))
+        })?;
+        let center = (min + max) / 2.0;
+        let radius = spheres
+            .map(|s| center.distance(s.center) + s.radius)
+            .fold(0.0, f32::max);
+        Some(ModuleBounds { center, radius })
+    }
+
+    /// Modules whose [`bounds`](RobotModule::bounds) come within `margin` metres of
+    /// `query`, in ascending ID order. A broad phase: the shapes themselves may still be
+    /// apart.
+    pub fn modules_near(&self, query: &ModuleBounds, margin: f32) -> Vec<ModuleId> {
+        let mut ids: Vec<ModuleId> = self
+            .modules
+            .iter()
+            .filter(|(_, m)| m.bounds.intersects(query, margin))
+            .map(|(&id, _)| id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
     /// Compute the axis-aligned bounding box of the entire robot
     /// after applying `rotation` to the blueprint's rest pose.
     pub fn aabb(&self, rotation: Quat) -> Aabb3d {
@@ -72,47 +1004,305 @@ impl RobotBlueprint {
     }
 }
 
+/// Factor turning `shape_mass` (plus the unscaled `point_mass`) into `target * volume`.
+pub(crate) fn density_scale(
+    target: f32,
+    volume: f32,
+    shape_mass: f32,
+    point_mass: f32,
+) -> Option<f32> {
+    let scale = (target * volume - point_mass) / shape_mass;
+    (shape_mass > 0.0 && scale.is_finite() && scale > 0.0).then_some(scale)
+}
+
 /// A single rigid body segment of the robot.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RobotModule {
     /// The physical shape of this segment.
     pub shape: ShapePrimitive,
 
-    /// Mass in kg, computed from shape volume and density via `bevy_heavy`.
+    /// Mass in kg, computed from shape volume and density via `bevy_heavy`, plus any point
+    /// masses (such as actuators) carried by this module.
     pub mass: f32,
 
+    /// Center of mass in the module's local space. At the origin unless point masses
+    /// have been added with [`add_point_mass`](Self::add_point_mass).
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub center_of_mass: Vec3,
+
     /// Density in kg/m³ used to derive mass properties.
     pub density: f32,
 
+    /// Offset from the pivot, where the joint to the parent attaches, to the module origin,
+    /// in local space. Segments grow from their bottom face, so this is `(0, h/2, 0)` for a
+    /// segment of length `h` (see [`ShapePrimitive::base_pivot`]). Formats that place links
+    /// relative to their joints, such as URDF, need it to express the body frame. Loading
+    /// restores it for blueprints serialized before this field existed; see
+    /// [`RobotBlueprint::fill_missing_pivot_offsets`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub pivot_offset: Vec3,
+
     /// Material ID for visual rendering (links to external palette).
     pub material_id: MaterialId,
 
+    /// Derivation step (L-System iteration) the module appeared in, as set by
+    /// [`RobotOp::SetGrowthStage`](crate::RobotOp::SetGrowthStage).
+    #[serde(default)]
+    pub growth_stage: u32,
+
     /// Sensors attached directly to this module.
     pub sensors: Vec<SensorMount>,
 
+    /// Named marker points on this module, for comparison with motion-capture data.
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+
+    /// Direction-dependent friction, as set by
+    /// [`RobotOp::SetAnisotropicFriction`](crate::RobotOp::SetAnisotropicFriction).
+    /// `None` for the usual isotropic friction.
+    #[serde(default)]
+    pub anisotropic_friction: Option<AnisotropicFriction>,
+
     /// Initial World Transform (Position, Rotation) for the Rest Pose.
     /// Essential for stable physics initialization.
+    #[cfg_attr(feature = "schemars", schemars(with = "([f32; 3], [f32; 4])"))]
     pub transform: (Vec3, Quat),
+
+    /// World-space bounding sphere of the shape at the rest pose, precomputed for overlap
+    /// and placement queries. Derived from [`shape`](Self::shape) and
+    /// [`transform`](Self::transform) like [`mass`](Self::mass) is from the shape and
+    /// density: call [`update_bounds`](Self::update_bounds) after changing either.
+    #[serde(default)]
+    pub bounds: ModuleBounds,
+}
+
+impl RobotModule {
+    /// Creates a module with no sensors and material `0`, deriving its mass from `shape`
+    /// and `density`.
+    pub fn new(shape: ShapePrimitive, density: f32, transform: (Vec3, Quat)) -> Self {
+        let mut module = Self::massless(shape, density, transform);
+        module.update_mass();
+        module
+    }
+
+    /// Like [`new`](Self::new), but skips the mass computation: [`mass`](Self::mass) stays
+    /// zero until [`update_mass`](Self::update_mass) is called.
+    pub fn massless(shape: ShapePrimitive, density: f32, transform: (Vec3, Quat)) -> Self {
+        Self {
+            shape,
+            mass: 0.0,
+            center_of_mass: Vec3::ZERO,
+            density,
+            pivot_offset: -shape.base_pivot(),
+            material_id: 0,
+            growth_stage: 0,
+            sensors: Vec::new(),
+            markers: Vec::new(),
+            anisotropic_friction: None,
+            transform,
+            bounds: ModuleBounds::of(&shape, transform),
+        }
+    }
+
+    /// Recomputes [`bounds`](Self::bounds) from the shape and the rest pose.
+    pub fn update_bounds(&mut self) {
+        self.bounds = ModuleBounds::of(&self.shape, self.transform);
+    }
+
+    /// Recomputes [`mass`](Self::mass) from the shape and [`density`](Self::density) and
+    /// resets [`center_of_mass`](Self::center_of_mass) to the shape's centroid, discarding
+    /// any point masses.
+    pub fn update_mass(&mut self) {
+        self.mass = self.shape.to_bevy_primitive().mass(self.density);
+        self.center_of_mass = Vec3::ZERO;
+    }
+
+    /// Adds a point mass of `mass` kg at `local_position`, updating
+    /// [`mass`](Self::mass) and [`center_of_mass`](Self::center_of_mass).
+    pub fn add_point_mass(&mut self, mass: f32, local_position: Vec3) {
+        let total = self.mass + mass;
+        if total > 0.0 {
+            self.center_of_mass = (self.center_of_mass * self.mass + local_position * mass) / total;
+        }
+        self.mass = total;
+    }
+
+    /// Offset from the pivot to the center of mass, in local space.
+    pub fn pivot_to_center_of_mass(&self) -> Vec3 {
+        self.pivot_offset + self.center_of_mass
+    }
+
+    /// Center of mass in world space at the rest pose.
+    pub fn world_center_of_mass(&self) -> Vec3 {
+        let (pos, rot) = self.transform;
+        pos + rot * self.center_of_mass
+    }
+}
+
+/// A sphere enclosing a module at its rest pose, in world space (see
+/// [`RobotModule::bounds`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ModuleBounds {
+    /// Center of the sphere.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub center: Vec3,
+    /// Radius of the sphere.
+    pub radius: f32,
+}
+
+impl ModuleBounds {
+    /// The bounding sphere of `shape` at `transform` (position, rotation).
+    pub fn of(shape: &ShapePrimitive, (pos, rot): (Vec3, Quat)) -> Self {
+        let sphere = shape
+            .to_bevy_primitive()
+            .bounding_sphere(Isometry3d::new(pos, rot));
+        Self {
+            center: sphere.center.into(),
+            radius: sphere.radius(),
+        }
+    }
+
+    /// Returns `true` if the two spheres come within `margin` metres of each other.
+    pub fn intersects(&self, other: &Self, margin: f32) -> bool {
+        self.center.distance(other.center) <= self.radius + other.radius + margin
+    }
+}
+
+/// Friction that differs along one direction of a module's surface, like the scales of a
+/// snake that slide forward but grip sideways.
+///
+/// The module's friction coefficient (from its ground pair in
+/// [`RobotBlueprint::contacts`], or the engine default) applies across `direction`;
+/// along it, the coefficient is scaled by `ratio`. Exporters whose engine has
+/// anisotropic friction (SDF) write both coefficients; others fall back to
+/// [`isotropic`](Self::isotropic).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AnisotropicFriction {
+    /// Unit direction in the module's local space.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub direction: Vec3,
+    /// Friction along `direction` divided by friction across it.
+    pub ratio: f32,
+}
+
+impl AnisotropicFriction {
+    /// The coefficients `(along, across)` for a module whose isotropic coefficient is
+    /// `base`.
+    pub fn coefficients(&self, base: f32) -> (f32, f32) {
+        (base * self.ratio, base)
+    }
+
+    /// A single coefficient standing in for both, for engines without anisotropic
+    /// friction: their mean.
+    pub fn isotropic(&self, base: f32) -> f32 {
+        base * (1.0 + self.ratio) / 2.0
+    }
+}
+
+/// Where [`RobotBlueprint::mount_imu_at_com`] places an IMU.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComTarget {
+    /// The center of mass of a single module.
+    Module(ModuleId),
+    /// The center of mass of the whole robot.
+    Robot,
 }
 
 /// Supported geometric primitives for robot segments.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ShapePrimitive {
     /// A box defined by half-extents (x, y, z).
-    Box(Vec3),
+    Box(#[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))] Vec3),
     /// A cylinder defined by radius and height (aligned along Y axis).
     Cylinder { radius: f32, height: f32 },
     /// A sphere defined by radius.
     Sphere(f32),
-    /// A capsule defined by radius and height (aligned along Y axis).
+    /// A capsule defined by radius and height (aligned along Y axis). `height` is the
+    /// length of the cylindrical section; the hemispherical caps add `radius` at each end.
     Capsule { radius: f32, height: f32 },
 }
 
+/// What the length of a capsule measures. Engines disagree: Bevy, Avian, Rapier, MuJoCo,
+/// SDF and USD size capsules by their cylindrical section, Unity and Godot from tip to
+/// tip.
+///
+/// [`ShapePrimitive::Capsule`] always stores the section; this only changes how a length
+/// is read or reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum CapsuleLength {
+    /// The cylindrical section, caps excluded.
+    #[default]
+    Section,
+    /// The whole capsule, from one tip to the other.
+    TipToTip,
+}
+
+impl CapsuleLength {
+    /// The cylindrical section of a capsule of `radius` measuring `length` in this
+    /// convention. Tip-to-tip lengths shorter than the diameter give a sphere-like capsule
+    /// with no section.
+    pub fn section(self, length: f32, radius: f32) -> f32 {
+        match self {
+            Self::Section => length,
+            Self::TipToTip => (length - 2.0 * radius).max(0.0),
+        }
+    }
+
+    /// The length of a capsule with cylindrical section `height`, in this convention.
+    pub fn length(self, height: f32, radius: f32) -> f32 {
+        match self {
+            Self::Section => height,
+            Self::TipToTip => height + 2.0 * radius,
+        }
+    }
+
+    /// Where a segment of `shape` grown with this convention attaches to its parent: the
+    /// bottom tip of a capsule measured tip to tip, and
+    /// [`ShapePrimitive::base_pivot`] otherwise.
+    pub fn base_pivot(self, shape: &ShapePrimitive) -> Vec3 {
+        match (self, *shape) {
+            (Self::TipToTip, ShapePrimitive::Capsule { radius, height }) => {
+                Vec3::new(0.0, -(height / 2.0 + radius), 0.0)
+            }
+            _ => shape.base_pivot(),
+        }
+    }
+}
+
+impl fmt::Display for ShapePrimitive {
+    /// Formats the shape with full (not half) dimensions, e.g. `Box(0.20 x 1.00 x 0.20)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Box(h) => write!(
+                f,
+                "Box({:.2} x {:.2} x {:.2})",
+                h.x * 2.0,
+                h.y * 2.0,
+                h.z * 2.0
+            ),
+            Self::Cylinder { radius, height } => {
+                write!(f, "Cylinder(r {radius:.2}, h {height:.2})")
+            }
+            Self::Sphere(r) => write!(f, "Sphere(r {r:.2})"),
+            Self::Capsule { radius, height } => {
+                write!(f, "Capsule(r {radius:.2}, h {height:.2})")
+            }
+        }
+    }
+}
+
 /// A type-erased wrapper around `bevy_math` primitives.
 ///
-/// Enables calling [`bevy_heavy::ComputeMassProperties3d`] and
-/// [`bevy_math::bounding::Bounded3d`] on any [`ShapePrimitive`] variant through a
-/// single enum dispatch. Obtain one via [`ShapePrimitive::to_bevy_primitive`].
+/// Enables calling [`bevy_heavy::ComputeMassProperties3d`],
+/// [`bevy_math::bounding::Bounded3d`], and [`bevy_math::primitives::Measured3d`] on any
+/// [`ShapePrimitive`] variant through a single enum dispatch. Obtain one via [`ShapePrimitive::to_bevy_primitive`].
 #[derive(Clone, Copy, Debug)]
 pub enum BevyPrimitive {
     Cuboid(Cuboid),
@@ -170,6 +1360,26 @@ impl Bounded3d for BevyPrimitive {
     }
 }
 
+impl Measured3d for BevyPrimitive {
+    fn area(&self) -> f32 {
+        match self {
+            Self::Cuboid(s) => s.area(),
+            Self::Cylinder(s) => s.area(),
+            Self::Sphere(s) => s.area(),
+            Self::Capsule(s) => s.area(),
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        match self {
+            Self::Cuboid(s) => s.volume(),
+            Self::Cylinder(s) => s.volume(),
+            Self::Sphere(s) => s.volume(),
+            Self::Capsule(s) => s.volume(),
+        }
+    }
+}
+
 impl ShapePrimitive {
     /// Convert to the corresponding `bevy_math` primitive for mass-property computation.
     pub fn to_bevy_primitive(self) -> BevyPrimitive {
@@ -186,10 +1396,121 @@ impl ShapePrimitive {
             }
         }
     }
+
+    /// The length parameter along local Y: the full height of a box, the `height` of a
+    /// cylinder or capsule (a capsule's caps come on top), or the diameter of a sphere.
+    pub fn height(&self) -> f32 {
+        match *self {
+            Self::Box(h) => h.y * 2.0,
+            Self::Cylinder { height, .. } | Self::Capsule { height, .. } => height,
+            Self::Sphere(r) => r * 2.0,
+        }
+    }
+
+    /// Sets the length parameter read by [`height`](Self::height). For a sphere this sets
+    /// its diameter.
+    pub fn set_height(&mut self, value: f32) {
+        match self {
+            Self::Box(h) => h.y = value / 2.0,
+            Self::Cylinder { height, .. } | Self::Capsule { height, .. } => *height = value,
+            Self::Sphere(r) => *r = value / 2.0,
+        }
+    }
+
+    /// The radius of a round shape, or `None` for a box.
+    pub fn radius(&self) -> Option<f32> {
+        match *self {
+            Self::Box(_) => None,
+            Self::Cylinder { radius, .. } | Self::Capsule { radius, .. } | Self::Sphere(radius) => {
+                Some(radius)
+            }
+        }
+    }
+
+    /// Sets the radius of a round shape. Boxes are left unchanged.
+    pub fn set_radius(&mut self, value: f32) {
+        match self {
+            Self::Box(_) => {}
+            Self::Cylinder { radius, .. } | Self::Capsule { radius, .. } | Self::Sphere(radius) => {
+                *radius = value
+            }
+        }
+    }
+
+    /// Half-size of the shape's local bounding box, caps included.
+    pub fn half_extents(&self) -> Vec3 {
+        match *self {
+            Self::Box(h) => h,
+            Self::Cylinder { radius, height } => Vec3::new(radius, height / 2.0, radius),
+            Self::Sphere(r) => Vec3::splat(r),
+            Self::Capsule { radius, height } => Vec3::new(radius, height / 2.0 + radius, radius),
+        }
+    }
+
+    /// Volume in m³.
+    pub fn volume(&self) -> f32 {
+        self.to_bevy_primitive().volume()
+    }
+
+    /// The center of the bottom face (the bottom pole for spheres) in local space: where a
+    /// segment grown along local Y starts, and where the interpreter attaches its parent
+    /// joint.
+    pub fn base_pivot(&self) -> Vec3 {
+        Vec3::new(0.0, -self.height() / 2.0, 0.0)
+    }
+
+    /// Returns `true` if `point` (in the shape's local space) lies inside or on the shape.
+    pub fn contains_local(&self, point: Vec3) -> bool {
+        match *self {
+            Self::Box(h) => point.abs().cmple(h).all(),
+            Self::Cylinder { radius, height } => {
+                point.y.abs() <= height / 2.0
+                    && point.x * point.x + point.z * point.z <= radius * radius
+            }
+            Self::Sphere(r) => point.length_squared() <= r * r,
+            Self::Capsule { radius, height } => {
+                let y = point.y.clamp(-height / 2.0, height / 2.0);
+                (point - Vec3::new(0.0, y, 0.0)).length_squared() <= radius * radius
+            }
+        }
+    }
+
+    /// Outward unit normal of the face nearest to `point` (in the shape's local space).
+    ///
+    /// Returns `None` where the nearest face is ambiguous, such as at the center of a sphere.
+    pub fn surface_normal_local(&self, point: Vec3) -> Option<Vec3> {
+        match *self {
+            Self::Box(h) => {
+                let scaled = (point / h).abs();
+                let axis = if scaled.x >= scaled.y && scaled.x >= scaled.z {
+                    Vec3::X
+                } else if scaled.y >= scaled.z {
+                    Vec3::Y
+                } else {
+                    Vec3::Z
+                };
+                (point.dot(axis) != 0.0).then(|| axis * point.dot(axis).signum())
+            }
+            Self::Cylinder { radius, height } => {
+                let radial = Vec3::new(point.x, 0.0, point.z);
+                if point.y.abs() / (height / 2.0) >= radial.length() / radius {
+                    (point.y != 0.0).then(|| Vec3::Y * point.y.signum())
+                } else {
+                    radial.try_normalize()
+                }
+            }
+            Self::Sphere(_) => point.try_normalize(),
+            Self::Capsule { height, .. } => {
+                let y = point.y.clamp(-height / 2.0, height / 2.0);
+                (point - Vec3::new(0.0, y, 0.0)).try_normalize()
+            }
+        }
+    }
 }
 
 /// A kinematic connection between two modules.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JointDefinition {
     /// The parent module (the one closer to the root).
     pub parent_id: ModuleId,
@@ -198,23 +1519,122 @@ pub struct JointDefinition {
     pub child_id: ModuleId,
 
     /// The anchor point on the parent module, in parent's local space.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
     pub anchor_parent: Vec3,
 
     /// The anchor point on the child module, in child's local space.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
     pub anchor_child: Vec3,
 
     /// The type of mechanical connection.
     pub joint_type: JointType,
 
     /// The axis of rotation/translation in the Parent's local space.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
     pub axis: Vec3,
 
+    /// The same axis in the Child's local space, for engines that define joint axes in the
+    /// child frame. Stored rather than derived from the rest pose so both conventions stay
+    /// exact. Loading fills it in for blueprints serialized before this field existed; see
+    /// [`RobotBlueprint::fill_missing_child_axes`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub child_axis: Vec3,
+
+    /// Joint position at the rest pose, in metres along `axis`. Only prismatic joints use it:
+    /// at rest the child's anchor sits `rest_displacement * axis` away from the parent's.
+    #[serde(default)]
+    pub rest_displacement: f32,
+
     /// Physical limits of the joint.
     pub limits: Option<JointLimit>,
+
+    /// Gearing between the actuator and the joint, if modelled.
+    #[serde(default)]
+    pub transmission: Option<Transmission>,
+
+    /// The physical actuator driving this joint, if modelled.
+    #[serde(default)]
+    pub actuator: Option<Actuator>,
 }
 
-/// Types of mechanical joints.
+impl JointDefinition {
+    /// Delay in seconds between a command and the actuator responding; `0` if unmodelled.
+    pub fn actuation_latency(&self) -> f32 {
+        self.actuator
+            .as_ref()
+            .and_then(|a| a.latency)
+            .unwrap_or(0.0)
+    }
+
+    /// Total free play at the joint: the transmission's backlash plus the actuator's own
+    /// backlash reduced through the gear ratio.
+    pub fn effective_backlash(&self) -> f32 {
+        let transmission = self.transmission.unwrap_or_default();
+        let actuator = self
+            .actuator
+            .as_ref()
+            .and_then(|a| a.backlash)
+            .unwrap_or(0.0);
+        let reduced = if transmission.gear_ratio > 0.0 {
+            actuator / transmission.gear_ratio
+        } else {
+            actuator
+        };
+        transmission.backlash + reduced
+    }
+}
+
+/// A fixed connection between a module and the world, the one kind of joint with no
+/// parent module.
+///
+/// The module is held at its rest pose, as for turrets, cranes or plants that stay rooted
+/// in place while their limbs move.
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WorldJoint {
+    /// The welded module.
+    pub child_id: ModuleId,
+    /// The weld point on the module, in its local space.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub anchor_child: Vec3,
+    /// The weld point in world space at the rest pose.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub anchor_world: Vec3,
+}
+
+/// What a joint has to move, as computed by [`RobotBlueprint::joint_load`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct JointLoad {
+    /// Total mass of the moved subtree in kg.
+    pub mass: f32,
+    /// Distance in metres from the joint anchor to the subtree's center of mass.
+    pub lever: f32,
+    /// Rotational inertia of the subtree about the joint axis through the anchor, in kg·m².
+    pub inertia: f32,
+}
+
+impl JointLoad {
+    /// Effort and velocity limits sized to this load, or `None` for a massless load.
+    ///
+    /// The effort is twice what it takes to hold the subtree against gravity with the lever
+    /// horizontal (a force of `2·m·g` for prismatic joints); the velocity is the speed
+    /// reached by accelerating the subtree at full effort over one radian (one metre for
+    /// prismatic joints). Large limbs get strong, slow joints and small limbs weak, fast
+    /// ones.
+    pub fn scaled_actuation(&self, joint_type: JointType) -> Option<(f32, f32)> {
+        const GRAVITY: f32 = 9.81;
+        let (effort, inertia) = match joint_type {
+            JointType::Prismatic => (2.0 * self.mass * GRAVITY, self.mass),
+            _ => (2.0 * self.mass * GRAVITY * self.lever, self.inertia),
+        };
+        (effort > 0.0 && inertia > 0.0).then(|| (effort, (2.0 * effort / inertia).sqrt()))
+    }
+}
+
+/// Types of mechanical joints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum JointType {
     /// Fixed connection (welded).
     Fixed,
@@ -226,8 +1646,23 @@ pub enum JointType {
     Prismatic,
 }
 
+impl JointType {
+    /// Number of degrees of freedom the joint leaves between its modules.
+    pub fn degrees_of_freedom(self) -> usize {
+        match self {
+            Self::Fixed => 0,
+            Self::Hinge | Self::Prismatic => 1,
+            Self::Ball => 3,
+        }
+    }
+}
+
 /// Limits for a joint's motion.
+///
+/// For [`JointType::Ball`] joints, `min`/`max` bound the twist about the joint axis and
+/// [`cone`](Self::cone) bounds the swing away from it.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JointLimit {
     /// Minimum angle (radians) or distance (meters).
     pub min: f32,
@@ -237,23 +1672,229 @@ pub struct JointLimit {
     pub effort: f32,
     /// Maximum velocity (rad/s or m/s).
     pub velocity: f32,
+    /// Swing/twist cone of a ball joint.
+    #[serde(default)]
+    pub cone: Option<ConeLimit>,
+}
+
+/// Swing/twist limits of a ball joint, as half-angles in radians.
+///
+/// The swing limits describe an elliptical cone around the joint axis: `swing1` about the
+/// first perpendicular axis and `swing2` about the second. `twist` bounds rotation about
+/// the joint axis itself, symmetrically.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConeLimit {
+    /// Maximum swing about the first perpendicular axis.
+    pub swing1: f32,
+    /// Maximum swing about the second perpendicular axis.
+    pub swing2: f32,
+    /// Maximum twist about the joint axis.
+    pub twist: f32,
+}
+
+impl ConeLimit {
+    /// Returns `true` if a pose with the given swing components and twist lies within the
+    /// limits.
+    pub fn allows(&self, swing1: f32, swing2: f32, twist: f32) -> bool {
+        let ratio = |angle: f32, limit: f32| {
+            if limit > 0.0 {
+                angle / limit
+            } else if angle == 0.0 {
+                0.0
+            } else {
+                f32::INFINITY
+            }
+        };
+        let (a, b) = (ratio(swing1, self.swing1), ratio(swing2, self.swing2));
+        a * a + b * b <= 1.0 && twist.abs() <= self.twist
+    }
+}
+
+/// A mechanical transmission between an actuator and its joint (gearbox, belt, lead screw).
+///
+/// [`JointLimit::effort`] and [`JointLimit::velocity`] describe the joint side; the
+/// transmission relates them to the actuator side, which is what URDF `<transmission>`
+/// elements and hardware-realistic simulations need.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Transmission {
+    /// Actuator revolutions per joint revolution (reduction ratio, e.g. `100.0` for 100:1).
+    pub gear_ratio: f32,
+    /// Fraction of actuator power delivered to the joint, in `[0, 1]`.
+    pub efficiency: f32,
+    /// Free play at the joint in radians (or metres for prismatic joints).
+    pub backlash: f32,
+}
+
+impl Default for Transmission {
+    /// A direct drive: 1:1, lossless, no backlash.
+    fn default() -> Self {
+        Self {
+            gear_ratio: 1.0,
+            efficiency: 1.0,
+            backlash: 0.0,
+        }
+    }
+}
+
+impl Transmission {
+    /// Joint-side torque (or force) produced by `actuator_effort` on the actuator side.
+    pub fn joint_effort(&self, actuator_effort: f32) -> f32 {
+        actuator_effort * self.gear_ratio * self.efficiency
+    }
+
+    /// Joint-side velocity produced by `actuator_velocity` on the actuator side.
+    pub fn joint_velocity(&self, actuator_velocity: f32) -> f32 {
+        if self.gear_ratio == 0.0 {
+            0.0
+        } else {
+            actuator_velocity / self.gear_ratio
+        }
+    }
+}
+
+/// The physical motor that drives a joint.
+///
+/// Its mass sits at the joint location and is carried by the parent module: the interpreter
+/// adds it to the parent as a point mass at [`JointDefinition::anchor_parent`] (see
+/// [`RobotModule::add_point_mass`]). Ignoring motor mass systematically favours designs with
+/// many tiny joints.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Actuator {
+    /// Catalog model name, if the actuator comes from a preset (see [`crate::servo`]).
+    pub model: Option<String>,
+    /// Actuator mass in kg.
+    pub mass: f32,
+    /// Housing geometry, centred on the joint anchor in the parent's frame.
+    pub housing: Option<ShapePrimitive>,
+    /// Command-to-motion delay in seconds, if modelled.
+    #[serde(default)]
+    pub latency: Option<f32>,
+    /// Free play at the actuator's output shaft in radians (metres for linear actuators),
+    /// if modelled.
+    #[serde(default)]
+    pub backlash: Option<f32>,
 }
 
 /// A sensor attachment point.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SensorMount {
     /// Type of sensor (Camera, Lidar, Touch, IMU).
     pub sensor_type: SensorType,
 
-    /// Position relative to the module's center.
+    /// Position relative to the attachment frame.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
     pub local_position: Vec3,
 
-    /// Orientation relative to the module.
+    /// Orientation relative to the attachment frame.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 4]"))]
     pub local_rotation: Quat,
+
+    /// The frame the sensor is mounted in.
+    #[serde(default)]
+    pub attachment: SensorAttachment,
+
+    /// When readings are taken and how late they arrive.
+    #[serde(default)]
+    pub timing: SensorTiming,
+}
+
+/// A named point fixed to a module, standing in for a reflective motion-capture marker.
+///
+/// Added by [`RobotOp::PlaceMarker`](crate::RobotOp::PlaceMarker) or
+/// [`RobotBlueprint::add_marker`]. Exporters write markers as sites or frames, so a
+/// simulator can report their trajectories under the same names as the capture system.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Marker {
+    /// The marker's label, matching the one used in the capture data.
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub name: SmolStr,
+
+    /// Position in the module's local space.
+    #[cfg_attr(feature = "schemars", schemars(with = "[f32; 3]"))]
+    pub local_position: Vec3,
+}
+
+impl Marker {
+    /// The marker's world position when its module is at `pose` (position, rotation).
+    pub fn world_position(&self, (pos, rot): (Vec3, Quat)) -> Vec3 {
+        pos + rot * self.local_position
+    }
+}
+
+/// Observation timing and noise of a sensor.
+///
+/// Engine adapters use this to decide on which physics steps a sensor produces a reading,
+/// when that reading becomes visible to the controller and how much noise it carries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SensorTiming {
+    /// Readings per second, or `None` to sample on every physics step.
+    #[serde(default)]
+    pub update_rate: Option<f32>,
+    /// Delay in seconds between taking a reading and it being available.
+    #[serde(default)]
+    pub latency: f32,
+    /// Standard deviation of the zero-mean Gaussian noise on every value of a reading, in
+    /// the reading's own units; `0` for a noiseless sensor.
+    #[serde(default)]
+    pub noise: f32,
+}
+
+impl SensorTiming {
+    /// Seconds between readings, or `None` if the sensor samples every step.
+    pub fn period(&self) -> Option<f32> {
+        self.update_rate
+            .filter(|&r| r > 0.0 && r.is_finite())
+            .map(|r| 1.0 / r)
+    }
+
+    /// Whether a reading is taken on physics step `step` of a simulation advancing `dt`
+    /// seconds per step. The first step always samples.
+    pub fn samples_on_step(&self, step: u64, dt: f32) -> bool {
+        let Some(period) = self.period() else {
+            return true;
+        };
+        if step == 0 {
+            return true;
+        }
+        let now = (step as f64 * dt as f64 / period as f64).floor();
+        let before = ((step - 1) as f64 * dt as f64 / period as f64).floor();
+        now > before
+    }
+
+    /// Number of whole physics steps a reading is delayed by.
+    pub fn latency_steps(&self, dt: f32) -> u64 {
+        if dt > 0.0 {
+            (self.latency.max(0.0) / dt).round() as u64
+        } else {
+            0
+        }
+    }
+}
+
+/// The frame a [`SensorMount`] is expressed in.
+///
+/// Sensors are always stored on a module. Joint-mounted sensors (encoders, torque cells)
+/// live on the child module of the joint they measure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SensorAttachment {
+    /// The owning module's frame, with the origin at its centre.
+    #[default]
+    Module,
+    /// The frame of the joint connecting the owning module to its parent: the module's
+    /// orientation, with the origin at the joint's child anchor.
+    Joint,
 }
 
 /// The kind of sensor mounted on a module.
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum SensorType {
     /// RGB or depth camera.
     Camera,
@@ -265,4 +1906,92 @@ pub enum SensorType {
     IMU,
     /// Ultrasonic distance sensor.
     Ultrasonic,
+    /// Joint position encoder.
+    Encoder,
+    /// Joint force/torque sensor.
+    ForceTorque,
+    /// Depth camera (structured light, stereo or time-of-flight).
+    DepthCamera(DepthCameraParams),
+    /// Thermal (long-wave infrared) camera.
+    Thermal(ThermalParams),
+    /// Any other modality, identified by name. Lets downstream code introduce new sensors
+    /// without changes to this crate.
+    Custom(#[cfg_attr(feature = "schemars", schemars(with = "String"))] SmolStr),
+}
+
+impl SensorType {
+    /// Short name of the sensor kind: the variant name, or the name of a
+    /// [`Custom`](Self::Custom) sensor.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Camera => "Camera",
+            Self::Lidar => "Lidar",
+            Self::Touch => "Touch",
+            Self::IMU => "IMU",
+            Self::Ultrasonic => "Ultrasonic",
+            Self::Encoder => "Encoder",
+            Self::ForceTorque => "ForceTorque",
+            Self::DepthCamera(_) => "DepthCamera",
+            Self::Thermal(_) => "Thermal",
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for SensorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Intrinsics of a [`SensorType::DepthCamera`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DepthCameraParams {
+    /// Vertical field of view in radians.
+    pub fov_y: f32,
+    /// Image size in pixels, `[width, height]`.
+    pub resolution: [u32; 2],
+    /// Closest measurable depth in metres.
+    pub min_range: f32,
+    /// Farthest measurable depth in metres.
+    pub max_range: f32,
+}
+
+impl Default for DepthCameraParams {
+    /// A typical consumer RGB-D camera: 640 × 480, 58° vertical FOV, 0.1–10 m.
+    fn default() -> Self {
+        Self {
+            fov_y: 58f32.to_radians(),
+            resolution: [640, 480],
+            min_range: 0.1,
+            max_range: 10.0,
+        }
+    }
+}
+
+/// Intrinsics of a [`SensorType::Thermal`] camera.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ThermalParams {
+    /// Vertical field of view in radians.
+    pub fov_y: f32,
+    /// Image size in pixels, `[width, height]`.
+    pub resolution: [u32; 2],
+    /// Lowest measurable temperature in °C.
+    pub min_temperature: f32,
+    /// Highest measurable temperature in °C.
+    pub max_temperature: f32,
+}
+
+impl Default for ThermalParams {
+    /// A small uncooled microbolometer: 160 × 120, 43° vertical FOV, −10–400 °C.
+    fn default() -> Self {
+        Self {
+            fov_y: 43f32.to_radians(),
+            resolution: [160, 120],
+            min_temperature: -10.0,
+            max_temperature: 400.0,
+        }
+    }
 }
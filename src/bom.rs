@@ -0,0 +1,178 @@
+//! Bill-of-materials reports for physically building a blueprint.
+//!
+//! [`RobotBlueprint::bom`] tallies every distinct part — structural segments grouped by
+//! shape and size, joints by type, actuators by model, and sensors by kind.
+//! [`RobotBlueprint::bom_with_costs`] additionally prices each line from a [`CostCatalog`].
+
+use crate::blueprint::RobotBlueprint;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// The kind of part a [`BomLine`] describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum BomCategory {
+    /// A structural segment; the item names the shape and its size class.
+    Segment,
+    /// A joint; the item names the joint type.
+    Joint,
+    /// A motor driving a joint; the item names the model (or `"custom"`).
+    Actuator,
+    /// A sensor; the item names the sensor type.
+    Sensor,
+}
+
+/// One line of a [`BillOfMaterials`]: a distinct part and how many are needed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BomLine {
+    /// Kind of part.
+    pub category: BomCategory,
+    /// Part description, e.g. `Box(0.20 x 1.00 x 0.20)` or `MG996R`.
+    pub item: String,
+    /// Number of parts required.
+    pub quantity: usize,
+    /// Price per part, if the catalog lists one.
+    pub unit_cost: Option<f32>,
+}
+
+impl BomLine {
+    /// `quantity × unit_cost`, if the unit cost is known.
+    pub fn line_cost(&self) -> Option<f32> {
+        self.unit_cost.map(|c| c * self.quantity as f32)
+    }
+}
+
+/// A parts list for a blueprint, sorted by category then item.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BillOfMaterials {
+    /// One line per distinct part.
+    pub lines: Vec<BomLine>,
+}
+
+impl BillOfMaterials {
+    /// Total number of parts of `category`.
+    pub fn count(&self, category: BomCategory) -> usize {
+        self.lines
+            .iter()
+            .filter(|l| l.category == category)
+            .map(|l| l.quantity)
+            .sum()
+    }
+
+    /// Sum of all priced lines.
+    pub fn total_cost(&self) -> f32 {
+        self.lines.iter().filter_map(BomLine::line_cost).sum()
+    }
+
+    /// Lines the catalog had no price for.
+    pub fn unpriced(&self) -> impl Iterator<Item = &BomLine> {
+        self.lines.iter().filter(|l| l.unit_cost.is_none())
+    }
+}
+
+impl fmt::Display for BillOfMaterials {
+    /// Renders a plain-text table, one line per part, followed by the total cost.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            write!(
+                f,
+                "{:>4} x {:<10} {}",
+                line.quantity,
+                format!("{:?}", line.category),
+                line.item
+            )?;
+            if let Some(cost) = line.line_cost() {
+                write!(f, "  {cost:.2}")?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "total: {:.2}", self.total_cost())
+    }
+}
+
+/// Unit prices used by [`RobotBlueprint::bom_with_costs`].
+///
+/// A price listed for an exact item (e.g. `"MG996R"`) wins over the category default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostCatalog {
+    /// Price per unit of a specific item, keyed by [`BomLine::item`].
+    pub items: HashMap<String, f32>,
+    /// Fallback price per unit for every item of a category.
+    pub categories: HashMap<BomCategory, f32>,
+}
+
+impl CostCatalog {
+    /// Creates an empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the price of a specific item (builder pattern).
+    pub fn with_item(mut self, item: impl Into<String>, cost: f32) -> Self {
+        self.items.insert(item.into(), cost);
+        self
+    }
+
+    /// Sets the fallback price for a whole category (builder pattern).
+    pub fn with_category(mut self, category: BomCategory, cost: f32) -> Self {
+        self.categories.insert(category, cost);
+        self
+    }
+
+    /// Looks up the unit price of `item` in `category`.
+    pub fn price(&self, category: BomCategory, item: &str) -> Option<f32> {
+        self.items
+            .get(item)
+            .or_else(|| self.categories.get(&category))
+            .copied()
+    }
+}
+
+impl RobotBlueprint {
+    /// Tallies the parts needed to build this robot.
+    ///
+    /// Segments are grouped by shape and dimensions rounded to the centimetre, so parts
+    /// that would be cut from the same stock share a line.
+    pub fn bom(&self) -> BillOfMaterials {
+        let mut counts: BTreeMap<(BomCategory, String), usize> = BTreeMap::new();
+        let mut add = |category, item: String| *counts.entry((category, item)).or_default() += 1;
+
+        for module in self.modules.values() {
+            add(BomCategory::Segment, module.shape.to_string());
+            for sensor in &module.sensors {
+                add(BomCategory::Sensor, sensor.sensor_type.to_string());
+            }
+        }
+        for joint in &self.joints {
+            add(BomCategory::Joint, format!("{:?}", joint.joint_type));
+            if let Some(actuator) = &joint.actuator {
+                let model = actuator
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| "custom".to_string());
+                add(BomCategory::Actuator, model);
+            }
+        }
+
+        BillOfMaterials {
+            lines: counts
+                .into_iter()
+                .map(|((category, item), quantity)| BomLine {
+                    category,
+                    item,
+                    quantity,
+                    unit_cost: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Like [`bom`](Self::bom), with each line priced from `catalog`.
+    pub fn bom_with_costs(&self, catalog: &CostCatalog) -> BillOfMaterials {
+        let mut bom = self.bom();
+        for line in &mut bom.lines {
+            line.unit_cost = catalog.price(line.category, &line.item);
+        }
+        bom
+    }
+}
@@ -0,0 +1,114 @@
+//! Recommended self-collision exclusions.
+//!
+//! Physics engines usually test every pair of bodies for contact, but bodies sharing a
+//! joint touch by construction and siblings growing from the same parent often start out
+//! interpenetrating. [`RobotBlueprint::self_collision_exclusions`] lists the pairs that
+//! should not collide, for exporters to translate into `<disable_collisions>` entries or
+//! contact excludes.
+
+use crate::blueprint::{ModuleId, RobotBlueprint};
+use crate::parallel;
+use bevy_math::bounding::{Aabb3d, Bounded3d as _, BoundingVolume as _, IntersectsVolume as _};
+use bevy_math::{Isometry3d, Vec3A};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Why a pair of modules is excluded from self-collision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ExclusionReason {
+    /// The modules are connected by a joint.
+    Adjacent,
+    /// The modules share a parent and nearly overlap at the rest pose.
+    Overlapping,
+}
+
+/// A pair of modules that should not be tested for collision with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CollisionExclusion {
+    /// The module with the smaller ID.
+    pub a: ModuleId,
+    /// The module with the larger ID.
+    pub b: ModuleId,
+    /// Why the pair is excluded.
+    pub reason: ExclusionReason,
+}
+
+impl CollisionExclusion {
+    /// Creates an exclusion, ordering the IDs so that `a <= b`.
+    pub fn new(a: ModuleId, b: ModuleId, reason: ExclusionReason) -> Self {
+        Self {
+            a: a.min(b),
+            b: a.max(b),
+            reason,
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// Computes the pairs of modules that should not collide with each other.
+    ///
+    /// Every pair connected by a joint is [`Adjacent`](ExclusionReason::Adjacent). Pairs of
+    /// siblings (children of the same parent) whose rest-pose bounding spheres and bounding
+    /// boxes both come within `margin` metres of each other are
+    /// [`Overlapping`](ExclusionReason::Overlapping).
+    /// The result is sorted by `(a, b)` and holds each pair once; adjacency wins if a pair
+    /// qualifies for both.
+    pub fn self_collision_exclusions(&self, margin: f32) -> Vec<CollisionExclusion> {
+        let mut pairs: BTreeMap<(ModuleId, ModuleId), ExclusionReason> = BTreeMap::new();
+        let mut parents: HashMap<ModuleId, ModuleId> = HashMap::new();
+        for joint in &self.joints {
+            if !self.modules.contains_key(&joint.parent_id)
+                || !self.modules.contains_key(&joint.child_id)
+            {
+                continue;
+            }
+            let pair =
+                CollisionExclusion::new(joint.parent_id, joint.child_id, ExclusionReason::Adjacent);
+            pairs.insert((pair.a, pair.b), pair.reason);
+            parents.insert(joint.child_id, joint.parent_id);
+        }
+
+        let bounds = |id: ModuleId| -> Aabb3d {
+            let module = &self.modules[&id];
+            let (pos, rot) = module.transform;
+            module
+                .shape
+                .to_bevy_primitive()
+                .aabb_3d(Isometry3d::new(pos, rot))
+                .grow(Vec3A::splat(margin.max(0.0) / 2.0))
+        };
+        // The spatial index rules out distant pairs without visiting every sibling.
+        let index = self.spatial_index();
+        let children: Vec<(ModuleId, ModuleId)> = parents.iter().map(|(&c, &p)| (c, p)).collect();
+        let overlapping = parallel::map(&children, |_, &(first, parent)| {
+            let first_bounds = bounds(first);
+            index
+                .overlapping(&self.modules[&first].bounds, margin.max(0.0))
+                .into_iter()
+                .filter(|&second| {
+                    second > first
+                        && parents.get(&second) == Some(&parent)
+                        && first_bounds.intersects(&bounds(second))
+                })
+                .map(|second| (first, second))
+                .collect::<Vec<_>>()
+        });
+        for (first, second) in overlapping.into_iter().flatten() {
+            let pair = CollisionExclusion::new(first, second, ExclusionReason::Overlapping);
+            pairs.entry((pair.a, pair.b)).or_insert(pair.reason);
+        }
+
+        pairs
+            .into_iter()
+            .map(|((a, b), reason)| CollisionExclusion { a, b, reason })
+            .collect()
+    }
+
+    /// Replaces [`collision_exclusions`](Self::collision_exclusions) with a freshly
+    /// computed [`self_collision_exclusions`](Self::self_collision_exclusions).
+    pub fn update_collision_exclusions(&mut self, margin: f32) {
+        self.collision_exclusions = self.self_collision_exclusions(margin);
+    }
+}
@@ -0,0 +1,416 @@
+//! Controller scaffolding for evolved robots.
+//!
+//! [`RobotBlueprint::control_interface`] lists, with names, every entry of the observation
+//! and action vectors a joint-space controller for the robot sees: joint positions and
+//! velocities, sensor readings, and one command per degree of freedom. The resulting
+//! [`ControlInterface`] serializes to a JSON spec and renders Python or Rust stubs, so
+//! hooking a policy up to a freshly evolved robot does not involve reading its blueprint.
+//! [`ControlInterface::policy_spec`] flattens it further into the tensor names, shapes and
+//! per-element ranges a training framework or an ONNX export declares.
+
+use crate::blueprint::{JointType, ModuleId, RobotBlueprint, SensorType};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// One named entry of an observation or action vector.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControlChannel {
+    /// Stable name, e.g. `joint_0_1.position` or `imu_2_0.angular_velocity`.
+    pub name: String,
+    /// Module the channel belongs to: a joint's child, or the sensor's owner.
+    pub module_id: ModuleId,
+    /// Index of the joint in [`RobotBlueprint::joints`], for joint channels.
+    pub joint_index: Option<usize>,
+    /// Dimensions of the reading; empty if the blueprint does not fix them (cameras
+    /// without intrinsics, lidars and custom sensors).
+    pub shape: Vec<usize>,
+    /// Position of the channel in the flat vector, or `None` for images and channels of
+    /// unknown shape, which are passed alongside the vector.
+    pub offset: Option<usize>,
+    /// Range of the values, if known.
+    pub bounds: Option<(f32, f32)>,
+    /// Readings per second, or `None` if the channel updates on every physics step.
+    #[serde(default)]
+    pub update_rate: Option<f32>,
+    /// Delay in seconds between a reading being taken and the controller seeing it, or
+    /// for actions between a command and the actuator responding.
+    #[serde(default)]
+    pub latency: f32,
+    /// Standard deviation of the noise on each value, in the channel's units.
+    #[serde(default)]
+    pub noise: f32,
+    /// Free play of the commanded joint, in radians (metres for prismatic joints); `0`
+    /// for observations.
+    #[serde(default)]
+    pub backlash: f32,
+}
+
+impl ControlChannel {
+    /// Number of values in the channel.
+    pub fn len(&self) -> usize {
+        if self.shape.is_empty() {
+            0
+        } else {
+            self.shape.iter().product()
+        }
+    }
+
+    /// True iff the channel holds no values of known shape.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The observation and action layout of a robot, produced by
+/// [`RobotBlueprint::control_interface`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlInterface {
+    /// Observation channels: joint states in joint order, then sensors in topological
+    /// module order.
+    pub observations: Vec<ControlChannel>,
+    /// Action channels: one effort command per degree of freedom, in joint order.
+    pub actions: Vec<ControlChannel>,
+    /// Length of the flat observation vector.
+    pub observation_size: usize,
+    /// Length of the flat action vector.
+    pub action_size: usize,
+}
+
+/// A float32 tensor of a [`PolicySpec`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TensorSpec {
+    /// Tensor name: `observation`, `action`, or the channel name of an extra observation.
+    pub name: String,
+    /// Element type, always `float32`.
+    pub dtype: String,
+    /// Dimensions, led by a `-1` batch axis. Dimensions the blueprint does not fix are `-1`
+    /// as well.
+    pub shape: Vec<i64>,
+    /// Name of every element, in order: the channel name, indexed as `name[k]` for
+    /// channels of several values. Empty for extra observations.
+    pub names: Vec<String>,
+    /// Lower bound of every element, `None` where unbounded. Empty for extra observations.
+    pub low: Vec<Option<f32>>,
+    /// Upper bound of every element, `None` where unbounded. Empty for extra observations.
+    pub high: Vec<Option<f32>>,
+}
+
+/// The inputs and outputs of a policy for a robot, produced by
+/// [`ControlInterface::policy_spec`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PolicySpec {
+    /// The flat observation vector.
+    pub observation: TensorSpec,
+    /// The flat action vector.
+    pub action: TensorSpec,
+    /// Observations passed alongside the flat vector, such as images.
+    pub extra_observations: Vec<TensorSpec>,
+}
+
+impl PolicySpec {
+    /// The spec as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+impl TensorSpec {
+    /// The flat tensor called `name` holding the channels with an offset, which are
+    /// pushed in offset order.
+    fn flat(name: &str, channels: &[ControlChannel], size: usize) -> Self {
+        let mut spec = Self {
+            name: name.to_owned(),
+            dtype: "float32".to_owned(),
+            shape: vec![-1, size as i64],
+            names: Vec::with_capacity(size),
+            low: Vec::with_capacity(size),
+            high: Vec::with_capacity(size),
+        };
+        for channel in channels.iter().filter(|c| c.offset.is_some()) {
+            let len = channel.len();
+            for k in 0..len {
+                spec.names.push(match len {
+                    1 => channel.name.clone(),
+                    _ => format!("{}[{k}]", channel.name),
+                });
+                spec.low.push(channel.bounds.map(|b| b.0));
+                spec.high.push(channel.bounds.map(|b| b.1));
+            }
+        }
+        spec
+    }
+}
+
+impl RobotBlueprint {
+    /// Lists the observations and actions of a joint-space controller for this robot.
+    ///
+    /// Every non-fixed joint contributes its position and velocity (a quaternion and an
+    /// angular velocity for ball joints) and one effort command per degree of freedom, so
+    /// [`action_size`](ControlInterface::action_size) equals
+    /// [`metrics::dof_count`](crate::metrics::dof_count). Position bounds come from the
+    /// joint limits; velocity and effort bounds from their velocity and effort limits.
+    pub fn control_interface(&self) -> ControlInterface {
+        let mut interface = ControlInterface::default();
+        for (index, joint) in self.joints.iter().enumerate() {
+            let dof = joint.joint_type.degrees_of_freedom();
+            if dof == 0 {
+                continue;
+            }
+            let prefix = format!("joint_{}_{}", joint.parent_id, joint.child_id);
+            let limits = joint.limits;
+            let position = match joint.joint_type {
+                JointType::Ball => (vec![4], Some((-1.0, 1.0))),
+                _ => (vec![1], limits.map(|l| (l.min, l.max))),
+            };
+            let symmetric = |v: f32| (-v, v);
+            for (quantity, (shape, bounds)) in [
+                ("position", position),
+                (
+                    "velocity",
+                    (vec![dof], limits.map(|l| symmetric(l.velocity))),
+                ),
+            ] {
+                interface.push_observation(ControlChannel {
+                    name: format!("{prefix}.{quantity}"),
+                    module_id: joint.child_id,
+                    joint_index: Some(index),
+                    shape,
+                    offset: None,
+                    bounds,
+                    update_rate: None,
+                    latency: 0.0,
+                    noise: 0.0,
+                    backlash: 0.0,
+                });
+            }
+            interface.push_action(ControlChannel {
+                name: format!("{prefix}.effort"),
+                module_id: joint.child_id,
+                joint_index: Some(index),
+                shape: vec![dof],
+                offset: None,
+                bounds: limits.map(|l| symmetric(l.effort)),
+                update_rate: None,
+                latency: joint.actuation_latency(),
+                noise: 0.0,
+                backlash: joint.effective_backlash(),
+            });
+        }
+
+        for (id, module) in self.modules_topological() {
+            for (i, sensor) in module.sensors.iter().enumerate() {
+                let label = format!("{}_{id}_{i}", sensor.sensor_type.name().to_lowercase());
+                let joint_index = self.joints.iter().position(|j| j.child_id == id);
+                for (quantity, shape) in sensor_readings(&sensor.sensor_type) {
+                    let name = match quantity {
+                        "" => label.clone(),
+                        q => format!("{label}.{q}"),
+                    };
+                    interface.push_observation(ControlChannel {
+                        name,
+                        module_id: id,
+                        joint_index: joint_index
+                            .filter(|_| matches!(sensor.sensor_type, SensorType::Encoder)),
+                        shape,
+                        offset: None,
+                        bounds: None,
+                        update_rate: sensor.timing.update_rate,
+                        latency: sensor.timing.latency,
+                        noise: sensor.timing.noise,
+                        backlash: 0.0,
+                    });
+                }
+            }
+        }
+        interface
+    }
+}
+
+/// The readings a sensor produces, as `(quantity, shape)`; an empty quantity names the
+/// sensor's only reading.
+fn sensor_readings(sensor: &SensorType) -> Vec<(&'static str, Vec<usize>)> {
+    match sensor {
+        SensorType::Touch | SensorType::Ultrasonic | SensorType::Encoder => vec![("", vec![1])],
+        SensorType::IMU => vec![
+            ("orientation", vec![4]),
+            ("angular_velocity", vec![3]),
+            ("linear_acceleration", vec![3]),
+        ],
+        SensorType::ForceTorque => vec![("force", vec![3]), ("torque", vec![3])],
+        SensorType::DepthCamera(p) => {
+            vec![(
+                "depth",
+                vec![p.resolution[1] as usize, p.resolution[0] as usize],
+            )]
+        }
+        SensorType::Thermal(p) => vec![(
+            "temperature",
+            vec![p.resolution[1] as usize, p.resolution[0] as usize],
+        )],
+        SensorType::Camera | SensorType::Lidar | SensorType::Custom(_) => vec![("", vec![])],
+    }
+}
+
+impl ControlInterface {
+    /// Appends an observation, placing it in the flat vector if it is one-dimensional.
+    fn push_observation(&mut self, mut channel: ControlChannel) {
+        if channel.shape.len() == 1 {
+            channel.offset = Some(self.observation_size);
+            self.observation_size += channel.len();
+        }
+        self.observations.push(channel);
+    }
+
+    fn push_action(&mut self, mut channel: ControlChannel) {
+        channel.offset = Some(self.action_size);
+        self.action_size += channel.len();
+        self.actions.push(channel);
+    }
+
+    /// The interface as a pretty-printed JSON spec.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// The interface as tensors: a flat `observation` and `action` with a name and range
+    /// per element, plus one tensor per observation kept out of the flat vector.
+    ///
+    /// Element names come from the channel names, which derive from module IDs. Those are
+    /// assigned in interpretation order, so adding or removing a module renames the
+    /// channels of every module interpreted after it.
+    pub fn policy_spec(&self) -> PolicySpec {
+        let extra_observations = self
+            .observations
+            .iter()
+            .filter(|c| c.offset.is_none())
+            .map(|c| TensorSpec {
+                name: c.name.clone(),
+                dtype: "float32".to_owned(),
+                shape: match c.shape.as_slice() {
+                    [] => vec![-1, -1],
+                    dims => std::iter::once(-1)
+                        .chain(dims.iter().map(|&d| d as i64))
+                        .collect(),
+                },
+                names: Vec::new(),
+                low: Vec::new(),
+                high: Vec::new(),
+            })
+            .collect();
+        PolicySpec {
+            observation: TensorSpec::flat("observation", &self.observations, self.observation_size),
+            action: TensorSpec::flat("action", &self.actions, self.action_size),
+            extra_observations,
+        }
+    }
+
+    /// A dependency-free Python module with the vector layout and a `Controller` class
+    /// whose `act` returns zero commands, ready to be filled in with a policy.
+    pub fn python_stub(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "\"\"\"Controller scaffold generated by symbios-robot.\"\"\""
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(out, "OBSERVATION_SIZE = {}", self.observation_size);
+        let _ = writeln!(out, "ACTION_SIZE = {}", self.action_size);
+        let _ = writeln!(out);
+        for (constant, channels) in [
+            ("OBSERVATIONS", &self.observations),
+            ("ACTIONS", &self.actions),
+        ] {
+            let _ = writeln!(out, "# name: (offset, length) in the flat vector");
+            let _ = writeln!(out, "{constant} = {{");
+            for c in channels {
+                if let Some(offset) = c.offset {
+                    let _ = writeln!(out, "    \"{}\": ({offset}, {}),", c.name, c.len());
+                }
+            }
+            let _ = writeln!(out, "}}");
+            let _ = writeln!(out);
+        }
+        let extras: Vec<&ControlChannel> = self
+            .observations
+            .iter()
+            .filter(|c| c.offset.is_none())
+            .collect();
+        if !extras.is_empty() {
+            let _ = writeln!(
+                out,
+                "# Observations passed alongside the flat vector: name: shape (None if unknown)"
+            );
+            let _ = writeln!(out, "EXTRA_OBSERVATIONS = {{");
+            for c in extras {
+                let shape = match c.shape.as_slice() {
+                    [] => "None".to_owned(),
+                    dims => format!("{dims:?}"),
+                };
+                let _ = writeln!(out, "    \"{}\": {shape},", c.name);
+            }
+            let _ = writeln!(out, "}}");
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "def split_observation(observation):");
+        let _ = writeln!(
+            out,
+            "    \"\"\"Slices a flat observation into its named channels.\"\"\""
+        );
+        let _ = writeln!(
+            out,
+            "    return {{name: observation[o:o + n] for name, (o, n) in OBSERVATIONS.items()}}"
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "class Controller:");
+        let _ = writeln!(out, "    def act(self, observation):");
+        let _ = writeln!(
+            out,
+            "        \"\"\"Maps a flat observation to ACTION_SIZE effort commands.\"\"\""
+        );
+        let _ = writeln!(out, "        assert len(observation) == OBSERVATION_SIZE");
+        let _ = writeln!(out, "        return [0.0] * ACTION_SIZE");
+        out
+    }
+
+    /// A Rust module with the vector layout as constants and an `act` function returning
+    /// zero commands, ready to be filled in with a policy.
+    pub fn rust_stub(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "//! Controller scaffold generated by symbios-robot.");
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "pub const OBSERVATION_SIZE: usize = {};",
+            self.observation_size
+        );
+        let _ = writeln!(out, "pub const ACTION_SIZE: usize = {};", self.action_size);
+        for (constant, channels) in [
+            ("OBSERVATIONS", &self.observations),
+            ("ACTIONS", &self.actions),
+        ] {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "/// `(name, offset, length)` in the flat vector.");
+            let _ = writeln!(out, "pub const {constant}: &[(&str, usize, usize)] = &[");
+            for c in channels {
+                if let Some(offset) = c.offset {
+                    let _ = writeln!(out, "    (\"{}\", {offset}, {}),", c.name, c.len());
+                }
+            }
+            let _ = writeln!(out, "];");
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "/// Maps a flat observation to `ACTION_SIZE` effort commands."
+        );
+        let _ = writeln!(
+            out,
+            "pub fn act(_observation: &[f32; OBSERVATION_SIZE]) -> [f32; ACTION_SIZE] {{"
+        );
+        let _ = writeln!(out, "    [0.0; ACTION_SIZE]");
+        let _ = writeln!(out, "}}");
+        out
+    }
+}
@@ -0,0 +1,106 @@
+//! Conversion between coordinate conventions.
+//!
+//! Blueprints are Y-up and right-handed, like Bevy and glTF. [`convert`] re-expresses a
+//! blueprint in another [`Convention`], changing every world position, rest rotation,
+//! local anchor and joint axis together, so a pipeline feeding Unity, Unreal or a Z-up
+//! tool does not have to swap bases by hand.
+//!
+//! A change of handedness is a reflection, which no rotation can express. World vectors
+//! go through the reflecting basis change, and every module and sensor frame is mirrored
+//! along its own X axis so that its rotation stays a proper rotation. All shape primitives
+//! are symmetric about that plane, and the growth (Y) and forward (Z) axes keep their
+//! meaning. Mirroring reverses the sense of rotation, so hinge axes also flip sign: joint
+//! angles and limits read the same in either convention.
+
+use crate::blueprint::{JointType, RobotBlueprint};
+use glam::{Mat3, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// A choice of up axis and handedness.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Convention {
+    /// Y up, right-handed: the blueprint's own frame (Bevy, glTF, Godot).
+    #[default]
+    YUpRightHanded,
+    /// Z up, right-handed (ROS, Gazebo, MuJoCo, Blender): a quarter turn about X, taking
+    /// `(x, y, z)` to `(x, -z, y)`.
+    ZUpRightHanded,
+    /// Y up, left-handed (Unity): `(x, y, z)` to `(x, y, -z)`.
+    YUpLeftHanded,
+    /// Z up, left-handed (Unreal): `(x, y, z)` to `(x, z, y)`.
+    ZUpLeftHanded,
+}
+
+impl Convention {
+    /// The matrix taking a blueprint-frame vector into this convention.
+    pub fn basis(self) -> Mat3 {
+        match self {
+            Self::YUpRightHanded => Mat3::IDENTITY,
+            Self::ZUpRightHanded => Mat3::from_cols(Vec3::X, Vec3::Z, -Vec3::Y),
+            Self::YUpLeftHanded => Mat3::from_diagonal(Vec3::new(1.0, 1.0, -1.0)),
+            Self::ZUpLeftHanded => Mat3::from_cols(Vec3::X, Vec3::Z, Vec3::Y),
+        }
+    }
+
+    /// Returns `true` for the left-handed conventions.
+    pub fn is_left_handed(self) -> bool {
+        matches!(self, Self::YUpLeftHanded | Self::ZUpLeftHanded)
+    }
+}
+
+/// Returns `blueprint` expressed in `convention`.
+///
+/// Module positions, welds' world anchors and rest rotations follow the basis change.
+/// For a left-handed target, everything stored in a module frame (anchors, centers of
+/// mass, pivots, markers, sensor poses, friction directions and joint axes) is mirrored
+/// along the module's X axis, and hinge axes are negated on top. Bounds are recomputed;
+/// masses, inertias and limits are unchanged.
+///
+/// The exporters assume the blueprint's own Y-up frame, so write a converted blueprint
+/// with [`UpAxis::Y`](crate::UpAxis::Y).
+pub fn convert(blueprint: &RobotBlueprint, convention: Convention) -> RobotBlueprint {
+    let world = convention.basis();
+    let (local, hinge) = if convention.is_left_handed() {
+        (Mat3::from_diagonal(Vec3::new(-1.0, 1.0, 1.0)), -1.0)
+    } else {
+        (Mat3::IDENTITY, 1.0)
+    };
+    // Both products have determinant +1, so they are rotations again.
+    let frame = |rot: Quat| Quat::from_mat3(&(world * Mat3::from_quat(rot) * local)).normalize();
+    let mount = |rot: Quat| Quat::from_mat3(&(local * Mat3::from_quat(rot) * local)).normalize();
+
+    let mut out = blueprint.clone();
+    for module in out.modules.values_mut() {
+        let (pos, rot) = module.transform;
+        module.transform = (world * pos, frame(rot));
+        module.center_of_mass = local * module.center_of_mass;
+        module.pivot_offset = local * module.pivot_offset;
+        for sensor in &mut module.sensors {
+            sensor.local_position = local * sensor.local_position;
+            sensor.local_rotation = mount(sensor.local_rotation);
+        }
+        for marker in &mut module.markers {
+            marker.local_position = local * marker.local_position;
+        }
+        if let Some(friction) = &mut module.anisotropic_friction {
+            friction.direction = local * friction.direction;
+        }
+        module.update_bounds();
+    }
+    for joint in &mut out.joints {
+        joint.anchor_parent = local * joint.anchor_parent;
+        joint.anchor_child = local * joint.anchor_child;
+        let sign = if joint.joint_type == JointType::Hinge {
+            hinge
+        } else {
+            1.0
+        };
+        joint.axis = local * joint.axis * sign;
+        joint.child_axis = local * joint.child_axis * sign;
+    }
+    for weld in &mut out.world_joints {
+        weld.anchor_child = local * weld.anchor_child;
+        weld.anchor_world = world * weld.anchor_world;
+    }
+    out
+}
@@ -0,0 +1,215 @@
+//! Compact, stable `Debug` output for blueprints.
+//!
+//! The derived `Debug` of a large blueprint prints every float at full precision and
+//! iterates modules in hash order, so two dumps of the same robot rarely match and a
+//! 200-module robot fills pages of log. [`RobotBlueprint::compact_debug`] prints one line
+//! per module and joint, sorted by ID, with floats rounded to a fixed number of decimals.
+
+use crate::blueprint::{JointDefinition, ModuleId, RobotBlueprint, RobotModule, ShapePrimitive};
+use glam::{Quat, Vec3};
+use std::fmt;
+
+/// Decimals printed by [`RobotBlueprint::compact_debug`] unless changed with
+/// [`CompactDebug::precision`].
+pub const DEFAULT_DEBUG_PRECISION: usize = 3;
+
+/// A [`fmt::Debug`] view of a blueprint that rounds floats and groups fields.
+///
+/// ```text
+/// RobotBlueprint { roots: [0], modules: 2, joints: 1 }
+///   #0 Box(0.2 x 1 x 0.2) at (0, 0.5, 0) rot (0, 0, 0, 1) mass 4 density 100 material 0
+///   #1 Sphere(r 0.1) at (0, 1.1, 0) rot (0, 0, 0, 1) mass 0.419 density 100 material 0
+///   0 -> 1 Hinge axis (1, 0, 0) anchors (0, 0.5, 0) / (0, -0.1, 0) limits [-3.142, 3.142]
+/// ```
+#[derive(Clone, Copy)]
+pub struct CompactDebug<'a> {
+    blueprint: &'a RobotBlueprint,
+    precision: usize,
+}
+
+impl CompactDebug<'_> {
+    /// Sets the number of decimals floats are rounded to (builder pattern).
+    pub fn precision(mut self, decimals: usize) -> Self {
+        self.precision = decimals;
+        self
+    }
+
+    fn float(&self, value: f32) -> Float {
+        Float(value, self.precision)
+    }
+
+    fn vec3(&self, v: Vec3) -> String {
+        format!(
+            "({}, {}, {})",
+            self.float(v.x),
+            self.float(v.y),
+            self.float(v.z)
+        )
+    }
+
+    fn quat(&self, q: Quat) -> String {
+        format!(
+            "({}, {}, {}, {})",
+            self.float(q.x),
+            self.float(q.y),
+            self.float(q.z),
+            self.float(q.w)
+        )
+    }
+
+    fn shape(&self, shape: &ShapePrimitive) -> String {
+        match *shape {
+            ShapePrimitive::Box(h) => format!(
+                "Box({} x {} x {})",
+                self.float(h.x * 2.0),
+                self.float(h.y * 2.0),
+                self.float(h.z * 2.0)
+            ),
+            ShapePrimitive::Cylinder { radius, height } => format!(
+                "Cylinder(r {}, h {})",
+                self.float(radius),
+                self.float(height)
+            ),
+            ShapePrimitive::Sphere(r) => format!("Sphere(r {})", self.float(r)),
+            ShapePrimitive::Capsule { radius, height } => format!(
+                "Capsule(r {}, h {})",
+                self.float(radius),
+                self.float(height)
+            ),
+        }
+    }
+
+    fn write_module(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        id: ModuleId,
+        m: &RobotModule,
+    ) -> fmt::Result {
+        let (pos, rot) = m.transform;
+        write!(
+            f,
+            "\n  #{id} {} at {} rot {} mass {} density {} material {}",
+            self.shape(&m.shape),
+            self.vec3(pos),
+            self.quat(rot),
+            self.float(m.mass),
+            self.float(m.density),
+            m.material_id
+        )?;
+        if !m.sensors.is_empty() {
+            let names: Vec<String> = m
+                .sensors
+                .iter()
+                .map(|s| {
+                    let mut name = format!(
+                        "{} at {}",
+                        s.sensor_type.name(),
+                        self.vec3(s.local_position)
+                    );
+                    if let Some(rate) = s.timing.update_rate {
+                        name.push_str(&format!(" {} Hz", self.float(rate)));
+                    }
+                    name
+                })
+                .collect();
+            write!(f, " sensors [{}]", names.join(", "))?;
+        }
+        Ok(())
+    }
+
+    fn write_joint(&self, f: &mut fmt::Formatter<'_>, j: &JointDefinition) -> fmt::Result {
+        write!(
+            f,
+            "\n  {} -> {} {:?} axis {} anchors {} / {}",
+            j.parent_id,
+            j.child_id,
+            j.joint_type,
+            self.vec3(j.axis),
+            self.vec3(j.anchor_parent),
+            self.vec3(j.anchor_child)
+        )?;
+        if j.rest_displacement != 0.0 {
+            write!(f, " rest {}", self.float(j.rest_displacement))?;
+        }
+        if let Some(l) = j.limits {
+            write!(f, " limits [{}, {}]", self.float(l.min), self.float(l.max))?;
+        }
+        if let Some(model) = j.actuator.as_ref().and_then(|a| a.model.as_deref()) {
+            write!(f, " actuator {model}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CompactDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bp = self.blueprint;
+        write!(
+            f,
+            "RobotBlueprint {{ roots: {:?}, modules: {}, joints: {}",
+            bp.roots,
+            bp.modules.len(),
+            bp.joints.len()
+        )?;
+        if !bp.world_joints.is_empty() {
+            write!(f, ", world_joints: {}", bp.world_joints.len())?;
+        }
+        if !bp.metadata.warnings.is_empty() {
+            write!(f, ", warnings: {}", bp.metadata.warnings.len())?;
+        }
+        if bp.metadata.dropped_pushes > 0 || bp.metadata.unmatched_pops > 0 {
+            write!(
+                f,
+                ", dropped_pushes: {}, unmatched_pops: {}",
+                bp.metadata.dropped_pushes, bp.metadata.unmatched_pops
+            )?;
+        }
+        write!(f, " }}")?;
+
+        let mut ids: Vec<ModuleId> = bp.modules.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            self.write_module(f, id, &bp.modules[&id])?;
+        }
+        let mut joints: Vec<&JointDefinition> = bp.joints.iter().collect();
+        joints.sort_by_key(|j| (j.parent_id, j.child_id));
+        for joint in joints {
+            self.write_joint(f, joint)?;
+        }
+        Ok(())
+    }
+}
+
+/// A float rounded to a fixed number of decimals, with trailing zeros and the sign of
+/// negative zero dropped so equal robots always print the same text.
+struct Float(f32, usize);
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = format!("{:.*}", self.1, self.0);
+        let text = if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &text
+        };
+        match text {
+            "-0" => f.write_str("0"),
+            _ => f.write_str(text),
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// A compact [`fmt::Debug`] view for logs: one line per module and joint, sorted by
+    /// ID, with floats rounded to [`DEFAULT_DEBUG_PRECISION`] decimals.
+    ///
+    /// ```rust,ignore
+    /// log::debug!("{:?}", blueprint.compact_debug().precision(2));
+    /// ```
+    pub fn compact_debug(&self) -> CompactDebug<'_> {
+        CompactDebug {
+            blueprint: self,
+            precision: DEFAULT_DEBUG_PRECISION,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Graphviz DOT export of the kinematic tree.
+//!
+//! [`RobotBlueprint::to_dot`] draws modules as nodes and joints as edges, which makes the
+//! topology of a large evolved robot readable at a glance:
+//!
+//! ```text
+//! dot -Tsvg robot.dot -o robot.svg
+//! ```
+
+use crate::blueprint::{JointDefinition, JointType, ModuleId, RobotBlueprint};
+use std::fmt::Write as _;
+
+impl RobotBlueprint {
+    /// Renders the kinematic tree as a Graphviz `digraph`.
+    ///
+    /// Each module is a node labelled with its ID, shape and mass (and its sensor count, if
+    /// any); roots are drawn with a double border. Each joint is an edge from parent to
+    /// child labelled with its type and limits; fixed joints are dashed. Modules welded to
+    /// the world hang from a separate `world` node. Nodes and edges are sorted by ID, so the
+    /// same blueprint always renders the same text.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph robot {{");
+        let _ = writeln!(out, "  node [shape=box, style=rounded];");
+
+        let mut ids: Vec<ModuleId> = self.modules.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let module = &self.modules[&id];
+            let mut label = format!("#{id}\\n{}\\n{:.2} kg", module.shape, module.mass);
+            match module.sensors.len() {
+                0 => {}
+                1 => label.push_str("\\n1 sensor"),
+                n => {
+                    let _ = write!(label, "\\n{n} sensors");
+                }
+            }
+            let border = if self.roots.contains(&id) {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "  m{id} [label=\"{label}\"{border}];");
+        }
+
+        let mut joints: Vec<&JointDefinition> = self.joints.iter().collect();
+        joints.sort_by_key(|j| (j.parent_id, j.child_id));
+        for joint in joints {
+            let mut label = format!("{:?}", joint.joint_type);
+            if let Some(limits) = joint.limits {
+                match limits.cone {
+                    Some(cone) => {
+                        let _ = write!(
+                            label,
+                            "\\nswing {:.2} / {:.2}, twist {:.2}",
+                            cone.swing1, cone.swing2, cone.twist
+                        );
+                    }
+                    None => {
+                        let _ = write!(label, "\\n[{:.2}, {:.2}]", limits.min, limits.max);
+                    }
+                }
+            }
+            let style = if joint.joint_type == JointType::Fixed {
+                ", style=dashed"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "  m{} -> m{} [label=\"{label}\"{style}];",
+                joint.parent_id, joint.child_id
+            );
+        }
+
+        if !self.world_joints.is_empty() {
+            let _ = writeln!(out, "  world [shape=plaintext];");
+            let mut welded: Vec<ModuleId> = self.world_joints.iter().map(|w| w.child_id).collect();
+            welded.sort_unstable();
+            for id in welded {
+                let _ = writeln!(out, "  world -> m{id} [label=\"weld\", style=dashed];");
+            }
+        }
+        let _ = writeln!(out, "}}");
+        out
+    }
+}
@@ -0,0 +1,62 @@
+//! Options shared by the simulator exporters.
+//!
+//! Each text exporter takes its own options struct ([`UrdfOptions`](crate::UrdfOptions),
+//! [`SdfOptions`](crate::SdfOptions), [`MjcfOptions`](crate::MjcfOptions)) through its
+//! `*_with` methods, since the formats differ in what they can leave out. The pieces they
+//! have in common live here: how module geometry is written and which way is up in the
+//! target world. Every options struct defaults to the output of the plain `to_*` method.
+
+use crate::blueprint::ModuleId;
+use crate::stl::part_file_name;
+use glam::Quat;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// How an exporter writes the geometry of each module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportGeometry {
+    /// The format's own box, sphere, cylinder and capsule primitives.
+    #[default]
+    Primitive,
+    /// References to the per-module STL files of
+    /// [`stl_parts`](crate::RobotBlueprint::stl_parts), which are expressed in the module's
+    /// own frame.
+    Mesh {
+        /// Directory or URI the part files live under, e.g. `package://my_robot/meshes`.
+        directory: String,
+    },
+}
+
+impl ExportGeometry {
+    /// The mesh file of module `id`, or `None` for primitives.
+    pub(crate) fn mesh_uri(&self, id: ModuleId) -> Option<String> {
+        match self {
+            Self::Primitive => None,
+            Self::Mesh { directory } if directory.is_empty() || directory.ends_with('/') => {
+                Some(format!("{directory}{}", part_file_name(id)))
+            }
+            Self::Mesh { directory } => Some(format!("{directory}/{}", part_file_name(id))),
+        }
+    }
+}
+
+/// The up axis of the world an exporter writes into. Blueprints are always Y-up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpAxis {
+    /// Keep the blueprint's frame, for Y-up consumers.
+    Y,
+    /// Turn the robot a quarter turn about X so that it stands upright in a Z-up world,
+    /// as ROS, Gazebo and MuJoCo expect.
+    #[default]
+    Z,
+}
+
+impl UpAxis {
+    /// The rotation taking the blueprint's Y-up frame into this convention.
+    pub fn rotation(self) -> Quat {
+        match self {
+            Self::Y => Quat::IDENTITY,
+            Self::Z => Quat::from_rotation_x(FRAC_PI_2),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Loading and saving blueprints as files.
+//!
+//! [`RobotBlueprint::save`] and [`RobotBlueprint::load`] pick a [`BlueprintFormat`] from
+//! the file extension, so consumers no longer repeat the serde plumbing for every format.
+//! JSON and RON are human-readable and tolerate files written before a field was added,
+//! which then takes its default. Binary files carry their own version header and are
+//! migrated by [`RobotBlueprint::from_bytes`]. RON needs the `ron` feature.
+//!
+//! There is no filesystem on `wasm32-unknown-unknown`, where `save` and `load` fail with
+//! [`io::ErrorKind::Unsupported`]; use [`RobotBlueprint::encode`] and
+//! [`RobotBlueprint::decode`] on the bytes instead.
+
+use crate::binary::BINARY_MAGIC;
+use crate::blueprint::RobotBlueprint;
+use std::io;
+use std::path::Path;
+
+/// A file format [`RobotBlueprint::save`] and [`RobotBlueprint::load`] understand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlueprintFormat {
+    /// Pretty-printed JSON (`.json`).
+    Json,
+    /// Pretty-printed [RON](https://crates.io/crates/ron) (`.ron`), with `implicit_some`
+    /// enabled. Encoding and decoding need the `ron` feature.
+    Ron,
+    /// The compact binary format of [`RobotBlueprint::to_bytes`] (`.bin` or `.srbp`).
+    Binary,
+}
+
+impl BlueprintFormat {
+    /// The format matching the extension of `path`, ignoring case, or `None` if the
+    /// extension is missing or unknown.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "ron" => Some(Self::Ron),
+            "bin" | "srbp" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format of `data`: binary if it starts with [`BINARY_MAGIC`], JSON if
+    /// its first non-blank character opens an object, and RON otherwise.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&BINARY_MAGIC) {
+            return Self::Binary;
+        }
+        match data.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => Self::Json,
+            _ => Self::Ron,
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// Writes the blueprint to `path` in the format its extension names (see
+    /// [`BlueprintFormat::from_path`]).
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the extension is not recognised,
+    /// [`io::ErrorKind::Unsupported`] for RON without the `ron` feature, and with the
+    /// underlying error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = BlueprintFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown blueprint format: {}", path.display()),
+            )
+        })?;
+        std::fs::write(path, self.encode(format)?)
+    }
+
+    /// Reads a blueprint from `path`. The format comes from the extension if it names one,
+    /// and is [detected](BlueprintFormat::detect) from the contents otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the contents do not decode as a
+    /// blueprint (including binary data from a newer format version),
+    /// [`io::ErrorKind::Unsupported`] for RON without the `ron` feature, and with the
+    /// underlying error if the file cannot be read.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        let format = BlueprintFormat::from_path(path).unwrap_or(BlueprintFormat::detect(&data));
+        Self::decode(&data, format)
+    }
+
+    /// The blueprint encoded in `format`.
+    pub fn encode(&self, format: BlueprintFormat) -> io::Result<Vec<u8>> {
+        match format {
+            BlueprintFormat::Json => Ok(serde_json::to_vec_pretty(self)?),
+            BlueprintFormat::Ron => self.ron_bytes(),
+            BlueprintFormat::Binary => Ok(self.to_bytes()),
+        }
+    }
+
+    /// Decodes a blueprint from `data` in `format`. Module
+    /// [`bounds`](crate::RobotModule::bounds) are recomputed and missing module
+    /// [`pivot_offset`](crate::RobotModule::pivot_offset) and joint
+    /// [`child_axis`](crate::JointDefinition::child_axis) values derived, so files written
+    /// before they were stored load complete.
+    pub fn decode(data: &[u8], format: BlueprintFormat) -> io::Result<Self> {
+        let mut blueprint: Self = match format {
+            BlueprintFormat::Json => serde_json::from_slice(data)?,
+            BlueprintFormat::Ron => Self::from_ron_bytes(data)?,
+            BlueprintFormat::Binary => Self::from_bytes(data).map_err(invalid_data)?,
+        };
+        blueprint.update_bounds();
+        blueprint.fill_missing_pivot_offsets();
+        blueprint.fill_missing_child_axes();
+        Ok(blueprint)
+    }
+}
+
+#[cfg(feature = "ron")]
+impl RobotBlueprint {
+    /// The blueprint as pretty-printed RON.
+    ///
+    /// The output enables `implicit_some`, so optional fields read `limits: (min: -1.0, ..)`
+    /// rather than `limits: Some((min: -1.0, ..))`, and enums keep their variant names:
+    /// hand-edited test fixtures stay short.
+    pub fn to_ron(&self) -> String {
+        let config =
+            ron::ser::PrettyConfig::new().extensions(ron::extensions::Extensions::IMPLICIT_SOME);
+        ron::ser::to_string_pretty(self, config).expect("blueprints always serialize")
+    }
+
+    /// Parses a blueprint from RON, such as the output of [`to_ron`](Self::to_ron).
+    /// `implicit_some` is enabled whether or not the text asks for it, and fields with a
+    /// default may be left out; module [`bounds`](crate::RobotModule::bounds) are always
+    /// recomputed, and missing module [`pivot_offset`](crate::RobotModule::pivot_offset)
+    /// and joint [`child_axis`](crate::JointDefinition::child_axis) values derived.
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        let mut blueprint: Self = ron_options().from_str(text)?;
+        blueprint.update_bounds();
+        blueprint.fill_missing_pivot_offsets();
+        blueprint.fill_missing_child_axes();
+        Ok(blueprint)
+    }
+
+    fn ron_bytes(&self) -> io::Result<Vec<u8>> {
+        Ok(self.to_ron().into_bytes())
+    }
+
+    fn from_ron_bytes(data: &[u8]) -> io::Result<Self> {
+        ron_options().from_bytes(data).map_err(invalid_data)
+    }
+}
+
+#[cfg(feature = "ron")]
+fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+#[cfg(not(feature = "ron"))]
+impl RobotBlueprint {
+    fn ron_bytes(&self) -> io::Result<Vec<u8>> {
+        Err(ron_unsupported())
+    }
+
+    fn from_ron_bytes(_data: &[u8]) -> io::Result<Self> {
+        Err(ron_unsupported())
+    }
+}
+
+#[cfg(not(feature = "ron"))]
+fn ron_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "RON blueprints need the `ron` feature",
+    )
+}
+
+fn invalid_data(e: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
@@ -0,0 +1,264 @@
+//! glTF 2.0 export of the rest pose.
+//!
+//! [`RobotBlueprint::to_glb`] packs the robot into a single binary glTF (`.glb`) file that
+//! any web viewer can open, for a quick look at blueprints produced by headless evolution
+//! runs. Each module is a mesh node; the joint tree becomes the node hierarchy, with every
+//! node posed relative to its parent, and the joint itself travels in the node's `extras`.
+
+use crate::blueprint::{ModuleId, RobotBlueprint};
+use crate::mesh::TriMesh;
+use glam::Vec3;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Subdivisions around the axis of round shapes used by [`RobotBlueprint::to_glb`].
+pub const DEFAULT_GLTF_SEGMENTS: u32 = 24;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+impl RobotBlueprint {
+    /// Renders the rest pose as a binary glTF 2.0 file, tessellating round shapes with
+    /// [`DEFAULT_GLTF_SEGMENTS`] subdivisions.
+    ///
+    /// Nodes are named `module_N`. A module attached by a joint is a child of its parent's
+    /// node, and the node's `extras` hold the module ID, material, mass and the
+    /// [`JointDefinition`](crate::JointDefinition) attaching it (plus any world weld).
+    /// Every material ID gets its own flat colour.
+    pub fn to_glb(&self) -> Vec<u8> {
+        self.to_glb_with_segments(DEFAULT_GLTF_SEGMENTS)
+    }
+
+    /// Like [`to_glb`](Self::to_glb), with `segments` subdivisions for round shapes.
+    pub fn to_glb_with_segments(&self, segments: u32) -> Vec<u8> {
+        let order = self.topological_order();
+        let index: HashMap<ModuleId, usize> =
+            order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let (mut views, mut accessors, mut meshes, mut nodes) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        // Material IDs in the order they are first used, which is their glTF index.
+        let mut materials: Vec<u8> = Vec::new();
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); order.len()];
+        let mut scene = Vec::new();
+
+        for (i, &id) in order.iter().enumerate() {
+            let module = &self.modules[&id];
+            let mesh = module.shape.tessellate(segments);
+            let positions = push_view(&mut buffer, &mut views, &mesh.vertices, ARRAY_BUFFER);
+            let indices = push_view(
+                &mut buffer,
+                &mut views,
+                &mesh.triangles,
+                ELEMENT_ARRAY_BUFFER,
+            );
+            let (min, max) = bounds(&mesh);
+            accessors.push(json!({
+                "bufferView": positions,
+                "componentType": FLOAT,
+                "count": mesh.vertices.len(),
+                "type": "VEC3",
+                "min": [min.x, min.y, min.z],
+                "max": [max.x, max.y, max.z],
+            }));
+            accessors.push(json!({
+                "bufferView": indices,
+                "componentType": UNSIGNED_INT,
+                "count": mesh.triangles.len() * 3,
+                "type": "SCALAR",
+            }));
+            let material = match materials.iter().position(|&m| m == module.material_id) {
+                Some(index) => index,
+                None => {
+                    materials.push(module.material_id);
+                    materials.len() - 1
+                }
+            };
+            meshes.push(json!({
+                "name": format!("module_{id}"),
+                "primitives": [{
+                    "attributes": { "POSITION": accessors.len() - 2 },
+                    "indices": accessors.len() - 1,
+                    "material": material,
+                }],
+            }));
+
+            // Attach the node below its parent's, if the parent has been placed already.
+            let joint = self.parent_joint(id);
+            let parent = joint
+                .and_then(|j| index.get(&j.parent_id).copied())
+                .filter(|&p| p < i);
+            let (pos, rot) = module.transform;
+            let (translation, rotation) = match parent {
+                Some(p) => {
+                    let (parent_pos, parent_rot) = self.modules[&order[p]].transform;
+                    let inv = parent_rot.inverse();
+                    (inv * (pos - parent_pos), inv * rot)
+                }
+                None => (pos, rot),
+            };
+            match parent {
+                Some(p) => children[p].push(i),
+                None => scene.push(i),
+            }
+
+            let mut extras = json!({
+                "module_id": id,
+                "material_id": module.material_id,
+                "mass": module.mass,
+            });
+            if let Some(joint) = joint {
+                extras["joint"] = serde_json::to_value(joint).unwrap_or(Value::Null);
+            }
+            if let Some(weld) = self.world_joints.iter().find(|w| w.child_id == id) {
+                extras["world_weld"] = serde_json::to_value(weld).unwrap_or(Value::Null);
+            }
+            let rotation = rotation.normalize();
+            nodes.push(json!({
+                "name": format!("module_{id}"),
+                "mesh": i,
+                "translation": [translation.x, translation.y, translation.z],
+                "rotation": [rotation.x, rotation.y, rotation.z, rotation.w],
+                "extras": extras,
+            }));
+        }
+        for (node, children) in nodes.iter_mut().zip(children) {
+            if !children.is_empty() {
+                node["children"] = json!(children);
+            }
+        }
+
+        let materials: Vec<Value> = materials
+            .iter()
+            .map(|&id| {
+                json!({
+                    "name": format!("material_{id}"),
+                    "pbrMetallicRoughness": {
+                        "baseColorFactor": material_color(id),
+                        "metallicFactor": 0.0,
+                        "roughnessFactor": 0.8,
+                    },
+                })
+            })
+            .collect();
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "symbios-robot" },
+            "scene": 0,
+            "scenes": [{ "nodes": scene }],
+        });
+        let buffers = vec![json!({ "byteLength": buffer.len() })];
+        // glTF forbids empty top-level arrays, so an empty blueprint is a bare scene.
+        if !nodes.is_empty() {
+            for (key, array) in [
+                ("nodes", nodes),
+                ("meshes", meshes),
+                ("materials", materials),
+                ("accessors", accessors),
+                ("bufferViews", views),
+                ("buffers", buffers),
+            ] {
+                document[key] = Value::Array(array);
+            }
+        }
+        glb(&document.to_string(), &buffer)
+    }
+}
+
+/// Appends `items` to `buffer` as a new buffer view and returns the view's index.
+fn push_view<T: AsLeBytes>(
+    buffer: &mut Vec<u8>,
+    views: &mut Vec<Value>,
+    items: &[T],
+    target: u32,
+) -> usize {
+    let offset = buffer.len();
+    for item in items {
+        item.write_le(buffer);
+    }
+    views.push(json!({
+        "buffer": 0,
+        "byteOffset": offset,
+        "byteLength": buffer.len() - offset,
+        "target": target,
+    }));
+    views.len() - 1
+}
+
+/// Little-endian encoding of the element types the exporter stores.
+trait AsLeBytes {
+    fn write_le(&self, out: &mut Vec<u8>);
+}
+
+impl AsLeBytes for Vec3 {
+    fn write_le(&self, out: &mut Vec<u8>) {
+        for v in self.to_array() {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+}
+
+impl AsLeBytes for [u32; 3] {
+    fn write_le(&self, out: &mut Vec<u8>) {
+        for v in self {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+}
+
+/// Component-wise minimum and maximum of the mesh's vertices.
+fn bounds(mesh: &TriMesh) -> (Vec3, Vec3) {
+    mesh.vertices.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), &v| (min.min(v), max.max(v)),
+    )
+}
+
+/// A distinct, stable RGBA colour for a material ID: hues a golden angle apart.
+pub(crate) fn material_color(id: u8) -> [f32; 4] {
+    let hue = (f32::from(id) * 0.618_034).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    // Muted so that shading stays readable.
+    let muted = |c: f32| 0.25 + 0.6 * c;
+    [muted(r), muted(g), muted(b), 1.0]
+}
+
+/// Wraps a glTF JSON document and its binary buffer into a GLB container.
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    const MAGIC: u32 = 0x4654_6C67; // "glTF"
+    const JSON_CHUNK: u32 = 0x4E4F_534A; // "JSON"
+    const BIN_CHUNK: u32 = 0x004E_4942; // "BIN\0"
+
+    // Chunks are padded to 4 bytes: JSON with spaces, binary data with zeros.
+    let mut json = json.as_bytes().to_vec();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = bin.to_vec();
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let mut total = 12 + 8 + json.len();
+    if !bin.is_empty() {
+        total += 8 + bin.len();
+    }
+    let mut out = Vec::with_capacity(total);
+    for v in [MAGIC, 2, total as u32, json.len() as u32, JSON_CHUNK] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out.extend_from_slice(&json);
+    if !bin.is_empty() {
+        for v in [bin.len() as u32, BIN_CHUNK] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&bin);
+    }
+    out
+}
@@ -0,0 +1,129 @@
+//! Per-shape instancing data for renderers.
+//!
+//! A population of robots is made of only four primitive kinds, so a renderer can draw all
+//! of it with one instanced draw call per kind. [`RobotBlueprint::instancing_batches`] and
+//! [`population_batches`] group modules by kind and emit per-instance translation,
+//! rotation and scale arrays for a unit mesh of that kind.
+
+use crate::blueprint::{ModuleId, RobotBlueprint, ShapePrimitive};
+use glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// The primitive kind a batch instances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ShapeKind {
+    /// A unit cube (edge length 1) centred on the origin.
+    Box,
+    /// A cylinder of diameter 1 and height 1 along Y, centred on the origin.
+    Cylinder,
+    /// A sphere of diameter 1 centred on the origin.
+    Sphere,
+    /// A capsule along Y. Its scale is `(diameter, cylinder height, diameter)`; capsules do
+    /// not stretch uniformly, so the renderer must rebuild the caps from the scale.
+    Capsule,
+}
+
+impl ShapeKind {
+    /// The kind of `shape` and the scale that turns the unit mesh into it.
+    pub fn of(shape: &ShapePrimitive) -> (Self, Vec3) {
+        match *shape {
+            ShapePrimitive::Box(half) => (Self::Box, half * 2.0),
+            ShapePrimitive::Cylinder { radius, height } => (
+                Self::Cylinder,
+                Vec3::new(radius * 2.0, height, radius * 2.0),
+            ),
+            ShapePrimitive::Sphere(radius) => (Self::Sphere, Vec3::splat(radius * 2.0)),
+            ShapePrimitive::Capsule { radius, height } => {
+                (Self::Capsule, Vec3::new(radius * 2.0, height, radius * 2.0))
+            }
+        }
+    }
+}
+
+/// All instances of one [`ShapeKind`], as parallel per-instance arrays.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InstanceBatch {
+    /// The unit mesh every instance is drawn with.
+    pub kind: ShapeKind,
+    /// Index of the blueprint each instance belongs to (always `0` for a single blueprint).
+    pub robots: Vec<u32>,
+    /// Module ID of each instance within its blueprint.
+    pub modules: Vec<ModuleId>,
+    /// World-space position of each instance at the rest pose.
+    pub translations: Vec<Vec3>,
+    /// World-space orientation of each instance at the rest pose.
+    pub rotations: Vec<Quat>,
+    /// Scale applied to the unit mesh, see [`ShapeKind`].
+    pub scales: Vec<Vec3>,
+}
+
+impl InstanceBatch {
+    fn new(kind: ShapeKind) -> Self {
+        Self {
+            kind,
+            robots: Vec::new(),
+            modules: Vec::new(),
+            translations: Vec::new(),
+            rotations: Vec::new(),
+            scales: Vec::new(),
+        }
+    }
+
+    /// Number of instances in the batch.
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// True iff the batch has no instances.
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Model matrix (scale, then rotation, then translation) of every instance.
+    pub fn model_matrices(&self) -> Vec<Mat4> {
+        (0..self.len())
+            .map(|i| {
+                Mat4::from_scale_rotation_translation(
+                    self.scales[i],
+                    self.rotations[i],
+                    self.translations[i],
+                )
+            })
+            .collect()
+    }
+}
+
+impl RobotBlueprint {
+    /// Groups the modules by [`ShapeKind`] into instancing batches, in kind order and with
+    /// modules sorted by ID. Kinds without modules are left out.
+    pub fn instancing_batches(&self) -> Vec<InstanceBatch> {
+        population_batches(std::slice::from_ref(self))
+    }
+}
+
+/// Like [`RobotBlueprint::instancing_batches`], but for a whole population: instances of
+/// every blueprint share one batch per kind, tagged with the blueprint's index.
+pub fn population_batches(blueprints: &[RobotBlueprint]) -> Vec<InstanceBatch> {
+    let mut batches = [
+        ShapeKind::Box,
+        ShapeKind::Cylinder,
+        ShapeKind::Sphere,
+        ShapeKind::Capsule,
+    ]
+    .map(InstanceBatch::new);
+    for (robot, blueprint) in blueprints.iter().enumerate() {
+        let mut ids: Vec<ModuleId> = blueprint.modules.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let module = &blueprint.modules[&id];
+            let (kind, scale) = ShapeKind::of(&module.shape);
+            let batch = &mut batches[kind as usize];
+            batch.robots.push(robot as u32);
+            batch.modules.push(id);
+            batch.translations.push(module.transform.0);
+            batch.rotations.push(module.transform.1);
+            batch.scales.push(scale);
+        }
+    }
+    batches.into_iter().filter(|b| !b.is_empty()).collect()
+}
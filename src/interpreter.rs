@@ -6,18 +6,66 @@
 //! [`RobotInterpreter::build_blueprint`] with a [`symbios::SymbiosState`].
 
 use crate::blueprint::{
-    JointDefinition, JointLimit, JointType, ModuleId, RobotBlueprint, RobotModule, SensorMount,
-    SensorType, ShapePrimitive,
+    Actuator, AnisotropicFriction, BlueprintMetadata, BuildWarning, CapsuleLength, ComTarget,
+    ConeLimit, JointDefinition, JointLimit, JointType, ModuleId, RobotBlueprint, RobotModule,
+    SensorAttachment, SensorMount, SensorTiming, SensorType, ShapePrimitive, Transmission,
+    WorldJoint, density_scale,
 };
-use crate::turtle::{RobotOp, RobotTurtleState};
+use crate::material::MaterialPalette;
+use crate::program::CompiledProgram;
+use crate::servo;
+use crate::turtle::{RadialBranch, RobotOp, RobotTurtleState, TurtleStyle, splitmix64};
 use bevy_heavy::ComputeMassProperties3d as _;
+use bevy_math::Isometry3d;
+use bevy_math::bounding::{Aabb3d, Bounded3d as _, BoundingVolume as _};
 use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
 use std::collections::HashMap;
-use std::f32::consts::PI;
-use symbios::{SymbiosState, SymbolTable};
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use symbios::{SymbiosState, SymbolTable, SymbolView};
+use thiserror::Error;
+
+/// Stable 64-bit FNV-1a hash of a genotype's symbols and parameters.
+///
+/// Symbol IDs come from the [`SymbolTable`], so hashes are only comparable between states
+/// interned against the same table. Intended for [`Lineage`](crate::Lineage) records.
+pub fn genotype_hash(state: &SymbiosState) -> u64 {
+    prefix_hash(state, state.len())
+}
+
+/// [`genotype_hash`] of the first `len` symbols of `state`.
+fn prefix_hash(state: &SymbiosState, len: usize) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    let mut hash = OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for &b in bytes {
+            hash = (hash ^ b as u64).wrapping_mul(PRIME);
+        }
+    };
+    for i in 0..len {
+        let Some(view) = state.get_view(i) else {
+            continue;
+        };
+        feed(&view.sym.to_le_bytes());
+        feed(&(view.params.len() as u32).to_le_bytes());
+        for p in view.params {
+            feed(&p.to_bits().to_le_bytes());
+        }
+    }
+    hash
+}
 
 /// Configuration for robot interpretation.
-#[derive(Clone, Debug)]
+///
+/// Fields missing from a deserialized config take their [`Default`] values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RobotConfig {
     /// Default length/height for shapes if no parameter is provided.
     pub default_length: f32,
@@ -25,10 +73,105 @@ pub struct RobotConfig {
     pub default_width: f32,
     /// Default density (kg/m^3) for calculating mass. Default: 50 (Hollow Plastic-ish).
     pub default_density: f32,
+    /// Densities per material ID. Modules whose material is in the palette take its
+    /// density instead of `default_density`; [`RobotOp::SetDensity`] still overrides both.
+    /// Default: `None`.
+    pub material_palette: Option<MaterialPalette>,
+    /// Average density (kg/m³) the finished robot is scaled to, e.g. `1000.0` for neutrally
+    /// buoyant swimmers. See [`RobotBlueprint::scale_to_average_density`]; the factor applied
+    /// is recorded in [`BlueprintMetadata::density_scale`](crate::BlueprintMetadata).
+    /// Default: `None`.
+    pub target_density: Option<f32>,
+    /// Size the effort and velocity limits that `Jl` leaves at their defaults to the joint's
+    /// load ([`JointLoad::scaled_actuation`](crate::JointLoad::scaled_actuation)) instead of
+    /// the constant `100.0` / `10.0`. Default: `false`.
+    pub inertia_scaled_limits: bool,
     /// Default rotation angle (in radians) for Yaw/Pitch/Roll.
     pub default_angle: f32,
+    /// Renormalize the turtle's orientation after this many rotations. Each rotation rounds
+    /// the quaternion slightly off unit length, and after thousands of small turns the drift
+    /// measurably skews deep branches. `0` never renormalizes. Default: `16`.
+    pub renormalize_interval: u32,
     /// Maximum stack depth for push/pop operations.
     pub max_stack_depth: usize,
+    /// Global seed that [`RobotOp::DeriveSeed`] combines with the branch path, and the
+    /// turtle's initial random seed. Default: `0`.
+    pub seed: u64,
+    /// Orient hinge axes perpendicular to both the parent's and the child's growth
+    /// direction, so bent limbs flex like elbows and knees. Hinges between collinear
+    /// segments keep the configured axis. Default: `false`.
+    pub auto_hinge_axis: bool,
+    /// When a module is spawned off a side face of its parent (any face other than the
+    /// end caps of the parent's growth axis), grow it along that face's outward normal
+    /// instead of the turtle's up direction. Default: `false`.
+    pub align_to_surface_normal: bool,
+    /// Region around the origin that every module must fit inside at its rest pose.
+    /// Modules reaching outside it are skipped and reported as
+    /// [`BuildWarning::ModuleOutOfBounds`]. Default: `None` (unbounded).
+    pub max_extent: Option<Extent>,
+    /// Total degrees of freedom the robot may have, so that a controller's input and output
+    /// sizes stay bounded. Joints that would exceed it are made fixed and reported as
+    /// [`BuildWarning::DofLimitReached`]. Default: `None` (unbounded).
+    pub max_dof: Option<usize>,
+    /// Record the recommended self-collision exclusions in
+    /// [`RobotBlueprint::collision_exclusions`], treating siblings whose bounding boxes come
+    /// within this many metres as overlapping
+    /// (see [`RobotBlueprint::self_collision_exclusions`]). Default: `None` (not computed).
+    pub collision_exclusion_margin: Option<f32>,
+    /// What the length parameter of [`RobotOp::SpawnCapsule`] measures. With
+    /// [`CapsuleLength::TipToTip`] the whole capsule spans the segment, so it touches its
+    /// neighbours tip to tip; with [`CapsuleLength::Section`] the caps overlap them by one
+    /// radius at each end. Recorded in the blueprint's metadata. Default: `Section`.
+    pub capsule_length: CapsuleLength,
+    /// How far the turtle moves past a sphere spawned with [`RobotOp::SpawnSphere`].
+    /// Default: [`SphereAdvance::Diameter`].
+    pub sphere_advance: SphereAdvance,
+}
+
+/// Where the turtle ends up after spawning a sphere, used by
+/// [`RobotConfig::sphere_advance`].
+///
+/// Segments grown from a sphere attach at the turtle, so this picks where on the sphere
+/// they are jointed: at its far side, at its centre, or where it was spawned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SphereAdvance {
+    /// The sphere grows from the turtle like any other segment and the turtle moves to its
+    /// far side, so spheres chain like beads.
+    #[default]
+    Diameter,
+    /// The sphere grows from the turtle and the turtle stops at its centre: the next
+    /// segments are jointed there, with the sphere as their hub.
+    Radius,
+    /// The sphere is centred on the turtle, which stays put: a knuckle around the joint
+    /// to its parent, at which the next segments are jointed too.
+    Zero,
+}
+
+/// A region centred on the origin, used by [`RobotConfig::max_extent`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Extent {
+    /// A sphere of the given radius.
+    Sphere(f32),
+    /// An axis-aligned box with the given half-extents.
+    Box(Vec3),
+}
+
+impl Extent {
+    /// Returns `true` if `shape` at the given world pose lies entirely inside the region.
+    pub fn contains(&self, shape: ShapePrimitive, position: Vec3, rotation: Quat) -> bool {
+        let iso = Isometry3d::new(position, rotation);
+        match *self {
+            Self::Sphere(radius) => {
+                let bounds = shape.to_bevy_primitive().bounding_sphere(iso);
+                Vec3::from(bounds.center).length() + bounds.radius() <= radius
+            }
+            Self::Box(half_extents) => {
+                let bounds = shape.to_bevy_primitive().aabb_3d(iso);
+                Vec3::from(bounds.min).cmpge(-half_extents).all()
+                    && Vec3::from(bounds.max).cmple(half_extents).all()
+            }
+        }
+    }
 }
 
 impl Default for RobotConfig {
@@ -37,16 +180,117 @@ impl Default for RobotConfig {
             default_length: 1.0,
             default_width: 0.2,
             default_density: 100.0,
+            material_palette: None,
+            target_density: None,
+            inertia_scaled_limits: false,
             default_angle: 45.0f32.to_radians(),
+            renormalize_interval: 16,
             max_stack_depth: 1024,
+            seed: 0,
+            auto_hinge_axis: false,
+            align_to_surface_normal: false,
+            max_extent: None,
+            max_dof: None,
+            collision_exclusion_margin: None,
+            capsule_length: CapsuleLength::Section,
+            sphere_advance: SphereAdvance::Diameter,
         }
     }
 }
 
+/// A complete interpretation setup: the configuration plus the symbol bindings keyed by
+/// name (see [`RobotInterpreter::export_map`]), for storing next to experiment results.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterpreterSetup {
+    /// Interpreter configuration.
+    #[serde(default)]
+    pub config: RobotConfig,
+    /// Symbol names and the ops they are bound to.
+    #[serde(default)]
+    pub ops: Vec<(String, RobotOp)>,
+}
+
+impl InterpreterSetup {
+    /// Writes the setup to `path` as pretty-printed JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    /// Reads a setup written by [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
 /// Interprets L-System output to build a RobotBlueprint.
 pub struct RobotInterpreter {
     op_map: Vec<RobotOp>,
     config: RobotConfig,
+    custom_ops: HashMap<SmolStr, Arc<dyn CustomOp>>,
+}
+
+/// A domain-specific operation, run for symbols bound to [`RobotOp::Custom`].
+///
+/// Custom ops let downstream crates add symbols such as "spawn gripper" without forking
+/// [`RobotOp`]. They compose built-in ops through [`CustomOpContext::run`] and can steer
+/// the turtle directly. Closures taking `(&mut CustomOpContext, &[f64])` implement the
+/// trait.
+pub trait CustomOp: Send + Sync {
+    /// Applies the op with the symbol's parameters.
+    fn apply(&self, ctx: &mut CustomOpContext<'_>, params: &[f64]);
+}
+
+impl<F> CustomOp for F
+where
+    F: Fn(&mut CustomOpContext<'_>, &[f64]) + Send + Sync,
+{
+    fn apply(&self, ctx: &mut CustomOpContext<'_>, params: &[f64]) {
+        self(ctx, params)
+    }
+}
+
+/// What a [`CustomOp`] can see and change while it runs.
+pub struct CustomOpContext<'a> {
+    interpreter: &'a RobotInterpreter,
+    build: &'a mut BuildContext,
+    neighbors: &'a OpNeighbors<'a>,
+}
+
+impl CustomOpContext<'_> {
+    /// Executes a built-in (or another custom) op as if its symbol stood in place of the
+    /// custom one, with the same neighbours.
+    pub fn run(&mut self, op: &RobotOp, params: &[f64]) {
+        self.interpreter
+            .execute(self.build, op, params, self.neighbors);
+    }
+
+    /// The turtle.
+    pub fn turtle(&self) -> &RobotTurtleState {
+        &self.build.turtle
+    }
+
+    /// The turtle, to move, rotate or restyle it before running further ops.
+    pub fn turtle_mut(&mut self) -> &mut RobotTurtleState {
+        &mut self.build.turtle
+    }
+
+    /// The robot built so far, without masses (see [`RobotLayout::blueprint`]). Empty
+    /// during [`analyze`](RobotInterpreter::analyze), which adds no modules.
+    pub fn blueprint(&self) -> &RobotBlueprint {
+        &self.build.blueprint
+    }
+
+    /// The interpreter's configuration.
+    pub fn config(&self) -> &RobotConfig {
+        &self.interpreter.config
+    }
+
+    /// The ops around the custom one in the symbol stream.
+    pub fn neighbors(&self) -> &OpNeighbors<'_> {
+        self.neighbors
+    }
 }
 
 impl RobotInterpreter {
@@ -59,6 +303,24 @@ impl RobotInterpreter {
         Self {
             op_map: Vec::new(),
             config,
+            custom_ops: HashMap::new(),
+        }
+    }
+
+    /// Creates an interpreter from a stored [`InterpreterSetup`], binding its ops to the
+    /// symbols of `interner`. Names missing from `interner` are skipped, as in
+    /// [`import_map`](Self::import_map).
+    pub fn from_setup(setup: &InterpreterSetup, interner: &SymbolTable) -> Self {
+        let mut interpreter = Self::new(setup.config.clone());
+        interpreter.import_map(interner, &setup.ops);
+        interpreter
+    }
+
+    /// Captures the configuration and the name-keyed op map as an [`InterpreterSetup`].
+    pub fn setup(&self, interner: &SymbolTable) -> InterpreterSetup {
+        InterpreterSetup {
+            config: self.config.clone(),
+            ops: self.export_map(interner),
         }
     }
 
@@ -83,6 +345,28 @@ impl RobotInterpreter {
         self.op_map[idx] = op;
     }
 
+    /// Registers `op` under `name`, for symbols bound to [`RobotOp::Custom`] with that name.
+    /// Replaces any op already registered under `name`.
+    ///
+    /// Only the name is part of the op map, so [`export_map`](Self::export_map) and
+    /// [`InterpreterSetup`] record `Custom(name)` bindings; register the implementations
+    /// again after loading them.
+    pub fn register_custom_op(&mut self, name: impl Into<SmolStr>, op: impl CustomOp + 'static) {
+        self.custom_ops.insert(name.into(), Arc::new(op));
+    }
+
+    /// Registers `op` under `name` and binds `sym_id` to it.
+    pub fn set_custom_op(
+        &mut self,
+        sym_id: u16,
+        name: impl Into<SmolStr>,
+        op: impl CustomOp + 'static,
+    ) {
+        let name = name.into();
+        self.register_custom_op(name.clone(), op);
+        self.set_op(sym_id, RobotOp::Custom(name));
+    }
+
     /// Registers the conventional symbol-to-operation mappings for all standard symbols.
     ///
     /// Looks up each standard symbol string (e.g. `"B"`, `"+"`, `"["`) in `interner`
@@ -91,46 +375,47 @@ impl RobotInterpreter {
     ///
     /// See the crate README for the full symbol table.
     pub fn populate_standard_symbols(&mut self, interner: &SymbolTable) {
-        let mappings = [
-            // Spatial
-            ("f", RobotOp::Move),
-            ("+", RobotOp::Yaw(1.0)),
-            ("-", RobotOp::Yaw(-1.0)),
-            ("&", RobotOp::Pitch(1.0)),
-            ("^", RobotOp::Pitch(-1.0)),
-            ("\\", RobotOp::Roll(1.0)),
-            ("/", RobotOp::Roll(-1.0)),
-            ("|", RobotOp::TurnAround),
-            // Geometry
-            ("B", RobotOp::SpawnBox),
-            ("C", RobotOp::SpawnCylinder),
-            ("O", RobotOp::SpawnSphere), // Orb
-            ("K", RobotOp::SpawnCapsule),
-            // Config
-            ("!", RobotOp::SetWidth),
-            ("'", RobotOp::SetMaterial), // Using ' like visual turtle
-            // Joint Configuration
-            ("J", RobotOp::SetJointType(JointType::Hinge)), // Default J is Hinge
-            ("Jf", RobotOp::SetJointType(JointType::Fixed)),
-            ("Jb", RobotOp::SetJointType(JointType::Ball)),
-            ("Jl", RobotOp::SetJointLimits),
-            // Sensors
-            ("S", RobotOp::MountSensor(SensorType::Camera)), // Generic S
-            ("Si", RobotOp::MountSensor(SensorType::IMU)),
-            ("St", RobotOp::MountSensor(SensorType::Touch)),
-            ("Sl", RobotOp::MountSensor(SensorType::Lidar)),
-            // Flow
-            ("[", RobotOp::Push),
-            ("]", RobotOp::Pop),
-        ];
-
-        for (sym, op) in mappings {
+        for (sym, op) in standard_symbols() {
             if let Some(id) = interner.resolve_id(sym) {
                 self.set_op(id, op);
             }
         }
     }
 
+    /// Returns the current bindings keyed by symbol name instead of ID, in ID order.
+    ///
+    /// Symbol IDs depend on the order symbols were interned, so a map saved by ID can bind
+    /// different ops when an experiment is rerun. Persist this list instead and restore it
+    /// with [`import_map`](Self::import_map). Unbound IDs and IDs missing from `interner`
+    /// are left out.
+    pub fn export_map(&self, interner: &SymbolTable) -> Vec<(String, RobotOp)> {
+        self.op_map
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| **op != RobotOp::Ignore)
+            .filter_map(|(id, op)| {
+                let name = interner.resolve(u16::try_from(id).ok()?)?;
+                Some((name.to_string(), op.clone()))
+            })
+            .collect()
+    }
+
+    /// Replaces the symbol-to-operation map with the named bindings from
+    /// [`export_map`](Self::export_map), resolving each name against `interner`.
+    ///
+    /// Returns the names that are not in `interner`; their bindings are skipped.
+    pub fn import_map(&mut self, interner: &SymbolTable, map: &[(String, RobotOp)]) -> Vec<String> {
+        self.op_map.clear();
+        let mut missing = Vec::new();
+        for (name, op) in map {
+            match interner.resolve_id(name) {
+                Some(id) => self.set_op(id, op.clone()),
+                None => missing.push(name.clone()),
+            }
+        }
+        missing
+    }
+
     /// Interprets the full L-System `state` and returns the resulting [`RobotBlueprint`].
     ///
     /// Walks every symbol in `state` in order, dispatching each to its registered
@@ -145,139 +430,724 @@ impl RobotInterpreter {
     /// 3. If a previous module exists, a [`JointDefinition`] is created connecting it to the new one.
     /// 4. The turtle advances to the distal end: `turtle_pos + up × height`.
     ///
+    /// Spheres follow [`RobotConfig::sphere_advance`], which can stop the turtle at their
+    /// centre or centre them on it.
+    ///
     /// # Push / Pop
     ///
     /// `[` saves the full turtle state (position, rotation, current module, joint config, width,
     /// material) onto a stack. `]` restores it. This enables branching morphologies.
-    /// Pushes beyond `max_stack_depth` are silently dropped.
+    /// Pushes beyond `max_stack_depth` and pops on an empty stack are ignored, and counted in
+    /// the blueprint's [`metadata`](RobotBlueprint::metadata).
+    ///
+    /// This is [`layout`](Self::layout) followed by [`annotate`](Self::annotate).
     pub fn build_blueprint(&self, state: &SymbiosState) -> RobotBlueprint {
-        let mut blueprint = RobotBlueprint::default();
-        let mut turtle = RobotTurtleState {
-            width: self.config.default_width,
-            ..Default::default()
-        };
-        let mut stack = Vec::new();
+        self.annotate(self.layout(state))
+    }
+
+    /// Like [`build_blueprint`](Self::build_blueprint), but fails if anything in `state`
+    /// was silently ignored or produced a module with a zero, negative or non-finite
+    /// dimension, listing every problem with the position of its symbol.
+    ///
+    /// Symbols bound to [`RobotOp::Ignore`] count as unmapped, so nonterminals left in the
+    /// final derivation are reported too. Problems the build works around on purpose, such
+    /// as modules outside [`RobotConfig::max_extent`], stay in the blueprint's
+    /// [`warnings`](crate::BlueprintMetadata::warnings) instead.
+    pub fn try_build_blueprint(
+        &self,
+        state: &SymbiosState,
+    ) -> Result<RobotBlueprint, InterpretError> {
+        let mut ctx = self.new_context(None);
+        ctx.problems = Some(Vec::new());
+        self.run(&mut ctx, state);
+        match ctx.problems.take() {
+            Some(problems) if !problems.is_empty() => Err(InterpretError { problems }),
+            _ => Ok(self.annotate(ctx.into_layout())),
+        }
+    }
+
+    /// Like [`build_blueprint`](Self::build_blueprint), but also records one [`TraceStep`]
+    /// per dispatched symbol: the op, the turtle before and after it, and the modules and
+    /// joints it created. Unmapped symbols are not dispatched and get no step.
+    ///
+    /// Cloning the turtle twice per symbol makes this much slower than a plain build; use
+    /// it to find where a particular genome goes wrong, not inside the evolutionary loop.
+    pub fn build_blueprint_traced(&self, state: &SymbiosState) -> (RobotBlueprint, Vec<TraceStep>) {
+        let mut ctx = self.new_context(None);
+        ctx.trace = Some(Vec::new());
+        self.run(&mut ctx, state);
+        let trace = ctx.trace.take().unwrap_or_default();
+        (self.annotate(ctx.into_layout()), trace)
+    }
+
+    /// Like [`build_blueprint`](Self::build_blueprint), but takes the symbols one at a time
+    /// from an iterator instead of a [`SymbiosState`], so a rewriting pass can feed the
+    /// interpreter as it produces symbols and the whole string never has to exist at once.
+    ///
+    /// Symbols are pulled lazily: each op runs as soon as the next mapped symbol (the
+    /// lookahead context-sensitive ops need) has been read. The result is the same as
+    /// [`build_blueprint`](Self::build_blueprint) on a state holding the same symbols.
+    pub fn build_blueprint_from_iter<'s>(
+        &self,
+        symbols: impl IntoIterator<Item = SymbolView<'s>>,
+    ) -> RobotBlueprint {
+        let mut ctx = self.new_context(None);
+        self.run_symbols(&mut ctx, symbols.into_iter().enumerate(), usize::MAX, None);
+        self.annotate(ctx.into_layout())
+    }
 
-        // Track the world-space transform (Position, Rotation) of every module we spawn.
-        // We need this to calculate relative anchor points for joints and sensors.
-        let mut module_transforms: HashMap<ModuleId, (Vec3, Quat)> = HashMap::new();
-        let mut next_module_id: ModuleId = 0;
+    /// Builds one blueprint per state of a derivation (one [`SymbiosState`] per L-System
+    /// iteration), for animating how a robot develops.
+    ///
+    /// Consecutive states usually share a prefix of symbols. The build of each state is
+    /// checkpointed at the end of the prefix it shares with the next one, as
+    /// [`extend_blueprint`](Self::extend_blueprint) does, and the next build resumes from
+    /// there, so shared prefixes are interpreted only once. Each blueprint is the same as
+    /// [`build_blueprint`](Self::build_blueprint) of its state.
+    pub fn build_sequence(&self, states: &[SymbiosState]) -> Vec<RobotBlueprint> {
+        let mut blueprints = Vec::with_capacity(states.len());
+        let mut checkpoint = TurtleCheckpoint::default();
+        for (i, state) in states.iter().enumerate() {
+            let (start, mut ctx) = self.resume(state, std::mem::take(&mut checkpoint));
+            match states.get(i + 1).map(|next| self.resume_point(state, next)) {
+                Some(resume) if resume >= start => {
+                    self.run_range(&mut ctx, state, start..resume);
+                    checkpoint = TurtleCheckpoint::at(state, resume, &ctx);
+                    self.run_range(&mut ctx, state, resume..state.len());
+                }
+                // The next state diverges before this build's starting point.
+                _ => self.run_range(&mut ctx, state, start..state.len()),
+            }
+            blueprints.push(self.annotate(ctx.into_layout()));
+        }
+        blueprints
+    }
 
+    /// Builds the blueprint of `state`, interpreting only the symbols after `resume`, and
+    /// returns it with the checkpoint to pass with the next state.
+    ///
+    /// Start from [`TurtleCheckpoint::default`]; after each derivation step, pass the new
+    /// state with the checkpoint returned for the previous one. As long as the new state
+    /// keeps the symbols the checkpoint covers, the build resumes there, so a robot growing
+    /// at its tips costs one pass over its new symbols per generation instead of a rebuild.
+    /// If they changed, `state` is interpreted from scratch. Either way the blueprint
+    /// equals [`build_blueprint`](Self::build_blueprint) of `state`; the mass pass
+    /// ([`annotate`](Self::annotate)) always runs over the whole robot. Checkpoints only
+    /// carry over between builds by the same interpreter.
+    pub fn extend_blueprint(
+        &self,
+        state: &SymbiosState,
+        resume: TurtleCheckpoint,
+    ) -> (RobotBlueprint, TurtleCheckpoint) {
+        let (start, mut ctx) = self.resume(state, resume);
+        // Snapshot before the last mapped symbol: it is the only op whose lookahead the
+        // next derivation step can change without touching the symbols before it.
+        let cursor = (start..state.len())
+            .rev()
+            .find(|&i| {
+                state
+                    .get_view(i)
+                    .is_some_and(|v| *self.op_for(v.sym) != RobotOp::Ignore)
+            })
+            .unwrap_or(start);
+        self.run_range(&mut ctx, state, start..cursor);
+        let checkpoint = TurtleCheckpoint::at(state, cursor, &ctx);
+        self.run_range(&mut ctx, state, cursor..state.len());
+        (self.annotate(ctx.into_layout()), checkpoint)
+    }
+
+    /// Runs only the geometric pass over `state`: module poses and shapes, topology, sensors
+    /// and joint settings, without computing any mass.
+    ///
+    /// Viewers and geometric metrics can use [`RobotLayout::blueprint`] directly; pass the
+    /// layout to [`annotate`](Self::annotate) to finish the build.
+    pub fn layout(&self, state: &SymbiosState) -> RobotLayout {
+        let mut ctx = self.new_context(None);
+        self.run(&mut ctx, state);
+        ctx.into_layout()
+    }
+
+    /// Runs the turtle over `state` without building a blueprint and returns summary counts.
+    ///
+    /// This is a cheap pre-filter for large populations: no [`RobotModule`]s are stored and
+    /// no joints are allocated, but every spatial and flow operation is still applied, so the
+    /// counts, estimated mass, and bounding box match what
+    /// [`build_blueprint`](Self::build_blueprint) would produce.
+    pub fn analyze(&self, state: &SymbiosState) -> InterpretationStats {
+        let mut ctx = self.new_context(Some(InterpretationStats::default()));
+        self.run(&mut ctx, state);
+        let mut stats = ctx.analysis.unwrap_or_default();
+        if let Some(target) = self.config.target_density
+            && let Some(scale) = density_scale(
+                target,
+                stats.volume,
+                stats.estimated_mass - ctx.point_mass,
+                ctx.point_mass,
+            )
+        {
+            stats.estimated_mass = ctx.point_mass + (stats.estimated_mass - ctx.point_mass) * scale;
+        }
+        stats
+    }
+
+    /// Resolves every symbol of `state` against the op map once.
+    ///
+    /// The resulting [`CompiledProgram`] can be interpreted repeatedly with
+    /// [`build_from_program`](Self::build_from_program) — including by interpreters with a
+    /// different [`RobotConfig`] — without any further symbol lookup. Symbols mapped to
+    /// [`RobotOp::Ignore`] are dropped.
+    pub fn compile(&self, state: &SymbiosState) -> CompiledProgram {
+        let mut program = CompiledProgram::new();
         for i in 0..state.len() {
-            let view = match state.get_view(i) {
-                Some(v) => v,
-                None => break,
+            let Some(view) = state.get_view(i) else {
+                break;
             };
+            let op = self.op_for(view.sym);
+            if *op != RobotOp::Ignore {
+                program.push(op.clone(), view.params);
+            }
+        }
+        program
+    }
 
-            let op = self
-                .op_map
-                .get(view.sym as usize)
-                .unwrap_or(&RobotOp::Ignore);
-
-            // Param helpers
-            let p = |idx: usize, def: f32| -> f32 {
-                view.params.get(idx).map(|&x| x as f32).unwrap_or(def)
+    /// Interprets a [`CompiledProgram`] and returns the resulting [`RobotBlueprint`].
+    ///
+    /// Produces the same blueprint as [`build_blueprint`](Self::build_blueprint) on the state
+    /// the program was compiled from, using this interpreter's configuration.
+    pub fn build_from_program(&self, program: &CompiledProgram) -> RobotBlueprint {
+        let mut ctx = self.new_context(None);
+        for (i, (op, params)) in program.iter().enumerate() {
+            let neighbors = OpNeighbors {
+                prev: i.checked_sub(1).and_then(|j| program.get(j)),
+                next: program.get(i + 1),
             };
-            let p0 = p(0, 0.0);
+            self.execute(&mut ctx, op, params, &neighbors);
+        }
+        self.annotate(ctx.into_layout())
+    }
+
+    /// Starts a step-by-step interpretation of `state`.
+    ///
+    /// The returned [`InterpreterSession`] executes one symbol per
+    /// [`step`](InterpreterSession::step) call and exposes the turtle and partial blueprint in
+    /// between, for interactive grammar debuggers and growth animations. Running a session to
+    /// completion yields the same blueprint as [`build_blueprint`](Self::build_blueprint).
+    pub fn session<'a>(&'a self, state: &'a SymbiosState) -> InterpreterSession<'a> {
+        InterpreterSession {
+            interpreter: self,
+            source: SessionSource::State(state),
+            cursor: 0,
+            prev_mapped: None,
+            ctx: self.new_context(None),
+        }
+    }
 
-            match op {
-                // --- SPATIAL ---
-                RobotOp::Move => {
-                    let len = p(0, self.config.default_length);
-                    turtle.position += turtle.up() * len;
+    /// Starts a step-by-step interpretation of a [`CompiledProgram`].
+    ///
+    /// See [`session`](Self::session); each step executes one compiled operation.
+    pub fn program_session<'a>(&'a self, program: &'a CompiledProgram) -> InterpreterSession<'a> {
+        InterpreterSession {
+            interpreter: self,
+            source: SessionSource::Program(program),
+            cursor: 0,
+            prev_mapped: None,
+            ctx: self.new_context(None),
+        }
+    }
+
+    /// Runs the physics pass over a [`layout`](Self::layout) and returns the finished
+    /// blueprint.
+    ///
+    /// Computes every module's mass from its shape and density, adds actuator masses to
+    /// their parents, mounts IMUs placed at a center of mass, then applies whole-robot
+    /// adjustments such as [`RobotConfig::target_density`].
+    pub fn annotate(&self, layout: RobotLayout) -> RobotBlueprint {
+        let RobotLayout {
+            mut blueprint,
+            annotations,
+            derived_limits,
+        } = layout;
+        for module in blueprint.modules.values_mut() {
+            module.update_mass();
+        }
+        // In op order, so each annotation sees the robot as it was when its op ran.
+        for annotation in annotations {
+            match annotation {
+                Annotation::PointMass {
+                    module,
+                    mass,
+                    position,
+                } => {
+                    if let Some(module) = blueprint.modules.get_mut(&module) {
+                        module.add_point_mass(mass, position);
+                    }
+                }
+                Annotation::ImuAtCom {
+                    target,
+                    timing,
+                    modules,
+                } => mount_imu_at_com(&mut blueprint, target, timing, modules),
+            }
+        }
+        if let Some(target) = self.config.target_density {
+            blueprint.metadata.density_scale = blueprint.scale_to_average_density(target);
+        }
+        if self.config.inertia_scaled_limits {
+            // Every module exists now, so each joint sees its complete subtree.
+            for (index, effort, velocity) in derived_limits {
+                let Some((scaled_effort, scaled_velocity)) = blueprint
+                    .joint_load(index)
+                    .and_then(|load| load.scaled_actuation(blueprint.joints[index].joint_type))
+                else {
+                    continue;
+                };
+                if let Some(limits) = &mut blueprint.joints[index].limits {
+                    if effort {
+                        limits.effort = scaled_effort;
+                    }
+                    if velocity {
+                        limits.velocity = scaled_velocity;
+                    }
+                }
+            }
+        }
+        if let Some(margin) = self.config.collision_exclusion_margin {
+            blueprint.update_collision_exclusions(margin);
+        }
+        blueprint
+    }
+
+    /// Creates the initial build context: turtle at the origin, empty stack and blueprint.
+    fn new_context(&self, analysis: Option<InterpretationStats>) -> BuildContext {
+        BuildContext {
+            turtle: RobotTurtleState {
+                width: self.config.default_width,
+                seed: self.config.seed,
+                renormalize_interval: self.config.renormalize_interval,
+                ..Default::default()
+            },
+            stack: Vec::new(),
+            scopes: Vec::new(),
+            module_transforms: HashMap::new(),
+            module_shapes: HashMap::new(),
+            module_depths: HashMap::new(),
+            point_mass: 0.0,
+            derived_limits: Vec::new(),
+            annotations: Vec::new(),
+            next_module_id: 0,
+            dof: 0,
+            blueprint: RobotBlueprint {
+                metadata: BlueprintMetadata {
+                    capsule_length: self.config.capsule_length,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            analysis,
+            index: 0,
+            problems: None,
+            trace: None,
+        }
+    }
+
+    /// Dispatches every symbol of `state` to [`execute`](Self::execute).
+    fn run(&self, ctx: &mut BuildContext, state: &SymbiosState) {
+        self.run_range(ctx, state, 0..state.len());
+    }
+
+    /// Dispatches the symbols of `state` in `range` to [`execute`](Self::execute), as if
+    /// the symbols before it had just been executed into `ctx`.
+    fn run_range(&self, ctx: &mut BuildContext, state: &SymbiosState, range: Range<usize>) {
+        let prev = (0..range.start).rev().find_map(|j| {
+            let view = state.get_view(j)?;
+            let op = self.op_for(view.sym);
+            (*op != RobotOp::Ignore).then_some((op, view.params))
+        });
+        // Symbols past the range are only read as lookahead.
+        let symbols =
+            (range.start..state.len()).map_while(|i| state.get_view(i).map(|view| (i, view)));
+        self.run_symbols(ctx, symbols, range.end, prev);
+    }
+
+    /// Dispatches the mapped symbols of `symbols` with an index below `end` to
+    /// [`execute`](Self::execute), and reports the unmapped ones. `prev` is the op executed
+    /// just before the first symbol.
+    ///
+    /// Symbols are pulled lazily: each op runs as soon as the next mapped symbol, its
+    /// lookahead, has been read, so the iterator is consumed at most one op past `end`.
+    fn run_symbols<'a, 's: 'a>(
+        &'a self,
+        ctx: &mut BuildContext,
+        symbols: impl IntoIterator<Item = (usize, SymbolView<'s>)>,
+        end: usize,
+        mut prev: Option<(&'a RobotOp, &'a [f64])>,
+    ) {
+        // The op waiting for its lookahead, and the unmapped symbols read since, which are
+        // reported after it runs to keep problems in symbol order.
+        let mut pending: Option<(usize, &RobotOp, &[f64])> = None;
+        let mut unmapped: Vec<(usize, u16)> = Vec::new();
+        for (i, view) in symbols {
+            if i >= end && pending.is_none() {
+                break;
+            }
+            let op = self.op_for(view.sym);
+            if *op == RobotOp::Ignore {
+                if i < end {
+                    unmapped.push((i, view.sym));
+                }
+                continue;
+            }
+            if let Some(current) = pending.take() {
+                self.dispatch(ctx, current, prev, Some((op, view.params)));
+                prev = Some((current.1, current.2));
+            }
+            ctx.report_unmapped(&mut unmapped);
+            if i < end {
+                pending = Some((i, op, view.params));
+            }
+        }
+        if let Some(current) = pending {
+            self.dispatch(ctx, current, prev, None);
+        }
+        ctx.report_unmapped(&mut unmapped);
+    }
+
+    /// Executes the op of symbol `index` between its neighbours, recording a trace step if
+    /// `ctx` is tracing.
+    fn dispatch(
+        &self,
+        ctx: &mut BuildContext,
+        (index, op, params): (usize, &RobotOp, &[f64]),
+        prev: Option<(&RobotOp, &[f64])>,
+        next: Option<(&RobotOp, &[f64])>,
+    ) {
+        ctx.index = index;
+        let before = ctx.trace.is_some().then(|| {
+            (
+                ctx.turtle.clone(),
+                ctx.next_module_id,
+                ctx.blueprint.joints.len(),
+            )
+        });
+        self.execute(ctx, op, params, &OpNeighbors { prev, next });
+        if let Some((turtle, first_module, first_joint)) = before {
+            ctx.record_step(index, op, params, turtle, first_module, first_joint);
+        }
+    }
+
+    /// The context `checkpoint` resumes `state` from, with the index of the first symbol
+    /// still to interpret, or a fresh context if `state` no longer starts with the symbols
+    /// the checkpoint covers.
+    fn resume(&self, state: &SymbiosState, checkpoint: TurtleCheckpoint) -> (usize, BuildContext) {
+        match checkpoint.ctx {
+            Some(ctx)
+                if state.len() >= checkpoint.shared
+                    && prefix_hash(state, checkpoint.shared) == checkpoint.hash =>
+            {
+                (checkpoint.cursor, *ctx)
+            }
+            _ => (0, self.new_context(None)),
+        }
+    }
+
+    /// Where a build of `state` can be snapshotted and resumed on `next`: the index of the
+    /// last mapped symbol of their shared prefix. Every op before it sees the same
+    /// neighbours in both states.
+    fn resume_point(&self, state: &SymbiosState, next: &SymbiosState) -> usize {
+        let shared = (0..state.len().min(next.len()))
+            .take_while(|&i| match (state.get_view(i), next.get_view(i)) {
+                (Some(a), Some(b)) => a.sym == b.sym && a.params == b.params,
+                _ => false,
+            })
+            .count();
+        (0..shared)
+            .rev()
+            .find(|&i| {
+                state
+                    .get_view(i)
+                    .is_some_and(|v| *self.op_for(v.sym) != RobotOp::Ignore)
+            })
+            .unwrap_or(0)
+    }
+
+    /// Finds the first symbol at or after `from` that maps to an operation other than
+    /// [`RobotOp::Ignore`].
+    fn next_mapped<'s>(
+        &'s self,
+        state: &'s SymbiosState,
+        from: usize,
+    ) -> Option<(usize, &'s RobotOp, &'s [f64])> {
+        (from..state.len()).find_map(|j| {
+            let view = state.get_view(j)?;
+            let op = self.op_for(view.sym);
+            (*op != RobotOp::Ignore).then_some((j, op, view.params))
+        })
+    }
+
+    /// Returns the operation registered for `sym`, or [`RobotOp::Ignore`] if unmapped.
+    fn op_for(&self, sym: u16) -> &RobotOp {
+        static IGNORE: RobotOp = RobotOp::Ignore;
+        self.op_map.get(sym as usize).unwrap_or(&IGNORE)
+    }
+
+    /// Applies a single operation with its parameters to the build context.
+    ///
+    /// `neighbors` gives context-sensitive ops access to the surrounding operations.
+    fn execute(
+        &self,
+        ctx: &mut BuildContext,
+        op: &RobotOp,
+        params: &[f64],
+        neighbors: &OpNeighbors<'_>,
+    ) {
+        let turtle = &mut ctx.turtle;
+
+        // Param helpers
+        let p = |idx: usize, def: f32| -> f32 { params.get(idx).map(|&x| x as f32).unwrap_or(def) };
+        let p0 = p(0, 0.0);
+
+        match *op {
+            // --- SPATIAL ---
+            RobotOp::Move => {
+                let len = p(0, self.config.default_length);
+                turtle.position += turtle.up() * len;
+            }
+            RobotOp::Yaw(s) => {
+                turtle.rotate_local_z(p(0, self.config.default_angle.to_degrees()).to_radians() * s)
+            }
+            RobotOp::Pitch(s) => {
+                turtle.rotate_local_x(p(0, self.config.default_angle.to_degrees()).to_radians() * s)
+            }
+            RobotOp::Roll(s) => {
+                turtle.rotate_local_y(p(0, self.config.default_angle.to_degrees()).to_radians() * s)
+            }
+            RobotOp::TurnAround => turtle.rotate_local_z(PI),
+            RobotOp::RadialBranch => {
+                let n = p0.max(0.0) as u32;
+                turtle.radial_branch = (n >= 2).then(|| RadialBranch {
+                    remaining: n,
+                    step: 2.0 * PI / n as f32,
+                    depth: ctx.stack.len(),
+                });
+            }
+
+            RobotOp::Jitter => {
+                let max = p(0, self.config.default_angle.to_degrees()).to_radians();
+                let yaw = (turtle.next_random() * 2.0 - 1.0) * max;
+                let pitch = (turtle.next_random() * 2.0 - 1.0) * max;
+                turtle.rotate_local_z(yaw);
+                turtle.rotate_local_x(pitch);
+            }
+            RobotOp::DeriveSeed => {
+                let mut state = turtle.branch_path ^ p0.to_bits() as u64;
+                let mut seed = self.config.seed ^ splitmix64(&mut state);
+                turtle.seed = splitmix64(&mut seed);
+            }
+
+            // --- GEOMETRY ---
+            RobotOp::SpawnBox
+            | RobotOp::SpawnCylinder
+            | RobotOp::SpawnSphere
+            | RobotOp::SpawnCapsule => {
+                // 1. Determine Dimensions & Shape
+                // Default growth axis is Y (Up).
+                let (shape, advance) = match op {
+                    RobotOp::SpawnBox => {
+                        let len = p(0, self.config.default_length).abs(); // Y axis (Growth)
+                        let wid = p(1, turtle.width).abs(); // X axis
+                        let hgt = p(2, turtle.width).abs(); // Z axis
+                        (
+                            ShapePrimitive::Box(Vec3::new(wid / 2.0, len / 2.0, hgt / 2.0)),
+                            len,
+                        )
+                    }
+                    RobotOp::SpawnCylinder => {
+                        let len = p(0, self.config.default_length).abs();
+                        let rad = p(1, turtle.width / 2.0).abs();
+                        (
+                            ShapePrimitive::Cylinder {
+                                radius: rad,
+                                height: len,
+                            },
+                            len,
+                        )
+                    }
+                    RobotOp::SpawnCapsule => {
+                        let len = p(0, self.config.default_length).abs();
+                        let rad = p(1, turtle.width / 2.0).abs();
+                        let convention = self.config.capsule_length;
+                        let height = convention.section(len, rad);
+                        (
+                            ShapePrimitive::Capsule {
+                                radius: rad,
+                                height,
+                            },
+                            convention.length(height, rad),
+                        )
+                    }
+                    RobotOp::SpawnSphere => {
+                        let rad = p(0, turtle.width / 2.0).abs();
+                        let advance = match self.config.sphere_advance {
+                            SphereAdvance::Diameter => rad * 2.0,
+                            SphereAdvance::Radius => rad,
+                            SphereAdvance::Zero => 0.0,
+                        };
+                        (ShapePrimitive::Sphere(rad), advance)
+                    }
+                    _ => unreachable!(),
+                };
+                // `turtle` still borrows the context, so push to the field directly.
+                if is_degenerate(&shape)
+                    && let Some(problems) = &mut ctx.problems
+                {
+                    problems.push(InterpretProblem::DegenerateShape {
+                        index: ctx.index,
+                        shape,
+                    });
                 }
-                RobotOp::Yaw(s) => turtle
-                    .rotate_local_z(p(0, self.config.default_angle.to_degrees()).to_radians() * s),
-                RobotOp::Pitch(s) => turtle
-                    .rotate_local_x(p(0, self.config.default_angle.to_degrees()).to_radians() * s),
-                RobotOp::Roll(s) => turtle
-                    .rotate_local_y(p(0, self.config.default_angle.to_degrees()).to_radians() * s),
-                RobotOp::TurnAround => turtle.rotate_local_z(PI),
-
-                // --- GEOMETRY ---
-                RobotOp::SpawnBox
-                | RobotOp::SpawnCylinder
-                | RobotOp::SpawnSphere
-                | RobotOp::SpawnCapsule => {
-                    let id = next_module_id;
-                    next_module_id += 1;
-
-                    // 1. Determine Dimensions & Shape
-                    // Default growth axis is Y (Up).
-                    let (shape, height_axis_len) = match op {
-                        RobotOp::SpawnBox => {
-                            let len = p(0, self.config.default_length).abs(); // Y axis (Growth)
-                            let wid = p(1, turtle.width).abs(); // X axis
-                            let hgt = p(2, turtle.width).abs(); // Z axis
-                            (
-                                ShapePrimitive::Box(Vec3::new(wid / 2.0, len / 2.0, hgt / 2.0)),
-                                len,
-                            )
-                        }
-                        RobotOp::SpawnCylinder => {
-                            let len = p(0, self.config.default_length).abs();
-                            let rad = p(1, turtle.width / 2.0).abs();
-                            (
-                                ShapePrimitive::Cylinder {
-                                    radius: rad,
-                                    height: len,
-                                },
-                                len,
-                            )
-                        }
-                        RobotOp::SpawnCapsule => {
-                            let len = p(0, self.config.default_length).abs();
-                            let rad = p(1, turtle.width / 2.0).abs();
-                            (
-                                ShapePrimitive::Capsule {
-                                    radius: rad,
-                                    height: len,
-                                },
-                                len,
-                            )
-                        }
-                        RobotOp::SpawnSphere => {
-                            let rad = p(0, turtle.width / 2.0).abs();
-                            (ShapePrimitive::Sphere(rad), rad * 2.0)
-                        }
-                        _ => unreachable!(),
-                    };
 
-                    // 2. Calculate World Transform of the new Module
-                    // The module's pivot is at the bottom (0, -h/2, 0).
-                    // The turtle is at the pivot point.
-                    // So the module's CENTER is TurtlePos + (TurtleUp * h/2).
-                    let module_center_pos =
-                        turtle.position + (turtle.up() * (height_axis_len / 2.0));
-                    let module_rotation = turtle.rotation;
+                // 2. Calculate World Transform of the new Module
+                // The module's pivot is at the bottom (0, -h/2, 0).
+                // The turtle is at the pivot point.
+                // So the module's CENTER is TurtlePos + (TurtleUp * h/2).
+                let pivot = match shape {
+                    ShapePrimitive::Sphere(_)
+                        if self.config.sphere_advance == SphereAdvance::Zero =>
+                    {
+                        Vec3::ZERO
+                    }
+                    _ => self.config.capsule_length.base_pivot(&shape),
+                };
+                let mut module_rotation = turtle.rotation;
+                if self.config.align_to_surface_normal
+                    && let Some(parent_id) = turtle.current_module_id
+                    && let Some((parent_pos, parent_rot)) = ctx.module_transforms.get(&parent_id)
+                    && let Some(parent_shape) = ctx.module_shapes.get(&parent_id)
+                    && let Some(normal) = parent_shape.surface_normal_local(
+                        parent_rot.inverse() * (turtle.position - *parent_pos),
+                    )
+                    && normal.y.abs() < 0.5
+                {
+                    // Side face: re-aim the turtle so the child grows straight out of it.
+                    let normal = *parent_rot * normal;
+                    module_rotation =
+                        Quat::from_rotation_arc(turtle.up(), normal) * module_rotation;
+                }
+                // A prismatic joint with a rest displacement spawns its child already slid
+                // along the joint axis, away from the joint's zero position.
+                let config = &turtle.joint_config;
+                let rest_displacement = if turtle.current_module_id.is_some()
+                    && config.joint_type == JointType::Prismatic
+                {
+                    let d = config.rest_displacement;
+                    config.limits.map_or(d, |l| d.clamp(l.min, l.max))
+                } else {
+                    0.0
+                };
+                let rest_offset = module_rotation * turtle.joint_config.axis * rest_displacement;
+                let module_center_pos = turtle.position + rest_offset - module_rotation * pivot;
 
-                    // 3. Register Module
-                    let density = self.config.default_density;
+                if let Some(extent) = self.config.max_extent
+                    && !extent.contains(shape, module_center_pos, module_rotation)
+                {
+                    // Outside the arena: skip the module and leave the turtle where it is.
+                    match &mut ctx.analysis {
+                        Some(stats) => stats.skipped_modules += 1,
+                        None => {
+                            ctx.blueprint
+                                .metadata
+                                .warnings
+                                .push(BuildWarning::ModuleOutOfBounds {
+                                    center: module_center_pos,
+                                })
+                        }
+                    }
+                    return;
+                }
+                // Friction directions are given in the turtle's frame before alignment.
+                let turtle_rotation = turtle.rotation;
+                if module_rotation != turtle.rotation {
+                    turtle.set_rotation(module_rotation);
+                }
+                let id = ctx.next_module_id;
+                ctx.next_module_id += 1;
+
+                // 3. Register Module
+                let density = turtle
+                    .density
+                    .or_else(|| {
+                        let palette = self.config.material_palette.as_ref()?;
+                        palette.density(turtle.material())
+                    })
+                    .unwrap_or(self.config.default_density);
+                let module = RobotModule {
+                    material_id: turtle.material(),
+                    growth_stage: turtle.growth_stage,
+                    pivot_offset: -pivot,
+                    anisotropic_friction: turtle.anisotropic_friction.map(|friction| {
+                        AnisotropicFriction {
+                            direction: module_rotation.inverse()
+                                * turtle_rotation
+                                * friction.direction,
+                            ..friction
+                        }
+                    }),
+                    ..RobotModule::massless(shape, density, (module_center_pos, module_rotation))
+                };
+                if let Some(stats) = &mut ctx.analysis {
                     let mass = shape.to_bevy_primitive().mass(density);
-                    blueprint.add_module(
-                        id,
-                        RobotModule {
-                            shape,
-                            mass,
-                            density,
-                            material_id: turtle.material_id,
-                            sensors: Vec::new(),
-                            transform: (module_center_pos, module_rotation),
-                        },
-                    );
-                    module_transforms.insert(id, (module_center_pos, module_rotation));
+                    stats.record_module(shape, mass, module_center_pos, module_rotation);
+                } else {
+                    ctx.blueprint.add_module(id, module);
+                    if turtle.current_module_id.is_none() {
+                        // Nothing to attach to: this module starts a tree of its own.
+                        ctx.blueprint.add_root(id);
+                    }
+                }
+                ctx.module_transforms
+                    .insert(id, (module_center_pos, module_rotation));
+                ctx.module_shapes.insert(id, shape);
 
-                    // 4. Create Joint (if parent exists)
-                    if let Some(parent_id) = turtle.current_module_id
-                        && let Some((parent_pos, parent_rot)) = module_transforms.get(&parent_id)
+                // 4. Create Joint (if parent exists)
+                if let Some(parent_id) = turtle.current_module_id
+                    && let Some((parent_pos, parent_rot)) = ctx.module_transforms.get(&parent_id)
+                {
+                    let mut joint_type = turtle.joint_config.joint_type;
+                    if let Some(max_dof) = self.config.max_dof
+                        && ctx.dof + joint_type.degrees_of_freedom() > max_dof
                     {
+                        joint_type = JointType::Fixed;
+                        if ctx.analysis.is_none() {
+                            ctx.blueprint
+                                .metadata
+                                .warnings
+                                .push(BuildWarning::DofLimitReached { child_id: id });
+                        }
+                    }
+                    ctx.dof += joint_type.degrees_of_freedom();
+
+                    // Actuators only drive joints that can move; their mass rides on the parent.
+                    let actuator = turtle
+                        .joint_config
+                        .actuator
+                        .clone()
+                        .filter(|_| joint_type != JointType::Fixed);
+                    let actuator_mass = actuator.as_ref().map_or(0.0, |a| a.mass);
+
+                    if let Some(stats) = &mut ctx.analysis {
+                        stats.estimated_mass += actuator_mass;
+                        ctx.point_mass += actuator_mass;
+                        let depth = ctx.module_depths.get(&parent_id).copied().unwrap_or(0) + 1;
+                        ctx.module_depths.insert(id, depth);
+                        stats.record_joint(joint_type, depth);
+                    } else {
                         // Anchor on Parent: Where is the Turtle relative to Parent Center?
                         // Transform (TurtlePos - ParentPos) into Parent Local Space.
                         let world_offset = turtle.position - *parent_pos;
                         let anchor_parent = parent_rot.inverse() * world_offset;
 
                         // Anchor on Child: The child's pivot is at its 'bottom' relative to its center.
-                        let anchor_child = Vec3::new(0.0, -height_axis_len / 2.0, 0.0);
+                        let anchor_child = pivot;
 
                         // Axis: Transform turtle's joint axis (usually X) into Parent Local Space
                         // Note: Axis is defined relative to the *joint frame*, which usually aligns with child?
@@ -286,82 +1156,882 @@ impl RobotInterpreter {
                         // We need the axis in Parent Local Space.
                         // Global Axis = turtle.rotation * config.axis
                         // Local Axis = parent_rot.inverse() * Global Axis
-                        let global_axis = turtle.rotation * turtle.joint_config.axis;
+                        let mut global_axis = turtle.rotation * turtle.joint_config.axis;
+                        if self.config.auto_hinge_axis
+                            && joint_type == JointType::Hinge
+                            && let Some(bend_axis) =
+                                (*parent_rot * Vec3::Y).cross(turtle.up()).try_normalize()
+                        {
+                            // Parent and child growth directions span the bending plane.
+                            global_axis = bend_axis;
+                        }
                         let local_axis = parent_rot.inverse() * global_axis;
 
-                        blueprint.add_joint(JointDefinition {
+                        let config = &turtle.joint_config;
+                        if config.limits.is_some()
+                            && joint_type != JointType::Fixed
+                            && (config.derive_effort || config.derive_velocity)
+                        {
+                            ctx.derived_limits.push((
+                                ctx.blueprint.joints.len(),
+                                config.derive_effort,
+                                config.derive_velocity,
+                            ));
+                        }
+                        ctx.blueprint.add_joint(JointDefinition {
                             parent_id,
                             child_id: id,
                             anchor_parent,
                             anchor_child,
-                            joint_type: turtle.joint_config.joint_type,
+                            joint_type,
                             axis: local_axis,
+                            child_axis: turtle.rotation.inverse() * global_axis,
+                            rest_displacement,
                             limits: turtle.joint_config.limits,
+                            transmission: turtle.joint_config.transmission,
+                            actuator,
                         });
+                        if actuator_mass > 0.0 {
+                            ctx.annotations.push(Annotation::PointMass {
+                                module: parent_id,
+                                mass: actuator_mass,
+                                position: anchor_parent,
+                            });
+                        }
                     }
-
-                    // 5. Advance Turtle
-                    // Move the cursor to the 'top' of the new module (the distal end).
-                    turtle.position += turtle.up() * height_axis_len;
-                    turtle.current_module_id = Some(id);
                 }
 
-                // --- CONFIG ---
-                RobotOp::SetJointType(t) => turtle.joint_config.joint_type = *t,
-                RobotOp::SetJointLimits => {
-                    // Params: min, max, effort, velocity
-                    let a = p(0, -PI);
-                    let b = p(1, PI);
-                    // Mutation can jitter limits so min > max; swap to avoid Avian3D panic.
-                    let (min, max) = if a <= b { (a, b) } else { (b, a) };
-                    let effort = p(2, 100.0);
-                    let vel = p(3, 10.0);
+                // 5. Advance Turtle
+                // Move the cursor to the 'top' of the new module (the distal end).
+                turtle.position += rest_offset + turtle.up() * advance;
+                turtle.current_module_id = Some(id);
+            }
+
+            // --- CONFIG ---
+            RobotOp::SetJointType(t) => {
+                turtle.joint_config.joint_type = t;
+                // `Jp(d)`: the slider rests `d` metres along its axis.
+                turtle.joint_config.rest_displacement = match t {
+                    JointType::Prismatic => p0,
+                    _ => 0.0,
+                };
+            }
+            RobotOp::SetJointAxis(preset) => {
+                let axis = Vec3::new(p(0, preset.x), p(1, preset.y), p(2, preset.z));
+                if let Some(axis) = axis.try_normalize() {
+                    turtle.joint_config.axis = axis;
+                }
+            }
+            RobotOp::SetJointLimits if turtle.joint_config.joint_type == JointType::Ball => {
+                // Params: swing1, swing2, twist, effort, velocity
+                let swing1 = p(0, FRAC_PI_2).abs();
+                let swing2 = p(1, swing1).abs();
+                let twist = p(2, PI).abs();
+                turtle.joint_config.limits = Some(JointLimit {
+                    min: -twist,
+                    max: twist,
+                    effort: p(3, 100.0),
+                    velocity: p(4, 10.0),
+                    cone: Some(ConeLimit {
+                        swing1,
+                        swing2,
+                        twist,
+                    }),
+                });
+                turtle.joint_config.derive_effort = params.len() < 4;
+                turtle.joint_config.derive_velocity = params.len() < 5;
+            }
+            RobotOp::SetJointLimits => {
+                // Params: min, max, effort, velocity
+                // A bare `Jl` on a prismatic joint right before a segment takes its travel
+                // range from that segment: the slider can retract by its full length.
+                let (def_min, def_max) =
+                    match neighbors.next_segment_length(&self.config, turtle.width) {
+                        Some(len)
+                            if params.is_empty()
+                                && turtle.joint_config.joint_type == JointType::Prismatic =>
+                        {
+                            (0.0, len)
+                        }
+                        _ => (-PI, PI),
+                    };
+                let a = p(0, def_min);
+                let b = p(1, def_max);
+                // Mutation can jitter limits so min > max; swap to avoid Avian3D panic.
+                let (min, max) = if a <= b { (a, b) } else { (b, a) };
+                let effort = p(2, 100.0);
+                let vel = p(3, 10.0);
+                turtle.joint_config.limits = Some(JointLimit {
+                    min,
+                    max,
+                    effort,
+                    velocity: vel,
+                    cone: None,
+                });
+                turtle.joint_config.derive_effort = params.len() < 3;
+                turtle.joint_config.derive_velocity = params.len() < 4;
+            }
+            RobotOp::SetTransmission => {
+                let defaults = Transmission::default();
+                let gear_ratio = p(0, defaults.gear_ratio);
+                turtle.joint_config.transmission = (gear_ratio != 0.0).then(|| Transmission {
+                    gear_ratio: gear_ratio.abs(),
+                    efficiency: p(1, defaults.efficiency).clamp(0.0, 1.0),
+                    backlash: p(2, defaults.backlash).abs(),
+                });
+            }
+            RobotOp::SetServo => match servo::preset(p0 as usize).filter(|_| p0 >= 0.0) {
+                Some(preset) => {
+                    let (min, max, cone) = turtle
+                        .joint_config
+                        .limits
+                        .map_or((-PI, PI, None), |l| (l.min, l.max, l.cone));
                     turtle.joint_config.limits = Some(JointLimit {
                         min,
                         max,
-                        effort,
-                        velocity: vel,
+                        effort: preset.stall_torque,
+                        velocity: preset.no_load_speed,
+                        cone,
                     });
+                    turtle.joint_config.derive_effort = false;
+                    turtle.joint_config.derive_velocity = false;
+                    turtle.joint_config.actuator = Some(preset.actuator());
+                }
+                None => turtle.joint_config.actuator = None,
+            },
+            RobotOp::SetActuator => {
+                // Params: mass, housing width, height, depth
+                let mass = p0.max(0.0);
+                let housing = (params.len() >= 4).then(|| {
+                    ShapePrimitive::Box(Vec3::new(p(1, 0.0), p(2, 0.0), p(3, 0.0)).abs() / 2.0)
+                });
+                turtle.joint_config.actuator = (mass > 0.0).then_some(Actuator {
+                    model: None,
+                    mass,
+                    housing,
+                    latency: None,
+                    backlash: None,
+                });
+            }
+            RobotOp::SetActuatorImperfection => {
+                if let Some(actuator) = &mut turtle.joint_config.actuator {
+                    let latency = p0.max(0.0) / 1000.0;
+                    let backlash = p(1, 0.0).abs();
+                    actuator.latency = (latency > 0.0).then_some(latency);
+                    actuator.backlash = (backlash > 0.0).then_some(backlash);
                 }
-                RobotOp::SetMaterial => turtle.material_id = p0 as u8,
-                RobotOp::SetWidth => turtle.width = p(0, turtle.width),
+            }
+            RobotOp::SetMaterial => turtle.material_id = p0 as u8,
+            RobotOp::SetMaterialTheme => {
+                turtle.material_theme = (!params.is_empty() && p0 >= 0.0).then_some(p0 as u8);
+            }
+            RobotOp::SetDensity => turtle.density = (p0 > 0.0).then_some(p0),
+            RobotOp::SetAnisotropicFriction => {
+                let direction = Vec3::new(p(1, 0.0), p(2, 1.0), p(3, 0.0)).normalize_or(Vec3::Y);
+                turtle.anisotropic_friction = (p0 > 0.0).then_some(AnisotropicFriction {
+                    direction,
+                    ratio: p0,
+                });
+            }
+            RobotOp::SetGrowthStage => turtle.growth_stage = p0.max(0.0) as u32,
+            RobotOp::SetWidth => turtle.width = p(0, turtle.width),
 
-                // --- SENSORS ---
-                RobotOp::MountSensor(sensor_type) => {
-                    if let Some(mod_id) = turtle.current_module_id
-                        && let Some((mod_pos, mod_rot)) = module_transforms.get(&mod_id)
-                    {
-                        // Calculate relative transform from Module Center to Turtle Pos
-                        let world_offset = turtle.position - *mod_pos;
-                        let local_pos = mod_rot.inverse() * world_offset;
-                        // Sensor orientation relative to module
-                        let local_rot = mod_rot.inverse() * turtle.rotation;
-
-                        if let Some(module) = blueprint.modules.get_mut(&mod_id) {
-                            module.sensors.push(SensorMount {
-                                sensor_type: *sensor_type,
-                                local_position: local_pos,
-                                local_rotation: local_rot,
-                            });
-                        }
+            // --- SENSORS ---
+            RobotOp::MountSensor(ref sensor_type) => {
+                if let Some(mod_id) = turtle.current_module_id
+                    && let Some((mod_pos, mod_rot)) = ctx.module_transforms.get(&mod_id)
+                {
+                    if let Some(stats) = &mut ctx.analysis {
+                        stats.sensor_count += 1;
+                        return;
+                    }
+
+                    // Calculate relative transform from Module Center to Turtle Pos
+                    let world_offset = turtle.position - *mod_pos;
+                    let local_pos = mod_rot.inverse() * world_offset;
+                    // Sensor orientation relative to module
+                    let local_rot = mod_rot.inverse() * turtle.rotation;
+
+                    if let Some(module) = ctx.blueprint.modules.get_mut(&mod_id) {
+                        module.sensors.push(SensorMount {
+                            sensor_type: sensor_type.clone(),
+                            local_position: local_pos,
+                            local_rotation: local_rot,
+                            attachment: SensorAttachment::Module,
+                            timing: turtle.sensor_timing,
+                        });
+                    }
+                }
+            }
+            RobotOp::SetSensorTiming => {
+                turtle.sensor_timing = SensorTiming {
+                    update_rate: (p0 > 0.0).then_some(p0),
+                    latency: p(1, 0.0).max(0.0) / 1000.0,
+                    noise: p(2, 0.0).max(0.0),
+                };
+            }
+            RobotOp::MountImuAtCom => {
+                let target = if p0 == 0.0 {
+                    match turtle.current_module_id {
+                        Some(id) => ComTarget::Module(id),
+                        None => return,
                     }
+                } else {
+                    ComTarget::Robot
+                };
+                if let Some(stats) = &mut ctx.analysis {
+                    if stats.module_count > 0 {
+                        stats.sensor_count += 1;
+                    }
+                    return;
+                }
+                // Needs masses, so the IMU is placed by the physics pass.
+                ctx.annotations.push(Annotation::ImuAtCom {
+                    target,
+                    timing: turtle.sensor_timing,
+                    modules: ctx.next_module_id,
+                });
+            }
+            RobotOp::MountJointSensor(ref sensor_type) => {
+                let Some(mod_id) = turtle.current_module_id else {
+                    return;
+                };
+                if let Some(stats) = &mut ctx.analysis {
+                    if ctx.module_depths.contains_key(&mod_id) {
+                        stats.sensor_count += 1;
+                    }
+                    return;
+                }
+                if ctx.blueprint.parent_joint(mod_id).is_some()
+                    && let Some(module) = ctx.blueprint.modules.get_mut(&mod_id)
+                {
+                    module.sensors.push(SensorMount {
+                        sensor_type: sensor_type.clone(),
+                        local_position: Vec3::ZERO,
+                        local_rotation: Quat::IDENTITY,
+                        attachment: SensorAttachment::Joint,
+                        timing: turtle.sensor_timing,
+                    });
+                }
+            }
+            RobotOp::PlaceMarker(ref name) => {
+                if ctx.analysis.is_some() {
+                    return;
                 }
+                if let Some(mod_id) = turtle.current_module_id
+                    && let Some((mod_pos, mod_rot)) = ctx.module_transforms.get(&mod_id)
+                {
+                    let name = match params.first() {
+                        Some(&index) => format!("{name}{}", index as i64).into(),
+                        None => name.clone(),
+                    };
+                    let local_pos = mod_rot.inverse() * (turtle.position - *mod_pos);
+                    let _ = ctx.blueprint.add_marker(mod_id, name, local_pos);
+                }
+            }
 
-                // --- FLOW ---
-                RobotOp::Push => {
-                    if stack.len() < self.config.max_stack_depth {
-                        stack.push(turtle.clone());
+            RobotOp::AnchorToWorld => {
+                if ctx.analysis.is_none()
+                    && let Some(mod_id) = turtle.current_module_id
+                    && let Some((mod_pos, mod_rot)) = ctx.module_transforms.get(&mod_id)
+                {
+                    ctx.blueprint.add_world_joint(WorldJoint {
+                        child_id: mod_id,
+                        anchor_child: mod_rot.inverse() * (turtle.position - *mod_pos),
+                        anchor_world: turtle.position,
+                    });
+                }
+            }
+
+            // --- FLOW ---
+            RobotOp::Push => {
+                if ctx.stack.len() < self.config.max_stack_depth {
+                    let branch = turtle.branch_count;
+                    turtle.branch_count += 1;
+                    ctx.stack.push(turtle.clone());
+                    let mut path = turtle.branch_path ^ u64::from(branch);
+                    turtle.branch_path = splitmix64(&mut path);
+                    turtle.branch_count = 0;
+                    if let Some(stats) = &mut ctx.analysis {
+                        stats.max_stack_depth = stats.max_stack_depth.max(ctx.stack.len());
+                    }
+                } else {
+                    match &mut ctx.analysis {
+                        Some(stats) => stats.dropped_pushes += 1,
+                        None => ctx.blueprint.metadata.dropped_pushes += 1,
                     }
+                    ctx.report(InterpretProblem::DroppedPush { index: ctx.index });
                 }
-                RobotOp::Pop => {
-                    if let Some(state) = stack.pop() {
-                        turtle = state;
+            }
+            RobotOp::Pop => {
+                if let Some(state) = ctx.stack.pop() {
+                    *turtle = state;
+                    if let Some(radial) = &mut turtle.radial_branch
+                        && radial.depth == ctx.stack.len()
+                    {
+                        let step = radial.step;
+                        radial.remaining -= 1;
+                        if radial.remaining == 0 {
+                            turtle.radial_branch = None;
+                        }
+                        turtle.rotate_local_y(step);
                     }
+                } else {
+                    match &mut ctx.analysis {
+                        Some(stats) => stats.unmatched_pops += 1,
+                        None => ctx.blueprint.metadata.unmatched_pops += 1,
+                    }
+                    ctx.report(InterpretProblem::StackUnderflow { index: ctx.index });
+                }
+            }
+            RobotOp::BeginScope => {
+                if ctx.scopes.len() < self.config.max_stack_depth {
+                    ctx.scopes.push(turtle.style());
+                }
+            }
+            RobotOp::EndScope => {
+                if let Some(style) = ctx.scopes.pop() {
+                    turtle.set_style(style);
+                }
+            }
+
+            // --- EXTENSIONS ---
+            RobotOp::Custom(ref name) => {
+                if let Some(custom) = self.custom_ops.get(name) {
+                    custom.apply(
+                        &mut CustomOpContext {
+                            interpreter: self,
+                            build: ctx,
+                            neighbors,
+                        },
+                        params,
+                    );
                 }
-                RobotOp::Ignore => {}
             }
+            RobotOp::Ignore => {}
         }
+    }
+}
 
-        blueprint
+/// The conventional symbol bindings installed by
+/// [`RobotInterpreter::populate_standard_symbols`], as listed in the crate README.
+pub(crate) fn standard_symbols() -> Vec<(&'static str, RobotOp)> {
+    vec![
+        // Spatial
+        ("f", RobotOp::Move),
+        ("+", RobotOp::Yaw(1.0)),
+        ("-", RobotOp::Yaw(-1.0)),
+        ("&", RobotOp::Pitch(1.0)),
+        ("^", RobotOp::Pitch(-1.0)),
+        ("\\", RobotOp::Roll(1.0)),
+        ("/", RobotOp::Roll(-1.0)),
+        ("|", RobotOp::TurnAround),
+        ("R", RobotOp::RadialBranch),
+        ("~", RobotOp::Jitter),
+        ("Rs", RobotOp::DeriveSeed),
+        // Geometry
+        ("B", RobotOp::SpawnBox),
+        ("C", RobotOp::SpawnCylinder),
+        ("O", RobotOp::SpawnSphere), // Orb
+        ("K", RobotOp::SpawnCapsule),
+        // Config
+        ("!", RobotOp::SetWidth),
+        ("'", RobotOp::SetMaterial), // Using ' like visual turtle
+        ("M", RobotOp::SetMaterialTheme),
+        ("D", RobotOp::SetDensity),
+        ("Fa", RobotOp::SetAnisotropicFriction),
+        ("G", RobotOp::SetGrowthStage),
+        // Joint Configuration
+        ("J", RobotOp::SetJointType(JointType::Hinge)), // Default J is Hinge
+        ("Jf", RobotOp::SetJointType(JointType::Fixed)),
+        ("Jb", RobotOp::SetJointType(JointType::Ball)),
+        ("Jp", RobotOp::SetJointType(JointType::Prismatic)),
+        ("Jx", RobotOp::SetJointAxis(Vec3::X)),
+        ("Jy", RobotOp::SetJointAxis(Vec3::Y)),
+        ("Jz", RobotOp::SetJointAxis(Vec3::Z)),
+        ("Jl", RobotOp::SetJointLimits),
+        ("Jt", RobotOp::SetTransmission),
+        ("Js", RobotOp::SetServo),
+        ("Ja", RobotOp::SetActuator),
+        ("Jd", RobotOp::SetActuatorImperfection),
+        ("Jw", RobotOp::AnchorToWorld),
+        // Sensors
+        ("S", RobotOp::MountSensor(SensorType::Camera)), // Generic S
+        ("Si", RobotOp::MountSensor(SensorType::IMU)),
+        ("St", RobotOp::MountSensor(SensorType::Touch)),
+        ("Sl", RobotOp::MountSensor(SensorType::Lidar)),
+        (
+            "Sd",
+            RobotOp::MountSensor(SensorType::DepthCamera(Default::default())),
+        ),
+        (
+            "Sh",
+            RobotOp::MountSensor(SensorType::Thermal(Default::default())),
+        ),
+        ("Sr", RobotOp::SetSensorTiming),
+        ("Sc", RobotOp::MountImuAtCom),
+        ("Se", RobotOp::MountJointSensor(SensorType::Encoder)),
+        ("Sf", RobotOp::MountJointSensor(SensorType::ForceTorque)),
+        ("Mk", RobotOp::PlaceMarker("marker".into())),
+        // Flow
+        ("[", RobotOp::Push),
+        ("]", RobotOp::Pop),
+        ("{", RobotOp::BeginScope),
+        ("}", RobotOp::EndScope),
+    ]
+}
+
+/// A step-by-step interpretation in progress, created by [`RobotInterpreter::session`].
+pub struct InterpreterSession<'a> {
+    interpreter: &'a RobotInterpreter,
+    source: SessionSource<'a>,
+    cursor: usize,
+    /// Index of the last symbol that mapped to a real operation.
+    prev_mapped: Option<usize>,
+    ctx: BuildContext,
+}
+
+/// What an [`InterpreterSession`] steps through.
+enum SessionSource<'a> {
+    State(&'a SymbiosState),
+    Program(&'a CompiledProgram),
+}
+
+impl InterpreterSession<'_> {
+    /// Executes the next symbol and returns the operation it dispatched to, or `None` once
+    /// every symbol has been interpreted.
+    pub fn step(&mut self) -> Option<RobotOp> {
+        let op = match self.source {
+            SessionSource::State(state) => {
+                let view = state.get_view(self.cursor)?;
+                let op = self.interpreter.op_for(view.sym);
+                if *op != RobotOp::Ignore {
+                    let neighbors = OpNeighbors {
+                        prev: self.prev_mapped.and_then(|j| {
+                            let v = state.get_view(j)?;
+                            Some((self.interpreter.op_for(v.sym), v.params))
+                        }),
+                        next: self
+                            .interpreter
+                            .next_mapped(state, self.cursor + 1)
+                            .map(|(_, op, params)| (op, params)),
+                    };
+                    self.interpreter
+                        .execute(&mut self.ctx, op, view.params, &neighbors);
+                    self.prev_mapped = Some(self.cursor);
+                }
+                op.clone()
+            }
+            SessionSource::Program(program) => {
+                let (op, params) = program.get(self.cursor)?;
+                let neighbors = OpNeighbors {
+                    prev: self.cursor.checked_sub(1).and_then(|j| program.get(j)),
+                    next: program.get(self.cursor + 1),
+                };
+                self.interpreter
+                    .execute(&mut self.ctx, op, params, &neighbors);
+                op.clone()
+            }
+        };
+        self.cursor += 1;
+        Some(op)
+    }
+
+    /// Executes all remaining symbols.
+    pub fn run_to_end(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    /// Index of the next symbol (or compiled operation) to be interpreted.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// True once every symbol has been interpreted.
+    pub fn is_finished(&self) -> bool {
+        let len = match self.source {
+            SessionSource::State(state) => state.len(),
+            SessionSource::Program(program) => program.len(),
+        };
+        self.cursor >= len
+    }
+
+    /// The current turtle state.
+    pub fn turtle(&self) -> &RobotTurtleState {
+        &self.ctx.turtle
+    }
+
+    /// Number of turtle states currently saved on the push/pop stack.
+    pub fn stack_depth(&self) -> usize {
+        self.ctx.stack.len()
+    }
+
+    /// The blueprint laid out so far. Like [`RobotLayout::blueprint`], its masses are only
+    /// computed by [`into_blueprint`](Self::into_blueprint).
+    pub fn blueprint(&self) -> &RobotBlueprint {
+        &self.ctx.blueprint
+    }
+
+    /// Consumes the session and returns the blueprint built so far, without executing the
+    /// remaining symbols. Whole-robot adjustments such as
+    /// [`RobotConfig::target_density`] are applied to the partial robot.
+    pub fn into_blueprint(self) -> RobotBlueprint {
+        self.interpreter.annotate(self.ctx.into_layout())
+    }
+}
+
+/// The operations immediately before and after the one being executed, letting op
+/// handlers implement context-sensitive conventions.
+///
+/// Symbols mapped to [`RobotOp::Ignore`] are skipped, so the neighbours are the same whether
+/// a [`SymbiosState`] or its [`CompiledProgram`] is interpreted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OpNeighbors<'a> {
+    /// The previously executed operation and its parameters.
+    pub prev: Option<(&'a RobotOp, &'a [f64])>,
+    /// The operation that will execute next and its parameters.
+    pub next: Option<(&'a RobotOp, &'a [f64])>,
+}
+
+impl OpNeighbors<'_> {
+    /// Length of the segment the next operation would spawn, if it is a geometry op.
+    fn next_segment_length(&self, config: &RobotConfig, width: f32) -> Option<f32> {
+        let (op, params) = self.next?;
+        let p = |idx: usize, def: f32| params.get(idx).map_or(def, |&x| x as f32);
+        match op {
+            RobotOp::SpawnBox | RobotOp::SpawnCylinder => Some(p(0, config.default_length).abs()),
+            RobotOp::SpawnCapsule => {
+                let radius = p(1, width / 2.0).abs();
+                let height = config
+                    .capsule_length
+                    .section(p(0, config.default_length).abs(), radius);
+                Some(config.capsule_length.length(height, radius))
+            }
+            RobotOp::SpawnSphere => Some(p(0, width / 2.0).abs() * 2.0),
+            _ => None,
+        }
+    }
+}
+
+/// Mutable state threaded through a single interpretation run.
+#[derive(Clone)]
+struct BuildContext {
+    turtle: RobotTurtleState,
+    stack: Vec<RobotTurtleState>,
+    /// Configurations saved by [`RobotOp::BeginScope`].
+    scopes: Vec<TurtleStyle>,
+    /// World-space transform (Position, Rotation) of every module spawned so far.
+    /// We need this to calculate relative anchor points for joints and sensors.
+    module_transforms: HashMap<ModuleId, (Vec3, Quat)>,
+    /// Shape of every module spawned so far, for finding the face the turtle grows from.
+    module_shapes: HashMap<ModuleId, ShapePrimitive>,
+    /// Joint-chain depth of every module; only tracked during analysis.
+    module_depths: HashMap<ModuleId, usize>,
+    /// Total actuator mass; only tracked during analysis.
+    point_mass: f32,
+    /// Joints whose effort and/or velocity limits were defaulted, as
+    /// `(joint index, effort, velocity)`.
+    derived_limits: Vec<(usize, bool, bool)>,
+    /// Mass-dependent work left to the physics pass, in op order.
+    annotations: Vec<Annotation>,
+    next_module_id: ModuleId,
+    /// Degrees of freedom of the joints created so far.
+    dof: usize,
+    blueprint: RobotBlueprint,
+    /// `Some` during [`RobotInterpreter::analyze`]: statistics are recorded instead of
+    /// modules and joints being added to `blueprint`.
+    analysis: Option<InterpretationStats>,
+    /// Position in the symbol string of the op being executed.
+    index: usize,
+    /// `Some` during [`RobotInterpreter::try_build_blueprint`], collecting its problems.
+    problems: Option<Vec<InterpretProblem>>,
+    /// `Some` during [`RobotInterpreter::build_blueprint_traced`].
+    trace: Option<Vec<TraceStep>>,
+}
+
+impl BuildContext {
+    fn report(&mut self, problem: InterpretProblem) {
+        if let Some(problems) = &mut self.problems {
+            problems.push(problem);
+        }
+    }
+
+    /// Reports and clears `symbols`, the `(index, symbol)` of symbols mapped to no op.
+    fn report_unmapped(&mut self, symbols: &mut Vec<(usize, u16)>) {
+        for (index, symbol) in symbols.drain(..) {
+            self.index = index;
+            self.report(InterpretProblem::UnmappedSymbol { index, symbol });
+        }
+    }
+
+    /// Appends the step that took the turtle from `before` to its current state, creating
+    /// the modules from `first_module` and the joints from `first_joint` on.
+    fn record_step(
+        &mut self,
+        index: usize,
+        op: &RobotOp,
+        params: &[f64],
+        before: RobotTurtleState,
+        first_module: ModuleId,
+        first_joint: usize,
+    ) {
+        let Some(trace) = &mut self.trace else {
+            return;
+        };
+        let modules = (first_module..self.next_module_id).collect();
+        trace.push(TraceStep {
+            index,
+            op: op.clone(),
+            params: params.to_vec(),
+            before,
+            after: self.turtle.clone(),
+            modules,
+            joints: (first_joint..self.blueprint.joints.len()).collect(),
+        });
+    }
+
+    fn into_layout(self) -> RobotLayout {
+        RobotLayout {
+            blueprint: self.blueprint,
+            annotations: self.annotations,
+            derived_limits: self.derived_limits,
+        }
+    }
+}
+
+/// The result of the geometric pass, [`RobotInterpreter::layout`].
+///
+/// Holds everything the grammar decided except mass; [`RobotInterpreter::annotate`] turns
+/// it into a finished [`RobotBlueprint`].
+#[derive(Clone, Debug)]
+pub struct RobotLayout {
+    blueprint: RobotBlueprint,
+    annotations: Vec<Annotation>,
+    derived_limits: Vec<(usize, bool, bool)>,
+}
+
+impl RobotLayout {
+    /// Module poses and shapes, topology, sensors and joint settings.
+    ///
+    /// Module masses are zero, actuator masses are not yet added to their parents, and
+    /// IMUs placed at a center of mass ([`RobotOp::MountImuAtCom`]) are not yet mounted.
+    pub fn blueprint(&self) -> &RobotBlueprint {
+        &self.blueprint
+    }
+}
+
+/// Mass-dependent work recorded by the geometric pass.
+#[derive(Clone, Debug)]
+enum Annotation {
+    /// An actuator's mass, carried by `module` at `position` (module-local).
+    PointMass {
+        module: ModuleId,
+        mass: f32,
+        position: Vec3,
+    },
+    /// A [`RobotOp::MountImuAtCom`], with the number of modules spawned when it ran.
+    ImuAtCom {
+        target: ComTarget,
+        timing: SensorTiming,
+        modules: ModuleId,
+    },
+}
+
+/// Mounts an IMU at `target`'s center of mass as it was when only modules `0..modules`
+/// existed.
+fn mount_imu_at_com(
+    blueprint: &mut RobotBlueprint,
+    target: ComTarget,
+    timing: SensorTiming,
+    modules: ModuleId,
+) {
+    let mount = |bp: &mut RobotBlueprint| {
+        let host = bp.mount_imu_at_com(target).ok()?;
+        let imu = bp.modules.get_mut(&host)?.sensors.pop()?;
+        Some((host, imu))
+    };
+    let mounted = if blueprint.modules.keys().all(|&id| id < modules) {
+        mount(blueprint)
+    } else {
+        // Later modules did not exist yet when the op ran.
+        let mut partial = blueprint.clone();
+        partial.modules.retain(|&id, _| id < modules);
+        mount(&mut partial)
+    };
+    if let Some((host, mut imu)) = mounted
+        && let Some(module) = blueprint.modules.get_mut(&host)
+    {
+        imu.timing = timing;
+        module.sensors.push(imu);
+    }
+}
+
+/// One dispatched symbol of [`RobotInterpreter::build_blueprint_traced`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceStep {
+    /// Position of the symbol in the [`SymbiosState`].
+    pub index: usize,
+    /// The op the symbol was dispatched to.
+    pub op: RobotOp,
+    /// The symbol's parameters.
+    pub params: Vec<f64>,
+    /// The turtle before the op ran.
+    pub before: RobotTurtleState,
+    /// The turtle after the op ran.
+    pub after: RobotTurtleState,
+    /// Modules the op spawned.
+    pub modules: Vec<ModuleId>,
+    /// Indices into [`RobotBlueprint::joints`] of the joints the op created.
+    pub joints: Vec<usize>,
+}
+
+/// Where [`RobotInterpreter::extend_blueprint`] and
+/// [`build_sequence`](RobotInterpreter::build_sequence) resume interpretation: the turtle,
+/// stack and partial robot after a prefix of the previous state. The default checkpoint
+/// starts from scratch.
+#[derive(Clone, Default)]
+pub struct TurtleCheckpoint {
+    /// Index of the first symbol to interpret.
+    cursor: usize,
+    /// Number of leading symbols a state must share with the previous one to resume.
+    shared: usize,
+    /// [`prefix_hash`] of those symbols.
+    hash: u64,
+    ctx: Option<Box<BuildContext>>,
+}
+
+impl std::fmt::Debug for TurtleCheckpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TurtleCheckpoint")
+            .field("cursor", &self.cursor)
+            .field("shared", &self.shared)
+            .field("hash", &self.hash)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TurtleCheckpoint {
+    /// Snapshots `ctx`, which has interpreted `state` up to `cursor`. The symbol at
+    /// `cursor` must stay the same too, since the op before it has already looked ahead.
+    fn at(state: &SymbiosState, cursor: usize, ctx: &BuildContext) -> Self {
+        let shared = (cursor + 1).min(state.len());
+        Self {
+            cursor,
+            shared,
+            hash: prefix_hash(state, shared),
+            ctx: Some(Box::new(ctx.clone())),
+        }
+    }
+
+    /// Index of the first symbol the next [`extend_blueprint`](RobotInterpreter::extend_blueprint)
+    /// interprets, if the state still starts with the symbols before it.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+}
+
+/// Why [`RobotInterpreter::try_build_blueprint`] rejected a symbol string.
+#[derive(Error, Clone, Debug, PartialEq)]
+#[error("{} interpretation problem(s), first: {}", .problems.len(), .problems[0])]
+pub struct InterpretError {
+    /// Every problem found, in symbol order. Never empty.
+    pub problems: Vec<InterpretProblem>,
+}
+
+/// Something [`RobotInterpreter::build_blueprint`] silently ignores. `index` is the
+/// symbol's position in the [`SymbiosState`].
+#[derive(Error, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InterpretProblem {
+    /// A pop (`]`) on an empty stack.
+    #[error("symbol {index}: pop on an empty stack")]
+    StackUnderflow {
+        /// Position of the symbol.
+        index: usize,
+    },
+    /// A push (`[`) beyond [`RobotConfig::max_stack_depth`].
+    #[error("symbol {index}: push beyond the maximum stack depth")]
+    DroppedPush {
+        /// Position of the symbol.
+        index: usize,
+    },
+    /// A symbol with no op, or bound to [`RobotOp::Ignore`].
+    #[error("symbol {index}: id {symbol} is not mapped to an op")]
+    UnmappedSymbol {
+        /// Position of the symbol.
+        index: usize,
+        /// The symbol's ID in the [`SymbolTable`].
+        symbol: u16,
+    },
+    /// A module with a zero, negative or non-finite dimension. It is spawned anyway.
+    #[error("symbol {index}: degenerate shape {shape:?}")]
+    DegenerateShape {
+        /// Position of the symbol.
+        index: usize,
+        /// The shape as spawned.
+        shape: ShapePrimitive,
+    },
+}
+
+/// Whether `shape` has a dimension that is not positive and finite. A capsule's
+/// cylindrical section may be empty.
+fn is_degenerate(shape: &ShapePrimitive) -> bool {
+    let bad = |x: f32| !(x.is_finite() && x > 0.0);
+    match *shape {
+        ShapePrimitive::Box(h) => bad(h.x) || bad(h.y) || bad(h.z),
+        ShapePrimitive::Cylinder { radius, height } => bad(radius) || bad(height),
+        ShapePrimitive::Sphere(radius) => bad(radius),
+        ShapePrimitive::Capsule { radius, height } => {
+            bad(radius) || !(height.is_finite() && height >= 0.0)
+        }
+    }
+}
+
+/// Summary counts produced by [`RobotInterpreter::analyze`].
+#[derive(Clone, Debug, Default)]
+pub struct InterpretationStats {
+    /// Number of modules that would be spawned.
+    pub module_count: usize,
+    /// Number of joints that would be created, per joint type.
+    pub joints_by_type: HashMap<JointType, usize>,
+    /// Number of sensors that would be mounted.
+    pub sensor_count: usize,
+    /// Deepest push/pop nesting reached.
+    pub max_stack_depth: usize,
+    /// Pushes ignored for exceeding [`RobotConfig::max_stack_depth`].
+    pub dropped_pushes: usize,
+    /// Pops ignored because the stack was empty.
+    pub unmatched_pops: usize,
+    /// Number of joints on the longest root-to-leaf chain.
+    pub max_chain_depth: usize,
+    /// Estimated total mass in kg.
+    pub estimated_mass: f32,
+    /// Rest-pose bounding box, or `None` if no module would be spawned.
+    pub aabb: Option<Aabb3d>,
+    /// Total shape volume in m³.
+    pub volume: f32,
+    /// Number of modules skipped for reaching outside [`RobotConfig::max_extent`].
+    pub skipped_modules: usize,
+}
+
+impl InterpretationStats {
+    /// Total number of joints of every type.
+    pub fn joint_count(&self) -> usize {
+        self.joints_by_type.values().sum()
+    }
+
+    fn record_module(&mut self, shape: ShapePrimitive, mass: f32, pos: Vec3, rot: Quat) {
+        self.module_count += 1;
+        self.estimated_mass += mass;
+        self.volume += shape.volume();
+        let aabb = shape.to_bevy_primitive().aabb_3d(Isometry3d::new(pos, rot));
+        self.aabb = Some(match self.aabb {
+            Some(c) => c.merge(&aabb),
+            None => aabb,
+        });
+    }
+
+    fn record_joint(&mut self, joint_type: JointType, depth: usize) {
+        *self.joints_by_type.entry(joint_type).or_default() += 1;
+        self.max_chain_depth = self.max_chain_depth.max(depth);
     }
 }
@@ -28,15 +28,147 @@
 //!
 //! ## Modules
 //!
+//! - [`backends`] — The [`backends::PhysicsBackend`] trait driven by
+//!   [`blueprint::RobotBlueprint::instantiate`], and engine adapters:
+//!   `backends::avian::spawn_blueprint` builds Avian3D bodies and joints (feature `avian`),
+//!   `backends::rapier::build_blueprint` fills Rapier3D body, collider and joint sets
+//!   (feature `rapier`).
+//! - `bevy_spawn` — [Bevy](https://bevyengine.org) plugin spawning blueprints as entities
+//!   via `RobotPlugin` (feature `bevy`).
+//! - [`binary`] — Compact, versioned binary serialization via
+//!   [`blueprint::RobotBlueprint::to_bytes`] and [`blueprint::RobotBlueprint::from_bytes`].
 //! - [`blueprint`] — Data structures: [`blueprint::RobotBlueprint`], [`blueprint::RobotModule`],
 //!   [`blueprint::JointDefinition`], [`blueprint::ShapePrimitive`], etc.
+//! - [`bom`] — Bill-of-materials reports via [`blueprint::RobotBlueprint::bom`].
+//! - [`collision`] — Recommended self-collision exclusions via
+//!   [`blueprint::RobotBlueprint::self_collision_exclusions`].
+//! - [`control`] — Controller scaffolding via [`blueprint::RobotBlueprint::control_interface`]:
+//!   named observation/action layouts as JSON, Python or Rust.
+//! - [`convention`] — Conversion to Z-up and left-handed coordinate conventions via
+//!   [`convention::convert`].
+//! - [`debug`] — Compact, stable `Debug` output via
+//!   [`blueprint::RobotBlueprint::compact_debug`].
+//! - [`dot`] — Graphviz rendering of the kinematic tree via
+//!   [`blueprint::RobotBlueprint::to_dot`].
+//! - [`export`] — [`export::ExportGeometry`] and [`export::UpAxis`], shared by the
+//!   per-format options of the URDF, SDF and MJCF exporters.
+//! - [`file`] — Loading and saving by file extension via
+//!   [`blueprint::RobotBlueprint::load`] and [`blueprint::RobotBlueprint::save`].
+//! - [`gltf`] — Binary glTF 2.0 export of the rest pose via
+//!   [`blueprint::RobotBlueprint::to_glb`].
+//! - [`instancing`] — Per-shape instancing batches via
+//!   [`blueprint::RobotBlueprint::instancing_batches`].
 //! - [`interpreter`] — [`interpreter::RobotInterpreter`] and [`interpreter::RobotConfig`].
+//! - [`lod`] — Level-of-detail variants via [`blueprint::RobotBlueprint::lod`].
+//! - [`material`] — [`material::MaterialPalette`] mapping material IDs to densities, and
+//!   [`material::ContactTable`] contact parameters between materials.
+//! - [`mesh`] — [`mesh::TriMesh`] tessellation of shape primitives, and whole-robot meshes
+//!   via [`blueprint::RobotBlueprint::to_trimesh`].
+//! - [`metrics`] — Morphological descriptors such as [`metrics::feature_vector`], and
+//!   population summaries via [`metrics::PopulationStats`].
+//! - [`mjcf`] — MuJoCo export with a nested body tree via
+//!   [`blueprint::RobotBlueprint::to_mjcf`], and import via
+//!   [`blueprint::RobotBlueprint::from_mjcf`].
+//! - [`obj`] — Wavefront OBJ export of the rest pose via [`blueprint::RobotBlueprint::to_obj`].
+//! - [`program`] — [`program::CompiledProgram`], a pre-resolved op stream.
+//! - `python` — Python bindings for the interpreter and blueprints (feature `pyo3`).
+//! - [`recenter`] — Canonical placement (center of mass at the origin, principal axes
+//!   along the world axes) via [`blueprint::RobotBlueprint::recenter`].
+//! - [`ros`] — ROS 2 `robot_description` packaging (plain URDF plus a joint manifest) via
+//!   [`blueprint::RobotBlueprint::ros_description`].
+//! - [`sampling`] — Deterministic surface/volume point sampling of the rest pose.
+//! - [`validation`] — Joint-limit checks and repair via [`blueprint::RobotBlueprint::validate_limits`],
+//!   and floating-anchor checks via [`blueprint::RobotBlueprint::anchor_issues`].
+//! - [`voxel`] — Occupancy-grid voxelization via [`blueprint::RobotBlueprint::voxelize`].
+//! - [`scad`] — OpenSCAD export via [`blueprint::RobotBlueprint::to_scad`].
+//! - `schema` — JSON Schema of serialized blueprints via `RobotBlueprint::json_schema`
+//!   (feature `schemars`).
+//! - [`sdf`] — SDF 1.9 export for Gazebo via [`blueprint::RobotBlueprint::to_sdf`].
+//! - [`stl`] — Per-module STL export with an assembly manifest, and whole-robot STL via
+//!   [`blueprint::RobotBlueprint::to_stl`].
+//! - [`svg`] — 2D SVG schematics of the rest pose via [`blueprint::RobotBlueprint::to_svg`].
+//! - [`servo`] — Catalog of real servo presets ([`servo::SERVO_PRESETS`]).
+//! - [`spatial`] — Rest-pose overlap and nearest-module queries via
+//!   [`blueprint::RobotBlueprint::spatial_index`].
+//! - `usd` — USD stage export via `RobotBlueprint::to_usda` and `to_usdz` (feature `usd`).
+//! - [`urdf`] — Plain URDF export via [`blueprint::RobotBlueprint::to_urdf`].
+//! - [`xacro`] — Parameterized URDF export via [`blueprint::RobotBlueprint::to_xacro`].
 //! - [`turtle`] — [`turtle::RobotTurtleState`], [`turtle::RobotOp`], and [`turtle::ActiveJointConfig`].
 
+pub mod backends;
+#[cfg(feature = "bevy")]
+pub mod bevy_spawn;
+pub mod binary;
 pub mod blueprint;
+pub mod bom;
+pub mod collision;
+pub mod control;
+pub mod convention;
+pub mod debug;
+pub mod dot;
+pub mod export;
+pub mod file;
+pub mod gltf;
+pub mod instancing;
 pub mod interpreter;
+pub mod lod;
+pub mod material;
+pub mod mesh;
+pub mod metrics;
+pub mod mjcf;
+pub mod obj;
+mod parallel;
+pub mod program;
+#[cfg(feature = "pyo3")]
+pub mod python;
+pub mod recenter;
+pub mod ros;
+pub mod sampling;
+pub mod scad;
+#[cfg(feature = "schemars")]
+pub mod schema;
+pub mod sdf;
+pub mod servo;
+pub mod spatial;
+pub mod stl;
+mod stream;
+pub mod svg;
 pub mod turtle;
+pub mod urdf;
+#[cfg(feature = "usd")]
+pub mod usd;
+pub mod validation;
+pub mod voxel;
+pub mod xacro;
 
+pub use backends::{JointFrame, PhysicsBackend};
+#[cfg(feature = "bevy")]
+pub use bevy_spawn::{RobotJoint, RobotPart, RobotPlugin, RobotRoot, RobotSensor, SpawnRobot};
+pub use binary::{BINARY_FORMAT_VERSION, BINARY_MAGIC, BinaryError};
 pub use blueprint::*;
+pub use bom::{BillOfMaterials, BomCategory, BomLine, CostCatalog};
+pub use collision::{CollisionExclusion, ExclusionReason};
+pub use control::{ControlChannel, ControlInterface, PolicySpec, TensorSpec};
+pub use convention::{Convention, convert};
+pub use debug::{CompactDebug, DEFAULT_DEBUG_PRECISION};
+pub use export::{ExportGeometry, UpAxis};
+pub use file::BlueprintFormat;
+pub use gltf::DEFAULT_GLTF_SEGMENTS;
+pub use instancing::{InstanceBatch, ShapeKind, population_batches};
 pub use interpreter::*;
+pub use lod::{LodSet, MAX_LOD_LEVELS};
+pub use material::{ContactPair, ContactSurface, ContactTable, Material, MaterialPalette};
+pub use mesh::{DEFAULT_MESH_SEGMENTS, TriMesh};
+pub use mjcf::{MjcfError, MjcfOptions};
+pub use program::CompiledProgram;
+pub use recenter::RecenterOrigin;
+pub use ros::{RosDescription, RosJoint};
+pub use sampling::{PointCloud, SampleMode};
+pub use sdf::SdfOptions;
+pub use spatial::SpatialIndex;
+pub use stl::{AssemblyManifest, StlFormat, StlOptions};
+pub use svg::ProjectionPlane;
 pub use turtle::*;
+pub use urdf::UrdfOptions;
+pub use validation::{AnchorIssue, AnchorSide, LimitIssue, LimitIssueKind, LimitValidation};
+pub use voxel::VoxelGrid;
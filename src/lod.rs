@@ -0,0 +1,252 @@
+//! Simplified level-of-detail variants of a blueprint.
+//!
+//! Viewers that show hundreds of robots at once do not need every fixed bracket and
+//! sensor of each one. [`RobotBlueprint::lod`] packages the full model together with
+//! progressively coarser variants, so a renderer can pick a level by distance while the
+//! simulator keeps using level `0`.
+
+use crate::blueprint::{
+    JointDefinition, JointType, Marker, ModuleId, RobotBlueprint, RobotModule, SensorAttachment,
+    SensorType, ShapePrimitive, WorldJoint,
+};
+use crate::collision::CollisionExclusion;
+use bevy_math::Isometry3d;
+use bevy_math::bounding::{Aabb3d, Bounded3d as _, BoundingVolume as _};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Number of distinct levels [`RobotBlueprint::lod`] can produce.
+pub const MAX_LOD_LEVELS: usize = 4;
+
+/// A blueprint and its simplified variants, finest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LodSet {
+    /// Level `0` is the full model; each further level is coarser than the one before.
+    pub levels: Vec<RobotBlueprint>,
+}
+
+impl LodSet {
+    /// The variant at `level`, or `None` if the set has fewer levels.
+    pub fn level(&self, level: usize) -> Option<&RobotBlueprint> {
+        self.levels.get(level)
+    }
+
+    /// The coarsest variant, or `None` if the set is empty.
+    pub fn coarsest(&self) -> Option<&RobotBlueprint> {
+        self.levels.last()
+    }
+}
+
+impl RobotBlueprint {
+    /// Builds `levels` variants of this blueprint, from the full model down.
+    ///
+    /// Each level applies one more simplification on top of the previous one:
+    ///
+    /// 1. Modules connected by [`JointType::Fixed`] joints are merged into a single box
+    ///    hull (see [`merge_fixed_joints`](Self::merge_fixed_joints)).
+    /// 2. Every sensor except IMUs is removed.
+    /// 3. Each articulated tree collapses into one box hull.
+    ///
+    /// At most [`MAX_LOD_LEVELS`] levels are produced.
+    pub fn lod(&self, levels: usize) -> LodSet {
+        let mut set = LodSet::default();
+        let mut current = self.clone();
+        for level in 0..levels.min(MAX_LOD_LEVELS) {
+            current = match level {
+                0 => current,
+                1 => current.merge_fixed_joints(),
+                2 => {
+                    for module in current.modules.values_mut() {
+                        module
+                            .sensors
+                            .retain(|s| matches!(s.sensor_type, SensorType::IMU));
+                    }
+                    current
+                }
+                _ => current.fold_joints(|_| true),
+            };
+            set.levels.push(current.clone());
+        }
+        set
+    }
+
+    /// Returns a copy in which every group of modules held together by fixed joints is
+    /// replaced by one module.
+    ///
+    /// The merged module keeps the ID, orientation, material and parent joint of the
+    /// group's topmost module. Its shape is the box hull of the group in that orientation,
+    /// and its mass and center of mass are the group's totals. Remaining joints, world welds,
+    /// sensors and markers are re-expressed in the merged frames, so every anchor, sensor
+    /// and marker keeps its world-space rest pose; sensors on the fixed joints that
+    /// disappear become module-mounted. Collision exclusions between merged modules are
+    /// dropped.
+    pub fn merge_fixed_joints(&self) -> RobotBlueprint {
+        self.fold_joints(|j| j.joint_type == JointType::Fixed)
+    }
+
+    /// Merges the child of every joint accepted by `fold` into its parent.
+    fn fold_joints(&self, fold: impl Fn(&JointDefinition) -> bool) -> RobotBlueprint {
+        // Representative of every folded module, resolved parents first.
+        let mut rep: HashMap<ModuleId, ModuleId> = HashMap::new();
+        for joint in self.joints_topological() {
+            if fold(joint) {
+                let parent = rep
+                    .get(&joint.parent_id)
+                    .copied()
+                    .unwrap_or(joint.parent_id);
+                rep.insert(joint.child_id, parent);
+            }
+        }
+        let rep_of = |id: ModuleId| rep.get(&id).copied().unwrap_or(id);
+
+        let mut groups: HashMap<ModuleId, Vec<ModuleId>> = HashMap::new();
+        for id in self.topological_order() {
+            groups.entry(rep_of(id)).or_default().push(id);
+        }
+
+        let mut out = RobotBlueprint {
+            roots: self.roots.iter().map(|&id| rep_of(id)).collect(),
+            contacts: self.contacts.clone(),
+            metadata: self.metadata.clone(),
+            ..RobotBlueprint::new()
+        };
+        for (&id, members) in &groups {
+            out.modules.insert(id, self.hull(id, members));
+        }
+
+        // Re-expresses a point or direction given in module `id`'s old frame in the frame
+        // of the module that replaced it.
+        let to_new = |id: ModuleId, local: Vec3| -> Vec3 {
+            let (pos, rot) = self.modules[&id].transform;
+            let (new_pos, new_rot) = out.modules[&rep_of(id)].transform;
+            new_rot.inverse() * (pos + rot * local - new_pos)
+        };
+        let rotate_to_new = |id: ModuleId, dir: Vec3| -> Vec3 {
+            let rot = self.modules[&id].transform.1;
+            let new_rot = out.modules[&rep_of(id)].transform.1;
+            new_rot.inverse() * rot * dir
+        };
+
+        let mut joints = Vec::new();
+        for joint in &self.joints {
+            if fold(joint)
+                || !self.modules.contains_key(&joint.parent_id)
+                || !self.modules.contains_key(&joint.child_id)
+            {
+                continue;
+            }
+            joints.push(JointDefinition {
+                parent_id: rep_of(joint.parent_id),
+                child_id: joint.child_id,
+                anchor_parent: to_new(joint.parent_id, joint.anchor_parent),
+                anchor_child: to_new(joint.child_id, joint.anchor_child),
+                axis: rotate_to_new(joint.parent_id, joint.axis),
+                child_axis: rotate_to_new(joint.child_id, joint.child_axis),
+                ..joint.clone()
+            });
+        }
+        let mut welds = Vec::new();
+        for weld in &self.world_joints {
+            if self.modules.contains_key(&weld.child_id) {
+                welds.push(WorldJoint {
+                    child_id: rep_of(weld.child_id),
+                    anchor_child: to_new(weld.child_id, weld.anchor_child),
+                    ..*weld
+                });
+            }
+        }
+        for pair in &self.collision_exclusions {
+            let pair = CollisionExclusion::new(rep_of(pair.a), rep_of(pair.b), pair.reason);
+            let known = out
+                .collision_exclusions
+                .iter()
+                .any(|p| (p.a, p.b) == (pair.a, pair.b));
+            if pair.a != pair.b && !known {
+                out.collision_exclusions.push(pair);
+            }
+        }
+        for joint in joints {
+            out.add_joint(joint);
+        }
+        for weld in welds {
+            out.add_world_joint(weld);
+        }
+        out
+    }
+
+    /// One module standing in for `members`, in the frame of `id` (the first member).
+    fn hull(&self, id: ModuleId, members: &[ModuleId]) -> RobotModule {
+        let base = &self.modules[&id];
+        if members.len() == 1 {
+            return base.clone();
+        }
+        let (pos, rot) = base.transform;
+        let inv = rot.inverse();
+
+        let mut bounds: Option<Aabb3d> = None;
+        let (mut mass, mut weighted_com) = (0.0, Vec3::ZERO);
+        for member in members {
+            let module = &self.modules[member];
+            let (m_pos, m_rot) = module.transform;
+            let aabb = module
+                .shape
+                .to_bevy_primitive()
+                .aabb_3d(Isometry3d::new(inv * (m_pos - pos), inv * m_rot));
+            bounds = Some(bounds.map_or(aabb, |b| b.merge(&aabb)));
+            mass += module.mass;
+            weighted_com += module.world_center_of_mass() * module.mass;
+        }
+        let bounds = bounds.expect("groups are never empty");
+        let center = pos + rot * Vec3::from(bounds.center());
+        let shape = ShapePrimitive::Box(Vec3::from(bounds.half_size()));
+        let volume = shape.volume();
+        let pivot = pos - rot * base.pivot_offset;
+
+        let mut hull = RobotModule {
+            mass,
+            center_of_mass: if mass > 0.0 {
+                inv * (weighted_com / mass - center)
+            } else {
+                Vec3::ZERO
+            },
+            density: if volume > 0.0 { mass / volume } else { 0.0 },
+            pivot_offset: inv * (center - pivot),
+            material_id: base.material_id,
+            growth_stage: members
+                .iter()
+                .map(|m| self.modules[m].growth_stage)
+                .min()
+                .unwrap_or(base.growth_stage),
+            sensors: Vec::new(),
+            markers: Vec::new(),
+            ..RobotModule::new(shape, 0.0, (center, rot))
+        };
+        for member in members {
+            for sensor in &self.modules[member].sensors {
+                // The representative's own parent joint survives, so its joint-mounted
+                // sensors (already in the unchanged joint frame) are kept as they are.
+                if *member == id && sensor.attachment == SensorAttachment::Joint {
+                    hull.sensors.push(sensor.clone());
+                    continue;
+                }
+                let Some((s_pos, s_rot)) = self.sensor_pose(*member, sensor) else {
+                    continue;
+                };
+                let mut sensor = sensor.clone();
+                sensor.attachment = SensorAttachment::Module;
+                sensor.local_position = inv * (s_pos - center);
+                sensor.local_rotation = inv * s_rot;
+                hull.sensors.push(sensor);
+            }
+            let transform = self.modules[member].transform;
+            for marker in &self.modules[member].markers {
+                hull.markers.push(Marker {
+                    name: marker.name.clone(),
+                    local_position: inv * (marker.world_position(transform) - center),
+                });
+            }
+        }
+        hull
+    }
+}
@@ -0,0 +1,142 @@
+//! Physical materials referenced by [`MaterialId`].
+//!
+//! A module's material ID is mostly a rendering hint, but with a [`MaterialPalette`] in
+//! [`RobotConfig::material_palette`](crate::RobotConfig::material_palette) it also selects
+//! the module's density, so a part that looks like aluminum weighs like aluminum.
+//! A [`ContactTable`] on the blueprint describes how those materials grip each other and
+//! the ground.
+
+use crate::blueprint::MaterialId;
+use serde::{Deserialize, Serialize};
+
+/// A named material with the density used for mass computation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    /// Display name, e.g. `"aluminum"`.
+    pub name: String,
+    /// Density in kg/m³.
+    pub density: f32,
+}
+
+impl Material {
+    /// Creates a material.
+    pub fn new(name: impl Into<String>, density: f32) -> Self {
+        Self {
+            name: name.into(),
+            density,
+        }
+    }
+}
+
+/// Materials indexed by [`MaterialId`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaterialPalette {
+    /// Entry `i` describes material ID `i`.
+    pub materials: Vec<Material>,
+}
+
+impl MaterialPalette {
+    /// Creates a palette from materials in ID order.
+    pub fn new(materials: Vec<Material>) -> Self {
+        Self { materials }
+    }
+
+    /// Plastic (`0`), aluminum (`1`) and rubber (`2`) at the density of solid stock
+    /// (PLA, 6061 alloy, natural rubber). Hollow or printed parts with sparse infill are
+    /// considerably lighter.
+    pub fn standard() -> Self {
+        Self::new(vec![
+            Material::new("plastic", 1240.0),
+            Material::new("aluminum", 2700.0),
+            Material::new("rubber", 1100.0),
+        ])
+    }
+
+    /// The material with ID `id`, if the palette defines it.
+    pub fn get(&self, id: MaterialId) -> Option<&Material> {
+        self.materials.get(id as usize)
+    }
+
+    /// Density of material `id`, if the palette defines it.
+    pub fn density(&self, id: MaterialId) -> Option<f32> {
+        self.get(id).map(|m| m.density)
+    }
+}
+
+/// One side of a contact: a module material, or the ground the robot stands on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ContactSurface {
+    /// Any module with this material.
+    Material(MaterialId),
+    /// The ground plane of the simulation.
+    Ground,
+}
+
+/// Contact parameters between two surfaces.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ContactPair {
+    /// First surface; the pair is symmetric.
+    pub a: ContactSurface,
+    /// Second surface.
+    pub b: ContactSurface,
+    /// Coulomb friction coefficient.
+    pub friction: f32,
+    /// Coefficient of restitution in `[0, 1]`.
+    pub restitution: f32,
+    /// Contact stiffness in N/m, if the simulator models soft contacts.
+    #[serde(default)]
+    pub stiffness: Option<f32>,
+    /// Contact damping in N·s/m, if the simulator models soft contacts.
+    #[serde(default)]
+    pub damping: Option<f32>,
+}
+
+impl ContactPair {
+    /// Creates a rigid contact with the given friction and restitution.
+    pub fn new(a: ContactSurface, b: ContactSurface, friction: f32, restitution: f32) -> Self {
+        Self {
+            a,
+            b,
+            friction,
+            restitution,
+            stiffness: None,
+            damping: None,
+        }
+    }
+
+    /// Returns `true` if this pair describes contacts between `a` and `b`, in either order.
+    pub fn matches(&self, a: ContactSurface, b: ContactSurface) -> bool {
+        (self.a == a && self.b == b) || (self.a == b && self.b == a)
+    }
+}
+
+/// Contact parameters per surface pair, carried by a blueprint so that grip tuning
+/// travels with the phenotype into whichever simulator or exporter consumes it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ContactTable {
+    /// Entries in insertion order; at most one per unordered pair.
+    pub pairs: Vec<ContactPair>,
+}
+
+impl ContactTable {
+    /// Adds `pair`, replacing any existing entry for the same two surfaces.
+    pub fn set(&mut self, pair: ContactPair) {
+        match self.pairs.iter_mut().find(|p| p.matches(pair.a, pair.b)) {
+            Some(existing) => *existing = pair,
+            None => self.pairs.push(pair),
+        }
+    }
+
+    /// Contact parameters between `a` and `b`, in either order.
+    pub fn get(&self, a: ContactSurface, b: ContactSurface) -> Option<&ContactPair> {
+        self.pairs.iter().find(|p| p.matches(a, b))
+    }
+
+    /// Returns `true` if no pair is defined.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
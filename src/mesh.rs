@@ -0,0 +1,216 @@
+//! Triangle meshes of blueprint shapes.
+//!
+//! [`ShapePrimitive::tessellate`] turns each primitive into a closed, consistently wound
+//! [`TriMesh`] (counter-clockwise seen from outside), which the file exporters build on.
+//! Round shapes are revolved around the local Y axis, the segment's growth direction.
+//! [`RobotBlueprint::to_trimesh`] merges every module's mesh, placed at its rest pose, into
+//! one mesh of the whole robot.
+
+use crate::blueprint::{RobotBlueprint, ShapePrimitive};
+use glam::{Quat, Vec3};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Subdivisions around the axis of round shapes used by [`RobotBlueprint::to_trimesh`].
+pub const DEFAULT_MESH_SEGMENTS: u32 = 32;
+
+/// An indexed triangle mesh.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriMesh {
+    /// Vertex positions.
+    pub vertices: Vec<Vec3>,
+    /// Triangles as indices into [`vertices`](Self::vertices), wound counter-clockwise
+    /// when seen from outside.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl TriMesh {
+    /// Creates an empty mesh.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends another mesh, re-basing its indices.
+    pub fn append(&mut self, other: &TriMesh) {
+        let base = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&other.vertices);
+        self.triangles.extend(
+            other
+                .triangles
+                .iter()
+                .map(|t| [t[0] + base, t[1] + base, t[2] + base]),
+        );
+    }
+
+    /// Returns the mesh moved by `rotation` then `translation`.
+    pub fn transformed(&self, translation: Vec3, rotation: Quat) -> TriMesh {
+        TriMesh {
+            vertices: self
+                .vertices
+                .iter()
+                .map(|&v| translation + rotation * v)
+                .collect(),
+            triangles: self.triangles.clone(),
+        }
+    }
+
+    /// Reverses the winding of every triangle, turning the mesh inside out.
+    pub fn flip(&mut self) {
+        for t in &mut self.triangles {
+            t.swap(1, 2);
+        }
+    }
+
+    /// Corner positions of triangle `i`.
+    pub fn triangle(&self, i: usize) -> [Vec3; 3] {
+        self.triangles[i].map(|v| self.vertices[v as usize])
+    }
+
+    /// Unit normal of triangle `i`, or zero if it is degenerate.
+    pub fn face_normal(&self, i: usize) -> Vec3 {
+        let [a, b, c] = self.triangle(i);
+        (b - a).cross(c - a).normalize_or_zero()
+    }
+
+    /// Enclosed volume by the divergence theorem; negative if the mesh is inside out.
+    pub fn signed_volume(&self) -> f32 {
+        (0..self.triangles.len())
+            .map(|i| {
+                let [a, b, c] = self.triangle(i);
+                a.dot(b.cross(c)) / 6.0
+            })
+            .sum()
+    }
+}
+
+impl RobotBlueprint {
+    /// The whole robot at its rest pose as one world-space mesh, tessellating round shapes
+    /// with [`DEFAULT_MESH_SEGMENTS`] subdivisions.
+    ///
+    /// Modules are appended in topological order and are not fused: overlapping parts stay
+    /// separate closed shells, which slicers and MeshLab handle as a union.
+    pub fn to_trimesh(&self) -> TriMesh {
+        self.to_trimesh_with_segments(DEFAULT_MESH_SEGMENTS)
+    }
+
+    /// Like [`to_trimesh`](Self::to_trimesh), with `segments` subdivisions for round shapes.
+    pub fn to_trimesh_with_segments(&self, segments: u32) -> TriMesh {
+        let mut mesh = TriMesh::new();
+        for (_, module) in self.modules_topological() {
+            let (pos, rot) = module.transform;
+            mesh.append(&module.shape.tessellate(segments).transformed(pos, rot));
+        }
+        mesh
+    }
+}
+
+impl ShapePrimitive {
+    /// Tessellates the shape into a closed mesh in local space.
+    ///
+    /// `segments` is the number of subdivisions around the Y axis for round shapes (at
+    /// least 3); spheres and capsule caps use a quarter as many rings per hemisphere.
+    pub fn tessellate(&self, segments: u32) -> TriMesh {
+        let segments = segments.max(3);
+        let rings = (segments / 4).max(1);
+        match *self {
+            Self::Box(h) => box_mesh(h),
+            Self::Sphere(r) => {
+                let mut profile = Vec::new();
+                push_arc(&mut profile, r, 0.0, -FRAC_PI_2, FRAC_PI_2, 2 * rings);
+                lathe(&profile, segments)
+            }
+            Self::Cylinder { radius, height } => {
+                let y = height / 2.0;
+                lathe(&[(0.0, -y), (radius, -y), (radius, y), (0.0, y)], segments)
+            }
+            Self::Capsule { radius, height } => {
+                let y = height / 2.0;
+                let mut profile = Vec::new();
+                push_arc(&mut profile, radius, -y, -FRAC_PI_2, 0.0, rings);
+                push_arc(&mut profile, radius, y, 0.0, FRAC_PI_2, rings);
+                lathe(&profile, segments)
+            }
+        }
+    }
+}
+
+/// Appends `steps + 1` points of a quarter/half circle profile `(radius, y)` centred at
+/// `(0, y0)`, from latitude `from` to `to`.
+fn push_arc(profile: &mut Vec<(f32, f32)>, r: f32, y0: f32, from: f32, to: f32, steps: u32) {
+    for i in 0..=steps {
+        let lat = from + (to - from) * i as f32 / steps as f32;
+        let (sin, cos) = lat.sin_cos();
+        // Snap the poles so they collapse to a single vertex.
+        let radius = if cos.abs() < 1e-6 { 0.0 } else { r * cos };
+        profile.push((radius, y0 + r * sin));
+    }
+}
+
+/// Revolves a profile of `(radius, y)` points, ordered bottom to top, around the Y axis.
+///
+/// Points with zero radius become single pole vertices.
+fn lathe(profile: &[(f32, f32)], segments: u32) -> TriMesh {
+    let mut mesh = TriMesh::new();
+    // Each profile point maps to its first vertex index and whether it is a pole.
+    let mut rings: Vec<(u32, bool)> = Vec::with_capacity(profile.len());
+    for &(radius, y) in profile {
+        let start = mesh.vertices.len() as u32;
+        if radius <= 0.0 {
+            mesh.vertices.push(Vec3::new(0.0, y, 0.0));
+            rings.push((start, true));
+        } else {
+            for j in 0..segments {
+                let (sin, cos) = (TAU * j as f32 / segments as f32).sin_cos();
+                mesh.vertices.push(Vec3::new(radius * cos, y, radius * sin));
+            }
+            rings.push((start, false));
+        }
+    }
+
+    for pair in rings.windows(2) {
+        let ((a, a_pole), (b, b_pole)) = (pair[0], pair[1]);
+        for j in 0..segments {
+            let k = (j + 1) % segments;
+            match (a_pole, b_pole) {
+                (true, true) => {}
+                (true, false) => mesh.triangles.push([a, b + j, b + k]),
+                (false, true) => mesh.triangles.push([a + j, b, a + k]),
+                (false, false) => {
+                    mesh.triangles.push([a + j, b + j, a + k]);
+                    mesh.triangles.push([a + k, b + j, b + k]);
+                }
+            }
+        }
+    }
+    mesh
+}
+
+/// Eight-corner box with half extents `h`.
+fn box_mesh(h: Vec3) -> TriMesh {
+    // Corner `i` has bit 0 → +X, bit 1 → +Y, bit 2 → +Z.
+    let vertices = (0..8)
+        .map(|i| {
+            Vec3::new(
+                if i & 1 != 0 { h.x } else { -h.x },
+                if i & 2 != 0 { h.y } else { -h.y },
+                if i & 4 != 0 { h.z } else { -h.z },
+            )
+        })
+        .collect();
+    TriMesh {
+        vertices,
+        triangles: vec![
+            [0, 4, 6],
+            [0, 6, 2],
+            [1, 3, 7],
+            [1, 7, 5],
+            [0, 1, 5],
+            [0, 5, 4],
+            [2, 6, 7],
+            [2, 7, 3],
+            [0, 2, 3],
+            [0, 3, 1],
+            [4, 5, 7],
+            [4, 7, 6],
+        ],
+    }
+}
@@ -0,0 +1,483 @@
+//! Morphological descriptors computed from a [`RobotBlueprint`].
+//!
+//! These helpers summarise a phenotype as scalars so that evolutionary algorithms can
+//! compare, bin, and log robots without walking the module graph themselves.
+//! The main entry point for quality-diversity archives is [`feature_vector`], which
+//! turns a blueprint into a fixed-length descriptor according to a [`FeatureSpec`].
+//! [`PopulationStats`] aggregates the same features over a whole population for logging.
+
+use crate::blueprint::{JointType, RobotBlueprint, ShapePrimitive};
+use crate::parallel;
+use crate::sampling::SampleMode;
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A scalar morphological property of a blueprint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Feature {
+    /// Number of modules (rigid bodies).
+    ModuleCount,
+    /// Number of joints, including fixed ones.
+    JointCount,
+    /// Number of non-fixed joints (see [`actuator_count`]).
+    ActuatorCount,
+    /// Total degrees of freedom of all joints (see [`dof_count`]).
+    DofCount,
+    /// Number of limbs (see [`limb_count`]).
+    LimbCount,
+    /// Number of mounted sensors.
+    SensorCount,
+    /// Total mass in kg.
+    TotalMass,
+    /// Rest-pose height (Y extent) divided by the larger horizontal extent.
+    HeightWidthRatio,
+    /// Length of the longest root-to-leaf joint chain (see [`max_depth`]).
+    MaxDepth,
+}
+
+/// One entry of a [`FeatureSpec`]: a feature and the range it is normalised against.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeatureRange {
+    /// The property to measure.
+    pub feature: Feature,
+    /// Raw value mapped to `0.0`.
+    pub min: f32,
+    /// Raw value mapped to `1.0`.
+    pub max: f32,
+}
+
+impl FeatureRange {
+    /// Creates a range for `feature` spanning `min..=max`.
+    pub fn new(feature: Feature, min: f32, max: f32) -> Self {
+        Self { feature, min, max }
+    }
+
+    /// Maps a raw value into `[0, 1]`, clamping values outside the range.
+    ///
+    /// A degenerate range (`max <= min`) maps everything to `0.0`.
+    pub fn normalize(&self, value: f32) -> f32 {
+        let span = self.max - self.min;
+        if span <= 0.0 || !value.is_finite() {
+            return 0.0;
+        }
+        ((value - self.min) / span).clamp(0.0, 1.0)
+    }
+}
+
+/// Describes which features make up a descriptor and how each is normalised.
+///
+/// Persist the spec next to an archive so that binning stays consistent across runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSpec {
+    /// Features in descriptor order.
+    pub features: Vec<FeatureRange>,
+}
+
+impl Default for FeatureSpec {
+    /// Limb count, total mass, height/width ratio, and actuator count.
+    fn default() -> Self {
+        Self {
+            features: vec![
+                FeatureRange::new(Feature::LimbCount, 0.0, 16.0),
+                FeatureRange::new(Feature::TotalMass, 0.0, 50.0),
+                FeatureRange::new(Feature::HeightWidthRatio, 0.0, 4.0),
+                FeatureRange::new(Feature::ActuatorCount, 0.0, 32.0),
+            ],
+        }
+    }
+}
+
+impl FeatureSpec {
+    /// Creates a spec from an explicit list of ranges.
+    pub fn new(features: Vec<FeatureRange>) -> Self {
+        Self { features }
+    }
+
+    /// Number of entries in the descriptor produced by this spec.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// True iff the spec has no features.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+}
+
+/// Computes a fixed-length descriptor with one normalised (`[0, 1]`) entry per spec feature.
+pub fn feature_vector(blueprint: &RobotBlueprint, spec: &FeatureSpec) -> Vec<f32> {
+    spec.features
+        .iter()
+        .map(|range| range.normalize(feature_value(blueprint, range.feature)))
+        .collect()
+}
+
+/// Computes the raw (un-normalised) value of a single feature.
+pub fn feature_value(blueprint: &RobotBlueprint, feature: Feature) -> f32 {
+    match feature {
+        Feature::ModuleCount => blueprint.modules.len() as f32,
+        Feature::JointCount => blueprint.joints.len() as f32,
+        Feature::ActuatorCount => actuator_count(blueprint) as f32,
+        Feature::DofCount => dof_count(blueprint) as f32,
+        Feature::LimbCount => limb_count(blueprint) as f32,
+        Feature::SensorCount => sensor_count(blueprint) as f32,
+        Feature::TotalMass => total_mass(blueprint),
+        Feature::HeightWidthRatio => height_width_ratio(blueprint),
+        Feature::MaxDepth => max_depth(blueprint) as f32,
+    }
+}
+
+/// Summary statistics of one feature across a population.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    /// Arithmetic mean.
+    pub mean: f32,
+    /// Population standard deviation.
+    pub std_dev: f32,
+    /// Smallest value.
+    pub min: f32,
+    /// Median value (mean of the two middle values for even counts).
+    pub median: f32,
+    /// Largest value.
+    pub max: f32,
+}
+
+impl Distribution {
+    /// Summarises `values`. An empty slice yields all zeros.
+    pub fn from_values(values: &[f32]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f32>() / n as f32;
+        let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n as f32;
+        let median = if n % 2 == 0 {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        } else {
+            sorted[n / 2]
+        };
+        Self {
+            mean,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            median,
+            max: sorted[n - 1],
+        }
+    }
+}
+
+/// Per-feature [`Distribution`]s over a population of blueprints, e.g. one generation of
+/// an evolution run. Serialise one per generation to log a time series.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PopulationStats {
+    /// Number of blueprints summarised.
+    pub size: usize,
+    /// One distribution per requested feature, in request order.
+    pub features: Vec<(Feature, Distribution)>,
+}
+
+impl PopulationStats {
+    /// Summarises total mass, module count and limb count over `blueprints`.
+    pub fn new(blueprints: &[RobotBlueprint]) -> Self {
+        Self::with_features(
+            blueprints,
+            &[Feature::TotalMass, Feature::ModuleCount, Feature::LimbCount],
+        )
+    }
+
+    /// Summarises the given features over `blueprints`.
+    pub fn with_features(blueprints: &[RobotBlueprint], features: &[Feature]) -> Self {
+        let features = features
+            .iter()
+            .map(|&feature| {
+                let values = parallel::map(blueprints, |_, bp| feature_value(bp, feature));
+                (feature, Distribution::from_values(&values))
+            })
+            .collect();
+        Self {
+            size: blueprints.len(),
+            features,
+        }
+    }
+
+    /// The distribution of `feature`, or `None` if it was not summarised.
+    pub fn get(&self, feature: Feature) -> Option<&Distribution> {
+        self.features
+            .iter()
+            .find(|(f, _)| *f == feature)
+            .map(|(_, d)| d)
+    }
+}
+
+/// Number of joints that can move, i.e. every joint except [`JointType::Fixed`].
+pub fn actuator_count(blueprint: &RobotBlueprint) -> usize {
+    blueprint
+        .joints
+        .iter()
+        .filter(|j| j.joint_type != JointType::Fixed)
+        .count()
+}
+
+/// Total degrees of freedom over all joints: one per hinge or prismatic joint and three
+/// per ball joint. This is the size of a joint-space controller's action vector.
+pub fn dof_count(blueprint: &RobotBlueprint) -> usize {
+    blueprint
+        .joints
+        .iter()
+        .map(|j| j.joint_type.degrees_of_freedom())
+        .sum()
+}
+
+/// Number of limbs, counted as leaf modules (modules with no children).
+///
+/// A blueprint consisting of a lone root has no limbs.
+pub fn limb_count(blueprint: &RobotBlueprint) -> usize {
+    blueprint
+        .modules
+        .keys()
+        .filter(|&&id| !blueprint.roots.contains(&id) && blueprint.children(id).next().is_none())
+        .count()
+}
+
+/// Total number of sensors across all modules.
+pub fn sensor_count(blueprint: &RobotBlueprint) -> usize {
+    blueprint.modules.values().map(|m| m.sensors.len()).sum()
+}
+
+/// Sum of all module masses in kg.
+pub fn total_mass(blueprint: &RobotBlueprint) -> f32 {
+    blueprint.modules.values().map(|m| m.mass).sum()
+}
+
+/// Rest-pose height (Y extent) divided by the larger of the X and Z extents.
+///
+/// Returns `0.0` for an empty or infinitely thin blueprint.
+pub fn height_width_ratio(blueprint: &RobotBlueprint) -> f32 {
+    let aabb = blueprint.aabb(Quat::IDENTITY);
+    let size = aabb.max - aabb.min;
+    let width = size.x.max(size.z);
+    if width <= f32::EPSILON {
+        0.0
+    } else {
+        size.y / width
+    }
+}
+
+/// Number of joints on the longest path from a root to a leaf.
+pub fn max_depth(blueprint: &RobotBlueprint) -> usize {
+    let mut depth = HashMap::new();
+    let mut deepest = 0;
+    for joint in blueprint.joints_topological() {
+        let d = depth.get(&joint.parent_id).copied().unwrap_or(0) + 1;
+        depth.insert(joint.child_id, d);
+        deepest = deepest.max(d);
+    }
+    deepest
+}
+
+/// Default quantization step (in metres) applied to shape dimensions by [`edit_distance`].
+pub const DEFAULT_EDIT_QUANTUM: f32 = 0.05;
+
+/// Approximate tree edit distance between two blueprints.
+///
+/// Equivalent to [`edit_distance_with`] using [`DEFAULT_EDIT_QUANTUM`].
+pub fn edit_distance(a: &RobotBlueprint, b: &RobotBlueprint) -> f32 {
+    edit_distance_with(a, b, DEFAULT_EDIT_QUANTUM)
+}
+
+/// Approximate tree edit distance between two blueprints, with shape dimensions
+/// quantized to multiples of `quantum` metres before comparison.
+///
+/// Both kinematic trees are compared top-down as unordered trees. Inserting or deleting a
+/// module costs `1.0`; relabelling a matched module costs up to `1.0` (`0.5` for a different
+/// shape kind, `0.25` for different quantized dimensions, `0.25` for a different joint type to
+/// its parent). Children are paired greedily by cheapest subtree distance, which makes the
+/// result an upper bound on the exact edit distance while staying fast enough for novelty
+/// search over large populations. Detached trees are compared as children of a shared
+/// virtual root.
+pub fn edit_distance_with(a: &RobotBlueprint, b: &RobotBlueprint, quantum: f32) -> f32 {
+    let ta = LabeledTree::new(a, quantum);
+    let tb = LabeledTree::new(b, quantum);
+    ta.distance(LabeledTree::VIRTUAL_ROOT, &tb, LabeledTree::VIRTUAL_ROOT)
+}
+
+/// Quantized per-node attributes used for edit-distance comparison.
+#[derive(Clone, Copy, PartialEq)]
+struct NodeLabel {
+    shape_kind: u8,
+    dims: [i32; 3],
+    joint_type: Option<JointType>,
+}
+
+impl NodeLabel {
+    fn relabel_cost(&self, other: &NodeLabel) -> f32 {
+        let mut cost = 0.0;
+        if self.shape_kind != other.shape_kind {
+            cost += 0.5;
+        }
+        if self.dims != other.dims {
+            cost += 0.25;
+        }
+        if self.joint_type != other.joint_type {
+            cost += 0.25;
+        }
+        cost
+    }
+}
+
+/// A blueprint flattened into index-addressed nodes under a virtual root at index 0.
+struct LabeledTree {
+    labels: Vec<Option<NodeLabel>>,
+    children: Vec<Vec<usize>>,
+    sizes: Vec<usize>,
+}
+
+impl LabeledTree {
+    const VIRTUAL_ROOT: usize = 0;
+
+    fn new(blueprint: &RobotBlueprint, quantum: f32) -> Self {
+        let q = |v: f32| (v / quantum.max(f32::EPSILON)).round() as i32;
+        let order = blueprint.topological_order();
+        let index: HashMap<_, _> = order
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i + 1))
+            .collect();
+
+        let mut labels = vec![None; order.len() + 1];
+        let mut children = vec![Vec::new(); order.len() + 1];
+        for (i, id) in order.iter().enumerate() {
+            let module = &blueprint.modules[id];
+            let (shape_kind, dims) = match module.shape {
+                ShapePrimitive::Box(h) => (0, [q(h.x), q(h.y), q(h.z)]),
+                ShapePrimitive::Cylinder { radius, height } => (1, [q(radius), q(height), 0]),
+                ShapePrimitive::Sphere(r) => (2, [q(r), 0, 0]),
+                ShapePrimitive::Capsule { radius, height } => (3, [q(radius), q(height), 0]),
+            };
+            let parent = blueprint.parent_joint(*id);
+            labels[i + 1] = Some(NodeLabel {
+                shape_kind,
+                dims,
+                joint_type: parent.map(|j| j.joint_type),
+            });
+            let parent_idx = parent
+                .and_then(|j| index.get(&j.parent_id).copied())
+                .unwrap_or(Self::VIRTUAL_ROOT);
+            children[parent_idx].push(i + 1);
+        }
+
+        // Topological order guarantees children have larger indices than parents,
+        // so a reverse sweep accumulates subtree sizes bottom-up.
+        let mut sizes = vec![1; order.len() + 1];
+        for node in (0..sizes.len()).rev() {
+            sizes[node] += children[node].iter().map(|&c| sizes[c]).sum::<usize>();
+        }
+
+        Self {
+            labels,
+            children,
+            sizes,
+        }
+    }
+
+    fn distance(&self, a: usize, other: &LabeledTree, b: usize) -> f32 {
+        let relabel = match (&self.labels[a], &other.labels[b]) {
+            (Some(la), Some(lb)) => la.relabel_cost(lb),
+            _ => 0.0,
+        };
+
+        let ca = &self.children[a];
+        let cb = &other.children[b];
+        let mut pairs = Vec::with_capacity(ca.len() * cb.len());
+        for &x in ca {
+            for &y in cb {
+                pairs.push((self.distance(x, other, y), x, y));
+            }
+        }
+        pairs.sort_by(|p, q| p.0.total_cmp(&q.0));
+
+        let mut used_a = Vec::new();
+        let mut used_b = Vec::new();
+        let mut cost = relabel;
+        for (d, x, y) in pairs {
+            if used_a.contains(&x) || used_b.contains(&y) {
+                continue;
+            }
+            // Replacing a subtree wholesale is never worse than a poor match.
+            let replace = (self.sizes[x] + other.sizes[y]) as f32;
+            cost += d.min(replace);
+            used_a.push(x);
+            used_b.push(y);
+        }
+        cost += ca
+            .iter()
+            .filter(|x| !used_a.contains(x))
+            .map(|&x| self.sizes[x] as f32)
+            .sum::<f32>();
+        cost += cb
+            .iter()
+            .filter(|y| !used_b.contains(y))
+            .map(|&y| other.sizes[y] as f32)
+            .sum::<f32>();
+        cost
+    }
+}
+
+/// Symmetric Chamfer distance between two point sets: the mean nearest-neighbour distance
+/// from `a` to `b` plus the mean from `b` to `a`.
+///
+/// Returns `0.0` if both sets are empty and `f32::INFINITY` if only one is.
+pub fn chamfer_distance(a: &[Vec3], b: &[Vec3]) -> f32 {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => 0.0,
+        (false, false) => {
+            let ab: f32 = nearest_distances(a, b).into_iter().sum();
+            let ba: f32 = nearest_distances(b, a).into_iter().sum();
+            ab / a.len() as f32 + ba / b.len() as f32
+        }
+        _ => f32::INFINITY,
+    }
+}
+
+/// Symmetric Hausdorff distance between two point sets: the largest distance from any
+/// point in either set to its nearest neighbour in the other.
+///
+/// Returns `0.0` if both sets are empty and `f32::INFINITY` if only one is.
+pub fn hausdorff_distance(a: &[Vec3], b: &[Vec3]) -> f32 {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => 0.0,
+        (false, false) => {
+            let ab = nearest_distances(a, b).into_iter().fold(0.0, f32::max);
+            let ba = nearest_distances(b, a).into_iter().fold(0.0, f32::max);
+            ab.max(ba)
+        }
+        _ => f32::INFINITY,
+    }
+}
+
+/// Topology-agnostic geometric distance between two rest poses.
+///
+/// Samples `n` surface points from each blueprint (see [`RobotBlueprint::sample_points`])
+/// and returns their [`chamfer_distance`]. Blueprints are compared in place; normalise
+/// position and orientation beforehand if those should not count.
+pub fn shape_distance(a: &RobotBlueprint, b: &RobotBlueprint, n: usize) -> f32 {
+    chamfer_distance(
+        &a.sample_points(n, SampleMode::Surface),
+        &b.sample_points(n, SampleMode::Surface),
+    )
+}
+
+/// The distance from every point of `from` to its nearest neighbour in `to`.
+fn nearest_distances(from: &[Vec3], to: &[Vec3]) -> Vec<f32> {
+    parallel::map(from, |_, p| nearest_distance(*p, to))
+}
+
+fn nearest_distance(p: Vec3, set: &[Vec3]) -> f32 {
+    set.iter()
+        .map(|q| p.distance_squared(*q))
+        .fold(f32::INFINITY, f32::min)
+        .sqrt()
+}
@@ -0,0 +1,942 @@
+//! MJCF (MuJoCo XML) export with a nested body tree.
+//!
+//! MuJoCo describes a robot as nested `<body>` elements, each posed relative to its
+//! parent and carrying the joint that attaches it. [`RobotBlueprint::to_mjcf`] walks the
+//! joint graph down from every root and converts the blueprint's world-space rest pose into
+//! those parent-relative frames, so the output loads directly into evolutionary robotics
+//! benchmarks built on MuJoCo.
+//!
+//! The blueprint is Y-up while MuJoCo is Z-up; root bodies are turned a quarter turn about
+//! X so that the robot stands upright. Frames below the roots are unaffected.
+//! [`RobotBlueprint::to_mjcf_with`] takes [`MjcfOptions`] to swap primitives for meshes,
+//! drop sensors or motors, keep the Y-up frame or prefix every name.
+//!
+//! [`RobotBlueprint::from_mjcf`] goes the other way, turning the geoms of a MuJoCo model
+//! into modules so that standard creatures can be compared with evolved ones.
+
+use crate::blueprint::{
+    ConeLimit, JointDefinition, JointLimit, JointType, ModuleId, RobotBlueprint, RobotModule,
+    SensorAttachment, SensorType, ShapePrimitive, WorldJoint,
+};
+use crate::export::{ExportGeometry, UpAxis};
+use crate::material::{ContactPair, ContactSurface};
+use crate::sdf::escape;
+use crate::stream::stream;
+use bevy_heavy::ComputeMassProperties3d as _;
+use glam::{Mat3, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::fmt::Write;
+use std::io;
+use thiserror::Error;
+
+/// Options for [`RobotBlueprint::to_mjcf_with`]. MuJoCo geoms both collide and render, so
+/// there is no collision-only mode.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MjcfOptions {
+    /// Primitive geoms or per-module meshes, declared as `<asset>` meshes named like their
+    /// bodies. Inertia still comes from the primitive.
+    pub geometry: ExportGeometry,
+    /// Write a site or camera for every mounted sensor, and its `<sensor>` entries.
+    /// Markers are written either way.
+    pub sensors: bool,
+    /// Write a `<motor>` for every limited joint with a nonzero effort.
+    pub actuators: bool,
+    /// Which way is up in the target world; turns the root bodies.
+    pub up_axis: UpAxis,
+    /// Prepended to every body, joint, geom, site, camera, sensor and actuator name.
+    pub prefix: String,
+}
+
+impl Default for MjcfOptions {
+    fn default() -> Self {
+        Self {
+            geometry: ExportGeometry::Primitive,
+            sensors: true,
+            actuators: true,
+            up_axis: UpAxis::Z,
+            prefix: String::new(),
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// Renders the robot as an MJCF document whose `<mujoco>` model is called `name`.
+    ///
+    /// Bodies are named `module_N` and joints `joint_P_C`. Roots get a free joint unless
+    /// they are welded to the world; [`JointType::Fixed`] children get no joint at all, which
+    /// MuJoCo treats as a rigid attachment. A prismatic joint's
+    /// [`rest_displacement`](JointDefinition::rest_displacement) becomes its `ref`, so the
+    /// joint reads the same position as in the blueprint.
+    ///
+    /// Sensors are mounted on sites and listed in `<sensor>`; cameras become `<camera>`
+    /// elements. Each [`Marker`](crate::Marker) becomes a site of the same name with a
+    /// `<framepos>` sensor, again of the same name, reporting its world position. Every
+    /// limited hinge or slide joint with a nonzero effort gets a `<motor>` clamped to that
+    /// effort, and [`collision_exclusions`](Self::collision_exclusions) become contact
+    /// excludes. [`contacts`](Self::contacts) entries between two materials become a
+    /// contact `<pair>` for every two geoms of those materials that come within a module
+    /// radius of each other at rest, and those geoms get `contype="0"` so that MuJoCo does
+    /// not collide them a second time outside the pair;
+    /// entries against the ground set the `friction` and `solref` of the geoms themselves,
+    /// since the model has no floor to pair with. MuJoCo's geom friction is isotropic, so
+    /// [`anisotropic_friction`](RobotModule::anisotropic_friction) is written as its
+    /// [`isotropic`](crate::AnisotropicFriction::isotropic) equivalent.
+    pub fn to_mjcf(&self, name: &str) -> String {
+        self.to_mjcf_with(name, &MjcfOptions::default())
+    }
+
+    /// [`to_mjcf`](Self::to_mjcf) with the geometry, elements, up axis and names chosen by
+    /// `options`.
+    pub fn to_mjcf_with(&self, name: &str, options: &MjcfOptions) -> String {
+        let mut out = String::new();
+        self.render_mjcf(&mut out, name, options);
+        out
+    }
+
+    /// Streams [`to_mjcf`](Self::to_mjcf) into `writer`, e.g. a `BufWriter<File>`. Only
+    /// the `<actuator>` and `<sensor>` entries, which follow the body tree, are buffered.
+    pub fn write_mjcf(&self, writer: impl io::Write, name: &str) -> io::Result<()> {
+        self.write_mjcf_with(writer, name, &MjcfOptions::default())
+    }
+
+    /// Streams [`to_mjcf_with`](Self::to_mjcf_with) into `writer`.
+    pub fn write_mjcf_with(
+        &self,
+        writer: impl io::Write,
+        name: &str,
+        options: &MjcfOptions,
+    ) -> io::Result<()> {
+        stream(writer, |out| self.render_mjcf(out, name, options))
+    }
+
+    fn render_mjcf(&self, out: &mut impl Write, name: &str, options: &MjcfOptions) {
+        let mut mjcf = Mjcf {
+            blueprint: self,
+            options,
+            prefix: escape(&options.prefix),
+            out,
+            sensors: Vec::new(),
+            actuators: Vec::new(),
+            visited: HashSet::new(),
+            paired: HashSet::new(),
+        };
+        let p = mjcf.prefix.clone();
+        let pairs = contact_pairs(self, &p);
+        mjcf.paired = pairs.iter().flat_map(|&(a, b, _)| [a, b]).collect();
+        let _ = writeln!(mjcf.out, "<!-- Generated by symbios-robot -->");
+        let _ = writeln!(mjcf.out, "<mujoco model=\"{}\">", escape(name));
+        let _ = writeln!(mjcf.out, "  <compiler angle=\"radian\"/>");
+        if matches!(options.geometry, ExportGeometry::Mesh { .. }) {
+            let _ = writeln!(mjcf.out, "  <asset>");
+            for (id, _) in self.modules_topological() {
+                if let Some(uri) = options.geometry.mesh_uri(id) {
+                    let _ = writeln!(
+                        mjcf.out,
+                        "    <mesh name=\"{p}module_{id}\" file=\"{}\"/>",
+                        escape(&uri)
+                    );
+                }
+            }
+            let _ = writeln!(mjcf.out, "  </asset>");
+        }
+        let _ = writeln!(mjcf.out, "  <worldbody>");
+        let up = options.up_axis.rotation();
+        for &root in &self.roots {
+            let Some(module) = self.modules.get(&root) else {
+                continue;
+            };
+            let (pos, rot) = module.transform;
+            mjcf.write_body(root, None, up * pos, up * rot, 2);
+        }
+        let _ = writeln!(mjcf.out, "  </worldbody>");
+
+        if !self.collision_exclusions.is_empty() || !pairs.is_empty() {
+            let _ = writeln!(mjcf.out, "  <contact>");
+            for (_, _, pair) in &pairs {
+                let _ = writeln!(mjcf.out, "    {pair}");
+            }
+            for pair in &self.collision_exclusions {
+                let _ = writeln!(
+                    mjcf.out,
+                    "    <exclude body1=\"{p}module_{}\" body2=\"{p}module_{}\"/>",
+                    pair.a, pair.b
+                );
+            }
+            let _ = writeln!(mjcf.out, "  </contact>");
+        }
+        for (section, lines) in [("actuator", &mjcf.actuators), ("sensor", &mjcf.sensors)] {
+            if !lines.is_empty() {
+                let _ = writeln!(mjcf.out, "  <{section}>");
+                for line in lines {
+                    let _ = writeln!(mjcf.out, "    {line}");
+                }
+                let _ = writeln!(mjcf.out, "  </{section}>");
+            }
+        }
+        let _ = writeln!(mjcf.out, "</mujoco>");
+    }
+}
+
+/// Output of [`RobotBlueprint::to_mjcf`] under construction.
+struct Mjcf<'a, W> {
+    blueprint: &'a RobotBlueprint,
+    options: &'a MjcfOptions,
+    /// The escaped name prefix.
+    prefix: String,
+    /// Where the document is written, up to the current body.
+    out: &'a mut W,
+    /// Entries of the `<sensor>` section, collected while walking the bodies.
+    sensors: Vec<String>,
+    /// Entries of the `<actuator>` section.
+    actuators: Vec<String>,
+    /// Bodies already written, guarding against joint graphs that are not trees.
+    visited: HashSet<ModuleId>,
+    /// Modules whose geom appears in a contact `<pair>`.
+    paired: HashSet<ModuleId>,
+}
+
+impl<W: Write> Mjcf<'_, W> {
+    /// Writes body `id`, attached by `joint` (`None` for roots) and posed at `pos`/`rot`
+    /// relative to its parent, followed by all of its descendants.
+    fn write_body(
+        &mut self,
+        id: ModuleId,
+        joint: Option<&JointDefinition>,
+        pos: Vec3,
+        rot: Quat,
+        depth: usize,
+    ) {
+        if !self.visited.insert(id) {
+            return;
+        }
+        let blueprint = self.blueprint;
+        let module = &blueprint.modules[&id];
+        let indent = "  ".repeat(depth);
+        let p = self.prefix.clone();
+        let _ = writeln!(
+            self.out,
+            "{indent}<body name=\"{p}module_{id}\" pos=\"{}\" quat=\"{}\">",
+            vec3(pos),
+            quat(rot)
+        );
+        match joint {
+            Some(joint) => self.write_joint(joint, &indent),
+            None if blueprint.world_joints.iter().any(|w| w.child_id == id) => {}
+            None => {
+                let _ = writeln!(self.out, "{indent}  <freejoint name=\"{p}root_{id}\"/>");
+            }
+        }
+        self.write_inertial(module, &indent);
+        // MuJoCo's default sliding friction is 1; its torsional and rolling defaults are kept.
+        let ground = blueprint.contacts.get(
+            ContactSurface::Material(module.material_id),
+            ContactSurface::Ground,
+        );
+        let sliding = match (module.anisotropic_friction, ground) {
+            (Some(f), _) => Some(f.isotropic(ground.map_or(1.0, |c| c.friction))),
+            (None, Some(c)) => Some(c.friction),
+            (None, None) => None,
+        };
+        let mut surface = sliding.map_or(String::new(), |f| {
+            format!(" friction=\"{f:.6} 0.005 0.0001\"")
+        });
+        if let Some(solref) = ground.and_then(solref) {
+            let _ = write!(surface, " solref=\"{solref}\"");
+        }
+        // Paired geoms collide with each other only through their pairs; the floor and
+        // unpaired geoms still reach them through conaffinity.
+        if self.paired.contains(&id) {
+            surface.push_str(" contype=\"0\" conaffinity=\"1\"");
+        }
+        let geometry = match self.options.geometry {
+            ExportGeometry::Primitive => geom(&module.shape),
+            ExportGeometry::Mesh { .. } => format!("type=\"mesh\" mesh=\"{p}module_{id}\""),
+        };
+        let _ = writeln!(
+            self.out,
+            "{indent}  <geom name=\"{p}module_{id}\" {geometry}{surface}/>"
+        );
+        if self.options.sensors {
+            self.write_sensors(id, module, joint, &indent);
+        }
+        for marker in &module.markers {
+            let name = format!("{p}{}", escape(&marker.name));
+            let _ = writeln!(
+                self.out,
+                "{indent}  <site name=\"{name}\" pos=\"{}\" size=\"0.01\" rgba=\"1 0.5 0 1\"/>",
+                vec3(marker.local_position)
+            );
+            self.sensors.push(format!(
+                "<framepos name=\"{name}\" objtype=\"site\" objname=\"{name}\"/>"
+            ));
+        }
+
+        let (parent_pos, parent_rot) = module.transform;
+        let inv = parent_rot.inverse();
+        for child_joint in blueprint.joints.iter().filter(|j| j.parent_id == id) {
+            let Some(child) = blueprint.modules.get(&child_joint.child_id) else {
+                continue;
+            };
+            let (child_pos, child_rot) = child.transform;
+            self.write_body(
+                child_joint.child_id,
+                Some(child_joint),
+                inv * (child_pos - parent_pos),
+                inv * child_rot,
+                depth + 1,
+            );
+        }
+        let _ = writeln!(self.out, "{indent}</body>");
+    }
+
+    /// Writes the joint attaching the current body to its parent, in the body's frame.
+    fn write_joint(&mut self, joint: &JointDefinition, indent: &str) {
+        let kind = match joint.joint_type {
+            JointType::Fixed => return,
+            JointType::Hinge => "hinge",
+            JointType::Ball => "ball",
+            JointType::Prismatic => "slide",
+        };
+        let name = format!(
+            "{}joint_{}_{}",
+            self.prefix, joint.parent_id, joint.child_id
+        );
+        let axis = joint.child_axis;
+        let mut attrs = format!(
+            "name=\"{name}\" type=\"{kind}\" pos=\"{}\"",
+            vec3(-self.blueprint.modules[&joint.child_id].pivot_offset)
+        );
+        if joint.joint_type != JointType::Ball {
+            let _ = write!(attrs, " axis=\"{}\"", vec3(axis));
+        }
+        if joint.joint_type == JointType::Prismatic && joint.rest_displacement != 0.0 {
+            let _ = write!(attrs, " ref=\"{:.6}\"", joint.rest_displacement);
+        }
+        if let Some(limits) = joint.limits {
+            let range = match (joint.joint_type, limits.cone) {
+                (JointType::Ball, Some(cone)) => Some((0.0, cone.swing1.max(cone.swing2))),
+                (JointType::Ball, None) => None,
+                _ => Some((limits.min, limits.max)),
+            };
+            if let Some((min, max)) = range {
+                let _ = write!(attrs, " limited=\"true\" range=\"{min:.6} {max:.6}\"");
+            }
+            if self.options.actuators && joint.joint_type != JointType::Ball && limits.effort > 0.0
+            {
+                self.actuators.push(format!(
+                    "<motor name=\"{name}_motor\" joint=\"{name}\" ctrllimited=\"true\" ctrlrange=\"{:.6} {:.6}\"/>",
+                    -limits.effort, limits.effort
+                ));
+            }
+        }
+        let _ = writeln!(self.out, "{indent}  <joint {attrs}/>");
+    }
+
+    fn write_inertial(&mut self, module: &RobotModule, indent: &str) {
+        let inertia = module
+            .shape
+            .to_bevy_primitive()
+            .unit_principal_angular_inertia()
+            * module.mass;
+        let _ = writeln!(
+            self.out,
+            "{indent}  <inertial pos=\"{}\" mass=\"{:.6}\" diaginertia=\"{}\"/>",
+            vec3(module.center_of_mass),
+            module.mass,
+            vec3(inertia)
+        );
+    }
+
+    /// Writes a site (or camera) for every sensor of body `id` and records the matching
+    /// `<sensor>` entries.
+    fn write_sensors(
+        &mut self,
+        id: ModuleId,
+        module: &RobotModule,
+        joint: Option<&JointDefinition>,
+        indent: &str,
+    ) {
+        for (i, sensor) in module.sensors.iter().enumerate() {
+            let origin = match (sensor.attachment, joint) {
+                (SensorAttachment::Module, _) => Vec3::ZERO,
+                (SensorAttachment::Joint, Some(_)) => -module.pivot_offset,
+                (SensorAttachment::Joint, None) => continue,
+            };
+            let label = sensor.sensor_type.name().to_lowercase();
+            let name = format!(
+                "{}{}",
+                self.prefix,
+                escape(&format!("module_{id}_{label}_{i}"))
+            );
+            let pose = format!(
+                "pos=\"{}\" quat=\"{}\"",
+                vec3(origin + sensor.local_position),
+                quat(sensor.local_rotation)
+            );
+            let fov_y = match &sensor.sensor_type {
+                SensorType::Camera => Some(45f32.to_radians()),
+                SensorType::DepthCamera(p) => Some(p.fov_y),
+                SensorType::Thermal(p) => Some(p.fov_y),
+                _ => None,
+            };
+            if let Some(fov_y) = fov_y {
+                // `fovy` is in degrees regardless of the compiler's angle setting.
+                let _ = writeln!(
+                    self.out,
+                    "{indent}  <camera name=\"{name}\" {pose} fovy=\"{:.6}\"/>",
+                    fov_y.to_degrees()
+                );
+                continue;
+            }
+            let _ = writeln!(self.out, "{indent}  <site name=\"{name}\" {pose}/>");
+            let entries: Vec<String> = match &sensor.sensor_type {
+                SensorType::IMU => vec![
+                    format!("<accelerometer name=\"{name}_acc\" site=\"{name}\"/>"),
+                    format!("<gyro name=\"{name}_gyro\" site=\"{name}\"/>"),
+                ],
+                SensorType::Touch => vec![format!("<touch name=\"{name}\" site=\"{name}\"/>")],
+                SensorType::Lidar | SensorType::Ultrasonic => {
+                    vec![format!("<rangefinder name=\"{name}\" site=\"{name}\"/>")]
+                }
+                SensorType::ForceTorque => vec![
+                    format!("<force name=\"{name}_force\" site=\"{name}\"/>"),
+                    format!("<torque name=\"{name}_torque\" site=\"{name}\"/>"),
+                ],
+                SensorType::Encoder => joint
+                    .filter(|j| matches!(j.joint_type, JointType::Hinge | JointType::Prismatic))
+                    .map(|j| {
+                        format!(
+                            "<jointpos name=\"{name}\" joint=\"{}joint_{}_{}\"/>",
+                            self.prefix, j.parent_id, j.child_id
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+                // Custom sensors keep their site for downstream plugins to read.
+                _ => Vec::new(),
+            };
+            self.sensors.extend(entries);
+        }
+    }
+}
+
+/// A `<pair>` for every two geoms whose materials have an entry in the contact table and
+/// whose bounds come within a module radius of each other at rest, except those on a
+/// parent and its child, which MuJoCo keeps apart by default, and those the blueprint
+/// excludes. Each pair comes with the modules of its geoms.
+fn contact_pairs(blueprint: &RobotBlueprint, prefix: &str) -> Vec<(ModuleId, ModuleId, String)> {
+    let between_materials = |c: &ContactPair| {
+        matches!(
+            (c.a, c.b),
+            (ContactSurface::Material(_), ContactSurface::Material(_))
+        )
+    };
+    if !blueprint.contacts.pairs.iter().any(between_materials) {
+        return Vec::new();
+    }
+    let apart: HashSet<(ModuleId, ModuleId)> = blueprint
+        .joints
+        .iter()
+        .map(|j| (j.parent_id, j.child_id))
+        .chain(blueprint.collision_exclusions.iter().map(|e| (e.a, e.b)))
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    let material = |id: ModuleId| ContactSurface::Material(blueprint.modules[&id].material_id);
+    let index = blueprint.spatial_index();
+    let mut ids: Vec<ModuleId> = blueprint.modules.keys().copied().collect();
+    ids.sort_unstable();
+    let mut pairs = Vec::new();
+    for &a in &ids {
+        let bounds = blueprint.modules[&a].bounds;
+        for b in index.overlapping(&bounds, bounds.radius) {
+            if b <= a || apart.contains(&(a, b)) {
+                continue;
+            }
+            let Some(contact) = blueprint.contacts.get(material(a), material(b)) else {
+                continue;
+            };
+            let f = contact.friction;
+            let mut pair = format!(
+                "<pair geom1=\"{prefix}module_{a}\" geom2=\"{prefix}module_{b}\" friction=\"{f:.6} {f:.6} 0.005 0.0001 0.0001\""
+            );
+            if let Some(solref) = solref(contact) {
+                let _ = write!(pair, " solref=\"{solref}\"");
+            }
+            pair.push_str("/>");
+            pairs.push((a, b, pair));
+        }
+    }
+    pairs
+}
+
+/// The `solref` of a contact entry: its stiffness and damping, negated as MuJoCo expects
+/// them, for a soft contact; otherwise the damping ratio that bounces back with its
+/// restitution under MuJoCo's default time constant. `None` for a rigid contact that does
+/// not bounce, which MuJoCo's defaults already describe.
+fn solref(contact: &ContactPair) -> Option<String> {
+    if let Some(stiffness) = contact.stiffness {
+        return Some(format!(
+            "{:.6} {:.6}",
+            -stiffness,
+            -contact.damping.unwrap_or(0.0)
+        ));
+    }
+    let restitution = contact.restitution.min(1.0);
+    if restitution <= 0.0 {
+        return None;
+    }
+    // A damped spring rebounds with e = exp(-ζπ / sqrt(1 - ζ²)).
+    let ln = restitution.ln();
+    let ratio = -ln / (PI * PI + ln * ln).sqrt();
+    Some(format!("0.02 {ratio:.6}"))
+}
+
+/// The `type` and `size` attributes of a geom. MuJoCo cylinders and capsules run along Z,
+/// so they also get a `quat` turning them onto the module's Y axis.
+fn geom(shape: &ShapePrimitive) -> String {
+    let upright = quat(Quat::from_rotation_x(-FRAC_PI_2));
+    match *shape {
+        ShapePrimitive::Box(h) => format!("type=\"box\" size=\"{}\"", vec3(h)),
+        ShapePrimitive::Sphere(r) => format!("type=\"sphere\" size=\"{r:.6}\""),
+        ShapePrimitive::Cylinder { radius, height } => format!(
+            "type=\"cylinder\" size=\"{radius:.6} {:.6}\" quat=\"{upright}\"",
+            height / 2.0
+        ),
+        ShapePrimitive::Capsule { radius, height } => format!(
+            "type=\"capsule\" size=\"{radius:.6} {:.6}\" quat=\"{upright}\"",
+            height / 2.0
+        ),
+    }
+}
+
+fn vec3(v: Vec3) -> String {
+    format!("{:.6} {:.6} {:.6}", v.x, v.y, v.z)
+}
+
+/// A quaternion in MuJoCo's `w x y z` order.
+fn quat(q: Quat) -> String {
+    let q = q.normalize();
+    format!("{:.6} {:.6} {:.6} {:.6}", q.w, q.x, q.y, q.z)
+}
+
+/// Errors produced by [`RobotBlueprint::from_mjcf`].
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum MjcfError {
+    /// The document is not well-formed XML.
+    #[error("Invalid XML: {0}")]
+    Xml(String),
+    /// The root element is not `<mujoco>` or it has no `<worldbody>`.
+    #[error("Document has no <mujoco> <worldbody>")]
+    NoWorldBody,
+    /// A numeric attribute could not be parsed or has too few components.
+    #[error("Invalid {attribute} value {value:?}")]
+    InvalidNumber {
+        /// Name of the attribute.
+        attribute: String,
+        /// Its text.
+        value: String,
+    },
+}
+
+impl RobotBlueprint {
+    /// Builds a blueprint from an MJCF document, so that standard MuJoCo creatures can be
+    /// measured with the same tools as evolved robots.
+    ///
+    /// Every sphere, capsule, cylinder and box geom becomes a module; other geoms (planes,
+    /// meshes, height fields, ellipsoids) are skipped. The first geom of a body is attached
+    /// to its parent body's first geom by the body's joint: a single hinge, slide or ball
+    /// joint maps to [`JointType::Hinge`], [`JointType::Prismatic`] or [`JointType::Ball`],
+    /// several joints collapse into one ball joint, and no joint gives a fixed one. Further
+    /// geoms of the same body are fixed to its first. Bodies without geoms pass their
+    /// children on to the nearest ancestor that has one. Top-level bodies without a free
+    /// joint are welded to the world.
+    ///
+    /// Joint ranges become limits (in radians, whatever the compiler's `angle`), with the
+    /// effort taken from an actuator's `gear` and `ctrlrange` where one drives the joint.
+    /// A body's `<inertial>` mass is spread over its modules in proportion to their volume.
+    /// Only the class-less top-level `<default>` is applied; sites and sensors are ignored.
+    /// The model is turned from MuJoCo's Z-up into the blueprint's Y-up, undoing
+    /// [`to_mjcf`](Self::to_mjcf).
+    pub fn from_mjcf(xml: &str) -> Result<RobotBlueprint, MjcfError> {
+        let doc = roxmltree::Document::parse(xml).map_err(|e| MjcfError::Xml(e.to_string()))?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "mujoco" {
+            return Err(MjcfError::NoWorldBody);
+        }
+        let worldbody = child(root, "worldbody").ok_or(MjcfError::NoWorldBody)?;
+
+        let mut import = MjcfImport {
+            blueprint: RobotBlueprint::new(),
+            degrees: child(root, "compiler").and_then(|c| c.attribute("angle")) != Some("radian"),
+            defaults: HashMap::new(),
+            efforts: HashMap::new(),
+            next_id: 0,
+        };
+        if let Some(defaults) = child(root, "default") {
+            for element in defaults.children().filter(|n| n.is_element()) {
+                let attrs = element
+                    .attributes()
+                    .map(|a| (a.name().to_owned(), a.value().to_owned()))
+                    .collect();
+                import
+                    .defaults
+                    .insert(element.tag_name().name().to_owned(), attrs);
+            }
+        }
+        if let Some(actuators) = child(root, "actuator") {
+            for actuator in actuators.children().filter(|n| n.is_element()) {
+                let Some(joint) = actuator.attribute("joint") else {
+                    continue;
+                };
+                let gear = import
+                    .floats(actuator, "gear")?
+                    .first()
+                    .copied()
+                    .unwrap_or(1.0);
+                let range = match import.floats(actuator, "ctrlrange")?.as_slice() {
+                    [lo, hi, ..] => lo.abs().max(hi.abs()),
+                    _ => match import.floats(actuator, "forcerange")?.as_slice() {
+                        [lo, hi, ..] => lo.abs().max(hi.abs()) / gear.abs().max(f32::EPSILON),
+                        _ => continue,
+                    },
+                };
+                import
+                    .efforts
+                    .insert(joint.to_owned(), (gear * range).abs());
+            }
+        }
+
+        let world = (Vec3::ZERO, Quat::from_rotation_x(-FRAC_PI_2));
+        for body in worldbody.children().filter(|n| n.has_tag_name("body")) {
+            import.body(body, world, None)?;
+        }
+        Ok(import.blueprint)
+    }
+}
+
+/// State of [`RobotBlueprint::from_mjcf`] while walking the body tree.
+struct MjcfImport {
+    blueprint: RobotBlueprint,
+    /// Whether the compiler reads angles in degrees (MuJoCo's default).
+    degrees: bool,
+    /// Attributes of the top-level `<default>`, by element name.
+    defaults: HashMap<String, HashMap<String, String>>,
+    /// Effort of the actuator driving each joint, by joint name.
+    efforts: HashMap<String, f32>,
+    next_id: ModuleId,
+}
+
+impl MjcfImport {
+    /// Imports `body`, whose parent frame sits at `parent` in the world, and its subtree.
+    /// `parent_module` is the module of the nearest ancestor body that has one.
+    fn body(
+        &mut self,
+        body: roxmltree::Node,
+        parent: (Vec3, Quat),
+        parent_module: Option<ModuleId>,
+    ) -> Result<(), MjcfError> {
+        let (pos, rot) = self.pose(body)?;
+        let frame = (parent.0 + parent.1 * pos, parent.1 * rot);
+
+        let mut modules = Vec::new();
+        for geom in body.children().filter(|n| n.has_tag_name("geom")) {
+            if let Some(module) = self.geom(geom, frame)? {
+                modules.push((self.next_id, module));
+                self.next_id += 1;
+            }
+        }
+        let inertial_mass = match child(body, "inertial") {
+            Some(inertial) => self.floats(inertial, "mass")?.first().copied(),
+            None => None,
+        };
+        let total: f32 = modules.iter().map(|(_, m)| m.mass).sum();
+        if let Some(mass) = inertial_mass
+            && total > 0.0
+        {
+            for (_, module) in &mut modules {
+                module.mass *= mass / total;
+                module.density *= mass / total;
+            }
+        }
+
+        let Some(&(id, _)) = modules.first() else {
+            for node in body.children().filter(|n| n.has_tag_name("body")) {
+                self.body(node, frame, parent_module)?;
+            }
+            return Ok(());
+        };
+        let (body_pos, body_rot) = modules[0].1.transform;
+        let extras: Vec<(ModuleId, Vec3)> = modules[1..]
+            .iter()
+            .map(|(m, module)| (*m, module.transform.0))
+            .collect();
+        for (module_id, module) in modules {
+            self.blueprint.add_module(module_id, module);
+        }
+        let joints: Vec<roxmltree::Node> = body
+            .children()
+            .filter(|n| n.has_tag_name("joint") || n.has_tag_name("freejoint"))
+            .collect();
+        let free = joints
+            .iter()
+            .any(|j| j.has_tag_name("freejoint") || self.attr(*j, "type") == Some("free"));
+        match parent_module {
+            Some(parent_id) if !free => self.joint(&joints, frame, parent_id, id)?,
+            _ => {
+                self.blueprint.add_root(id);
+                if parent_module.is_none() && joints.is_empty() {
+                    self.blueprint.add_world_joint(WorldJoint {
+                        child_id: id,
+                        anchor_child: Vec3::ZERO,
+                        anchor_world: body_pos,
+                    });
+                }
+            }
+        }
+        // Further geoms of the body are rigidly attached to its first, at their centers.
+        for (extra_id, center) in extras {
+            if let Some(extra) = self.blueprint.modules.get_mut(&extra_id) {
+                extra.pivot_offset = Vec3::ZERO;
+            }
+            self.blueprint.add_joint(JointDefinition {
+                parent_id: id,
+                child_id: extra_id,
+                anchor_parent: body_rot.inverse() * (center - body_pos),
+                anchor_child: Vec3::ZERO,
+                joint_type: JointType::Fixed,
+                axis: Vec3::X,
+                child_axis: Vec3::X,
+                rest_displacement: 0.0,
+                limits: None,
+                transmission: None,
+                actuator: None,
+            });
+        }
+
+        for node in body.children().filter(|n| n.has_tag_name("body")) {
+            self.body(node, frame, Some(id))?;
+        }
+        Ok(())
+    }
+
+    /// The module for `geom` in a body at `frame`, or `None` for unsupported geom types.
+    fn geom(
+        &self,
+        geom: roxmltree::Node,
+        frame: (Vec3, Quat),
+    ) -> Result<Option<RobotModule>, MjcfError> {
+        let size = self.floats(geom, "size")?;
+        let size_at = |i: usize| size.get(i).copied().unwrap_or(0.0);
+        let fromto = self.floats(geom, "fromto")?;
+        // Pose of the geom's own frame (Z along the axis of round shapes) in the body.
+        let (pos, rot, half_length) = match fromto.as_slice() {
+            [x0, y0, z0, x1, y1, z1, ..] => {
+                let (a, b) = (Vec3::new(*x0, *y0, *z0), Vec3::new(*x1, *y1, *z1));
+                let dir = (b - a).normalize_or(Vec3::Z);
+                (
+                    (a + b) / 2.0,
+                    Quat::from_rotation_arc(Vec3::Z, dir),
+                    (b - a).length() / 2.0,
+                )
+            }
+            _ => {
+                let (pos, rot) = self.pose(geom)?;
+                (pos, rot, size_at(1))
+            }
+        };
+        let kind = self.attr(geom, "type").unwrap_or("sphere");
+        let (shape, rot) = match kind {
+            "sphere" => (ShapePrimitive::Sphere(size_at(0)), rot),
+            "box" => (
+                ShapePrimitive::Box(Vec3::new(size_at(0), size_at(1), size_at(2))),
+                rot,
+            ),
+            // Blueprint round shapes run along Y rather than Z.
+            "capsule" | "cylinder" => {
+                let (radius, height) = (size_at(0), half_length * 2.0);
+                let shape = if kind == "capsule" {
+                    ShapePrimitive::Capsule { radius, height }
+                } else {
+                    ShapePrimitive::Cylinder { radius, height }
+                };
+                (shape, rot * Quat::from_rotation_x(FRAC_PI_2))
+            }
+            _ => return Ok(None),
+        };
+        let volume = shape.volume();
+        let density = match self.floats(geom, "mass")?.first() {
+            Some(&mass) if volume > 0.0 => mass / volume,
+            _ => self
+                .floats(geom, "density")?
+                .first()
+                .copied()
+                .unwrap_or(1000.0),
+        };
+        let transform = (frame.0 + frame.1 * pos, (frame.1 * rot).normalize());
+        Ok(Some(RobotModule::new(shape, density, transform)))
+    }
+
+    /// Adds the joint attaching module `child_id` of the body at `frame` to `parent_id`.
+    fn joint(
+        &mut self,
+        joints: &[roxmltree::Node],
+        frame: (Vec3, Quat),
+        parent_id: ModuleId,
+        child_id: ModuleId,
+    ) -> Result<(), MjcfError> {
+        let (parent_pos, parent_rot) = self.blueprint.modules[&parent_id].transform;
+        let (child_pos, child_rot) = self.blueprint.modules[&child_id].transform;
+        let node = joints.first().copied();
+        let kind = node.map(|j| self.attr(j, "type").unwrap_or("hinge"));
+        let joint_type = match (kind, joints.len()) {
+            (None, _) => JointType::Fixed,
+            (_, 2..) | (Some("ball"), _) => JointType::Ball,
+            (Some("slide"), _) => JointType::Prismatic,
+            _ => JointType::Hinge,
+        };
+
+        let (mut anchor, mut axis, mut rest_displacement, mut limits) =
+            (Vec3::ZERO, Vec3::Z, 0.0, None);
+        if let Some(node) = node {
+            anchor = self.vec3(node, "pos")?.unwrap_or(Vec3::ZERO);
+            axis = self
+                .vec3(node, "axis")?
+                .and_then(Vec3::try_normalize)
+                .unwrap_or(Vec3::Z);
+            if joint_type == JointType::Prismatic {
+                rest_displacement = self.floats(node, "ref")?.first().copied().unwrap_or(0.0);
+            }
+            let range = self.floats(node, "range")?;
+            let limited = match self.attr(node, "limited") {
+                Some("false") => false,
+                Some("true") => true,
+                _ => range.len() >= 2,
+            };
+            if limited && joints.len() == 1 {
+                let scale = match joint_type {
+                    JointType::Hinge | JointType::Ball if self.degrees => 1f32.to_radians(),
+                    _ => 1.0,
+                };
+                let (min, max) = match range.as_slice() {
+                    [a, b, ..] => (a * scale, b * scale),
+                    _ => (0.0, 0.0),
+                };
+                let effort = node
+                    .attribute("name")
+                    .and_then(|name| self.efforts.get(name))
+                    .copied()
+                    .unwrap_or(100.0);
+                limits = Some(JointLimit {
+                    min: if joint_type == JointType::Ball {
+                        -max
+                    } else {
+                        min
+                    },
+                    max,
+                    effort,
+                    velocity: 10.0,
+                    cone: (joint_type == JointType::Ball).then_some(ConeLimit {
+                        swing1: max,
+                        swing2: max,
+                        twist: max,
+                    }),
+                });
+            }
+        }
+
+        let anchor_world = frame.0 + frame.1 * anchor;
+        let axis_world = frame.1 * axis;
+        let anchor_child = child_rot.inverse() * (anchor_world - child_pos);
+        if let Some(child) = self.blueprint.modules.get_mut(&child_id) {
+            child.pivot_offset = -anchor_child;
+        }
+        self.blueprint.add_joint(JointDefinition {
+            parent_id,
+            child_id,
+            anchor_parent: parent_rot.inverse() * (anchor_world - parent_pos),
+            anchor_child,
+            joint_type,
+            axis: parent_rot.inverse() * axis_world,
+            child_axis: child_rot.inverse() * axis_world,
+            rest_displacement,
+            limits,
+            transmission: None,
+            actuator: None,
+        });
+        Ok(())
+    }
+
+    /// The `pos` and orientation of an element relative to its parent frame.
+    fn pose(&self, node: roxmltree::Node) -> Result<(Vec3, Quat), MjcfError> {
+        let pos = self.vec3(node, "pos")?.unwrap_or(Vec3::ZERO);
+        let angle = |a: f32| if self.degrees { a.to_radians() } else { a };
+        let rot = if let [w, x, y, z, ..] = self.floats(node, "quat")?.as_slice() {
+            Quat::from_xyzw(*x, *y, *z, *w).normalize()
+        } else if let [x, y, z, a, ..] = self.floats(node, "axisangle")?.as_slice() {
+            Quat::from_axis_angle(Vec3::new(*x, *y, *z).normalize_or(Vec3::Z), angle(*a))
+        } else if let [x, y, z, ..] = self.floats(node, "euler")?.as_slice() {
+            // MuJoCo's default `eulerseq` is intrinsic x, then y, then z.
+            Quat::from_rotation_x(angle(*x))
+                * Quat::from_rotation_y(angle(*y))
+                * Quat::from_rotation_z(angle(*z))
+        } else if let Some(z) = self.vec3(node, "zaxis")? {
+            Quat::from_rotation_arc(Vec3::Z, z.normalize_or(Vec3::Z))
+        } else if let [x0, x1, x2, y0, y1, y2, ..] = self.floats(node, "xyaxes")?.as_slice() {
+            let x = Vec3::new(*x0, *x1, *x2).normalize_or(Vec3::X);
+            let y = Vec3::new(*y0, *y1, *y2)
+                .reject_from(x)
+                .normalize_or(Vec3::Y);
+            Quat::from_mat3(&Mat3::from_cols(x, y, x.cross(y)))
+        } else {
+            Quat::IDENTITY
+        };
+        Ok((pos, rot))
+    }
+
+    /// An attribute of `node`, falling back to the top-level default for its element.
+    fn attr<'a>(&'a self, node: roxmltree::Node<'a, 'a>, name: &str) -> Option<&'a str> {
+        node.attribute(name).or_else(|| {
+            self.defaults
+                .get(node.tag_name().name())
+                .and_then(|d| d.get(name))
+                .map(String::as_str)
+        })
+    }
+
+    /// The whitespace-separated numbers of an attribute; empty if it is absent.
+    fn floats(&self, node: roxmltree::Node, name: &str) -> Result<Vec<f32>, MjcfError> {
+        let Some(text) = self.attr(node, name) else {
+            return Ok(Vec::new());
+        };
+        text.split_whitespace()
+            .map(|v| {
+                v.parse().map_err(|_| MjcfError::InvalidNumber {
+                    attribute: name.to_owned(),
+                    value: text.to_owned(),
+                })
+            })
+            .collect()
+    }
+
+    /// A three-component attribute, or `None` if it is absent.
+    fn vec3(&self, node: roxmltree::Node, name: &str) -> Result<Option<Vec3>, MjcfError> {
+        match self.floats(node, name)?.as_slice() {
+            [] => Ok(None),
+            [x, y, z, ..] => Ok(Some(Vec3::new(*x, *y, *z))),
+            _ => Err(MjcfError::InvalidNumber {
+                attribute: name.to_owned(),
+                value: self.attr(node, name).unwrap_or_default().to_owned(),
+            }),
+        }
+    }
+}
+
+/// The first child element of `node` called `name`.
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
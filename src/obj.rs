@@ -0,0 +1,42 @@
+//! Wavefront OBJ export of the rest pose.
+//!
+//! [`RobotBlueprint::to_obj`] writes the assembled robot in world space, with one object
+//! per module so that MeshLab and Blender can select, hide or recolour parts individually.
+
+use crate::blueprint::RobotBlueprint;
+use crate::mesh::DEFAULT_MESH_SEGMENTS;
+use std::fmt::Write as _;
+
+impl RobotBlueprint {
+    /// Renders the rest pose as a Wavefront OBJ document, tessellating round shapes with
+    /// [`DEFAULT_MESH_SEGMENTS`] subdivisions.
+    ///
+    /// Each module becomes an object named `module_N` using material `material_M`. No
+    /// `.mtl` library is referenced; viewers fall back to their default colours but keep
+    /// the material groups.
+    pub fn to_obj(&self) -> String {
+        self.to_obj_with_segments(DEFAULT_MESH_SEGMENTS)
+    }
+
+    /// Like [`to_obj`](Self::to_obj), with `segments` subdivisions for round shapes.
+    pub fn to_obj_with_segments(&self, segments: u32) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Generated by symbios-robot");
+        // OBJ indices are 1-based and global to the file.
+        let mut base = 1;
+        for (id, module) in self.modules_topological() {
+            let (pos, rot) = module.transform;
+            let mesh = module.shape.tessellate(segments).transformed(pos, rot);
+            let _ = writeln!(out, "o module_{id}");
+            let _ = writeln!(out, "usemtl material_{}", module.material_id);
+            for v in &mesh.vertices {
+                let _ = writeln!(out, "v {:.6} {:.6} {:.6}", v.x, v.y, v.z);
+            }
+            for [a, b, c] in &mesh.triangles {
+                let _ = writeln!(out, "f {} {} {}", a + base, b + base, c + base);
+            }
+            base += mesh.vertices.len() as u32;
+        }
+        out
+    }
+}
@@ -0,0 +1,24 @@
+//! Data parallelism behind the `rayon` feature.
+//!
+//! Passes over modules, joints or population members map each item independently and
+//! then fold the results in order, so the output is the same with and without the
+//! feature, down to the last bit of every float sum.
+
+/// `f` applied to every item of `items` with its index, in order. Runs on the rayon
+/// thread pool with the `rayon` feature and sequentially otherwise.
+#[cfg(feature = "rayon")]
+pub(crate) fn map<T, R>(items: &[T], f: impl Fn(usize, &T) -> R + Sync + Send) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    use rayon::prelude::*;
+    items.par_iter().enumerate().map(|(i, x)| f(i, x)).collect()
+}
+
+/// `f` applied to every item of `items` with its index, in order. Runs on the rayon
+/// thread pool with the `rayon` feature and sequentially otherwise.
+#[cfg(not(feature = "rayon"))]
+pub(crate) fn map<T, R>(items: &[T], f: impl Fn(usize, &T) -> R) -> Vec<R> {
+    items.iter().enumerate().map(|(i, x)| f(i, x)).collect()
+}
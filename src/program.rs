@@ -0,0 +1,58 @@
+//! Pre-resolved operation streams.
+//!
+//! A [`CompiledProgram`] is a [`symbios::SymbiosState`] with every symbol already looked
+//! up in a [`RobotInterpreter`](crate::RobotInterpreter)'s op map. Interpreting the same
+//! genotype many times — under different [`RobotConfig`](crate::RobotConfig)s, or in a
+//! step-through tool — then skips symbol lookup entirely.
+
+use crate::turtle::RobotOp;
+
+/// A flat sequence of resolved operations and their parameters.
+///
+/// Parameters are stored in a single contiguous buffer (Structure-of-Arrays, like
+/// [`symbios::SymbiosState`]) so compiling does not allocate per symbol.
+/// Symbols that resolve to [`RobotOp::Ignore`] are dropped during compilation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompiledProgram {
+    ops: Vec<RobotOp>,
+    param_ranges: Vec<(u32, u32)>,
+    params: Vec<f64>,
+}
+
+impl CompiledProgram {
+    /// Creates an empty program.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an operation and its parameters.
+    pub fn push(&mut self, op: RobotOp, params: &[f64]) {
+        let start = self.params.len() as u32;
+        self.params.extend_from_slice(params);
+        self.ops.push(op);
+        self.param_ranges.push((start, params.len() as u32));
+    }
+
+    /// Number of operations in the program.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// True iff the program has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns the operation at `index` and its parameters.
+    pub fn get(&self, index: usize) -> Option<(&RobotOp, &[f64])> {
+        let op = self.ops.get(index)?;
+        let (start, len) = self.param_ranges[index];
+        let start = start as usize;
+        Some((op, &self.params[start..start + len as usize]))
+    }
+
+    /// Iterates over `(op, params)` pairs in program order.
+    pub fn iter(&self) -> impl Iterator<Item = (&RobotOp, &[f64])> + '_ {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+}
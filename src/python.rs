@@ -0,0 +1,188 @@
+//! Python bindings (feature `pyo3`).
+//!
+//! Exposes [`RobotConfig`], [`RobotInterpreter`] and [`RobotBlueprint`] as a Python
+//! extension module called `symbios_robot`, so evolutionary frameworks written in Python
+//! can grow robots in-process. Build it with `maturin develop --features pyo3`.
+//!
+//! ```python
+//! import symbios_robot as sr
+//!
+//! interpreter = sr.RobotInterpreter(sr.RobotConfig(default_width=0.2))
+//! blueprint = interpreter.build([("B", [1.0]), ("J", []), ("B", [1.0])])
+//! open("robot.xml", "w").write(blueprint.to_mjcf("robot"))
+//! ```
+//!
+//! Configs, ops and blueprints cross the boundary in their serde JSON form, so Python
+//! sees the same field names as the crate's JSON files.
+
+use crate::blueprint::RobotBlueprint;
+use crate::file::BlueprintFormat;
+use crate::interpreter::{RobotConfig, RobotInterpreter, standard_symbols};
+use crate::metrics;
+use crate::turtle::RobotOp;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use symbios::{SymbiosState, SymbolTable};
+
+fn value_error(e: impl ToString) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Interpreter settings. Keyword arguments set the fields of the same name.
+#[pyclass(name = "RobotConfig", module = "symbios_robot")]
+pub struct PyRobotConfig(pub RobotConfig);
+
+#[pymethods]
+impl PyRobotConfig {
+    #[new]
+    #[pyo3(signature = (**fields))]
+    fn new(py: Python<'_>, fields: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let Some(fields) = fields else {
+            return Ok(Self(RobotConfig::default()));
+        };
+        let json: String = py
+            .import("json")?
+            .call_method1("dumps", (fields,))?
+            .extract()?;
+        serde_json::from_str(&json).map(Self).map_err(value_error)
+    }
+
+    /// The config as JSON.
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).expect("configs always serialize")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RobotConfig({})", self.to_json())
+    }
+}
+
+/// A robot description produced by [`PyRobotInterpreter::build`].
+#[pyclass(name = "RobotBlueprint", module = "symbios_robot")]
+pub struct PyRobotBlueprint(pub RobotBlueprint);
+
+#[pymethods]
+impl PyRobotBlueprint {
+    /// Parses a blueprint from its JSON form.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        RobotBlueprint::decode(json.as_bytes(), BlueprintFormat::Json)
+            .map(Self)
+            .map_err(value_error)
+    }
+
+    /// The blueprint as JSON.
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).expect("blueprints always serialize")
+    }
+
+    /// Decodes a blueprint from the compact binary format.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        RobotBlueprint::from_bytes(data)
+            .map(Self)
+            .map_err(value_error)
+    }
+
+    /// The blueprint in the compact binary format.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.to_bytes())
+    }
+
+    /// An MJCF document with a model called `name`.
+    fn to_mjcf(&self, name: &str) -> String {
+        self.0.to_mjcf(name)
+    }
+
+    /// An SDF document with a model called `name`.
+    fn to_sdf(&self, name: &str) -> String {
+        self.0.to_sdf(name)
+    }
+
+    /// The default morphological descriptor (see `metrics::feature_vector`).
+    fn feature_vector(&self) -> Vec<f32> {
+        metrics::feature_vector(&self.0, &Default::default())
+    }
+
+    /// Number of modules.
+    #[getter]
+    fn module_count(&self) -> usize {
+        self.0.modules.len()
+    }
+
+    /// Number of joints between modules.
+    #[getter]
+    fn joint_count(&self) -> usize {
+        self.0.joints.len()
+    }
+
+    /// Total mass in kg.
+    #[getter]
+    fn mass(&self) -> f32 {
+        metrics::total_mass(&self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RobotBlueprint(modules={}, joints={})",
+            self.0.modules.len(),
+            self.0.joints.len()
+        )
+    }
+}
+
+/// Turns symbol sequences into blueprints, with the standard symbols bound.
+#[pyclass(name = "RobotInterpreter", module = "symbios_robot")]
+pub struct PyRobotInterpreter {
+    interpreter: RobotInterpreter,
+    interner: SymbolTable,
+}
+
+#[pymethods]
+impl PyRobotInterpreter {
+    #[new]
+    #[pyo3(signature = (config=None))]
+    fn new(config: Option<PyRef<'_, PyRobotConfig>>) -> PyResult<Self> {
+        let mut interner = SymbolTable::new();
+        for (sym, _) in standard_symbols() {
+            interner.intern(sym).map_err(value_error)?;
+        }
+        let config = config.map(|c| c.0.clone()).unwrap_or_default();
+        let mut interpreter = RobotInterpreter::new(config);
+        interpreter.populate_standard_symbols(&interner);
+        Ok(Self {
+            interpreter,
+            interner,
+        })
+    }
+
+    /// Binds `symbol` to an op given in its JSON form, e.g. `'"SpawnBox"'` or
+    /// `'{"Yaw": 1.0}'`.
+    fn set_op(&mut self, symbol: &str, op: &str) -> PyResult<()> {
+        let op: RobotOp = serde_json::from_str(op).map_err(value_error)?;
+        let id = self.interner.intern(symbol).map_err(value_error)?;
+        self.interpreter.set_op(id, op);
+        Ok(())
+    }
+
+    /// Interprets `symbols`, a list of `(name, params)` pairs. Names without a binding
+    /// are ignored.
+    fn build(&mut self, symbols: Vec<(String, Vec<f64>)>) -> PyResult<PyRobotBlueprint> {
+        let mut state = SymbiosState::new();
+        for (name, params) in &symbols {
+            let id = self.interner.intern(name).map_err(value_error)?;
+            state.push(id, 0.0, params).map_err(value_error)?;
+        }
+        Ok(PyRobotBlueprint(self.interpreter.build_blueprint(&state)))
+    }
+}
+
+/// The `symbios_robot` Python module.
+#[pymodule]
+fn symbios_robot(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyRobotConfig>()?;
+    module.add_class::<PyRobotInterpreter>()?;
+    module.add_class::<PyRobotBlueprint>()?;
+    Ok(())
+}
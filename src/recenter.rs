@@ -0,0 +1,150 @@
+//! Canonical placement of a blueprint.
+//!
+//! [`RobotBlueprint::recenter`] moves a robot so that its centre of mass (or bounding-box
+//! centre) sits at the origin and, optionally, turns it so that its principal axes of
+//! inertia lie along the world axes. Two phenotypes that differ only by where and at what
+//! angle the turtle happened to grow them then compare equal under geometric metrics.
+
+use crate::blueprint::RobotBlueprint;
+use bevy_heavy::ComputeMassProperties3d;
+use bevy_math::bounding::BoundingVolume as _;
+use glam::{Mat3, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// The point [`RobotBlueprint::recenter`] moves to the origin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecenterOrigin {
+    /// The robot's center of mass.
+    #[default]
+    CenterOfMass,
+    /// The center of the robot's axis-aligned bounding box, taken after any alignment.
+    BoundsCenter,
+}
+
+impl RobotBlueprint {
+    /// Moves the robot so that `origin` lies at the world origin and returns the applied
+    /// transform `(translation, rotation)`, which maps an old world point `p` to
+    /// `rotation * p + translation`.
+    ///
+    /// With `align_axes`, the robot is first turned about its center of mass so that its
+    /// principal axes of inertia lie along X, Y and Z, in order of increasing moment: the
+    /// robot's longest direction ends up along X. Each axis points toward the side its
+    /// largest component already pointed to, so the result does not depend on the signs
+    /// the decomposition happens to pick. Modules, sensors and world welds move rigidly;
+    /// joints are stored in module frames and are unaffected.
+    ///
+    /// A massless blueprint (a [`layout`](crate::RobotInterpreter::layout) that has not
+    /// been annotated) is weighted by volume instead. An empty blueprint is left alone.
+    pub fn recenter(&mut self, origin: RecenterOrigin, align_axes: bool) -> (Vec3, Quat) {
+        let robot_mass: f32 = self.modules.values().map(|m| m.mass).sum();
+        let weighted: Vec<(f32, Vec3, Vec3, Quat)> = self
+            .modules
+            .values()
+            .map(|m| {
+                let primitive = m.shape.to_bevy_primitive();
+                let weight = if robot_mass > 0.0 {
+                    m.mass
+                } else {
+                    primitive.mass(1.0)
+                };
+                let inertia = primitive.unit_principal_angular_inertia() * weight;
+                (weight, m.world_center_of_mass(), inertia, m.transform.1)
+            })
+            .collect();
+        let total: f32 = weighted.iter().map(|w| w.0).sum();
+        if total <= 0.0 {
+            return (Vec3::ZERO, Quat::IDENTITY);
+        }
+        let com = weighted.iter().map(|w| w.1 * w.0).sum::<Vec3>() / total;
+
+        let rotation = if align_axes {
+            let mut tensor = Mat3::ZERO;
+            for &(weight, center, inertia, rot) in &weighted {
+                let r = Mat3::from_quat(rot);
+                let d = center - com;
+                // Parallel-axis theorem: w·(|d|²·I − d·dᵀ).
+                let offset = Mat3::from_diagonal(Vec3::splat(d.length_squared()))
+                    - Mat3::from_cols(d * d.x, d * d.y, d * d.z);
+                tensor += r * Mat3::from_diagonal(inertia) * r.transpose() + offset * weight;
+            }
+            principal_frame(tensor).inverse()
+        } else {
+            Quat::IDENTITY
+        };
+
+        let pivot = match origin {
+            RecenterOrigin::CenterOfMass => rotation * com,
+            RecenterOrigin::BoundsCenter => Vec3::from(self.aabb(rotation).center()),
+        };
+        let translation = -pivot;
+        for module in self.modules.values_mut() {
+            let (pos, rot) = module.transform;
+            module.transform = (rotation * pos + translation, (rotation * rot).normalize());
+            module.update_bounds();
+        }
+        for weld in &mut self.world_joints {
+            weld.anchor_world = rotation * weld.anchor_world + translation;
+        }
+        (translation, rotation)
+    }
+}
+
+/// Rotation whose X, Y and Z axes are the eigenvectors of the symmetric `tensor` in order
+/// of increasing eigenvalue, found by cyclic Jacobi sweeps.
+fn principal_frame(tensor: Mat3) -> Quat {
+    // Symmetric, so column-major and row-major indexing agree.
+    let mut a = tensor.to_cols_array_2d();
+    let mut v = Mat3::IDENTITY.to_cols_array_2d();
+    for _ in 0..32 {
+        let off = a[0][1].powi(2) + a[0][2].powi(2) + a[1][2].powi(2);
+        let diagonal = a[0][0].abs() + a[1][1].abs() + a[2][2].abs();
+        if off <= (diagonal * 1e-7).powi(2) {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            // Rotate in the (p, q) plane to zero a[p][q].
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            for k in 0..3 {
+                let (akp, akq) = (a[k][p], a[k][q]);
+                a[k][p] = c * akp - s * akq;
+                a[k][q] = s * akp + c * akq;
+            }
+            for k in 0..3 {
+                let (apk, aqk) = (a[p][k], a[q][k]);
+                a[p][k] = c * apk - s * aqk;
+                a[q][k] = s * apk + c * aqk;
+            }
+            for row in &mut v {
+                let (vp, vq) = (row[p], row[q]);
+                row[p] = c * vp - s * vq;
+                row[q] = s * vp + c * vq;
+            }
+        }
+    }
+
+    // Column i of `v` (indexed `v[row][column]`) is the eigenvector of `a[i][i]`.
+    let mut axes: Vec<(f32, Vec3)> = (0..3)
+        .map(|i| (a[i][i], Vec3::new(v[0][i], v[1][i], v[2][i])))
+        .collect();
+    axes.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let orient = |axis: Vec3| {
+        let largest = axis.abs().max_element();
+        let sign = if axis.x.abs() == largest {
+            axis.x
+        } else if axis.y.abs() == largest {
+            axis.y
+        } else {
+            axis.z
+        };
+        axis.normalize() * sign.signum()
+    };
+    let x = orient(axes[0].1);
+    let y = orient(axes[1].1);
+    Quat::from_mat3(&Mat3::from_cols(x, y, x.cross(y)))
+}
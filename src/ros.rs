@@ -0,0 +1,119 @@
+//! ROS 2 `robot_description` packaging.
+//!
+//! [`RobotBlueprint::ros_description`] bundles the plain URDF of
+//! [`to_urdf`](RobotBlueprint::to_urdf) with a manifest of the joints that
+//! `robot_state_publisher` expects to find in `/joint_states`, so an evolved robot can be
+//! handed to a ROS control stack without parsing the URDF again.
+//!
+//! Names derive from [`ModuleId`]s alone: links are `module_N`, joints `joint_P_C` (parent
+//! and child module) and the fixed joints holding roots `root_N`; a ball joint becomes
+//! `joint_P_C_swing1`, `joint_P_C_swing2` and `joint_P_C_twist` with the massless links
+//! `module_C_swing1` and `module_C_swing2` between them. The interpreter assigns
+//! IDs in symbol order, so the same symbol string always yields the same names, across runs
+//! and machines. These are the names the MJCF export and
+//! [`control_interface`](RobotBlueprint::control_interface) use as well.
+
+use crate::blueprint::{JointType, ModuleId, RobotBlueprint};
+use crate::urdf::{ball_stages, urdf_joint_type};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// A URDF and the joints a `sensor_msgs/JointState` publisher must report for it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RosDescription {
+    /// The robot's URDF, to be published as the `robot_description` parameter.
+    pub urdf: String,
+    /// The movable joints, in topological order.
+    pub joints: Vec<RosJoint>,
+}
+
+/// A movable joint of a [`RosDescription`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RosJoint {
+    /// Joint name in the URDF.
+    pub name: String,
+    /// URDF joint type: `revolute`, `continuous` or `prismatic`.
+    pub joint_type: String,
+    /// Name of the parent link.
+    pub parent_link: String,
+    /// Name of the child link.
+    pub child_link: String,
+    /// The module the joint moves.
+    pub module: ModuleId,
+    /// Lower and upper limit in the URDF's joint coordinates, if the joint is limited.
+    pub limits: Option<(f32, f32)>,
+}
+
+impl RobotBlueprint {
+    /// Packages the robot for `robot_state_publisher` under the name `name`.
+    ///
+    /// Fixed joints are published as static transforms and left out of the manifest. Ball
+    /// joints appear as the three revolute joints the URDF writes for them, swing1, swing2
+    /// and twist. A prismatic joint's limits are shifted by its
+    /// [`rest_displacement`](crate::JointDefinition::rest_displacement), as in the URDF.
+    pub fn ros_description(&self, name: &str) -> RosDescription {
+        let joints = self
+            .joints_topological()
+            .filter(|j| {
+                j.joint_type != JointType::Fixed
+                    && self.modules.contains_key(&j.parent_id)
+                    && self.modules.contains_key(&j.child_id)
+            })
+            .flat_map(|j| {
+                if j.joint_type == JointType::Ball {
+                    return ball_stages(self, j)
+                        .into_iter()
+                        .map(|stage| RosJoint {
+                            joint_type: match stage.limits {
+                                Some(_) => "revolute",
+                                None => "continuous",
+                            }
+                            .to_owned(),
+                            name: stage.name,
+                            parent_link: stage.parent_link,
+                            child_link: stage.child_link,
+                            module: j.child_id,
+                            limits: stage.limits,
+                        })
+                        .collect();
+                }
+                let offset = match j.joint_type {
+                    JointType::Prismatic => j.rest_displacement,
+                    _ => 0.0,
+                };
+                vec![RosJoint {
+                    name: format!("joint_{}_{}", j.parent_id, j.child_id),
+                    joint_type: urdf_joint_type(j).to_owned(),
+                    parent_link: format!("module_{}", j.parent_id),
+                    child_link: format!("module_{}", j.child_id),
+                    module: j.child_id,
+                    limits: j.limits.map(|l| (l.min - offset, l.max - offset)),
+                }]
+            })
+            .collect();
+        RosDescription {
+            urdf: self.to_urdf(name),
+            joints,
+        }
+    }
+}
+
+impl RosDescription {
+    /// The joint names, in the order a `JointState` message should list them.
+    pub fn joint_names(&self) -> Vec<&str> {
+        self.joints.iter().map(|j| j.name.as_str()).collect()
+    }
+
+    /// A ROS 2 parameters file setting `robot_description` for the node called `node`,
+    /// for `ros2 run robot_state_publisher robot_state_publisher --ros-args --params-file`.
+    pub fn params_yaml(&self, node: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{node}:");
+        let _ = writeln!(out, "  ros__parameters:");
+        let _ = writeln!(out, "    robot_description: |");
+        for line in self.urdf.lines() {
+            let _ = writeln!(out, "      {line}");
+        }
+        out
+    }
+}
@@ -0,0 +1,260 @@
+//! Deterministic point sampling of a blueprint's rest pose.
+//!
+//! Points are drawn from a low-discrepancy Halton sequence rather than a random number
+//! generator, so the same blueprint always yields the same point set. This keeps
+//! geometry-based metrics such as [`crate::metrics::chamfer_distance`] reproducible across
+//! runs without threading a seed through every caller.
+
+use crate::blueprint::{ModuleId, RobotBlueprint, ShapePrimitive};
+use bevy_math::primitives::Measured3d as _;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+
+/// Which part of each shape to sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleMode {
+    /// Points on the shape boundary, distributed proportionally to surface area.
+    #[default]
+    Surface,
+    /// Points inside the shape, distributed proportionally to volume.
+    Volume,
+}
+
+/// A labelled point cloud of a blueprint's rest pose.
+///
+/// `points[i]` lies on (or in) module `module_ids[i]`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointCloud {
+    /// Sample positions in world space.
+    pub points: Vec<Vec3>,
+    /// The module each point was sampled from.
+    pub module_ids: Vec<ModuleId>,
+}
+
+impl PointCloud {
+    /// Number of points.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns `true` if the cloud has no points.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Iterates over `(module, point)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (ModuleId, Vec3)> + '_ {
+        self.module_ids
+            .iter()
+            .copied()
+            .zip(self.points.iter().copied())
+    }
+
+    /// Points sampled from `module`.
+    pub fn points_of(&self, module: ModuleId) -> impl Iterator<Item = Vec3> + '_ {
+        self.iter()
+            .filter(move |&(id, _)| id == module)
+            .map(|(_, p)| p)
+    }
+}
+
+impl ShapePrimitive {
+    /// Maps `(u, v)` in `[0, 1)²` to a point on the shape's surface, in local space.
+    ///
+    /// Uniformly distributed `(u, v)` yields points uniformly distributed by area.
+    pub fn surface_point(&self, u: f32, v: f32) -> Vec3 {
+        match *self {
+            Self::Box(h) => {
+                let faces = [h.y * h.z, h.x * h.z, h.x * h.y];
+                let total = faces.iter().sum::<f32>().max(f32::EPSILON);
+                // Pick an axis by area, then one of its two faces.
+                let mut t = u * total;
+                let mut axis = 0;
+                while axis < 2 && t >= faces[axis] {
+                    t -= faces[axis];
+                    axis += 1;
+                }
+                let s = (t / faces[axis].max(f32::EPSILON)).clamp(0.0, 1.0);
+                let side = if s < 0.5 { -1.0 } else { 1.0 };
+                let a = (s * 2.0).fract() * 2.0 - 1.0;
+                let b = v * 2.0 - 1.0;
+                match axis {
+                    0 => Vec3::new(side * h.x, a * h.y, b * h.z),
+                    1 => Vec3::new(a * h.x, side * h.y, b * h.z),
+                    _ => Vec3::new(a * h.x, b * h.y, side * h.z),
+                }
+            }
+            Self::Sphere(r) => unit_sphere(u, v) * r,
+            Self::Cylinder { radius, height } => {
+                let side = TAU * radius * height;
+                let cap = PI * radius * radius;
+                let t = u * (side + 2.0 * cap);
+                if t < side {
+                    let y = (t / side.max(f32::EPSILON) - 0.5) * height;
+                    let (sin, cos) = (TAU * v).sin_cos();
+                    Vec3::new(radius * cos, y, radius * sin)
+                } else {
+                    let s = (t - side) / cap.max(f32::EPSILON);
+                    let y = if s < 1.0 { height / 2.0 } else { -height / 2.0 };
+                    let (sin, cos) = (TAU * v).sin_cos();
+                    let rr = radius * s.fract().sqrt();
+                    Vec3::new(rr * cos, y, rr * sin)
+                }
+            }
+            Self::Capsule { radius, height } => {
+                let side = TAU * radius * height;
+                let caps = 4.0 * PI * radius * radius;
+                let t = u * (side + caps);
+                if t < side {
+                    let y = (t / side.max(f32::EPSILON) - 0.5) * height;
+                    let (sin, cos) = (TAU * v).sin_cos();
+                    Vec3::new(radius * cos, y, radius * sin)
+                } else {
+                    let p = unit_sphere((t - side) / caps.max(f32::EPSILON), v) * radius;
+                    p + Vec3::Y * (height / 2.0).copysign(p.y)
+                }
+            }
+        }
+    }
+
+    /// Maps `(u, v, w)` in `[0, 1)³` to a point inside the shape, in local space.
+    ///
+    /// Uniformly distributed `(u, v, w)` yields points uniformly distributed by volume.
+    pub fn volume_point(&self, u: f32, v: f32, w: f32) -> Vec3 {
+        match *self {
+            Self::Box(h) => Vec3::new(u * 2.0 - 1.0, v * 2.0 - 1.0, w * 2.0 - 1.0) * h,
+            Self::Sphere(r) => unit_sphere(u, v) * r * w.cbrt(),
+            Self::Cylinder { radius, height } => {
+                let (sin, cos) = (TAU * v).sin_cos();
+                let rr = radius * u.sqrt();
+                Vec3::new(rr * cos, (w - 0.5) * height, rr * sin)
+            }
+            Self::Capsule { radius, height } => {
+                let cylinder = PI * radius * radius * height;
+                let ball = 4.0 / 3.0 * PI * radius * radius * radius;
+                let t = w * (cylinder + ball);
+                if t < cylinder {
+                    let (sin, cos) = (TAU * v).sin_cos();
+                    let rr = radius * u.sqrt();
+                    let y = (t / cylinder.max(f32::EPSILON) - 0.5) * height;
+                    Vec3::new(rr * cos, y, rr * sin)
+                } else {
+                    let s = (t - cylinder) / ball.max(f32::EPSILON);
+                    let p = unit_sphere(u, v) * radius * s.cbrt();
+                    p + Vec3::Y * (height / 2.0).copysign(p.y)
+                }
+            }
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// Samples `n` points from the rest pose, in world space.
+    ///
+    /// Points are allotted to modules in proportion to their surface area or volume (per
+    /// `mode`) and placed with a Halton sequence, so the result is deterministic. Modules are
+    /// visited in topological order.
+    pub fn sample_points(&self, n: usize, mode: SampleMode) -> Vec<Vec3> {
+        self.sample_points_with_ids(n, mode)
+            .into_iter()
+            .map(|(_, p)| p)
+            .collect()
+    }
+
+    /// Samples `n` surface points with the module each one lies on.
+    ///
+    /// Equivalent to [`sample_cloud`](Self::sample_cloud) with [`SampleMode::Surface`].
+    pub fn sample_surface(&self, n: usize) -> PointCloud {
+        self.sample_cloud(n, SampleMode::Surface)
+    }
+
+    /// Like [`sample_points`](Self::sample_points), but tags each point with its module.
+    pub fn sample_cloud(&self, n: usize, mode: SampleMode) -> PointCloud {
+        let (module_ids, points) = self.sample_points_with_ids(n, mode).into_iter().unzip();
+        PointCloud { points, module_ids }
+    }
+
+    fn sample_points_with_ids(&self, n: usize, mode: SampleMode) -> Vec<(ModuleId, Vec3)> {
+        let modules: Vec<_> = self.modules_topological().collect();
+        let weights: Vec<f32> = modules
+            .iter()
+            .map(|(_, m)| {
+                let prim = m.shape.to_bevy_primitive();
+                match mode {
+                    SampleMode::Surface => prim.area(),
+                    SampleMode::Volume => prim.volume(),
+                }
+            })
+            .collect();
+        let counts = apportion(n, &weights);
+
+        let mut points = Vec::with_capacity(n);
+        let mut index = 1;
+        for ((id, module), count) in modules.into_iter().zip(counts) {
+            let (pos, rot) = module.transform;
+            for _ in 0..count {
+                let (u, v, w) = (halton(index, 2), halton(index, 3), halton(index, 5));
+                index += 1;
+                let local = match mode {
+                    SampleMode::Surface => module.shape.surface_point(u, v),
+                    SampleMode::Volume => module.shape.volume_point(u, v, w),
+                };
+                points.push((id, pos + rot * local));
+            }
+        }
+        points
+    }
+}
+
+/// Point on the unit sphere for `(u, v)` in `[0, 1)²`, uniform by area.
+fn unit_sphere(u: f32, v: f32) -> Vec3 {
+    let y = 1.0 - 2.0 * u;
+    let r = (1.0 - y * y).max(0.0).sqrt();
+    let (sin, cos) = (TAU * v).sin_cos();
+    Vec3::new(r * cos, y, r * sin)
+}
+
+/// The `index`-th element of the Halton sequence in the given prime `base`.
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut f = 1.0;
+    let mut r = 0.0;
+    while index > 0 {
+        f /= base as f32;
+        r += f * (index % base) as f32;
+        index /= base;
+    }
+    r
+}
+
+/// Splits `n` into integer shares proportional to `weights` (largest-remainder method).
+fn apportion(n: usize, weights: &[f32]) -> Vec<usize> {
+    let total: f32 = weights.iter().filter(|w| w.is_finite()).sum();
+    if weights.is_empty() || total <= 0.0 {
+        return vec![0; weights.len()];
+    }
+    let quotas: Vec<f32> = weights
+        .iter()
+        .map(|&w| {
+            if w.is_finite() {
+                w / total * n as f32
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut counts: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+    let mut remaining = n.saturating_sub(counts.iter().sum());
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_by(|&a, &b| {
+        (quotas[b] - quotas[b].floor()).total_cmp(&(quotas[a] - quotas[a].floor()))
+    });
+    for i in order.into_iter().cycle() {
+        if remaining == 0 {
+            break;
+        }
+        counts[i] += 1;
+        remaining -= 1;
+    }
+    counts
+}
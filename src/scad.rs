@@ -0,0 +1,91 @@
+//! OpenSCAD export of the rest pose.
+//!
+//! [`RobotBlueprint::to_scad`] emits one OpenSCAD `module` per part, with its dimensions
+//! pulled out into named variables, and a `robot()` assembly that places every part at its
+//! rest transform. The output is meant as an editable CAD starting point rather than a
+//! finished drawing.
+
+use crate::blueprint::{ModuleId, RobotBlueprint, ShapePrimitive};
+use std::fmt::Write as _;
+
+impl RobotBlueprint {
+    /// Renders the robot as an OpenSCAD script.
+    ///
+    /// Dimensions are written in metres and the assembly is scaled by a top-level `unit`
+    /// variable (1000, i.e. millimetres, by default). Parts are listed in topological order.
+    pub fn to_scad(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "// Generated by symbios-robot");
+        let _ = writeln!(out, "unit = 1000; // metres -> millimetres");
+        let _ = writeln!(out, "$fn = 32;");
+        let modules: Vec<_> = self.modules_topological().collect();
+
+        let _ = writeln!(out, "\n// Part dimensions (metres)");
+        for (id, module) in &modules {
+            write_dimensions(&mut out, *id, &module.shape);
+        }
+
+        for (id, module) in &modules {
+            let _ = writeln!(out, "\n// {}", module.shape);
+            let _ = writeln!(out, "module part_{id}() {{");
+            let _ = writeln!(out, "    {}", shape_body(*id, &module.shape));
+            let _ = writeln!(out, "}}");
+        }
+
+        let _ = writeln!(out, "\nmodule robot() {{");
+        for (id, module) in &modules {
+            let (pos, rot) = module.transform;
+            let (axis, angle) = rot.normalize().to_axis_angle();
+            let _ = writeln!(
+                out,
+                "    translate([{:.6}, {:.6}, {:.6}]) rotate(a = {:.4}, v = [{:.6}, {:.6}, {:.6}]) part_{id}();",
+                pos.x,
+                pos.y,
+                pos.z,
+                angle.to_degrees(),
+                axis.x,
+                axis.y,
+                axis.z,
+            );
+        }
+        let _ = writeln!(out, "}}");
+        let _ = writeln!(out, "\nscale(unit) robot();");
+        out
+    }
+}
+
+/// Declares the named dimension variables of part `id`.
+fn write_dimensions(out: &mut String, id: ModuleId, shape: &ShapePrimitive) {
+    let _ = match *shape {
+        ShapePrimitive::Box(h) => writeln!(
+            out,
+            "part_{id}_size = [{:.6}, {:.6}, {:.6}];",
+            h.x * 2.0,
+            h.y * 2.0,
+            h.z * 2.0
+        ),
+        ShapePrimitive::Sphere(r) => writeln!(out, "part_{id}_radius = {r:.6};"),
+        ShapePrimitive::Cylinder { radius, height }
+        | ShapePrimitive::Capsule { radius, height } => {
+            writeln!(
+                out,
+                "part_{id}_radius = {radius:.6};\npart_{id}_height = {height:.6};"
+            )
+        }
+    };
+}
+
+/// OpenSCAD geometry for part `id`, centred on the origin with its long axis along +Y.
+fn shape_body(id: ModuleId, shape: &ShapePrimitive) -> String {
+    match shape {
+        ShapePrimitive::Box(_) => format!("cube(part_{id}_size, center = true);"),
+        ShapePrimitive::Sphere(_) => format!("sphere(r = part_{id}_radius);"),
+        // OpenSCAD cylinders run along Z.
+        ShapePrimitive::Cylinder { .. } => format!(
+            "rotate([-90, 0, 0]) cylinder(h = part_{id}_height, r = part_{id}_radius, center = true);"
+        ),
+        ShapePrimitive::Capsule { .. } => format!(
+            "hull() for (s = [-1, 1]) translate([0, s * part_{id}_height / 2, 0]) sphere(r = part_{id}_radius);"
+        ),
+    }
+}
@@ -0,0 +1,16 @@
+//! JSON Schema of the serialized blueprint (feature `schemars`).
+//!
+//! [`RobotBlueprint::json_schema`] describes the JSON that serde writes for a
+//! [`RobotBlueprint`], so that tools outside Rust (analysis scripts, web viewers) can
+//! validate blueprints before reading them. Vectors appear as `[x, y, z]` arrays and
+//! quaternions as `[x, y, z, w]`, matching glam's serde format.
+
+use crate::blueprint::RobotBlueprint;
+
+impl RobotBlueprint {
+    /// The JSON Schema (draft 2020-12) of a serialized blueprint, with every nested type
+    /// under `$defs`.
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(RobotBlueprint).to_value()
+    }
+}
@@ -0,0 +1,443 @@
+//! SDF (Simulation Description Format) 1.9 export for Gazebo.
+//!
+//! [`RobotBlueprint::to_sdf`] emits a single `<model>` with one `<link>` per module, one
+//! `<joint>` per joint and world weld, and a `<sensor>` for every mounted sensor. Unlike
+//! URDF, SDF can carry cameras, lidars, IMUs and contact sensors natively, so the sensor
+//! suite survives the trip into the simulator.
+//!
+//! The blueprint is Y-up while Gazebo is Z-up; the model pose rotates the robot a quarter
+//! turn about X so that it stands upright. Every other pose is written in the blueprint's
+//! own frame.
+//!
+//! [`RobotBlueprint::to_sdf_with`] takes [`SdfOptions`] to swap primitives for meshes,
+//! drop sensors or visuals, keep the Y-up frame or prefix every name.
+
+use crate::blueprint::{
+    JointDefinition, JointType, RobotBlueprint, RobotModule, SensorAttachment, SensorMount,
+    SensorType, ShapePrimitive,
+};
+use crate::export::{ExportGeometry, UpAxis};
+use crate::material::ContactSurface;
+use crate::stream::stream;
+use bevy_heavy::ComputeMassProperties3d as _;
+use glam::{EulerRot, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+use std::fmt::Write;
+use std::io;
+
+/// Options for [`RobotBlueprint::to_sdf_with`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SdfOptions {
+    /// Primitive shapes or per-module meshes.
+    pub geometry: ExportGeometry,
+    /// Write a `<sensor>` for every mounted sensor.
+    pub sensors: bool,
+    /// Write only `<collision>` elements, leaving visuals to the meshes of another tool.
+    pub collision_only: bool,
+    /// Which way is up in the target world; sets the model pose.
+    pub up_axis: UpAxis,
+    /// Prepended to every link, joint and frame name.
+    pub prefix: String,
+}
+
+impl Default for SdfOptions {
+    fn default() -> Self {
+        Self {
+            geometry: ExportGeometry::Primitive,
+            sensors: true,
+            collision_only: false,
+            up_axis: UpAxis::Z,
+            prefix: String::new(),
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// Renders the robot as an SDF 1.9 document containing one model called `name`.
+    ///
+    /// Links are named `module_N` and placed at their rest pose. Joint frames sit on the
+    /// child anchor with the child's orientation, so axes are written from
+    /// [`JointDefinition::child_axis`]. A prismatic joint's zero position is its rest pose:
+    /// its limits are shifted by [`JointDefinition::rest_displacement`] to match.
+    /// Joint-mounted sensors are written inside their joint; everything else inside its link.
+    /// Each [`Marker`](crate::Marker) becomes a model-level `<frame>` of the same name
+    /// attached to its link.
+    /// Each collision gets a `<surface>` from [`contacts`](Self::contacts): the friction,
+    /// restitution and soft-contact stiffness of its material against the ground, or
+    /// against itself if no ground entry exists. Modules with
+    /// [`anisotropic_friction`](crate::RobotModule::anisotropic_friction) get ODE
+    /// `mu`/`mu2` coefficients with `fdir1` along its direction.
+    pub fn to_sdf(&self, name: &str) -> String {
+        self.to_sdf_with(name, &SdfOptions::default())
+    }
+
+    /// [`to_sdf`](Self::to_sdf) with the geometry, elements, up axis and names chosen by
+    /// `options`.
+    pub fn to_sdf_with(&self, name: &str, options: &SdfOptions) -> String {
+        let mut out = String::new();
+        self.render_sdf(&mut out, name, options);
+        out
+    }
+
+    /// Streams [`to_sdf`](Self::to_sdf) into `writer`, e.g. a `BufWriter<File>`.
+    pub fn write_sdf(&self, writer: impl io::Write, name: &str) -> io::Result<()> {
+        self.write_sdf_with(writer, name, &SdfOptions::default())
+    }
+
+    /// Streams [`to_sdf_with`](Self::to_sdf_with) into `writer`.
+    pub fn write_sdf_with(
+        &self,
+        writer: impl io::Write,
+        name: &str,
+        options: &SdfOptions,
+    ) -> io::Result<()> {
+        stream(writer, |out| self.render_sdf(out, name, options))
+    }
+
+    fn render_sdf(&self, out: &mut impl Write, name: &str, options: &SdfOptions) {
+        let p = escape(&options.prefix);
+        let _ = writeln!(out, "<?xml version=\"1.0\"?>");
+        let _ = writeln!(out, "<!-- Generated by symbios-robot -->");
+        let _ = writeln!(
+            out,
+            "<sdf version=\"1.9\" xmlns:gz=\"http://gazebosim.org/schema\">"
+        );
+        let _ = writeln!(out, "  <model name=\"{}\">", escape(name));
+        let _ = writeln!(
+            out,
+            "    <pose>{}</pose>",
+            pose(Vec3::ZERO, options.up_axis.rotation())
+        );
+
+        for (id, module) in self.modules_topological() {
+            let (pos, rot) = module.transform;
+            let _ = writeln!(out, "    <link name=\"{p}module_{id}\">");
+            let _ = writeln!(out, "      <pose>{}</pose>", pose(pos, rot));
+            let inertia = module
+                .shape
+                .to_bevy_primitive()
+                .unit_principal_angular_inertia()
+                * module.mass;
+            let _ = writeln!(out, "      <inertial>");
+            let _ = writeln!(
+                out,
+                "        <pose>{}</pose>",
+                pose(module.center_of_mass, Quat::IDENTITY)
+            );
+            let _ = writeln!(out, "        <mass>{:.6}</mass>", module.mass);
+            let _ = writeln!(
+                out,
+                "        <inertia><ixx>{:.6}</ixx><ixy>0</ixy><ixz>0</ixz><iyy>{:.6}</iyy><iyz>0</iyz><izz>{:.6}</izz></inertia>",
+                inertia.x, inertia.y, inertia.z
+            );
+            let _ = writeln!(out, "      </inertial>");
+            let mesh = options.geometry.mesh_uri(id);
+            let elements: &[&str] = if options.collision_only {
+                &["collision"]
+            } else {
+                &["collision", "visual"]
+            };
+            for &element in elements {
+                let _ = writeln!(out, "      <{element} name=\"{element}\">");
+                write_geometry(out, &module.shape, mesh.as_deref());
+                if element == "collision" {
+                    write_surface(out, self, module, mesh.is_some());
+                }
+                let _ = writeln!(out, "      </{element}>");
+            }
+            for (i, sensor) in module.sensors.iter().enumerate() {
+                if options.sensors && sensor.attachment == SensorAttachment::Module {
+                    write_sensor(out, i, sensor);
+                }
+            }
+            let _ = writeln!(out, "    </link>");
+        }
+
+        for (id, marker) in self.markers() {
+            let _ = writeln!(
+                out,
+                "    <frame name=\"{p}{}\" attached_to=\"{p}module_{id}\">",
+                escape(&marker.name)
+            );
+            let _ = writeln!(
+                out,
+                "      <pose relative_to=\"{p}module_{id}\">{}</pose>",
+                pose(marker.local_position, Quat::IDENTITY)
+            );
+            let _ = writeln!(out, "    </frame>");
+        }
+
+        for joint in self.joints_topological() {
+            write_joint(out, self, joint, options);
+        }
+        for weld in &self.world_joints {
+            let child = weld.child_id;
+            let _ = writeln!(
+                out,
+                "    <joint name=\"{p}world_weld_{child}\" type=\"fixed\">"
+            );
+            let _ = writeln!(
+                out,
+                "      <pose>{}</pose>",
+                pose(weld.anchor_child, Quat::IDENTITY)
+            );
+            let _ = writeln!(out, "      <parent>world</parent>");
+            let _ = writeln!(out, "      <child>{p}module_{child}</child>");
+            let _ = writeln!(out, "    </joint>");
+        }
+
+        let _ = writeln!(out, "  </model>");
+        let _ = writeln!(out, "</sdf>");
+    }
+}
+
+/// Writes `joint`, along with the sensors mounted in its frame.
+fn write_joint(
+    out: &mut impl Write,
+    blueprint: &RobotBlueprint,
+    joint: &JointDefinition,
+    options: &SdfOptions,
+) {
+    let p = escape(&options.prefix);
+    let (parent, child) = (joint.parent_id, joint.child_id);
+    let kind = match joint.joint_type {
+        JointType::Fixed => "fixed",
+        JointType::Hinge => "revolute",
+        JointType::Ball => "ball",
+        JointType::Prismatic => "prismatic",
+    };
+    let _ = writeln!(
+        out,
+        "    <joint name=\"{p}joint_{parent}_{child}\" type=\"{kind}\">"
+    );
+    let pivot = blueprint
+        .modules
+        .get(&child)
+        .map_or(joint.anchor_child, |m| -m.pivot_offset);
+    let _ = writeln!(out, "      <pose>{}</pose>", pose(pivot, Quat::IDENTITY));
+    let _ = writeln!(out, "      <parent>{p}module_{parent}</parent>");
+    let _ = writeln!(out, "      <child>{p}module_{child}</child>");
+    if matches!(joint.joint_type, JointType::Hinge | JointType::Prismatic) {
+        let _ = writeln!(out, "      <axis>");
+        let _ = writeln!(out, "        <xyz>{}</xyz>", vec3(joint.child_axis));
+        if let Some(limits) = joint.limits {
+            let offset = match joint.joint_type {
+                JointType::Prismatic => joint.rest_displacement,
+                _ => 0.0,
+            };
+            let _ = writeln!(
+                out,
+                "        <limit><lower>{:.6}</lower><upper>{:.6}</upper><effort>{:.6}</effort><velocity>{:.6}</velocity></limit>",
+                limits.min - offset,
+                limits.max - offset,
+                limits.effort,
+                limits.velocity
+            );
+        }
+        let _ = writeln!(out, "      </axis>");
+    }
+    if let Some(module) = blueprint.modules.get(&child).filter(|_| options.sensors) {
+        for (i, sensor) in module.sensors.iter().enumerate() {
+            if sensor.attachment == SensorAttachment::Joint {
+                write_sensor(out, i, sensor);
+            }
+        }
+    }
+    let _ = writeln!(out, "    </joint>");
+}
+
+/// Writes the `<surface>` of module `id`'s collision, if its anisotropic friction or the
+/// contact table has anything to say about it.
+///
+/// SDF gives each collision its own surface rather than describing pairs, so the module
+/// takes the entry for its material against the ground, or failing that against itself.
+fn write_surface(
+    out: &mut impl Write,
+    blueprint: &RobotBlueprint,
+    module: &RobotModule,
+    mesh: bool,
+) {
+    let material = ContactSurface::Material(module.material_id);
+    let contact = blueprint
+        .contacts
+        .get(material, ContactSurface::Ground)
+        .or_else(|| blueprint.contacts.get(material, material));
+    // ODE's default coefficient is 1.
+    let base = contact.map_or(1.0, |c| c.friction);
+    let mut surface = String::new();
+    if let Some(friction) = module.anisotropic_friction {
+        let (along, across) = friction.coefficients(base);
+        // `fdir1` is in the collision frame, which stands round primitives along Z.
+        let frame = match module.shape {
+            ShapePrimitive::Cylinder { .. } | ShapePrimitive::Capsule { .. } if !mesh => {
+                Quat::from_rotation_x(-FRAC_PI_2)
+            }
+            _ => Quat::IDENTITY,
+        };
+        let _ = write!(
+            surface,
+            "<friction><ode><mu>{along:.6}</mu><mu2>{across:.6}</mu2><fdir1>{}</fdir1></ode></friction>",
+            vec3(frame.inverse() * friction.direction)
+        );
+    } else if contact.is_some() {
+        let _ = write!(
+            surface,
+            "<friction><ode><mu>{base:.6}</mu><mu2>{base:.6}</mu2></ode></friction>"
+        );
+    }
+    if let Some(contact) = contact {
+        if contact.restitution > 0.0 {
+            // Below the threshold speed contacts do not bounce; Gazebo's default of
+            // 100 km/s would disable restitution altogether.
+            let _ = write!(
+                surface,
+                "<bounce><restitution_coefficient>{:.6}</restitution_coefficient><threshold>0.01</threshold></bounce>",
+                contact.restitution
+            );
+        }
+        if let Some(stiffness) = contact.stiffness {
+            let _ = write!(
+                surface,
+                "<contact><ode><kp>{stiffness:.6}</kp><kd>{:.6}</kd></ode></contact>",
+                contact.damping.unwrap_or(0.0)
+            );
+        }
+    }
+    if !surface.is_empty() {
+        let _ = writeln!(out, "        <surface>{surface}</surface>");
+    }
+}
+
+/// Writes the `<geometry>` of a collision or visual element: the part mesh at `mesh` if
+/// there is one, otherwise the primitive. SDF cylinders and capsules run along Z, so they
+/// get a pose turning them onto the module's Y axis.
+fn write_geometry(out: &mut impl Write, shape: &ShapePrimitive, mesh: Option<&str>) {
+    if let Some(uri) = mesh {
+        let _ = writeln!(
+            out,
+            "        <geometry><mesh><uri>{}</uri></mesh></geometry>",
+            escape(uri)
+        );
+        return;
+    }
+    let upright = pose(Vec3::ZERO, Quat::from_rotation_x(-FRAC_PI_2));
+    let geometry = match *shape {
+        ShapePrimitive::Box(h) => format!("<box><size>{}</size></box>", vec3(h * 2.0)),
+        ShapePrimitive::Sphere(r) => format!("<sphere><radius>{r:.6}</radius></sphere>"),
+        ShapePrimitive::Cylinder { radius, height } => {
+            let _ = writeln!(out, "        <pose>{upright}</pose>");
+            format!("<cylinder><radius>{radius:.6}</radius><length>{height:.6}</length></cylinder>")
+        }
+        ShapePrimitive::Capsule { radius, height } => {
+            let _ = writeln!(out, "        <pose>{upright}</pose>");
+            format!("<capsule><radius>{radius:.6}</radius><length>{height:.6}</length></capsule>")
+        }
+    };
+    let _ = writeln!(out, "        <geometry>{geometry}</geometry>");
+}
+
+/// Writes sensor number `index` of its module, posed in the enclosing link or joint frame.
+fn write_sensor(out: &mut impl Write, index: usize, sensor: &SensorMount) {
+    let label = sensor.sensor_type.name().to_lowercase();
+    let name = escape(&format!("{label}_{index}"));
+    let _ = match &sensor.sensor_type {
+        SensorType::Encoder => {
+            writeln!(
+                out,
+                "      <sensor name=\"{name}\" type=\"custom\" gz:type=\"encoder\">"
+            )
+        }
+        SensorType::Custom(kind) => writeln!(
+            out,
+            "      <sensor name=\"{name}\" type=\"custom\" gz:type=\"{}\">",
+            escape(kind)
+        ),
+        other => writeln!(
+            out,
+            "      <sensor name=\"{name}\" type=\"{}\">",
+            sensor_kind(other)
+        ),
+    };
+    let _ = writeln!(
+        out,
+        "        <pose>{}</pose>",
+        pose(sensor.local_position, sensor.local_rotation)
+    );
+    let _ = writeln!(out, "        <always_on>true</always_on>");
+    if let Some(rate) = sensor.timing.update_rate {
+        let _ = writeln!(out, "        <update_rate>{rate:.6}</update_rate>");
+    }
+    let _ = match &sensor.sensor_type {
+        SensorType::Touch => writeln!(
+            out,
+            "        <contact><collision>collision</collision></contact>"
+        ),
+        SensorType::DepthCamera(p) => writeln!(
+            out,
+            "        {}",
+            camera(p.fov_y, p.resolution, Some((p.min_range, p.max_range)))
+        ),
+        SensorType::Thermal(p) => writeln!(out, "        {}", camera(p.fov_y, p.resolution, None)),
+        _ => Ok(()),
+    };
+    let _ = writeln!(out, "      </sensor>");
+}
+
+/// The SDF `type` attribute of a built-in sensor kind.
+fn sensor_kind(sensor: &SensorType) -> &'static str {
+    match sensor {
+        SensorType::Camera => "camera",
+        SensorType::Lidar => "gpu_lidar",
+        SensorType::Touch => "contact",
+        SensorType::IMU => "imu",
+        SensorType::Ultrasonic => "sonar",
+        SensorType::ForceTorque => "force_torque",
+        SensorType::DepthCamera(_) => "depth_camera",
+        SensorType::Thermal(_) => "thermal_camera",
+        SensorType::Encoder | SensorType::Custom(_) => "custom",
+    }
+}
+
+/// A `<camera>` element. SDF takes the horizontal field of view, so it is derived from the
+/// vertical one and the aspect ratio.
+fn camera(fov_y: f32, [width, height]: [u32; 2], clip: Option<(f32, f32)>) -> String {
+    let aspect = if height > 0 {
+        width as f32 / height as f32
+    } else {
+        1.0
+    };
+    let fov_x = 2.0 * ((fov_y / 2.0).tan() * aspect).atan();
+    let clip = clip.map_or(String::new(), |(near, far)| {
+        format!("<clip><near>{near:.6}</near><far>{far:.6}</far></clip>")
+    });
+    format!(
+        "<camera><horizontal_fov>{fov_x:.6}</horizontal_fov><image><width>{width}</width><height>{height}</height></image>{clip}</camera>"
+    )
+}
+
+/// An SDF pose: translation followed by roll, pitch and yaw (extrinsic X, Y, Z).
+fn pose(position: Vec3, rotation: Quat) -> String {
+    let (yaw, pitch, roll) = rotation.normalize().to_euler(EulerRot::ZYX);
+    format!("{} {roll:.6} {pitch:.6} {yaw:.6}", vec3(position))
+}
+
+fn vec3(v: Vec3) -> String {
+    format!("{:.6} {:.6} {:.6}", v.x, v.y, v.z)
+}
+
+/// Escapes the characters XML does not allow in attribute values.
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,112 @@
+//! Catalog of off-the-shelf servo actuators.
+//!
+//! Selecting a preset with [`RobotOp::SetServo`](crate::RobotOp::SetServo) gives the next
+//! joint the servo's torque and speed as limits and attaches an [`Actuator`] carrying the
+//! servo's mass, so evolved designs stay within what can actually be bought and built.
+//! Figures are the manufacturers' nominal ratings at the listed supply voltage.
+
+use crate::blueprint::{Actuator, ShapePrimitive};
+use glam::Vec3;
+
+/// Nominal specification of a hobby or robotics servo.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ServoPreset {
+    /// Model name, e.g. `"MG996R"`.
+    pub name: &'static str,
+    /// Stall torque in Nm.
+    pub stall_torque: f32,
+    /// No-load output speed in rad/s.
+    pub no_load_speed: f32,
+    /// Mass in kg.
+    pub mass: f32,
+    /// Case dimensions (width, height, depth) in metres.
+    pub size: Vec3,
+}
+
+impl ServoPreset {
+    /// Builds the [`Actuator`] attached to joints driven by this servo.
+    pub fn actuator(&self) -> Actuator {
+        Actuator {
+            model: Some(self.name.to_string()),
+            mass: self.mass,
+            housing: Some(ShapePrimitive::Box(self.size / 2.0)),
+            latency: None,
+            backlash: None,
+        }
+    }
+}
+
+/// The built-in servo catalog, indexed by the parameter of
+/// [`RobotOp::SetServo`](crate::RobotOp::SetServo).
+///
+/// New presets are only ever appended so that indices stored in genotypes stay valid.
+pub const SERVO_PRESETS: &[ServoPreset] = &[
+    // 4.8 V, 1.8 kgf·cm, 0.10 s/60°
+    ServoPreset {
+        name: "SG90",
+        stall_torque: 0.18,
+        no_load_speed: 10.47,
+        mass: 0.009,
+        size: Vec3::new(0.0222, 0.031, 0.0118),
+    },
+    // 6 V, 4.1 kgf·cm, 0.16 s/60°
+    ServoPreset {
+        name: "HS-422",
+        stall_torque: 0.40,
+        no_load_speed: 6.54,
+        mass: 0.0455,
+        size: Vec3::new(0.0406, 0.0366, 0.0198),
+    },
+    // 6 V, 11 kgf·cm, 0.14 s/60°
+    ServoPreset {
+        name: "MG996R",
+        stall_torque: 1.08,
+        no_load_speed: 7.48,
+        mass: 0.055,
+        size: Vec3::new(0.0407, 0.0429, 0.0197),
+    },
+    // 12 V, 59 rpm
+    ServoPreset {
+        name: "AX-12A",
+        stall_torque: 1.5,
+        no_load_speed: 6.18,
+        mass: 0.0546,
+        size: Vec3::new(0.032, 0.050, 0.040),
+    },
+    // 12 V, 61 rpm
+    ServoPreset {
+        name: "XL430-W250",
+        stall_torque: 1.5,
+        no_load_speed: 6.39,
+        mass: 0.0572,
+        size: Vec3::new(0.0285, 0.0465, 0.034),
+    },
+    // 12 V, 46 rpm
+    ServoPreset {
+        name: "XM430-W350",
+        stall_torque: 4.1,
+        no_load_speed: 4.82,
+        mass: 0.082,
+        size: Vec3::new(0.0285, 0.0465, 0.034),
+    },
+    // 12 V, 45 rpm
+    ServoPreset {
+        name: "MX-106",
+        stall_torque: 8.4,
+        no_load_speed: 4.71,
+        mass: 0.153,
+        size: Vec3::new(0.0402, 0.0651, 0.046),
+    },
+];
+
+/// Looks up a preset by catalog index.
+pub fn preset(index: usize) -> Option<&'static ServoPreset> {
+    SERVO_PRESETS.get(index)
+}
+
+/// Looks up a preset by model name (case-insensitive).
+pub fn preset_by_name(name: &str) -> Option<&'static ServoPreset> {
+    SERVO_PRESETS
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,209 @@
+//! Spatial hashing of the rest pose.
+//!
+//! [`RobotBlueprint::spatial_index`] buckets every module's precomputed
+//! [`bounds`](crate::RobotModule::bounds) into a uniform grid, so overlap and
+//! nearest-module queries only visit the modules in nearby cells instead of every module
+//! of the robot. Self-collision exclusions, IMU placement and the floating-anchor checks
+//! behind [`snap_anchors`](crate::RobotBlueprint::snap_anchors) use it internally; build
+//! one yourself for repeated queries against the same rest pose.
+//!
+//! The index copies what it needs, so the blueprint can be edited while an index is alive,
+//! but the index then describes the pose it was built from.
+
+use crate::blueprint::{ModuleBounds, ModuleId, RobotBlueprint, ShapePrimitive};
+use crate::validation::closest_point;
+use glam::{IVec3, Quat, Vec3};
+use std::collections::{HashMap, HashSet};
+
+/// Modules spanning more cells than this along an axis are kept out of the grid and
+/// tested by every query, so one huge module cannot fill thousands of cells.
+const MAX_CELLS_PER_AXIS: i32 = 8;
+
+/// Largest cell coordinate, in either direction.
+const CELL_LIMIT: f32 = 5.0e8;
+
+/// A uniform grid over the module bounding spheres of a blueprint's rest pose.
+#[derive(Clone, Debug)]
+pub struct SpatialIndex {
+    /// Edge length of a cell, in metres.
+    cell: f32,
+    cells: HashMap<IVec3, Vec<ModuleId>>,
+    /// Modules too large for the grid.
+    large: Vec<ModuleId>,
+    entries: HashMap<ModuleId, Entry>,
+    /// Inclusive range of occupied cells.
+    extent: Option<(IVec3, IVec3)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    bounds: ModuleBounds,
+    shape: ShapePrimitive,
+    transform: (Vec3, Quat),
+}
+
+impl RobotBlueprint {
+    /// Builds a [`SpatialIndex`] of the current rest pose. The cells are as wide as the
+    /// median module, which keeps each bucket small for typical robots.
+    pub fn spatial_index(&self) -> SpatialIndex {
+        let mut diameters: Vec<f32> = self
+            .modules
+            .values()
+            .map(|m| m.bounds.radius * 2.0)
+            .filter(|d| d.is_finite() && *d > 0.0)
+            .collect();
+        diameters.sort_unstable_by(f32::total_cmp);
+        let cell = diameters.get(diameters.len() / 2).copied().unwrap_or(1.0);
+
+        let mut index = SpatialIndex {
+            cell,
+            cells: HashMap::new(),
+            large: Vec::new(),
+            entries: HashMap::new(),
+            extent: None,
+        };
+        for (&id, module) in &self.modules {
+            index.insert(
+                id,
+                Entry {
+                    bounds: module.bounds,
+                    shape: module.shape,
+                    transform: module.transform,
+                },
+            );
+        }
+        index
+    }
+}
+
+impl SpatialIndex {
+    /// Number of indexed modules.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no module is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Modules whose bounding spheres come within `margin` metres of `query`, in ascending
+    /// ID order. The same result as [`RobotBlueprint::modules_near`].
+    pub fn overlapping(&self, query: &ModuleBounds, margin: f32) -> Vec<ModuleId> {
+        let reach = query.radius + margin.max(0.0);
+        let (lo, hi) = self.cell_range(query.center, reach);
+        let span = (hi - lo + 1).as_dvec3();
+        let candidates: Vec<ModuleId> = if span.x * span.y * span.z > self.entries.len() as f64 {
+            // Visiting the cells would cost more than testing every module.
+            self.entries.keys().copied().collect()
+        } else {
+            let mut seen = HashSet::new();
+            Self::cells_in(lo, hi)
+                .flat_map(|c| self.cells.get(&c).into_iter().flatten())
+                .chain(&self.large)
+                .copied()
+                .filter(|id| seen.insert(*id))
+                .collect()
+        };
+        let mut ids: Vec<ModuleId> = candidates
+            .into_iter()
+            .filter(|id| self.entries[id].bounds.intersects(query, margin))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// The module whose shape is closest to `point`, with the distance to its surface
+    /// (zero inside it), or `None` if the index is empty. Ties go to the smaller ID.
+    pub fn nearest(&self, point: Vec3) -> Option<(ModuleId, f32)> {
+        let mut best: Option<(ModuleId, f32)> = None;
+        let consider = |best: &mut Option<(ModuleId, f32)>, id: ModuleId| {
+            let distance = self.distance(id, point);
+            if best.is_none_or(|(b, d)| distance < d || (distance == d && id < b)) {
+                *best = Some((id, distance));
+            }
+        };
+        for &id in &self.large {
+            consider(&mut best, id);
+        }
+        let Some((min, max)) = self.extent else {
+            return best;
+        };
+        let origin = self.cell_of(point);
+        // Only rings reaching into the occupied range can hold a module.
+        let first = (min - origin)
+            .max(origin - max)
+            .max(IVec3::ZERO)
+            .max_element();
+        let last = (origin - min).abs().max((origin - max).abs()).max_element();
+        for ring in first..=last {
+            // A module first met in this ring is at least `ring - 1` cells away.
+            if best.is_some_and(|(_, d)| d <= (ring - 1) as f32 * self.cell) {
+                break;
+            }
+            for c in Self::ring(origin, ring, (min, max)) {
+                for &id in self.cells.get(&c).into_iter().flatten() {
+                    consider(&mut best, id);
+                }
+            }
+        }
+        best
+    }
+
+    /// Distance from `point` to the surface of module `id` (zero inside it), or `None` if
+    /// the module is not indexed.
+    pub fn distance_to(&self, id: ModuleId, point: Vec3) -> Option<f32> {
+        self.entries
+            .contains_key(&id)
+            .then(|| self.distance(id, point))
+    }
+
+    fn insert(&mut self, id: ModuleId, entry: Entry) {
+        let (lo, hi) = self.cell_range(entry.bounds.center, entry.bounds.radius);
+        self.entries.insert(id, entry);
+        if (hi - lo).max_element() >= MAX_CELLS_PER_AXIS {
+            self.large.push(id);
+            return;
+        }
+        for c in Self::cells_in(lo, hi) {
+            self.cells.entry(c).or_default().push(id);
+        }
+        self.extent = Some(match self.extent {
+            Some((min, max)) => (min.min(lo), max.max(hi)),
+            None => (lo, hi),
+        });
+    }
+
+    fn distance(&self, id: ModuleId, point: Vec3) -> f32 {
+        let entry = &self.entries[&id];
+        let (pos, rot) = entry.transform;
+        let local = rot.inverse() * (point - pos);
+        local.distance(closest_point(&entry.shape, local))
+    }
+
+    /// The cell containing `p`. Coordinates are clamped well inside `i32`, so cell
+    /// arithmetic cannot overflow even for points far outside the robot.
+    fn cell_of(&self, p: Vec3) -> IVec3 {
+        (p / self.cell)
+            .floor()
+            .clamp(Vec3::splat(-CELL_LIMIT), Vec3::splat(CELL_LIMIT))
+            .as_ivec3()
+    }
+
+    /// Inclusive range of cells touched by the box around a sphere.
+    fn cell_range(&self, center: Vec3, radius: f32) -> (IVec3, IVec3) {
+        (self.cell_of(center - radius), self.cell_of(center + radius))
+    }
+
+    fn cells_in(lo: IVec3, hi: IVec3) -> impl Iterator<Item = IVec3> {
+        (lo.z..=hi.z).flat_map(move |z| {
+            (lo.y..=hi.y).flat_map(move |y| (lo.x..=hi.x).map(move |x| IVec3::new(x, y, z)))
+        })
+    }
+
+    /// The cells within `(min, max)` at Chebyshev distance `ring` from `origin`.
+    fn ring(origin: IVec3, ring: i32, (min, max): (IVec3, IVec3)) -> impl Iterator<Item = IVec3> {
+        Self::cells_in((origin - ring).max(min), (origin + ring).min(max))
+            .filter(move |c| (*c - origin).abs().max_element() == ring)
+    }
+}
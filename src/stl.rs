@@ -0,0 +1,287 @@
+//! STL export of individual modules for fabrication.
+//!
+//! [`RobotBlueprint::stl_parts`] produces one watertight STL per module, expressed in the
+//! module's own frame so each part can be printed separately, together with an
+//! [`AssemblyManifest`] recording which file is which module and where it sits in the
+//! assembled robot. [`RobotBlueprint::write_stl_parts`] writes the lot to a directory.
+//! [`RobotBlueprint::to_stl`] instead writes the assembled robot as a single STL.
+
+use crate::blueprint::{MaterialId, ModuleId, RobotBlueprint, ShapePrimitive};
+use crate::mesh::TriMesh;
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write as _};
+use std::io;
+use std::path::Path;
+
+/// STL file flavour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StlFormat {
+    /// Compact little-endian binary STL.
+    #[default]
+    Binary,
+    /// Human-readable ASCII STL.
+    Ascii,
+}
+
+/// Options for [`RobotBlueprint::stl_parts`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StlOptions {
+    /// Output flavour.
+    pub format: StlFormat,
+    /// Subdivisions around the axis of round shapes.
+    pub segments: u32,
+    /// Embed a small cavity just inside each joint anchor, so the attachment point shows
+    /// up in the printed part and can be drilled out.
+    pub mark_anchors: bool,
+    /// Radius of the anchor cavity in metres.
+    pub anchor_mark_radius: f32,
+}
+
+impl Default for StlOptions {
+    fn default() -> Self {
+        Self {
+            format: StlFormat::Binary,
+            segments: 32,
+            mark_anchors: false,
+            anchor_mark_radius: 0.003,
+        }
+    }
+}
+
+/// A joint attachment point on a part, in the part's local frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnchorPoint {
+    /// Index of the joint in [`RobotBlueprint::joints`].
+    pub joint_index: usize,
+    /// The module on the other side of the joint.
+    pub other_module: ModuleId,
+    /// Anchor position in the part's local frame.
+    pub position: Vec3,
+}
+
+/// One printable part in an [`AssemblyManifest`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Module the part was generated from.
+    pub module_id: ModuleId,
+    /// STL file name, relative to the manifest.
+    pub file_name: String,
+    /// Shape of the part.
+    pub shape: ShapePrimitive,
+    /// Material the part should be made from.
+    pub material_id: MaterialId,
+    /// Rest-pose position of the part's origin in the assembled robot.
+    pub position: Vec3,
+    /// Rest-pose orientation of the part in the assembled robot.
+    pub rotation: Quat,
+    /// Joint attachment points on this part.
+    pub anchors: Vec<AnchorPoint>,
+}
+
+/// Maps exported part files back to blueprint modules, in topological order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyManifest {
+    /// One entry per part.
+    pub parts: Vec<ManifestEntry>,
+}
+
+impl fmt::Display for AssemblyManifest {
+    /// Renders a tab-separated table: module, file, shape, material, position, rotation
+    /// and the number of anchors.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "module\tfile\tshape\tmaterial\tposition\trotation\tanchors"
+        )?;
+        for p in &self.parts {
+            let (pos, rot) = (p.position, p.rotation);
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{:.4} {:.4} {:.4}\t{:.4} {:.4} {:.4} {:.4}\t{}",
+                p.module_id,
+                p.file_name,
+                p.shape,
+                p.material_id,
+                pos.x,
+                pos.y,
+                pos.z,
+                rot.x,
+                rot.y,
+                rot.z,
+                rot.w,
+                p.anchors.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A single exported part: its manifest entry and STL bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StlPart {
+    /// Manifest entry describing the part.
+    pub entry: ManifestEntry,
+    /// Encoded STL file.
+    pub data: Vec<u8>,
+}
+
+impl RobotBlueprint {
+    /// Exports every module as a separate STL in its local frame, in topological order.
+    pub fn stl_parts(&self, options: &StlOptions) -> Vec<StlPart> {
+        self.modules_topological()
+            .map(|(id, module)| {
+                let anchors: Vec<AnchorPoint> = self
+                    .joints
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(joint_index, j)| {
+                        if j.parent_id == id {
+                            Some((joint_index, j.child_id, j.anchor_parent))
+                        } else if j.child_id == id {
+                            Some((joint_index, j.parent_id, j.anchor_child))
+                        } else {
+                            None
+                        }
+                    })
+                    .map(|(joint_index, other_module, position)| AnchorPoint {
+                        joint_index,
+                        other_module,
+                        position,
+                    })
+                    .collect();
+
+                let mut mesh = module.shape.tessellate(options.segments);
+                if options.mark_anchors {
+                    for anchor in &anchors {
+                        if let Some(cavity) = anchor_cavity(
+                            &module.shape,
+                            anchor.position,
+                            options.anchor_mark_radius,
+                        ) {
+                            mesh.append(&cavity);
+                        }
+                    }
+                }
+
+                let file_name = part_file_name(id);
+                let solid = format!("module_{id}");
+                let data = match options.format {
+                    StlFormat::Binary => write_binary(&mesh, &solid),
+                    StlFormat::Ascii => write_ascii(&mesh, &solid).into_bytes(),
+                };
+                StlPart {
+                    entry: ManifestEntry {
+                        module_id: id,
+                        file_name,
+                        shape: module.shape,
+                        material_id: module.material_id,
+                        position: module.transform.0,
+                        rotation: module.transform.1,
+                        anchors,
+                    },
+                    data,
+                }
+            })
+            .collect()
+    }
+
+    /// Exports the assembled robot at its rest pose as one STL, in world space.
+    ///
+    /// Uses the format and segment count of `options`; anchors are never marked, since
+    /// they lie on the joints between parts.
+    pub fn to_stl(&self, options: &StlOptions) -> Vec<u8> {
+        let mesh = self.to_trimesh_with_segments(options.segments);
+        match options.format {
+            StlFormat::Binary => write_binary(&mesh, "robot"),
+            StlFormat::Ascii => write_ascii(&mesh, "robot").into_bytes(),
+        }
+    }
+
+    /// Writes [`stl_parts`](Self::stl_parts) into `dir`, plus the manifest as
+    /// `manifest.tsv`, creating the directory if needed.
+    pub fn write_stl_parts(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &StlOptions,
+    ) -> io::Result<AssemblyManifest> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let mut manifest = AssemblyManifest::default();
+        for part in self.stl_parts(options) {
+            std::fs::write(dir.join(&part.entry.file_name), &part.data)?;
+            manifest.parts.push(part.entry);
+        }
+        std::fs::write(dir.join("manifest.tsv"), manifest.to_string())?;
+        Ok(manifest)
+    }
+}
+
+/// An inside-out octahedron just beneath `anchor`, or `None` if it would not fit
+/// entirely inside the shape.
+fn anchor_cavity(shape: &ShapePrimitive, anchor: Vec3, radius: f32) -> Option<TriMesh> {
+    let center = anchor - anchor.normalize_or_zero() * radius * 2.0;
+    let tips =
+        [Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z].map(|d| center + d * radius);
+    if !tips.iter().all(|&p| shape.contains_local(p)) {
+        return None;
+    }
+    let mut cavity = TriMesh {
+        vertices: tips.to_vec(),
+        triangles: vec![
+            [0, 2, 4],
+            [2, 1, 4],
+            [1, 3, 4],
+            [3, 0, 4],
+            [2, 0, 5],
+            [1, 2, 5],
+            [3, 1, 5],
+            [0, 3, 5],
+        ],
+    };
+    cavity.flip();
+    Some(cavity)
+}
+
+/// The STL file name of module `id`'s part.
+pub(crate) fn part_file_name(id: ModuleId) -> String {
+    format!("module_{id:04}.stl")
+}
+
+/// Encodes a mesh as ASCII STL.
+pub fn write_ascii(mesh: &TriMesh, name: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "solid {name}");
+    for i in 0..mesh.triangles.len() {
+        let n = mesh.face_normal(i);
+        let _ = writeln!(out, "  facet normal {} {} {}", n.x, n.y, n.z);
+        out.push_str("    outer loop\n");
+        for v in mesh.triangle(i) {
+            let _ = writeln!(out, "      vertex {} {} {}", v.x, v.y, v.z);
+        }
+        out.push_str("    endloop\n  endfacet\n");
+    }
+    let _ = writeln!(out, "endsolid {name}");
+    out
+}
+
+/// Encodes a mesh as binary STL; `name` goes into the 80-byte header.
+pub fn write_binary(mesh: &TriMesh, name: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(84 + mesh.triangles.len() * 50);
+    let mut header = [0u8; 80];
+    let len = name.len().min(80);
+    header[..len].copy_from_slice(&name.as_bytes()[..len]);
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&(mesh.triangles.len() as u32).to_le_bytes());
+    for i in 0..mesh.triangles.len() {
+        let n = mesh.face_normal(i);
+        let [a, b, c] = mesh.triangle(i);
+        for v in [n, a, b, c] {
+            for x in v.to_array() {
+                out.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+        out.extend_from_slice(&0u16.to_le_bytes());
+    }
+    out
+}
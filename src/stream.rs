@@ -0,0 +1,44 @@
+//! Streaming exporter output into [`io::Write`] sinks.
+//!
+//! The text exporters format with [`fmt::Write`], so the same code renders into a
+//! `String` for `to_*` and straight into a file or socket for `write_*`, without holding
+//! the whole document in memory.
+
+use std::fmt;
+use std::io;
+
+/// A [`fmt::Write`] forwarding to an [`io::Write`], keeping the first I/O error. Writes
+/// after an error fail immediately, so an exporter that ignores formatting results stops
+/// doing I/O once the sink has failed.
+pub(crate) struct IoFmt<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoFmt<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// Runs `render` against `writer` and flushes it, returning the first I/O error.
+pub(crate) fn stream<W: io::Write>(
+    writer: W,
+    render: impl FnOnce(&mut IoFmt<W>),
+) -> io::Result<()> {
+    let mut out = IoFmt {
+        inner: writer,
+        error: None,
+    };
+    render(&mut out);
+    match out.error {
+        Some(e) => Err(e),
+        None => out.inner.flush(),
+    }
+}
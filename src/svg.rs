@@ -0,0 +1,152 @@
+//! 2D SVG schematics of the rest pose.
+//!
+//! [`RobotBlueprint::to_svg`] projects the robot onto one of the coordinate planes and
+//! draws each module's outline, filled with its material colour, plus a marker on every
+//! joint. The output is a few kilobytes of plain SVG, cheap enough to render thumbnails
+//! for thousands of phenotypes without a 3D renderer.
+
+use crate::blueprint::{JointType, RobotBlueprint};
+use crate::gltf::material_color;
+use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// Subdivisions of round shapes before their outline is projected.
+const SVG_SEGMENTS: u32 = 16;
+
+/// The coordinate plane [`RobotBlueprint::to_svg`] projects onto.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProjectionPlane {
+    /// Front view: X to the right, Y up, seen from +Z.
+    #[default]
+    XY,
+    /// Top view: X to the right, Z down, seen from +Y.
+    XZ,
+    /// Side view: Z to the left, Y up, seen from +X.
+    YZ,
+}
+
+impl ProjectionPlane {
+    /// Screen position (x right, y down) of `p`, and its distance toward the viewer.
+    fn project(self, p: Vec3) -> (Vec2, f32) {
+        match self {
+            Self::XY => (Vec2::new(p.x, -p.y), p.z),
+            Self::XZ => (Vec2::new(p.x, p.z), p.y),
+            Self::YZ => (Vec2::new(-p.z, -p.y), p.x),
+        }
+    }
+}
+
+impl RobotBlueprint {
+    /// Draws the rest pose projected onto `plane` as a square SVG image `size` pixels wide.
+    ///
+    /// Modules are drawn as the outline of their projection, farthest first, and filled
+    /// with the same per-material colours as [`to_glb`](Self::to_glb). Moving joints are
+    /// marked with a hollow circle and fixed joints with a dot. The view is fitted to the
+    /// robot with a small margin.
+    pub fn to_svg(&self, plane: ProjectionPlane, size: u32) -> String {
+        let mut outlines = Vec::new();
+        for (_, module) in self.modules_topological() {
+            let (pos, rot) = module.transform;
+            let points: Vec<Vec2> = module
+                .shape
+                .tessellate(SVG_SEGMENTS)
+                .vertices
+                .iter()
+                .map(|&v| plane.project(pos + rot * v).0)
+                .collect();
+            let depth = plane.project(pos).1;
+            outlines.push((depth, module.material_id, convex_hull(points)));
+        }
+        outlines.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut markers = Vec::new();
+        for joint in &self.joints {
+            if let Some(parent) = self.modules.get(&joint.parent_id) {
+                let (pos, rot) = parent.transform;
+                let at = plane.project(pos + rot * joint.anchor_parent).0;
+                markers.push((at, joint.joint_type == JointType::Fixed));
+            }
+        }
+
+        // A square view around everything drawn, with a 5% margin.
+        let all = outlines.iter().flat_map(|o| o.2.iter().copied());
+        let (min, max) = all.fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        let (center, extent) = if min.x <= max.x {
+            ((min + max) / 2.0, (max - min).max_element().max(1e-3) * 1.1)
+        } else {
+            (Vec2::ZERO, 1.0)
+        };
+        let corner = center - Vec2::splat(extent / 2.0);
+        let stroke = extent / 200.0;
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"{:.4} {:.4} {extent:.4} {extent:.4}\">",
+            corner.x, corner.y
+        );
+        for (_, material, hull) in &outlines {
+            let points: Vec<String> = hull
+                .iter()
+                .map(|p| format!("{:.4},{:.4}", p.x, p.y))
+                .collect();
+            let [r, g, b, _] = material_color(*material).map(|c| (c * 255.0).round() as u8);
+            let _ = writeln!(
+                out,
+                "  <polygon points=\"{}\" fill=\"#{r:02x}{g:02x}{b:02x}\" stroke=\"#333333\" stroke-width=\"{stroke:.4}\"/>",
+                points.join(" ")
+            );
+        }
+        for (at, fixed) in markers {
+            let _ = if fixed {
+                writeln!(
+                    out,
+                    "  <circle cx=\"{:.4}\" cy=\"{:.4}\" r=\"{:.4}\" fill=\"#333333\"/>",
+                    at.x,
+                    at.y,
+                    stroke * 2.0
+                )
+            } else {
+                writeln!(
+                    out,
+                    "  <circle cx=\"{:.4}\" cy=\"{:.4}\" r=\"{:.4}\" fill=\"white\" stroke=\"#000000\" stroke-width=\"{stroke:.4}\"/>",
+                    at.x,
+                    at.y,
+                    stroke * 4.0
+                )
+            };
+        }
+        let _ = writeln!(out, "</svg>");
+        out
+    }
+}
+
+/// Convex hull of `points` in counter-clockwise order (Andrew's monotone chain).
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup_by(|a, b| a.distance_squared(*b) < 1e-12);
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    let reversed: Vec<Vec2> = points.iter().rev().copied().collect();
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in [&points[..], &reversed[..]] {
+        let start = hull.len();
+        for &p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each chain starts the other one.
+        hull.pop();
+    }
+    hull
+}
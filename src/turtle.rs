@@ -1,8 +1,12 @@
 //! Turtle state and operations for robotic interpretation.
 
-use crate::blueprint::{JointLimit, JointType, MaterialId, ModuleId, SensorType};
+use crate::blueprint::{
+    Actuator, AnisotropicFriction, JointLimit, JointType, MaterialId, ModuleId, SensorTiming,
+    SensorType, Transmission,
+};
 use glam::{Quat, Vec3};
 use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
 
 /// Configuration for the next joint to be created.
 ///
@@ -13,12 +17,31 @@ pub struct ActiveJointConfig {
     /// The mechanical type of the connection (Hinge, Fixed, etc.).
     pub joint_type: JointType,
 
-    /// The axis of rotation/translation relative to the parent's orientation.
-    /// Defaults to X-axis (Pitch).
+    /// The axis of rotation/translation in the turtle's local frame, always unit length.
+    /// Defaults to X-axis (Pitch); set with [`RobotOp::SetJointAxis`].
     pub axis: Vec3,
 
+    /// Rest displacement of the next prismatic joint, in metres along `axis`.
+    #[serde(default)]
+    pub rest_displacement: f32,
+
     /// Physical limits (angle, velocity, effort).
     pub limits: Option<JointLimit>,
+
+    /// Gearing between actuator and joint.
+    pub transmission: Option<Transmission>,
+
+    /// Actuator attached to the next non-fixed joint.
+    pub actuator: Option<Actuator>,
+
+    /// The effort of `limits` was not given explicitly and may be sized to the joint's load
+    /// (see [`RobotConfig::inertia_scaled_limits`](crate::RobotConfig::inertia_scaled_limits)).
+    #[serde(default)]
+    pub derive_effort: bool,
+
+    /// Like [`derive_effort`](Self::derive_effort), for the velocity of `limits`.
+    #[serde(default)]
+    pub derive_velocity: bool,
 }
 
 impl Default for ActiveJointConfig {
@@ -26,7 +49,12 @@ impl Default for ActiveJointConfig {
         Self {
             joint_type: JointType::Fixed, // Default to rigid welding
             axis: Vec3::X,
+            rest_displacement: 0.0,
             limits: None,
+            transmission: None,
+            actuator: None,
+            derive_effort: false,
+            derive_velocity: false,
         }
     }
 }
@@ -52,8 +80,85 @@ pub struct RobotTurtleState {
     /// Current material ID for new modules.
     pub material_id: MaterialId,
 
+    /// Material forced onto every new module until the enclosing branch is popped, set by
+    /// [`RobotOp::SetMaterialTheme`]. Takes precedence over [`material_id`](Self::material_id).
+    #[serde(default)]
+    pub material_theme: Option<MaterialId>,
+
+    /// Density override for new modules, taking precedence over the material palette.
+    #[serde(default)]
+    pub density: Option<f32>,
+
+    /// Anisotropic friction given to new modules, with its direction in the turtle's local
+    /// frame; set by [`RobotOp::SetAnisotropicFriction`].
+    #[serde(default)]
+    pub anisotropic_friction: Option<AnisotropicFriction>,
+
     /// Current default width/radius for shapes (can be modified by `!`).
     pub width: f32,
+
+    /// Timing given to subsequently mounted sensors.
+    #[serde(default)]
+    pub sensor_timing: SensorTiming,
+
+    /// Pending radial distribution of branches, set by [`RobotOp::RadialBranch`].
+    #[serde(default)]
+    pub radial_branch: Option<RadialBranch>,
+
+    /// Hash of the branch path from the start of the genotype to the turtle: which branch
+    /// was opened at each push.
+    #[serde(default)]
+    pub branch_path: u64,
+
+    /// Branches opened so far from the current stack depth.
+    #[serde(default)]
+    pub branch_count: u32,
+
+    /// Growth stage stamped on new modules, set by [`RobotOp::SetGrowthStage`].
+    #[serde(default)]
+    pub growth_stage: u32,
+
+    /// State of the random number generator used by stochastic ops such as
+    /// [`RobotOp::Jitter`]; set with [`RobotOp::DeriveSeed`].
+    #[serde(default)]
+    pub seed: u64,
+
+    /// Rotations applied since [`rotation`](Self::rotation) was last renormalized.
+    #[serde(default)]
+    pub rotations_since_normalize: u32,
+
+    /// Rotations after which [`rotation`](Self::rotation) is renormalized, set from
+    /// [`RobotConfig::renormalize_interval`](crate::RobotConfig::renormalize_interval).
+    /// `0` never renormalizes.
+    #[serde(default)]
+    pub renormalize_interval: u32,
+}
+
+/// The configuration ("pen style") portion of the turtle, saved and restored by
+/// [`RobotOp::BeginScope`] / [`RobotOp::EndScope`] without touching its position.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TurtleStyle {
+    /// Configuration for the next joint creation.
+    pub joint_config: ActiveJointConfig,
+    /// Material ID for new modules.
+    pub material_id: MaterialId,
+    /// Density override for new modules.
+    pub density: Option<f32>,
+    /// Anisotropic friction of new modules, in the turtle's local frame.
+    pub anisotropic_friction: Option<AnisotropicFriction>,
+    /// Default width/radius for shapes.
+    pub width: f32,
+}
+
+/// Evenly spaced branches around the turtle's up axis (see [`RobotOp::RadialBranch`]).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RadialBranch {
+    /// Branches that still have to be closed before the distribution ends.
+    pub remaining: u32,
+    /// Roll applied after each branch, in radians.
+    pub step: f32,
+    /// Stack depth the branches are pushed from.
+    pub depth: usize,
 }
 
 impl Default for RobotTurtleState {
@@ -64,12 +169,54 @@ impl Default for RobotTurtleState {
             current_module_id: None,
             joint_config: ActiveJointConfig::default(),
             material_id: 0,
+            material_theme: None,
+            density: None,
+            anisotropic_friction: None,
             width: 0.1,
+            sensor_timing: SensorTiming::default(),
+            radial_branch: None,
+            branch_path: 0,
+            branch_count: 0,
+            growth_stage: 0,
+            seed: 0,
+            rotations_since_normalize: 0,
+            renormalize_interval: 0,
         }
     }
 }
 
 impl RobotTurtleState {
+    /// Draws the next pseudo-random number in `[0, 1)`, advancing [`seed`](Self::seed).
+    pub fn next_random(&mut self) -> f32 {
+        (splitmix64(&mut self.seed) >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// The material new modules get: the theme if one is active, else
+    /// [`material_id`](Self::material_id).
+    pub fn material(&self) -> MaterialId {
+        self.material_theme.unwrap_or(self.material_id)
+    }
+
+    /// A copy of the turtle's configuration, without its spatial state.
+    pub fn style(&self) -> TurtleStyle {
+        TurtleStyle {
+            joint_config: self.joint_config.clone(),
+            material_id: self.material_id,
+            density: self.density,
+            anisotropic_friction: self.anisotropic_friction,
+            width: self.width,
+        }
+    }
+
+    /// Replaces the turtle's configuration with `style`, keeping its spatial state.
+    pub fn set_style(&mut self, style: TurtleStyle) {
+        self.joint_config = style.joint_config;
+        self.material_id = style.material_id;
+        self.density = style.density;
+        self.anisotropic_friction = style.anisotropic_friction;
+        self.width = style.width;
+    }
+
     /// Returns the turtle's local up direction (Y-axis) in world space.
     pub fn up(&self) -> Vec3 {
         self.rotation * Vec3::Y
@@ -87,25 +234,46 @@ impl RobotTurtleState {
 
     /// Rotates the turtle around its local X axis by `angle` radians (Pitch).
     pub fn rotate_local_x(&mut self, angle: f32) {
-        let rot = Quat::from_axis_angle(Vec3::X, angle);
-        self.rotation *= rot;
+        self.rotate_local(Quat::from_axis_angle(Vec3::X, angle));
     }
 
     /// Rotates the turtle around its local Y axis by `angle` radians (Roll).
     pub fn rotate_local_y(&mut self, angle: f32) {
-        let rot = Quat::from_axis_angle(Vec3::Y, angle);
-        self.rotation *= rot;
+        self.rotate_local(Quat::from_axis_angle(Vec3::Y, angle));
     }
 
     /// Rotates the turtle around its local Z axis by `angle` radians (Yaw).
     pub fn rotate_local_z(&mut self, angle: f32) {
-        let rot = Quat::from_axis_angle(Vec3::Z, angle);
-        self.rotation *= rot;
+        self.rotate_local(Quat::from_axis_angle(Vec3::Z, angle));
+    }
+
+    /// Applies `rot` in the turtle's local frame.
+    pub fn rotate_local(&mut self, rot: Quat) {
+        self.set_rotation(self.rotation * rot);
+    }
+
+    /// Sets the turtle's orientation. Every change of orientation goes through here, so it
+    /// counts toward [`renormalize_interval`](Self::renormalize_interval).
+    pub fn set_rotation(&mut self, rotation: Quat) {
+        self.rotation = rotation;
+        self.rotations_since_normalize += 1;
+        if self.renormalize_interval > 0
+            && self.rotations_since_normalize >= self.renormalize_interval
+        {
+            self.renormalize();
+        }
+    }
+
+    /// Rescales [`rotation`](Self::rotation) to unit length, undoing the rounding drift of
+    /// repeated rotations.
+    pub fn renormalize(&mut self) {
+        self.rotation = self.rotation.normalize();
+        self.rotations_since_normalize = 0;
     }
 }
 
 /// Operations that can be performed by the robot turtle.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum RobotOp {
     // --- Spatial Navigation ---
     /// Move forward without spawning geometry (`f`).
@@ -118,6 +286,18 @@ pub enum RobotOp {
     Roll(f32),
     /// Turn 180 degrees (`|`).
     TurnAround,
+    /// Distribute the next branches evenly around the turtle's up axis. Params: `(n)`.
+    /// Each of the next `n` pops back to the current stack depth rolls the turtle by
+    /// `2π / n`, so `R(4) [B] [B] [B] [B]` grows four limbs a quarter turn apart.
+    /// `n < 2` cancels a pending distribution.
+    RadialBranch,
+    /// Turn by a random yaw and pitch, each uniform in `±angle` (`~`). Params: `(angle_deg)`,
+    /// default the configured angle. Draws from the turtle's [`seed`](RobotTurtleState::seed).
+    Jitter,
+    /// Derive the turtle's random seed from the global
+    /// [`RobotConfig::seed`](crate::RobotConfig::seed) and the current branch path (`Rs`).
+    /// Params: `(salt)`. Every limb grown by the same rule gets its own, reproducible stream.
+    DeriveSeed,
 
     // --- Geometry Spawning (The Body) ---
     /// Spawn a Box shape. Params: `(length, width, height)`.
@@ -131,24 +311,104 @@ pub enum RobotOp {
     SpawnCapsule,
 
     // --- Configuration (The Physics) ---
-    /// Set the type of the NEXT joint to be created.
+    /// Set the type of the NEXT joint to be created. Prismatic joints take `(rest)`, the
+    /// displacement along the joint axis at which the child spawns (clamped to the limits).
     SetJointType(JointType),
-    /// Set joint limits. Params: `(min, max, effort, velocity)`.
+    /// Set the axis of the NEXT joints, in the turtle's local frame. Params: `(x, y, z)`
+    /// replace the preset axis component-wise. A zero-length axis is ignored.
+    SetJointAxis(Vec3),
+    /// Set joint limits. Params: `(min, max, effort, velocity)`, or
+    /// `(swing1, swing2, twist, effort, velocity)` while the active joint type is Ball.
     SetJointLimits,
-    /// Set the Material ID for visual rendering.
+    /// Set the transmission of the NEXT joint. Params: `(gear_ratio, efficiency, backlash)`.
+    /// A gear ratio of `0` clears the transmission.
+    SetTransmission,
+    /// Drive the NEXT joints with a catalog servo. Params: `(preset_index)`.
+    /// Sets effort/velocity limits from the servo and attaches its mass; an index outside
+    /// [`crate::servo::SERVO_PRESETS`] removes the actuator.
+    SetServo,
+    /// Attach a custom actuator to the NEXT joints. Params: `(mass, width, height, depth)`.
+    /// The mass is added to the parent module at the joint; the optional dimensions describe
+    /// a box housing. A mass of `0` removes the actuator.
+    SetActuator,
+    /// Set imperfections of the current actuator. Params: `(latency_ms, backlash)`.
+    /// A value of `0` clears the corresponding field; no-op without an actuator.
+    SetActuatorImperfection,
+    /// Set the Material ID for visual rendering and, with a
+    /// [`MaterialPalette`](crate::MaterialPalette), the density of new modules.
     SetMaterial,
+    /// Give every module spawned until the matching pop the same material, whatever
+    /// [`SetMaterial`](Self::SetMaterial) ops the branch contains. Params: `(material_id)`;
+    /// a negative ID (or no parameters) ends the theme early. `[ M(4) ... ]` recolours a
+    /// whole limb with one symbol.
+    SetMaterialTheme,
+    /// Override the density of new modules. Params: `(density)` in kg/m³.
+    /// A density of `0` (or no parameters) removes the override.
+    SetDensity,
+    /// Give new modules anisotropic friction. Params: `(ratio, x, y, z)`: friction along
+    /// the direction `(x, y, z)` in the turtle's local frame (default: the growth axis) is
+    /// `ratio` times the friction across it. A ratio of `0` (or no parameters) restores
+    /// isotropic friction.
+    SetAnisotropicFriction,
+    /// Stamp new modules with a derivation step (`G`). Params: `(step)`. Rules that emit
+    /// `G(n)` with their iteration count, e.g. `X(n) -> G(n) B [X(n + 1)]`, record when each
+    /// part of the body grew.
+    SetGrowthStage,
     /// Set the default width/radius for subsequent shapes.
     SetWidth,
 
     // --- Attachments (The Senses) ---
     /// Mount a sensor at the current location.
     MountSensor(SensorType),
+    /// Set the timing and noise of subsequently mounted sensors. Params:
+    /// `(rate_hz, latency_ms, noise)`. A rate of `0` (or no parameters) samples on every
+    /// physics step; `noise` is the standard deviation added to each value.
+    SetSensorTiming,
+    /// Mount an IMU at a center of mass. Params: `(scope)`.
+    /// Scope `0` (default) uses the current module's center of mass; any other value uses
+    /// the center of mass of the robot built so far, so placing the op at the end of a
+    /// genotype targets the final robot.
+    MountImuAtCom,
+    /// Mount a sensor on the joint connecting the current module to its parent.
+    /// Ignored on the root module.
+    MountJointSensor(SensorType),
+    /// Place a motion-capture [`Marker`](crate::Marker) on the current module at the
+    /// turtle's position, labelled with the given name. Params: `(index)`. When given, the
+    /// index is appended to the name, so one symbol can label a series: `Mk(3)` places
+    /// `marker3`.
+    PlaceMarker(SmolStr),
+
+    /// Weld the current module to the world at its rest pose, at the turtle's position.
+    AnchorToWorld,
 
     // --- Flow Control ---
     /// Save the full turtle state onto the stack (`[`).
     Push,
     /// Restore the most recently pushed turtle state (`]`).
     Pop,
+    /// Save only the turtle's configuration (joint config, material, width and density)
+    /// onto the scope stack (`{`). Scopes nest independently of `[`/`]`, like style scopes
+    /// in CSS: `{ '(3) J B B }` styles two segments without moving the turtle back.
+    BeginScope,
+    /// Restore the configuration saved by the matching [`BeginScope`](Self::BeginScope)
+    /// (`}`), leaving position, orientation and the current module as they are.
+    EndScope,
+
+    // --- Extensions ---
+    /// Run the [`CustomOp`](crate::CustomOp) registered under this name with
+    /// [`RobotInterpreter::register_custom_op`](crate::RobotInterpreter::register_custom_op).
+    /// Ignored if no op of that name is registered.
+    Custom(SmolStr),
+
     /// No-op — symbol has no registered meaning.
     Ignore,
 }
+
+/// SplitMix64: advances `state` and returns the next output.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
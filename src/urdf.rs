@@ -0,0 +1,532 @@
+//! Plain URDF export for ROS.
+//!
+//! [`RobotBlueprint::to_urdf`] writes a link per module and a joint per blueprint joint,
+//! with every dimension as a number: the form `robot_state_publisher` and most URDF
+//! parsers expect. [`to_xacro`](RobotBlueprint::to_xacro) writes the same document with
+//! the dimensions as xacro properties.
+//!
+//! URDF joints sit at the origin of their child link, so each link's frame is placed at its
+//! module's pivot with the module's orientation. Root modules hang from a `base_link`
+//! through fixed joints at their rest transforms, turned a quarter turn about X so that
+//! the Y-up blueprint stands upright in ROS's Z-up world. The `*_with` variants take
+//! [`UrdfOptions`] to swap primitives for meshes, drop visuals, keep the Y-up frame or
+//! prefix every name.
+
+use crate::backends::{JOINT_FRAME_AXIS, joint_bases};
+use crate::blueprint::{
+    JointDefinition, JointLimit, JointType, ModuleId, RobotBlueprint, RobotModule, ShapePrimitive,
+    Transmission,
+};
+use crate::export::{ExportGeometry, UpAxis};
+use crate::sdf::escape;
+use crate::stream::stream;
+use crate::xacro::{Defaults, dimensions, local_point, xyz};
+use bevy_heavy::ComputeMassProperties3d as _;
+use glam::{EulerRot, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+use std::fmt::Write;
+use std::io;
+
+/// Options for [`RobotBlueprint::to_urdf_with`] and [`RobotBlueprint::to_xacro_with`].
+/// URDF cannot describe sensors, so there is nothing to leave out there.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrdfOptions {
+    /// Primitive shapes or per-module meshes. Mesh geometry is written as numbers even in
+    /// xacro, so it does not follow the dimension properties.
+    pub geometry: ExportGeometry,
+    /// Write only `<collision>` elements, leaving visuals to the meshes of another tool.
+    pub collision_only: bool,
+    /// Which way is up in the target world. Only the fixed joints holding the roots change.
+    pub up_axis: UpAxis,
+    /// Prepended to every link and joint name, `base_link` included, so that several
+    /// robots can share one TF tree.
+    pub prefix: String,
+}
+
+impl RobotBlueprint {
+    /// Renders the robot as a plain URDF document describing one robot called `name`.
+    ///
+    /// Module `N` becomes link `module_N`, and the joint to its parent `P` becomes
+    /// `joint_P_C`; roots hang from `base_link` by fixed joints `root_N`.
+    ///
+    /// Every movable joint with a [`transmission`](crate::JointDefinition::transmission)
+    /// gets a `SimpleTransmission` for `ros_control`, its `mechanicalReduction` the gear
+    /// ratio.
+    ///
+    /// URDF has no ball joints, world welds or capsules. A ball joint is written as three
+    /// revolute joints, `joint_P_C_swing1`, `joint_P_C_swing2` and `joint_P_C_twist`, joined
+    /// by massless links and limited by the swing/twist [`cone`](crate::JointLimit::cone)
+    /// (continuous without one). Welds are left out, and capsules are drawn as a cylinder
+    /// between two spheres.
+    pub fn to_urdf(&self, name: &str) -> String {
+        self.to_urdf_with(name, &UrdfOptions::default())
+    }
+
+    /// [`to_urdf`](Self::to_urdf) with the geometry, elements, up axis and names chosen by
+    /// `options`.
+    pub fn to_urdf_with(&self, name: &str, options: &UrdfOptions) -> String {
+        let mut out = String::new();
+        self.render_urdf(&mut out, name, None, options);
+        out
+    }
+
+    /// Streams [`to_urdf`](Self::to_urdf) into `writer`, e.g. a `BufWriter<File>`.
+    pub fn write_urdf(&self, writer: impl io::Write, name: &str) -> io::Result<()> {
+        self.write_urdf_with(writer, name, &UrdfOptions::default())
+    }
+
+    /// Streams [`to_urdf_with`](Self::to_urdf_with) into `writer`.
+    pub fn write_urdf_with(
+        &self,
+        writer: impl io::Write,
+        name: &str,
+        options: &UrdfOptions,
+    ) -> io::Result<()> {
+        stream(writer, |out| self.render_urdf(out, name, None, options))
+    }
+
+    /// Writes the document shared by [`to_xacro`](Self::to_xacro), which expresses
+    /// dimensions as multiples of `defaults`, and [`to_urdf`](Self::to_urdf), which has no
+    /// defaults and writes numbers.
+    pub(crate) fn render_urdf(
+        &self,
+        out: &mut impl Write,
+        name: &str,
+        defaults: Option<&Defaults>,
+        options: &UrdfOptions,
+    ) {
+        let xacro = defaults.is_some();
+        let p = escape(&options.prefix);
+        let modules: Vec<(ModuleId, &RobotModule)> = self.modules_topological().collect();
+
+        let _ = writeln!(out, "<?xml version=\"1.0\"?>");
+        let _ = writeln!(out, "<!-- Generated by symbios-robot -->");
+        match defaults {
+            Some(defaults) => {
+                let _ = writeln!(
+                    out,
+                    "<robot name=\"{}\" xmlns:xacro=\"http://www.ros.org/wiki/xacro\">",
+                    escape(name)
+                );
+                defaults.write_properties(out, &modules);
+            }
+            None => {
+                let _ = writeln!(out, "<robot name=\"{}\">", escape(name));
+            }
+        }
+
+        let _ = writeln!(out, "  <link name=\"{p}base_link\"/>");
+        for &(id, module) in &modules {
+            let frame = self.link_anchor(id);
+            write_link(out, id, module, frame, xacro, options);
+        }
+
+        for &(id, module) in &modules {
+            let joint = self
+                .parent_joint(id)
+                .filter(|j| self.modules.contains_key(&j.parent_id));
+            let Some(joint) = joint else {
+                let up = options.up_axis.rotation();
+                let (pos, rot) = (up * module.transform.0, up * module.transform.1);
+                let _ = writeln!(out, "  <joint name=\"{p}root_{id}\" type=\"fixed\">");
+                let _ = writeln!(
+                    out,
+                    "    <origin xyz=\"{:.6} {:.6} {:.6}\" rpy=\"{}\"/>",
+                    pos.x,
+                    pos.y,
+                    pos.z,
+                    rpy(rot)
+                );
+                let _ = writeln!(out, "    <parent link=\"{p}base_link\"/>");
+                let _ = writeln!(out, "    <child link=\"{p}module_{id}\"/>");
+                let _ = writeln!(out, "  </joint>");
+                continue;
+            };
+            let parent_id = joint.parent_id;
+            let parent = &self.modules[&parent_id];
+            let offset = joint.anchor_parent - self.link_anchor(parent_id);
+            let origin = format!(
+                "<origin xyz=\"{}\" rpy=\"{}\"/>",
+                point_xyz(parent_id, &parent.shape, offset, xacro),
+                rpy(parent.transform.1.inverse() * module.transform.1)
+            );
+            if joint.joint_type == JointType::Ball {
+                let _ = writeln!(out, "  <!-- ball joint: three revolute joints -->");
+                for (i, stage) in ball_stages(self, joint).iter().enumerate() {
+                    if i > 0 {
+                        let _ = writeln!(out, "  <link name=\"{p}{}\"/>", stage.parent_link);
+                    }
+                    let origin = if i == 0 {
+                        origin.as_str()
+                    } else {
+                        "<origin xyz=\"0 0 0\" rpy=\"0 0 0\"/>"
+                    };
+                    write_ball_stage(out, &p, stage, origin, joint.limits);
+                }
+                continue;
+            }
+            let kind = urdf_joint_type(joint);
+            let _ = writeln!(
+                out,
+                "  <joint name=\"{p}joint_{parent_id}_{id}\" type=\"{kind}\">"
+            );
+            let _ = writeln!(out, "    {origin}");
+            let _ = writeln!(out, "    <parent link=\"{p}module_{parent_id}\"/>");
+            let _ = writeln!(out, "    <child link=\"{p}module_{id}\"/>");
+            if matches!(joint.joint_type, JointType::Hinge | JointType::Prismatic) {
+                let axis = joint.child_axis;
+                let _ = writeln!(
+                    out,
+                    "    <axis xyz=\"{:.6} {:.6} {:.6}\"/>",
+                    axis.x, axis.y, axis.z
+                );
+            }
+            let offset = match joint.joint_type {
+                JointType::Prismatic => joint.rest_displacement,
+                _ => 0.0,
+            };
+            match (joint.joint_type, joint.limits) {
+                (JointType::Hinge | JointType::Prismatic, Some(limits)) => {
+                    let _ = writeln!(
+                        out,
+                        "    <limit lower=\"{:.6}\" upper=\"{:.6}\" effort=\"{:.6}\" velocity=\"{:.6}\"/>",
+                        limits.min - offset,
+                        limits.max - offset,
+                        limits.effort,
+                        limits.velocity
+                    );
+                }
+                // URDF requires limits on prismatic joints; an unlimited one cannot move.
+                (JointType::Prismatic, None) => {
+                    let _ = writeln!(
+                        out,
+                        "    <limit lower=\"0\" upper=\"0\" effort=\"0\" velocity=\"0\"/>"
+                    );
+                }
+                _ => {}
+            }
+            let _ = writeln!(out, "  </joint>");
+        }
+
+        for &(id, _) in &modules {
+            let Some(joint) = self
+                .parent_joint(id)
+                .filter(|j| self.modules.contains_key(&j.parent_id))
+            else {
+                continue;
+            };
+            let Some(transmission) = joint.transmission else {
+                continue;
+            };
+            match joint.joint_type {
+                JointType::Ball => {
+                    for stage in ball_stages(self, joint) {
+                        write_transmission(out, &format!("{p}{}", stage.name), &transmission);
+                    }
+                }
+                JointType::Hinge | JointType::Prismatic => {
+                    let name = format!("{p}joint_{}_{id}", joint.parent_id);
+                    write_transmission(out, &name, &transmission);
+                }
+                JointType::Fixed => {}
+            }
+        }
+
+        let _ = writeln!(out, "</robot>");
+    }
+
+    /// Origin of module `id`'s link in its local frame: its pivot (the negated
+    /// [`pivot_offset`](crate::RobotModule::pivot_offset)), or the module center for roots.
+    fn link_anchor(&self, id: ModuleId) -> Vec3 {
+        match self.parent_joint(id) {
+            Some(j) if self.modules.contains_key(&j.parent_id) => self
+                .modules
+                .get(&id)
+                .map_or(j.anchor_child, |m| -m.pivot_offset),
+            _ => Vec3::ZERO,
+        }
+    }
+}
+
+/// The URDF `type` of `joint`. URDF has no ball joints, which have no single type: they
+/// are written as the revolute joints of [`ball_stages`].
+pub(crate) fn urdf_joint_type(joint: &JointDefinition) -> &'static str {
+    match joint.joint_type {
+        JointType::Fixed | JointType::Ball => "fixed",
+        JointType::Hinge if joint.limits.is_some() => "revolute",
+        JointType::Hinge => "continuous",
+        JointType::Prismatic => "prismatic",
+    }
+}
+
+/// An `xyz` attribute for a point in module `id`'s local frame: scaling with the module's
+/// extents for xacro, as numbers otherwise.
+fn point_xyz(id: ModuleId, shape: &ShapePrimitive, point: Vec3, xacro: bool) -> String {
+    if xacro {
+        xyz(&local_point(id, shape, point))
+    } else {
+        format!("{:.6} {:.6} {:.6}", point.x, point.y, point.z)
+    }
+}
+
+/// One of the three revolute joints a ball joint is written as in URDF, with unprefixed
+/// names.
+pub(crate) struct BallStage {
+    /// `joint_P_C_swing1`, `joint_P_C_swing2` or `joint_P_C_twist`.
+    pub(crate) name: String,
+    /// The parent module's link for the first stage, a massless link in between otherwise.
+    pub(crate) parent_link: String,
+    /// The child module's link for the last stage, a massless link in between otherwise.
+    pub(crate) child_link: String,
+    /// The axis in the child module's frame, which all three joint frames share at rest.
+    pub(crate) axis: Vec3,
+    /// Lower and upper limit, if the joint has a swing/twist cone.
+    pub(crate) limits: Option<(f32, f32)>,
+}
+
+/// The swing1, swing2 and twist stages of ball joint `joint`, from parent to child. The
+/// swings turn about the perpendicular axes of the physics backends' joint frame and the
+/// twist about the joint axis, each limited by its half-angle in the
+/// [`cone`](crate::JointLimit::cone); without one they are unlimited.
+pub(crate) fn ball_stages(blueprint: &RobotBlueprint, joint: &JointDefinition) -> [BallStage; 3] {
+    let (parent_id, id) = (joint.parent_id, joint.child_id);
+    let basis = joint_bases(blueprint, joint).map_or(Quat::IDENTITY, |(_, child)| child);
+    let cone = joint.limits.and_then(|l| l.cone);
+    let link = |suffix: &str| format!("module_{id}_{suffix}");
+    let stage = |suffix: &str, links: (String, String), axis: Vec3, half: Option<f32>| BallStage {
+        name: format!("joint_{parent_id}_{id}_{suffix}"),
+        parent_link: links.0,
+        child_link: links.1,
+        axis: basis * axis,
+        limits: half.map(|h| (-h, h)),
+    };
+    // The joint frame twists about X, its `JOINT_FRAME_AXIS`; Y and Z are the swing axes.
+    let (twist, swing1, swing2) = (JOINT_FRAME_AXIS, Vec3::Y, Vec3::Z);
+    [
+        stage(
+            "swing1",
+            (format!("module_{parent_id}"), link("swing1")),
+            swing1,
+            cone.map(|c| c.swing1),
+        ),
+        stage(
+            "swing2",
+            (link("swing1"), link("swing2")),
+            swing2,
+            cone.map(|c| c.swing2),
+        ),
+        stage(
+            "twist",
+            (link("swing2"), format!("module_{id}")),
+            twist,
+            cone.map(|c| c.twist),
+        ),
+    ]
+}
+
+/// Writes `stage` of a ball joint whose limits are `limits`, placed by `origin`.
+fn write_ball_stage(
+    out: &mut impl Write,
+    p: &str,
+    stage: &BallStage,
+    origin: &str,
+    limits: Option<JointLimit>,
+) {
+    let kind = if stage.limits.is_some() {
+        "revolute"
+    } else {
+        "continuous"
+    };
+    let _ = writeln!(out, "  <joint name=\"{p}{}\" type=\"{kind}\">", stage.name);
+    let _ = writeln!(out, "    {origin}");
+    let _ = writeln!(out, "    <parent link=\"{p}{}\"/>", stage.parent_link);
+    let _ = writeln!(out, "    <child link=\"{p}{}\"/>", stage.child_link);
+    let axis = stage.axis;
+    let _ = writeln!(
+        out,
+        "    <axis xyz=\"{:.6} {:.6} {:.6}\"/>",
+        axis.x, axis.y, axis.z
+    );
+    if let (Some((lower, upper)), Some(limits)) = (stage.limits, limits) {
+        let _ = writeln!(
+            out,
+            "    <limit lower=\"{lower:.6}\" upper=\"{upper:.6}\" effort=\"{:.6}\" velocity=\"{:.6}\"/>",
+            limits.effort, limits.velocity
+        );
+    }
+    let _ = writeln!(out, "  </joint>");
+}
+
+/// Writes a `SimpleTransmission` driving the joint called `joint` through
+/// `transmission`'s reduction, for `ros_control`'s effort interface.
+fn write_transmission(out: &mut impl Write, joint: &str, transmission: &Transmission) {
+    let _ = writeln!(out, "  <transmission name=\"{joint}_transmission\">");
+    let _ = writeln!(
+        out,
+        "    <type>transmission_interface/SimpleTransmission</type>"
+    );
+    let _ = writeln!(out, "    <joint name=\"{joint}\">");
+    let _ = writeln!(
+        out,
+        "      <hardwareInterface>hardware_interface/EffortJointInterface</hardwareInterface>"
+    );
+    let _ = writeln!(out, "    </joint>");
+    let _ = writeln!(out, "    <actuator name=\"{joint}_actuator\">");
+    let _ = writeln!(
+        out,
+        "      <mechanicalReduction>{:.6}</mechanicalReduction>",
+        transmission.gear_ratio
+    );
+    let _ = writeln!(out, "    </actuator>");
+    let _ = writeln!(out, "  </transmission>");
+}
+
+/// Writes the link of module `id`, whose frame sits at `frame` in the module's local space.
+fn write_link(
+    out: &mut impl Write,
+    id: ModuleId,
+    module: &RobotModule,
+    frame: Vec3,
+    xacro: bool,
+    options: &UrdfOptions,
+) {
+    let shape = &module.shape;
+    let _ = writeln!(
+        out,
+        "  <link name=\"{}module_{id}\">",
+        escape(&options.prefix)
+    );
+    let _ = writeln!(out, "    <inertial>");
+    let _ = writeln!(
+        out,
+        "      <origin xyz=\"{}\" rpy=\"0 0 0\"/>",
+        point_xyz(id, shape, module.center_of_mass - frame, xacro)
+    );
+    let _ = writeln!(out, "      <mass value=\"{:.6}\"/>", module.mass);
+    let _ = writeln!(out, "      {}", inertia(id, module, xacro));
+    let _ = writeln!(out, "    </inertial>");
+    let elements: &[&str] = if options.collision_only {
+        &["collision"]
+    } else {
+        &["visual", "collision"]
+    };
+    let geometries = match options.geometry.mesh_uri(id) {
+        // Part meshes are already in the module frame.
+        Some(uri) => vec![(
+            0.0,
+            "0 0 0".to_owned(),
+            format!("<mesh filename=\"{}\"/>", escape(&uri)),
+        )],
+        None => geometries(id, shape, xacro),
+    };
+    for element in elements {
+        for &(offset, ref rotation, ref geometry) in &geometries {
+            let origin = if xacro {
+                let mut origin = local_point(id, shape, -frame);
+                if offset != 0.0 {
+                    let sign = if offset > 0.0 { '+' } else { '-' };
+                    origin[1] = format!("{} {sign} module_{id}_length / 2", origin[1]);
+                }
+                xyz(&origin)
+            } else {
+                point_xyz(id, shape, Vec3::Y * offset - frame, false)
+            };
+            let _ = writeln!(out, "    <{element}>");
+            let _ = writeln!(out, "      <origin xyz=\"{origin}\" rpy=\"{rotation}\"/>");
+            let _ = writeln!(out, "      <geometry>{geometry}</geometry>");
+            let _ = writeln!(out, "    </{element}>");
+        }
+    }
+    let _ = writeln!(out, "  </link>");
+}
+
+/// The URDF geometries drawing `shape`: an offset from the module center along Y, an `rpy`
+/// rotation and the geometry element, with dimensions as properties for xacro. URDF
+/// cylinders run along Z.
+fn geometries(id: ModuleId, shape: &ShapePrimitive, xacro: bool) -> Vec<(f32, String, String)> {
+    let dims = dimensions(shape);
+    let dim = |property: &str| {
+        if xacro {
+            format!("${{module_{id}_{property}}}")
+        } else {
+            let value = dims.iter().find(|d| d.0 == property).map_or(0.0, |d| d.1);
+            format!("{value:.6}")
+        }
+    };
+    let upright = format!("{FRAC_PI_2:.6} 0 0");
+    let none = "0 0 0".to_owned();
+    let cylinder = format!(
+        "<cylinder radius=\"{}\" length=\"{}\"/>",
+        dim("radius"),
+        dim("length")
+    );
+    let sphere = format!("<sphere radius=\"{}\"/>", dim("radius"));
+    match *shape {
+        ShapePrimitive::Box(_) => vec![(
+            0.0,
+            none,
+            format!(
+                "<box size=\"{} {} {}\"/>",
+                dim("width"),
+                dim("length"),
+                dim("depth")
+            ),
+        )],
+        ShapePrimitive::Sphere(_) => vec![(0.0, none, sphere)],
+        ShapePrimitive::Cylinder { .. } => vec![(0.0, upright, cylinder)],
+        ShapePrimitive::Capsule { height, .. } => vec![
+            (0.0, upright, cylinder),
+            (height / 2.0, none.clone(), sphere.clone()),
+            (-height / 2.0, none, sphere),
+        ],
+    }
+}
+
+/// The `<inertia>` element of module `id` about its center of mass. For xacro it uses the
+/// module's dimension properties where the shape has a closed form.
+fn inertia(id: ModuleId, module: &RobotModule, xacro: bool) -> String {
+    let m = module.mass;
+    let (ixx, iyy, izz) = match module.shape {
+        ShapePrimitive::Box(_) if xacro => {
+            let (x, y, z) = (
+                format!("module_{id}_width"),
+                format!("module_{id}_length"),
+                format!("module_{id}_depth"),
+            );
+            (
+                format!("${{{m:.6} / 12 * ({y}**2 + {z}**2)}}"),
+                format!("${{{m:.6} / 12 * ({x}**2 + {z}**2)}}"),
+                format!("${{{m:.6} / 12 * ({x}**2 + {y}**2)}}"),
+            )
+        }
+        ShapePrimitive::Sphere(_) if xacro => {
+            let i = format!("${{0.4 * {m:.6} * module_{id}_radius**2}}");
+            (i.clone(), i.clone(), i)
+        }
+        ShapePrimitive::Cylinder { .. } if xacro => {
+            let (r, h) = (format!("module_{id}_radius"), format!("module_{id}_length"));
+            let side = format!("${{{m:.6} / 12 * (3 * {r}**2 + {h}**2)}}");
+            (side.clone(), format!("${{{m:.6} / 2 * {r}**2}}"), side)
+        }
+        _ => {
+            let i = module
+                .shape
+                .to_bevy_primitive()
+                .unit_principal_angular_inertia()
+                * m;
+            (
+                format!("{:.6}", i.x),
+                format!("{:.6}", i.y),
+                format!("{:.6}", i.z),
+            )
+        }
+    };
+    format!("<inertia ixx=\"{ixx}\" ixy=\"0\" ixz=\"0\" iyy=\"{iyy}\" iyz=\"0\" izz=\"{izz}\"/>")
+}
+
+/// A URDF `rpy` attribute: roll, pitch and yaw about the fixed X, Y and Z axes.
+fn rpy(rotation: Quat) -> String {
+    let (yaw, pitch, roll) = rotation.normalize().to_euler(EulerRot::ZYX);
+    format!("{roll:.6} {pitch:.6} {yaw:.6}")
+}
@@ -0,0 +1,372 @@
+//! USD stage export for Omniverse and other USD-based renderers (feature `usd`).
+//!
+//! [`RobotBlueprint::to_usda`] writes a text (`.usda`) stage in which every module is an
+//! `Xform` carrying `UsdPhysics` rigid-body and mass schemas, with its shape as a child
+//! geometry prim, and every joint is a `UsdPhysics` joint between two module prims.
+//! [`RobotBlueprint::to_usdz`] packages that stage as a `.usdz` archive.
+//!
+//! Modules are written flat under the robot prim at their world-space rest pose; the
+//! kinematic tree lives entirely in the joints. The stage is Y-up like the blueprint.
+
+use crate::blueprint::{
+    JointDefinition, JointType, ModuleId, RobotBlueprint, RobotModule, ShapePrimitive,
+};
+use glam::{Quat, Vec3};
+use std::fmt::Write as _;
+
+impl RobotBlueprint {
+    /// Renders the robot as a USDA stage whose default prim is named after `name`.
+    ///
+    /// Prims are `/<name>/module_N` and `/<name>/joint_P_C`; world welds are fixed joints
+    /// named `world_weld_N` with no `body0`. Joint frames sit on the anchors and are turned
+    /// so that their X axis is the joint axis. Sensors become child `Xform`s tagged with a
+    /// `symbios:sensorType` attribute, since USD has no sensor schema of its own.
+    pub fn to_usda(&self, name: &str) -> String {
+        let root = identifier(name);
+        let mut out = String::new();
+        let _ = writeln!(out, "#usda 1.0");
+        let _ = writeln!(out, "(");
+        let _ = writeln!(out, "    defaultPrim = \"{root}\"");
+        let _ = writeln!(out, "    doc = \"Generated by symbios-robot\"");
+        let _ = writeln!(out, "    metersPerUnit = 1");
+        let _ = writeln!(out, "    upAxis = \"Y\"");
+        let _ = writeln!(out, ")");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "def Xform \"{root}\" (");
+        let _ = writeln!(
+            out,
+            "    prepend apiSchemas = [\"PhysicsArticulationRootAPI\"]"
+        );
+        let _ = writeln!(out, ")");
+        let _ = writeln!(out, "{{");
+
+        for (id, module) in self.modules_topological() {
+            write_module(&mut out, self, id, module);
+        }
+        for joint in self.joints_topological() {
+            write_joint(&mut out, self, &root, joint);
+        }
+        for weld in &self.world_joints {
+            let child = weld.child_id;
+            let _ = writeln!(out, "    def PhysicsFixedJoint \"world_weld_{child}\"");
+            let _ = writeln!(out, "    {{");
+            let _ = writeln!(out, "        rel physics:body1 = </{root}/module_{child}>");
+            let _ = writeln!(
+                out,
+                "        point3f physics:localPos0 = {}",
+                vec3(weld.anchor_world)
+            );
+            let _ = writeln!(
+                out,
+                "        point3f physics:localPos1 = {}",
+                vec3(weld.anchor_child)
+            );
+            if let Some(module) = self.modules.get(&child) {
+                let _ = writeln!(
+                    out,
+                    "        quatf physics:localRot0 = {}",
+                    quat(module.transform.1)
+                );
+            }
+            let _ = writeln!(out, "    }}");
+        }
+        let _ = writeln!(out, "}}");
+        out
+    }
+
+    /// Packages [`to_usda`](Self::to_usda) as a USDZ archive: an uncompressed zip holding
+    /// `<name>.usda`, with its data aligned to 64 bytes as the USDZ format requires.
+    pub fn to_usdz(&self, name: &str) -> Vec<u8> {
+        let file_name = format!("{}.usda", identifier(name));
+        let stage = self.to_usda(name);
+        zip_stored(&file_name, stage.as_bytes())
+    }
+}
+
+fn write_module(out: &mut String, blueprint: &RobotBlueprint, id: ModuleId, module: &RobotModule) {
+    let (pos, rot) = module.transform;
+    let _ = writeln!(out, "    def Xform \"module_{id}\" (");
+    let _ = writeln!(
+        out,
+        "        prepend apiSchemas = [\"PhysicsRigidBodyAPI\", \"PhysicsMassAPI\"]"
+    );
+    let _ = writeln!(out, "    )");
+    let _ = writeln!(out, "    {{");
+    let _ = writeln!(out, "        float physics:mass = {:.6}", module.mass);
+    let _ = writeln!(
+        out,
+        "        point3f physics:centerOfMass = {}",
+        vec3(module.center_of_mass)
+    );
+    let _ = writeln!(
+        out,
+        "        custom int symbios:materialId = {}",
+        module.material_id
+    );
+    let _ = writeln!(out, "        double3 xformOp:translate = {}", vec3(pos));
+    let _ = writeln!(out, "        quatf xformOp:orient = {}", quat(rot));
+    let _ = writeln!(
+        out,
+        "        uniform token[] xformOpOrder = [\"xformOp:translate\", \"xformOp:orient\"]"
+    );
+    write_geometry(out, &module.shape);
+    for (i, sensor) in module.sensors.iter().enumerate() {
+        // Joint-mounted sensors are re-expressed in the module frame.
+        let Some((sensor_pos, sensor_rot)) = blueprint.sensor_pose(id, sensor) else {
+            continue;
+        };
+        let label = identifier(&sensor.sensor_type.name().to_lowercase());
+        let _ = writeln!(out);
+        let _ = writeln!(out, "        def Xform \"{label}_{i}\"");
+        let _ = writeln!(out, "        {{");
+        let _ = writeln!(
+            out,
+            "            custom string symbios:sensorType = \"{}\"",
+            escape(sensor.sensor_type.name())
+        );
+        let _ = writeln!(
+            out,
+            "            custom token symbios:attachment = \"{:?}\"",
+            sensor.attachment
+        );
+        let _ = writeln!(
+            out,
+            "            double3 xformOp:translate = {}",
+            vec3(rot.inverse() * (sensor_pos - pos))
+        );
+        let _ = writeln!(
+            out,
+            "            quatf xformOp:orient = {}",
+            quat(rot.inverse() * sensor_rot)
+        );
+        let _ = writeln!(
+            out,
+            "            uniform token[] xformOpOrder = [\"xformOp:translate\", \"xformOp:orient\"]"
+        );
+        let _ = writeln!(out, "        }}");
+    }
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out);
+}
+
+/// Writes the collision geometry of a module as a child `geom` prim.
+fn write_geometry(out: &mut String, shape: &ShapePrimitive) {
+    let (kind, attributes) = match *shape {
+        // Cubes have a single edge length; the half-extents become a scale.
+        ShapePrimitive::Box(h) => (
+            "Cube",
+            vec![
+                "double size = 1".to_string(),
+                format!("float3 xformOp:scale = {}", vec3(h * 2.0)),
+                "uniform token[] xformOpOrder = [\"xformOp:scale\"]".to_string(),
+            ],
+        ),
+        ShapePrimitive::Sphere(r) => ("Sphere", vec![format!("double radius = {r:.6}")]),
+        ShapePrimitive::Cylinder { radius, height } => (
+            "Cylinder",
+            vec![
+                "uniform token axis = \"Y\"".to_string(),
+                format!("double height = {height:.6}"),
+                format!("double radius = {radius:.6}"),
+            ],
+        ),
+        ShapePrimitive::Capsule { radius, height } => (
+            "Capsule",
+            vec![
+                "uniform token axis = \"Y\"".to_string(),
+                format!("double height = {height:.6}"),
+                format!("double radius = {radius:.6}"),
+            ],
+        ),
+    };
+    let _ = writeln!(out);
+    let _ = writeln!(out, "        def {kind} \"geom\" (");
+    let _ = writeln!(
+        out,
+        "            prepend apiSchemas = [\"PhysicsCollisionAPI\"]"
+    );
+    let _ = writeln!(out, "        )");
+    let _ = writeln!(out, "        {{");
+    for attribute in attributes {
+        let _ = writeln!(out, "            {attribute}");
+    }
+    let _ = writeln!(out, "        }}");
+}
+
+fn write_joint(out: &mut String, blueprint: &RobotBlueprint, root: &str, joint: &JointDefinition) {
+    let (parent, child) = (joint.parent_id, joint.child_id);
+    let rot = |id: ModuleId| {
+        blueprint
+            .modules
+            .get(&id)
+            .map_or(Quat::IDENTITY, |m| m.transform.1)
+    };
+    let kind = match joint.joint_type {
+        JointType::Fixed => "PhysicsFixedJoint",
+        JointType::Hinge => "PhysicsRevoluteJoint",
+        JointType::Ball => "PhysicsSphericalJoint",
+        JointType::Prismatic => "PhysicsPrismaticJoint",
+    };
+    // UsdPhysics joints act along an axis token, so the frame is turned to put the
+    // joint axis on X. The child side is the same frame seen from the child.
+    let frame0 = Quat::from_rotation_arc(Vec3::X, joint.axis.normalize_or(Vec3::X));
+    let frame1 = rot(child).inverse() * rot(parent) * frame0;
+
+    let _ = writeln!(out, "    def {kind} \"joint_{parent}_{child}\"");
+    let _ = writeln!(out, "    {{");
+    let _ = writeln!(out, "        rel physics:body0 = </{root}/module_{parent}>");
+    let _ = writeln!(out, "        rel physics:body1 = </{root}/module_{child}>");
+    let _ = writeln!(
+        out,
+        "        point3f physics:localPos0 = {}",
+        vec3(joint.anchor_parent)
+    );
+    let _ = writeln!(out, "        quatf physics:localRot0 = {}", quat(frame0));
+    let _ = writeln!(
+        out,
+        "        point3f physics:localPos1 = {}",
+        vec3(joint.anchor_child)
+    );
+    let _ = writeln!(out, "        quatf physics:localRot1 = {}", quat(frame1));
+    if joint.joint_type != JointType::Fixed {
+        let _ = writeln!(out, "        uniform token physics:axis = \"X\"");
+    }
+    if let Some(limits) = joint.limits {
+        // Angular limits are in degrees in UsdPhysics.
+        match joint.joint_type {
+            JointType::Hinge => {
+                let _ = writeln!(
+                    out,
+                    "        float physics:lowerLimit = {:.6}",
+                    limits.min.to_degrees()
+                );
+                let _ = writeln!(
+                    out,
+                    "        float physics:upperLimit = {:.6}",
+                    limits.max.to_degrees()
+                );
+            }
+            JointType::Prismatic => {
+                let _ = writeln!(out, "        float physics:lowerLimit = {:.6}", limits.min);
+                let _ = writeln!(out, "        float physics:upperLimit = {:.6}", limits.max);
+            }
+            JointType::Ball => {
+                if let Some(cone) = limits.cone {
+                    let _ = writeln!(
+                        out,
+                        "        float physics:coneAngle0Limit = {:.6}",
+                        cone.swing1.to_degrees()
+                    );
+                    let _ = writeln!(
+                        out,
+                        "        float physics:coneAngle1Limit = {:.6}",
+                        cone.swing2.to_degrees()
+                    );
+                }
+            }
+            JointType::Fixed => {}
+        }
+    }
+    let _ = writeln!(out, "    }}");
+}
+
+fn vec3(v: Vec3) -> String {
+    format!("({:.6}, {:.6}, {:.6})", v.x, v.y, v.z)
+}
+
+/// A quaternion in USD's `(w, x, y, z)` order.
+fn quat(q: Quat) -> String {
+    let q = q.normalize();
+    format!("({:.6}, {:.6}, {:.6}, {:.6})", q.w, q.x, q.y, q.z)
+}
+
+/// A valid prim name: letters, digits and underscores, not starting with a digit.
+fn identifier(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Escapes a string for a double-quoted USDA literal.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A zip archive holding `data` uncompressed as `file_name`, padded so the data starts on
+/// a 64-byte boundary.
+fn zip_stored(file_name: &str, data: &[u8]) -> Vec<u8> {
+    const ALIGNMENT: usize = 64;
+    // Padding lives in an extra field, whose own header takes 4 bytes.
+    const LOCAL_HEADER: usize = 30;
+    let name = file_name.as_bytes();
+    let padding = (ALIGNMENT - (LOCAL_HEADER + name.len() + 4) % ALIGNMENT) % ALIGNMENT;
+    let crc = crc32(data);
+    let (size, name_len) = (data.len() as u32, name.len() as u16);
+    // 1980-01-01, the earliest date zip can store.
+    let (time, date) = (0u16, (1 << 5) | 1);
+
+    let mut out = Vec::new();
+    let put16 = |out: &mut Vec<u8>, v: u16| out.extend_from_slice(&v.to_le_bytes());
+    let put32 = |out: &mut Vec<u8>, v: u32| out.extend_from_slice(&v.to_le_bytes());
+
+    put32(&mut out, 0x0403_4b50);
+    for v in [10, 0, 0, time, date] {
+        put16(&mut out, v);
+    }
+    for v in [crc, size, size] {
+        put32(&mut out, v);
+    }
+    put16(&mut out, name_len);
+    put16(&mut out, 4 + padding as u16);
+    out.extend_from_slice(name);
+    put16(&mut out, 0x1986);
+    put16(&mut out, padding as u16);
+    out.resize(out.len() + padding, 0);
+    out.extend_from_slice(data);
+
+    let directory = out.len() as u32;
+    put32(&mut out, 0x0201_4b50);
+    for v in [20, 10, 0, 0, time, date] {
+        put16(&mut out, v);
+    }
+    for v in [crc, size, size] {
+        put32(&mut out, v);
+    }
+    for v in [name_len, 0, 0, 0, 0] {
+        put16(&mut out, v);
+    }
+    put32(&mut out, 0);
+    put32(&mut out, 0);
+    out.extend_from_slice(name);
+    let directory_size = out.len() as u32 - directory;
+
+    put32(&mut out, 0x0605_4b50);
+    for v in [0, 0, 1, 1] {
+        put16(&mut out, v);
+    }
+    put32(&mut out, directory_size);
+    put32(&mut out, directory);
+    put16(&mut out, 0);
+    out
+}
+
+/// The CRC-32 (IEEE) checksum zip stores for every file.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
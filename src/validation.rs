@@ -0,0 +1,301 @@
+//! Sanity checks for evolved joint parameters.
+//!
+//! Grammar parameters are mutated blindly, so a joint frequently ends up with limits that
+//! make no sense for its type: a prismatic joint whose "travel" is the default `±π` angle,
+//! a fixed joint with limits at all, or a ball joint without a swing cone.
+//! [`RobotBlueprint::limit_issues`] reports these and [`RobotBlueprint::validate_limits`]
+//! either rejects ([`LimitValidation::Strict`]) or fixes ([`LimitValidation::Repair`]) them.
+//!
+//! Moves (`f`) between spawns can also leave a joint "floating": its anchor sits in empty
+//! space away from the module it is attached to. [`RobotBlueprint::anchor_issues`] reports
+//! these and [`RobotBlueprint::snap_anchors`] pulls them onto the surface.
+
+use crate::blueprint::{
+    BlueprintError, ConeLimit, JointDefinition, JointType, RobotBlueprint, ShapePrimitive,
+};
+use crate::parallel;
+use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI};
+use thiserror::Error;
+
+/// What [`RobotBlueprint::validate_limits`] does with nonsensical limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitValidation {
+    /// Leave the blueprint untouched and fail on the first issue.
+    Strict,
+    /// Fix every issue in place.
+    #[default]
+    Repair,
+}
+
+/// A joint whose limits do not fit its type.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LimitIssue {
+    /// Index of the joint in [`RobotBlueprint::joints`].
+    pub joint_index: usize,
+    /// What is wrong.
+    pub kind: LimitIssueKind,
+}
+
+/// The ways joint limits can be wrong, and how [`LimitValidation::Repair`] fixes each.
+#[derive(Error, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LimitIssueKind {
+    /// A limit is NaN or infinite. Repair removes the limits.
+    #[error("limits are not finite")]
+    NonFinite,
+    /// A fixed joint carries limits. Repair removes them.
+    #[error("fixed joint has limits")]
+    LimitsOnFixed,
+    /// `min > max`. Repair swaps them.
+    #[error("min is greater than max")]
+    InvertedRange,
+    /// Effort or velocity is not positive. Repair takes the absolute value.
+    #[error("effort or velocity is not positive")]
+    NonPositiveActuation,
+    /// An angular limit lies outside `[-π, π]`. Repair clamps it.
+    #[error("angle outside [-pi, pi]")]
+    AngleOutOfRange,
+    /// A prismatic joint travels further than its child segment is long — typically an
+    /// angular limit read as metres. Repair clamps the travel to the segment length.
+    #[error("travel {travel} m exceeds segment length {length} m")]
+    TravelExceedsSegment {
+        /// Largest displacement allowed by the limits.
+        travel: f32,
+        /// Length of the child segment.
+        length: f32,
+    },
+    /// A ball joint has no swing cone. Repair adds one with 90° swing and the existing
+    /// range as twist.
+    #[error("ball joint has no swing cone")]
+    MissingCone,
+    /// A swing or twist half-angle lies outside `[0, π]`. Repair clamps it.
+    #[error("cone angle outside [0, pi]")]
+    ConeOutOfRange,
+}
+
+/// A joint anchor lying outside the shape of the module it belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnchorIssue {
+    /// Index of the joint in [`RobotBlueprint::joints`].
+    pub joint_index: usize,
+    /// Which of the joint's two anchors is off its module.
+    pub side: AnchorSide,
+    /// Distance in metres from the anchor to the module's surface.
+    pub distance: f32,
+}
+
+/// One end of a joint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnchorSide {
+    /// [`JointDefinition::anchor_parent`].
+    Parent,
+    /// [`JointDefinition::anchor_child`].
+    Child,
+}
+
+impl RobotBlueprint {
+    /// Lists every joint anchor lying more than `tolerance` metres outside its module's
+    /// shape, without changing anything.
+    pub fn anchor_issues(&self, tolerance: f32) -> Vec<AnchorIssue> {
+        let index = self.spatial_index();
+        parallel::map(&self.joints, |joint_index, joint| {
+            [
+                (AnchorSide::Parent, joint.parent_id, joint.anchor_parent),
+                (AnchorSide::Child, joint.child_id, joint.anchor_child),
+            ]
+            .into_iter()
+            .filter_map(|(side, id, anchor)| {
+                let (pos, rot) = self.modules.get(&id)?.transform;
+                let distance = index.distance_to(id, pos + rot * anchor)?;
+                (distance > tolerance).then_some(AnchorIssue {
+                    joint_index,
+                    side,
+                    distance,
+                })
+            })
+            .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Moves every anchor reported by [`anchor_issues`](Self::anchor_issues) to the closest
+    /// point on its module's surface and returns the issues it fixed.
+    ///
+    /// Only the anchors change: modules keep their rest pose, so the two anchors of a
+    /// snapped joint no longer coincide in world space until the simulator pulls them
+    /// together.
+    pub fn snap_anchors(&mut self, tolerance: f32) -> Vec<AnchorIssue> {
+        let issues = self.anchor_issues(tolerance);
+        for issue in &issues {
+            let joint = &mut self.joints[issue.joint_index];
+            let (id, anchor) = match issue.side {
+                AnchorSide::Parent => (joint.parent_id, &mut joint.anchor_parent),
+                AnchorSide::Child => (joint.child_id, &mut joint.anchor_child),
+            };
+            *anchor = closest_point(&self.modules[&id].shape, *anchor);
+        }
+        issues
+    }
+
+    /// Lists every joint whose limits do not fit its type, without changing anything.
+    pub fn limit_issues(&self) -> Vec<LimitIssue> {
+        parallel::map(&self.joints, |joint_index, joint| {
+            let mut joint = joint.clone();
+            let length = self.child_length(&joint);
+            check_joint(&mut joint, length)
+                .into_iter()
+                .map(|kind| LimitIssue { joint_index, kind })
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Checks joint limits against their joint types.
+    ///
+    /// With [`LimitValidation::Repair`] every issue is fixed in place and the list of fixed
+    /// issues is returned.
+    ///
+    /// # Errors
+    ///
+    /// With [`LimitValidation::Strict`], returns [`BlueprintError::InvalidLimits`] for the
+    /// first issue found and leaves the blueprint unchanged.
+    pub fn validate_limits(
+        &mut self,
+        mode: LimitValidation,
+    ) -> Result<Vec<LimitIssue>, BlueprintError> {
+        match mode {
+            LimitValidation::Strict => match self.limit_issues().first() {
+                Some(&issue) => Err(BlueprintError::InvalidLimits(issue)),
+                None => Ok(Vec::new()),
+            },
+            LimitValidation::Repair => {
+                let mut fixed = Vec::new();
+                for joint_index in 0..self.joints.len() {
+                    let length = self.child_length(&self.joints[joint_index]);
+                    let kinds = check_joint(&mut self.joints[joint_index], length);
+                    fixed.extend(
+                        kinds
+                            .into_iter()
+                            .map(|kind| LimitIssue { joint_index, kind }),
+                    );
+                }
+                Ok(fixed)
+            }
+        }
+    }
+
+    fn child_length(&self, joint: &JointDefinition) -> Option<f32> {
+        self.modules
+            .get(&joint.child_id)
+            .map(|m| segment_length(&m.shape))
+    }
+}
+
+/// Extent of a shape along its local Y (growth) axis.
+fn segment_length(shape: &ShapePrimitive) -> f32 {
+    shape.half_extents().y * 2.0
+}
+
+/// The point of `shape` (surface or interior) closest to `local`, in the shape's frame.
+pub(crate) fn closest_point(shape: &ShapePrimitive, local: Vec3) -> Vec3 {
+    // Pulls `p` into the ball of radius `r` around the origin.
+    let into_ball = |p: Vec3, r: f32| if p.length() > r { p.normalize() * r } else { p };
+    match *shape {
+        ShapePrimitive::Box(h) => local.clamp(-h, h),
+        ShapePrimitive::Sphere(r) => into_ball(local, r),
+        ShapePrimitive::Cylinder { radius, height } => {
+            let radial = Vec2::new(local.x, local.z).clamp_length_max(radius);
+            let y = local.y.clamp(-height / 2.0, height / 2.0);
+            Vec3::new(radial.x, y, radial.y)
+        }
+        ShapePrimitive::Capsule { radius, height } => {
+            let spine = Vec3::Y * local.y.clamp(-height / 2.0, height / 2.0);
+            spine + into_ball(local - spine, radius)
+        }
+    }
+}
+
+/// Finds the issues with one joint's limits, repairing each as it goes so that later
+/// checks see sane values. Callers that only want a report pass a copy.
+fn check_joint(joint: &mut JointDefinition, child_length: Option<f32>) -> Vec<LimitIssueKind> {
+    let mut issues = Vec::new();
+    let Some(limits) = &mut joint.limits else {
+        return issues;
+    };
+
+    let cone_finite = limits
+        .cone
+        .is_none_or(|c| c.swing1.is_finite() && c.swing2.is_finite() && c.twist.is_finite());
+    if ![limits.min, limits.max, limits.effort, limits.velocity]
+        .iter()
+        .all(|v| v.is_finite())
+        || !cone_finite
+    {
+        issues.push(LimitIssueKind::NonFinite);
+        joint.limits = None;
+        return issues;
+    }
+    if joint.joint_type == JointType::Fixed {
+        issues.push(LimitIssueKind::LimitsOnFixed);
+        joint.limits = None;
+        return issues;
+    }
+
+    if limits.min > limits.max {
+        issues.push(LimitIssueKind::InvertedRange);
+        std::mem::swap(&mut limits.min, &mut limits.max);
+    }
+    if limits.effort <= 0.0 || limits.velocity <= 0.0 {
+        issues.push(LimitIssueKind::NonPositiveActuation);
+        limits.effort = limits.effort.abs();
+        limits.velocity = limits.velocity.abs();
+    }
+
+    match joint.joint_type {
+        JointType::Hinge | JointType::Ball => {
+            if limits.min < -PI || limits.max > PI {
+                issues.push(LimitIssueKind::AngleOutOfRange);
+                limits.min = limits.min.clamp(-PI, PI);
+                limits.max = limits.max.clamp(-PI, PI);
+            }
+        }
+        JointType::Prismatic => {
+            let travel = limits.min.abs().max(limits.max.abs());
+            if let Some(length) = child_length
+                && travel > length
+            {
+                issues.push(LimitIssueKind::TravelExceedsSegment { travel, length });
+                limits.min = limits.min.clamp(-length, length);
+                limits.max = limits.max.clamp(-length, length);
+            }
+        }
+        JointType::Fixed => unreachable!("handled above"),
+    }
+
+    if joint.joint_type == JointType::Ball {
+        if limits.cone.is_none() {
+            issues.push(LimitIssueKind::MissingCone);
+            limits.cone = Some(ConeLimit {
+                swing1: FRAC_PI_2,
+                swing2: FRAC_PI_2,
+                twist: limits.min.abs().max(limits.max.abs()),
+            });
+        }
+        let cone = limits.cone.as_mut().expect("inserted above");
+        if [cone.swing1, cone.swing2, cone.twist]
+            .iter()
+            .any(|a| !(0.0..=PI).contains(a))
+        {
+            issues.push(LimitIssueKind::ConeOutOfRange);
+            cone.swing1 = cone.swing1.clamp(0.0, PI);
+            cone.swing2 = cone.swing2.clamp(0.0, PI);
+            cone.twist = cone.twist.clamp(0.0, PI);
+        }
+    }
+    issues
+}
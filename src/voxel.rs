@@ -0,0 +1,126 @@
+//! Occupancy-grid voxelization of a blueprint's rest pose.
+//!
+//! [`RobotBlueprint::voxelize`] rasterises every module into a regular grid by testing
+//! voxel centres against the module shapes. The resulting [`VoxelGrid`] feeds soft-robot
+//! and morphology-analysis pipelines, and doubles as a cheap volume and self-overlap
+//! estimate.
+
+use crate::blueprint::RobotBlueprint;
+use bevy_math::Isometry3d;
+use bevy_math::bounding::Bounded3d as _;
+use glam::{Quat, UVec3, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// A regular occupancy grid aligned with the world axes.
+///
+/// Each cell stores how many modules cover its centre, so both plain occupancy and
+/// module overlap can be queried from the same grid.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoxelGrid {
+    /// World-space position of the minimum corner of cell `(0, 0, 0)`.
+    pub origin: Vec3,
+    /// Edge length of a cubic cell, in metres.
+    pub voxel_size: f32,
+    /// Number of cells along each axis.
+    pub dims: UVec3,
+    /// Per-cell module coverage counts, laid out x-fastest then y then z.
+    pub cells: Vec<u16>,
+}
+
+impl VoxelGrid {
+    /// Linear index of cell `(x, y, z)`, or `None` if it is outside the grid.
+    pub fn index(&self, x: u32, y: u32, z: u32) -> Option<usize> {
+        if x >= self.dims.x || y >= self.dims.y || z >= self.dims.z {
+            return None;
+        }
+        Some((x + self.dims.x * (y + self.dims.y * z)) as usize)
+    }
+
+    /// Returns `true` if cell `(x, y, z)` is covered by at least one module.
+    pub fn is_occupied(&self, x: u32, y: u32, z: u32) -> bool {
+        self.index(x, y, z).is_some_and(|i| self.cells[i] > 0)
+    }
+
+    /// World-space centre of cell `(x, y, z)`.
+    pub fn cell_center(&self, x: u32, y: u32, z: u32) -> Vec3 {
+        self.origin + (Vec3::new(x as f32, y as f32, z as f32) + 0.5) * self.voxel_size
+    }
+
+    /// Number of occupied cells.
+    pub fn occupied_count(&self) -> usize {
+        self.cells.iter().filter(|&&c| c > 0).count()
+    }
+
+    /// Number of cells covered by two or more modules.
+    pub fn overlap_count(&self) -> usize {
+        self.cells.iter().filter(|&&c| c > 1).count()
+    }
+
+    /// Estimated occupied volume in m³.
+    pub fn volume(&self) -> f32 {
+        self.occupied_count() as f32 * self.voxel_size.powi(3)
+    }
+
+    /// Estimated volume in m³ shared by two or more modules.
+    pub fn overlap_volume(&self) -> f32 {
+        self.overlap_count() as f32 * self.voxel_size.powi(3)
+    }
+}
+
+impl RobotBlueprint {
+    /// Rasterises the rest pose into a [`VoxelGrid`] with cubic cells of edge `resolution`.
+    ///
+    /// The grid covers the blueprint's axis-aligned bounding box. A cell counts as covered
+    /// by a module when the cell centre lies inside the module's shape. Returns an empty
+    /// grid if `resolution` is not a positive finite number or the blueprint is empty.
+    pub fn voxelize(&self, resolution: f32) -> VoxelGrid {
+        if !(resolution.is_finite() && resolution > 0.0) || self.modules.is_empty() {
+            return VoxelGrid {
+                voxel_size: resolution,
+                ..Default::default()
+            };
+        }
+
+        let aabb = self.aabb(Quat::IDENTITY);
+        let origin = Vec3::from(aabb.min);
+        let size = Vec3::from(aabb.max) - origin;
+        let dims = (size / resolution).ceil().max(Vec3::ONE).as_uvec3();
+        let mut grid = VoxelGrid {
+            origin,
+            voxel_size: resolution,
+            dims,
+            cells: vec![0; (dims.x * dims.y * dims.z) as usize],
+        };
+
+        for module in self.modules.values() {
+            let (pos, rot) = module.transform;
+            let inv = rot.inverse();
+            // Only visit cells overlapping this module's own bounding box.
+            let m_aabb = module
+                .shape
+                .to_bevy_primitive()
+                .aabb_3d(Isometry3d::new(pos, rot));
+            let lo = ((Vec3::from(m_aabb.min) - origin) / resolution)
+                .floor()
+                .max(Vec3::ZERO)
+                .as_uvec3();
+            let hi = ((Vec3::from(m_aabb.max) - origin) / resolution)
+                .ceil()
+                .as_uvec3()
+                .min(dims);
+            for z in lo.z..hi.z {
+                for y in lo.y..hi.y {
+                    for x in lo.x..hi.x {
+                        let local = inv * (grid.cell_center(x, y, z) - pos);
+                        if module.shape.contains_local(local)
+                            && let Some(i) = grid.index(x, y, z)
+                        {
+                            grid.cells[i] = grid.cells[i].saturating_add(1);
+                        }
+                    }
+                }
+            }
+        }
+        grid
+    }
+}
@@ -0,0 +1,196 @@
+//! Xacro (parameterized URDF) export for ROS.
+//!
+//! [`RobotBlueprint::to_xacro`] writes a URDF in which every dimension is a xacro property:
+//! `default_length` and `default_width` from the [`RobotConfig`] that built the robot, and
+//! one set of properties per module defined as multiples of them. Link geometry, inertia
+//! and joint origins are expressions over those properties, so an operator can rescale the
+//! whole robot, or a single segment, by editing one value instead of re-running the
+//! interpreter.
+//!
+//! Links, joints and frames are those of the plain URDF export in [`crate::urdf`], which
+//! this module drives with the dimension properties in place of numbers; the same
+//! [`UrdfOptions`] apply.
+
+use crate::blueprint::{ModuleId, RobotBlueprint, RobotModule, ShapePrimitive};
+use crate::interpreter::RobotConfig;
+use crate::stream::stream;
+use crate::urdf::UrdfOptions;
+use glam::Vec3;
+use std::fmt::Write;
+use std::io;
+
+impl RobotBlueprint {
+    /// Renders the robot as a xacro document describing one robot called `name`.
+    ///
+    /// Module `N` becomes link `module_N` with properties `module_N_length` (along its
+    /// growth axis) and `module_N_width` / `module_N_depth` (boxes) or `module_N_radius`
+    /// (round shapes), scaled from `config.default_length` and `config.default_width`.
+    /// Joint origins and visual offsets scale with the properties of the module they lie
+    /// on. Masses are written as numbers and do not follow the dimensions. Links, joints
+    /// and frames are otherwise those of [`to_urdf`](Self::to_urdf).
+    pub fn to_xacro(&self, name: &str, config: &RobotConfig) -> String {
+        self.to_xacro_with(name, config, &UrdfOptions::default())
+    }
+
+    /// [`to_xacro`](Self::to_xacro) with the geometry, elements, up axis and names chosen
+    /// by `options`.
+    pub fn to_xacro_with(&self, name: &str, config: &RobotConfig, options: &UrdfOptions) -> String {
+        let mut out = String::new();
+        self.render_urdf(&mut out, name, Some(&Defaults::of(config)), options);
+        out
+    }
+
+    /// Streams [`to_xacro`](Self::to_xacro) into `writer`, e.g. a `BufWriter<File>`.
+    pub fn write_xacro(
+        &self,
+        writer: impl io::Write,
+        name: &str,
+        config: &RobotConfig,
+    ) -> io::Result<()> {
+        self.write_xacro_with(writer, name, config, &UrdfOptions::default())
+    }
+
+    /// Streams [`to_xacro_with`](Self::to_xacro_with) into `writer`.
+    pub fn write_xacro_with(
+        &self,
+        writer: impl io::Write,
+        name: &str,
+        config: &RobotConfig,
+        options: &UrdfOptions,
+    ) -> io::Result<()> {
+        stream(writer, |out| {
+            self.render_urdf(out, name, Some(&Defaults::of(config)), options)
+        })
+    }
+}
+
+/// The interpreter defaults every module dimension is expressed in.
+pub(crate) struct Defaults {
+    length: f32,
+    width: f32,
+}
+
+impl Defaults {
+    fn of(config: &RobotConfig) -> Self {
+        Self {
+            length: config.default_length,
+            width: config.default_width,
+        }
+    }
+
+    /// Writes the property definitions: the defaults, then the dimensions of each of
+    /// `modules` as multiples of them.
+    pub(crate) fn write_properties(
+        &self,
+        out: &mut impl Write,
+        modules: &[(ModuleId, &RobotModule)],
+    ) {
+        let _ = writeln!(
+            out,
+            "  <xacro:property name=\"default_length\" value=\"{:.6}\"/>",
+            self.length
+        );
+        let _ = writeln!(
+            out,
+            "  <xacro:property name=\"default_width\" value=\"{:.6}\"/>",
+            self.width
+        );
+        for &(id, module) in modules {
+            for (property, value, base) in dimensions(&module.shape) {
+                let value = self.scaled(value, base);
+                let _ = writeln!(
+                    out,
+                    "  <xacro:property name=\"module_{id}_{property}\" value=\"{value}\"/>"
+                );
+            }
+        }
+    }
+
+    /// `value` as a multiple of its default, or as a plain number if the default is zero.
+    fn scaled(&self, value: f32, base: Base) -> String {
+        let (name, default) = match base {
+            Base::Length => ("default_length", self.length),
+            Base::Width => ("default_width", self.width),
+        };
+        if default.abs() > f32::EPSILON {
+            format!("${{{:.6} * {name}}}", value / default)
+        } else {
+            format!("{value:.6}")
+        }
+    }
+}
+
+/// Which default a module dimension scales with.
+#[derive(Clone, Copy)]
+pub(crate) enum Base {
+    Length,
+    Width,
+}
+
+/// The named dimensions of a shape, with their values and the default they scale with.
+pub(crate) fn dimensions(shape: &ShapePrimitive) -> Vec<(&'static str, f32, Base)> {
+    match *shape {
+        ShapePrimitive::Box(h) => vec![
+            ("width", h.x * 2.0, Base::Width),
+            ("length", h.y * 2.0, Base::Length),
+            ("depth", h.z * 2.0, Base::Width),
+        ],
+        ShapePrimitive::Sphere(r) => vec![("radius", r, Base::Width)],
+        ShapePrimitive::Cylinder { radius, height }
+        | ShapePrimitive::Capsule { radius, height } => vec![
+            ("radius", radius, Base::Width),
+            ("length", height, Base::Length),
+        ],
+    }
+}
+
+/// Full extent of module `id` along each local axis, as a number and as an expression
+/// over its properties.
+fn extents(id: ModuleId, shape: &ShapePrimitive) -> [(f32, String); 3] {
+    let radius = format!("2 * module_{id}_radius");
+    match *shape {
+        ShapePrimitive::Box(h) => [
+            (h.x * 2.0, format!("module_{id}_width")),
+            (h.y * 2.0, format!("module_{id}_length")),
+            (h.z * 2.0, format!("module_{id}_depth")),
+        ],
+        ShapePrimitive::Sphere(r) => [
+            (r * 2.0, radius.clone()),
+            (r * 2.0, radius.clone()),
+            (r * 2.0, radius),
+        ],
+        ShapePrimitive::Cylinder { radius: r, height } => [
+            (r * 2.0, radius.clone()),
+            (height, format!("module_{id}_length")),
+            (r * 2.0, radius),
+        ],
+        ShapePrimitive::Capsule { radius: r, height } => [
+            (r * 2.0, radius.clone()),
+            (
+                height + r * 2.0,
+                format!("module_{id}_length + 2 * module_{id}_radius"),
+            ),
+            (r * 2.0, radius),
+        ],
+    }
+}
+
+/// Expressions for the coordinates of a point in module `id`'s local frame, each scaling
+/// with the module's extent along its axis.
+pub(crate) fn local_point(id: ModuleId, shape: &ShapePrimitive, point: Vec3) -> [String; 3] {
+    let [x, y, z] = extents(id, shape);
+    [(x, point.x), (y, point.y), (z, point.z)].map(|((extent, expr), c)| {
+        if c.abs() < 1e-6 {
+            "0".to_owned()
+        } else if extent > f32::EPSILON {
+            format!("{:.6} * ({expr})", c / extent)
+        } else {
+            format!("{c:.6}")
+        }
+    })
+}
+
+/// An `xyz` attribute evaluating the three coordinate expressions.
+pub(crate) fn xyz([x, y, z]: &[String; 3]) -> String {
+    format!("${{{x}}} ${{{y}}} ${{{z}}}")
+}
@@ -0,0 +1,574 @@
+// tests/blueprint_graph.rs
+use glam::{Quat, Vec3};
+use symbios_robot::{
+    BlueprintError, BlueprintFormat, CollisionExclusion, ContactPair, ContactSurface, Convention,
+    ExclusionReason, JointDefinition, JointType, MAX_LOD_LEVELS, ModuleBounds, ModuleId,
+    RecenterOrigin, RobotBlueprint, RobotModule, SensorAttachment, SensorMount, SensorTiming,
+    SensorType, ShapePrimitive, convert,
+};
+
+fn unit_box(center: Vec3) -> RobotModule {
+    RobotModule {
+        mass: 1.0,
+        ..RobotModule::new(
+            ShapePrimitive::Box(Vec3::new(0.1, 0.5, 0.1)),
+            100.0,
+            (center, Quat::IDENTITY),
+        )
+    }
+}
+
+fn joint(parent_id: ModuleId, child_id: ModuleId) -> JointDefinition {
+    JointDefinition {
+        parent_id,
+        child_id,
+        anchor_parent: Vec3::new(0.0, 0.5, 0.0),
+        anchor_child: Vec3::new(0.0, -0.5, 0.0),
+        joint_type: JointType::Hinge,
+        axis: Vec3::X,
+        child_axis: Vec3::X,
+        rest_displacement: 0.0,
+        limits: None,
+        transmission: None,
+        actuator: None,
+    }
+}
+
+#[test]
+fn test_topological_order_parents_first() {
+    // Root 5 -> 2 -> 7, root 5 -> 1. IDs deliberately not in spawn order.
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(5, unit_box(Vec3::new(0.0, 0.5, 0.0)));
+    bp.add_module(7, unit_box(Vec3::new(0.0, 2.5, 0.0)));
+    bp.add_module(2, unit_box(Vec3::new(0.0, 1.5, 0.0)));
+    bp.add_module(1, unit_box(Vec3::new(1.0, 0.5, 0.0)));
+    bp.add_joint(joint(2, 7));
+    bp.add_joint(joint(5, 2));
+    bp.add_joint(joint(5, 1));
+
+    let order: Vec<ModuleId> = bp.modules_topological().map(|(id, _)| id).collect();
+    assert_eq!(order, vec![5, 2, 1, 7]);
+
+    let joint_children: Vec<ModuleId> = bp.joints_topological().map(|j| j.child_id).collect();
+    assert_eq!(joint_children, vec![2, 1, 7]);
+}
+
+#[test]
+fn test_reroot_flips_path_to_new_root() {
+    let mut bp = RobotBlueprint::new();
+    for id in 0..3 {
+        bp.add_module(id, unit_box(Vec3::new(0.0, 0.5 + id as f32, 0.0)));
+    }
+    bp.add_joint(joint(0, 1));
+    bp.add_joint(joint(1, 2));
+
+    bp.reroot(2).unwrap();
+
+    assert_eq!(bp.root_module(), Some(2));
+    let order: Vec<ModuleId> = bp.modules_topological().map(|(id, _)| id).collect();
+    assert_eq!(order, vec![2, 1, 0]);
+
+    let top = bp.parent_joint(1).unwrap();
+    assert_eq!(top.parent_id, 2);
+    // Anchors swap sides: the old child's bottom is now the parent anchor.
+    assert_eq!(top.anchor_parent, Vec3::new(0.0, -0.5, 0.0));
+    assert_eq!(top.anchor_child, Vec3::new(0.0, 0.5, 0.0));
+    // Module 1 now hangs from its top end.
+    assert_eq!(bp.modules[&1].pivot_offset, Vec3::new(0.0, -0.5, 0.0));
+
+    assert!(bp.reroot(42).is_err());
+}
+
+#[test]
+fn test_extract_subtree_remaps_ids() {
+    // 0 -> 1 -> 2, 0 -> 3
+    let mut bp = RobotBlueprint::new();
+    for id in 0..4 {
+        bp.add_module(id, unit_box(Vec3::new(id as f32, 0.5, 0.0)));
+    }
+    bp.add_joint(joint(0, 1));
+    bp.add_joint(joint(1, 2));
+    bp.add_joint(joint(0, 3));
+
+    let limb = bp.extract_subtree(1).unwrap();
+    assert_eq!(limb.root_module(), Some(0));
+    assert_eq!(limb.modules.len(), 2);
+    assert_eq!(limb.joints.len(), 1);
+    assert_eq!(limb.joints[0].parent_id, 0);
+    assert_eq!(limb.joints[0].child_id, 1);
+    // Module 2 of the source became module 1 of the limb and kept its rest pose.
+    assert_eq!(limb.modules[&1].transform.0, Vec3::new(2.0, 0.5, 0.0));
+}
+
+#[test]
+fn test_legacy_root_module_loads_as_roots() {
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(3, unit_box(Vec3::ZERO));
+    let mut json = serde_json::to_value(&bp).unwrap();
+    json.as_object_mut().unwrap().remove("roots");
+
+    json["root_module"] = 3.into();
+    let bytes = serde_json::to_vec(&json).unwrap();
+    let loaded = RobotBlueprint::decode(&bytes, BlueprintFormat::Json).unwrap();
+    assert_eq!(loaded.roots, vec![3]);
+
+    json["root_module"] = serde_json::Value::Null;
+    let bytes = serde_json::to_vec(&json).unwrap();
+    let loaded = RobotBlueprint::decode(&bytes, BlueprintFormat::Json).unwrap();
+    assert!(loaded.roots.is_empty());
+    // The parentless module still leads the traversal.
+    assert_eq!(loaded.topological_order(), vec![3]);
+
+    // The binary format is unaffected.
+    let restored = RobotBlueprint::from_bytes(&bp.to_bytes()).unwrap();
+    assert_eq!(restored.roots, vec![3]);
+}
+
+#[test]
+fn test_merge_offsets_ids_and_connects() {
+    // body: 0 -> 1, gripper: 0 -> 1
+    let mut body = RobotBlueprint::new();
+    let mut gripper = RobotBlueprint::new();
+    for id in 0..2 {
+        body.add_module(id, unit_box(Vec3::new(0.0, 0.5 + id as f32, 0.0)));
+        gripper.add_module(id, unit_box(Vec3::new(0.0, 2.5 + id as f32, 0.0)));
+    }
+    body.add_joint(joint(0, 1));
+    gripper.add_joint(joint(0, 1));
+
+    let mut detached = body.clone();
+    detached.merge(gripper.clone(), 10, None).unwrap();
+    assert_eq!(detached.roots, vec![0, 10]);
+    assert_eq!(detached.parent_joint(11).unwrap().parent_id, 10);
+
+    body.merge(gripper.clone(), 10, Some(joint(1, 0))).unwrap();
+    assert_eq!(body.modules.len(), 4);
+    assert_eq!(body.roots, vec![0]);
+    assert_eq!(body.parent_joint(10).unwrap().parent_id, 1);
+    assert_eq!(body.tree_root(11), Some(0));
+    assert_eq!(body.modules[&11].transform.0, Vec3::new(0.0, 3.5, 0.0));
+
+    assert!(matches!(
+        body.merge(gripper.clone(), 10, None),
+        Err(BlueprintError::DuplicateModule(10))
+    ));
+    assert!(matches!(
+        body.merge(gripper.clone(), u16::MAX, None),
+        Err(BlueprintError::IdOverflow(1))
+    ));
+    assert!(matches!(
+        body.merge(gripper, 20, Some(joint(42, 0))),
+        Err(BlueprintError::UnknownModule(42))
+    ));
+    assert_eq!(body.modules.len(), 4);
+}
+
+#[test]
+fn test_contact_table_is_symmetric_and_merged() {
+    let rubber = ContactSurface::Material(2);
+    let plastic = ContactSurface::Material(0);
+    let mut body = RobotBlueprint::new();
+    body.add_module(0, unit_box(Vec3::new(0.0, 0.5, 0.0)));
+    body.contacts
+        .set(ContactPair::new(rubber, ContactSurface::Ground, 0.9, 0.1));
+    body.contacts
+        .set(ContactPair::new(ContactSurface::Ground, rubber, 1.2, 0.0));
+    assert_eq!(body.contacts.pairs.len(), 1);
+    assert_eq!(
+        body.contacts
+            .get(ContactSurface::Ground, rubber)
+            .unwrap()
+            .friction,
+        1.2
+    );
+
+    let mut tool = RobotBlueprint::new();
+    tool.add_module(0, unit_box(Vec3::new(2.0, 0.5, 0.0)));
+    tool.contacts
+        .set(ContactPair::new(rubber, ContactSurface::Ground, 0.3, 0.0));
+    tool.contacts
+        .set(ContactPair::new(plastic, plastic, 0.4, 0.2));
+    body.merge(tool, 1, None).unwrap();
+    assert_eq!(body.contacts.pairs.len(), 2);
+    assert_eq!(
+        body.contacts
+            .get(rubber, ContactSurface::Ground)
+            .unwrap()
+            .friction,
+        1.2
+    );
+    assert!(body.contacts.get(plastic, plastic).is_some());
+    assert!(body.contacts.get(plastic, rubber).is_none());
+}
+
+#[test]
+fn test_voxelize_estimates_volume() {
+    let mut bp = RobotBlueprint::new();
+    // 0.2 x 1.0 x 0.2 box = 0.04 m³
+    bp.add_module(0, unit_box(Vec3::new(0.0, 0.5, 0.0)));
+
+    let grid = bp.voxelize(0.05);
+    assert_eq!(grid.dims.to_array(), [4, 20, 4]);
+    assert!((grid.volume() - 0.04).abs() < 1e-4);
+    assert_eq!(grid.overlap_count(), 0);
+    assert!(bp.voxelize(0.0).cells.is_empty());
+}
+
+#[test]
+fn test_print_tree_indents_children() {
+    let mut bp = RobotBlueprint::new();
+    for id in 0..3 {
+        bp.add_module(id, unit_box(Vec3::ZERO));
+    }
+    bp.add_joint(joint(0, 1));
+    bp.add_joint(joint(0, 2));
+
+    let tree = bp.print_tree();
+    let lines: Vec<&str> = tree.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "#0 Box(0.20 x 1.00 x 0.20) 1.00 kg");
+    assert!(lines[1].starts_with("├── #1 "));
+    assert!(lines[2].starts_with("└── #2 "));
+    assert!(lines[2].ends_with("[Hinge]"));
+}
+
+#[test]
+fn test_lod_merges_fixed_joints() {
+    // 0 -fixed-> 1 -hinge-> 2, with a touch sensor on module 1
+    let mut bp = RobotBlueprint::new();
+    for id in 0..3 {
+        bp.add_module(id, unit_box(Vec3::new(0.0, 0.5 + id as f32, 0.0)));
+    }
+    bp.add_joint(JointDefinition {
+        joint_type: JointType::Fixed,
+        ..joint(0, 1)
+    });
+    bp.add_joint(joint(1, 2));
+    bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+        sensor_type: SensorType::Touch,
+        local_position: Vec3::new(0.1, 0.0, 0.0),
+        local_rotation: Quat::IDENTITY,
+        attachment: SensorAttachment::Module,
+        timing: Default::default(),
+    });
+
+    let lod = bp.lod(10);
+    assert_eq!(lod.levels.len(), MAX_LOD_LEVELS);
+    assert_eq!(lod.level(0).unwrap().modules.len(), 3);
+
+    let merged = lod.level(1).unwrap();
+    assert_eq!(merged.modules.len(), 2);
+    let hull = &merged.modules[&0];
+    let ShapePrimitive::Box(half) = hull.shape else {
+        panic!("hulls are boxes");
+    };
+    assert!((half - Vec3::new(0.1, 1.0, 0.1)).length() < 1e-5);
+    assert!((hull.transform.0 - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+    assert!((hull.mass - 2.0).abs() < 1e-5);
+    assert!((hull.pivot_offset - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+    // The hinge still sits where it did, now on the hull.
+    let hinge = &merged.joints[0];
+    assert_eq!((hinge.parent_id, hinge.child_id), (0, 2));
+    assert!((hinge.anchor_parent - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+    let (pos, _) = merged.sensor_pose(0, &hull.sensors[0]).unwrap();
+    assert!((pos - Vec3::new(0.1, 1.5, 0.0)).length() < 1e-5);
+
+    assert!(lod.level(2).unwrap().modules[&0].sensors.is_empty());
+    let coarsest = lod.coarsest().unwrap();
+    assert_eq!(coarsest.modules.len(), 1);
+    assert!(coarsest.joints.is_empty());
+    assert!((coarsest.modules[&0].mass - 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_self_collision_exclusions() {
+    // Root 0 with three children side by side (1 and 2 overlap, 3 is 0.15 m from 2),
+    // and 4 on top of 1.
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(0, unit_box(Vec3::ZERO));
+    bp.add_module(1, unit_box(Vec3::new(0.0, 1.0, 0.0)));
+    bp.add_module(2, unit_box(Vec3::new(0.15, 1.0, 0.0)));
+    bp.add_module(3, unit_box(Vec3::new(0.5, 1.0, 0.0)));
+    bp.add_module(4, unit_box(Vec3::new(0.0, 2.0, 0.0)));
+    for (parent, child) in [(0, 1), (0, 2), (0, 3), (1, 4)] {
+        bp.add_joint(joint(parent, child));
+    }
+
+    use ExclusionReason::*;
+    let expected = |extra: &[(ModuleId, ModuleId, ExclusionReason)]| {
+        let mut pairs = vec![
+            (0, 1, Adjacent),
+            (0, 2, Adjacent),
+            (0, 3, Adjacent),
+            (1, 4, Adjacent),
+        ];
+        pairs.extend_from_slice(extra);
+        pairs.sort_by_key(|&(a, b, _)| (a, b));
+        pairs
+            .into_iter()
+            .map(|(a, b, reason)| CollisionExclusion::new(a, b, reason))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        bp.self_collision_exclusions(0.01),
+        expected(&[(1, 2, Overlapping)])
+    );
+    assert_eq!(
+        bp.self_collision_exclusions(0.2),
+        expected(&[(1, 2, Overlapping), (2, 3, Overlapping)])
+    );
+
+    bp.update_collision_exclusions(0.01);
+    let subtree = bp.extract_subtree(1).unwrap();
+    assert_eq!(
+        subtree.collision_exclusions,
+        vec![CollisionExclusion::new(0, 1, Adjacent)]
+    );
+}
+
+#[test]
+fn test_compact_debug_is_rounded_and_sorted() {
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(0, unit_box(Vec3::new(0.0, 0.5, 0.0)));
+    bp.add_module(1, unit_box(Vec3::new(-0.00001, 1.5, 0.0))); // rounds to -0
+    bp.add_joint(joint(0, 1));
+    bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+        sensor_type: SensorType::IMU,
+        local_position: Vec3::new(0.0, 0.123456, 0.0),
+        local_rotation: Quat::IDENTITY,
+        attachment: SensorAttachment::Module,
+        timing: SensorTiming {
+            update_rate: Some(200.0),
+            ..Default::default()
+        },
+    });
+
+    let text = format!("{:?}", bp.compact_debug().precision(2));
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "RobotBlueprint { roots: [0], modules: 2, joints: 1 }",
+            "  #0 Box(0.2 x 1 x 0.2) at (0, 0.5, 0) rot (0, 0, 0, 1) mass 1 density 100 material 0",
+            "  #1 Box(0.2 x 1 x 0.2) at (0, 1.5, 0) rot (0, 0, 0, 1) mass 1 density 100 material 0 sensors [IMU at (0, 0.12, 0) 200 Hz]",
+            "  0 -> 1 Hinge axis (1, 0, 0) anchors (0, 0.5, 0) / (0, -0.5, 0)",
+        ]
+    );
+}
+
+#[test]
+fn test_recenter_moves_com_to_origin_and_aligns_axes() {
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(0, unit_box(Vec3::new(2.0, 0.5, -1.0)));
+    bp.add_module(1, unit_box(Vec3::new(2.0, 1.5, -1.0)));
+    let (translation, rotation) = bp.recenter(RecenterOrigin::CenterOfMass, false);
+    assert_eq!(rotation, Quat::IDENTITY);
+    assert!(translation.distance(Vec3::new(-2.0, -1.0, 1.0)) < 1e-5);
+    assert!(bp.modules[&1].transform.0.distance(Vec3::Y * 0.5) < 1e-5);
+
+    // A flat box grown at an odd angle: longest side to X, shortest to Z.
+    let tilted = Quat::from_euler(glam::EulerRot::YXZ, 0.7, -0.4, 1.1);
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(
+        0,
+        RobotModule::new(
+            ShapePrimitive::Box(Vec3::new(0.3, 0.1, 0.2)),
+            100.0,
+            (Vec3::new(1.0, 2.0, 3.0), tilted),
+        ),
+    );
+    bp.recenter(RecenterOrigin::BoundsCenter, true);
+    let (pos, rot) = bp.modules[&0].transform;
+    assert!(pos.length() < 1e-4);
+    assert!((rot * Vec3::X).dot(Vec3::X).abs() > 0.9999);
+    assert!((rot * Vec3::Z).dot(Vec3::Y).abs() > 0.9999);
+    assert!((rot * Vec3::Y).dot(Vec3::Z).abs() > 0.9999);
+}
+
+#[test]
+fn test_convert_coordinate_conventions() {
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(0, unit_box(Vec3::new(0.0, 0.5, 0.0)));
+    let tilted = Quat::from_rotation_z(0.4) * Quat::from_rotation_x(0.3);
+    bp.add_module(
+        1,
+        RobotModule::new(
+            ShapePrimitive::Box(Vec3::new(0.1, 0.5, 0.1)),
+            100.0,
+            (Vec3::new(0.3, 1.2, 0.2), tilted),
+        ),
+    );
+    bp.add_joint(JointDefinition {
+        parent_id: 0,
+        child_id: 1,
+        anchor_parent: Vec3::new(0.05, 0.5, 0.0),
+        anchor_child: tilted.inverse() * (Vec3::new(0.05, 1.0, 0.0) - Vec3::new(0.3, 1.2, 0.2)),
+        joint_type: JointType::Hinge,
+        axis: Vec3::X,
+        child_axis: tilted.inverse() * Vec3::X,
+        rest_displacement: 0.0,
+        limits: None,
+        transmission: None,
+        actuator: None,
+    });
+    let world_anchor = |bp: &RobotBlueprint, id: ModuleId, local: Vec3| {
+        let (pos, rot) = bp.modules[&id].transform;
+        pos + rot * local
+    };
+
+    let same = convert(&bp, Convention::YUpRightHanded);
+    assert!(same.modules[&1].transform.1.angle_between(tilted) < 1e-5);
+    assert_eq!(same.joints[0].axis, Vec3::X);
+    for convention in [
+        Convention::ZUpRightHanded,
+        Convention::YUpLeftHanded,
+        Convention::ZUpLeftHanded,
+    ] {
+        let basis = convention.basis();
+        let converted = convert(&bp, convention);
+        // Both joint anchors still meet, at the converted world point.
+        let joint = &converted.joints[0];
+        let parent = world_anchor(&converted, 0, joint.anchor_parent);
+        let child = world_anchor(&converted, 1, joint.anchor_child);
+        assert!(parent.distance(child) < 1e-5, "{convention:?}");
+        assert!(parent.distance(basis * Vec3::new(0.05, 1.0, 0.0)) < 1e-5);
+        // The hinge turns the same way, so its axis is a pseudovector.
+        let sign = if convention.is_left_handed() {
+            -1.0
+        } else {
+            1.0
+        };
+        let axis = converted.modules[&0].transform.1 * joint.axis;
+        assert!(
+            axis.distance(basis * Vec3::X * sign) < 1e-5,
+            "{convention:?}"
+        );
+        let child_axis = converted.modules[&1].transform.1 * joint.child_axis;
+        assert!(child_axis.distance(axis) < 1e-5);
+        assert!(
+            converted.modules[&1]
+                .bounds
+                .center
+                .distance(basis * Vec3::new(0.3, 1.2, 0.2))
+                < 1e-5
+        );
+    }
+    let z_up = convert(&bp, Convention::ZUpRightHanded);
+    assert!(z_up.modules[&0].transform.0.distance(Vec3::Z * 0.5) < 1e-6);
+
+    // Child-frame axes missing from older files are derived from the rest pose on load.
+    let mut json = serde_json::to_value(&bp).unwrap();
+    json["joints"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("child_axis");
+    let bytes = serde_json::to_vec(&json).unwrap();
+    let loaded = RobotBlueprint::decode(&bytes, BlueprintFormat::Json).unwrap();
+    assert!(
+        loaded.joints[0]
+            .child_axis
+            .distance(bp.joints[0].child_axis)
+            < 1e-5
+    );
+}
+
+#[test]
+fn test_module_bounds() {
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(0, unit_box(Vec3::ZERO));
+    bp.add_module(1, unit_box(Vec3::new(3.0, 0.0, 0.0)));
+    let radius = Vec3::new(0.1, 0.5, 0.1).length();
+    let bounds = bp.modules[&1].bounds;
+    assert!(bounds.center.distance(Vec3::new(3.0, 0.0, 0.0)) < 1e-6);
+    assert!((bounds.radius - radius).abs() < 1e-6);
+
+    let query = ModuleBounds {
+        center: Vec3::new(1.5, 0.0, 0.0),
+        radius: 0.5,
+    };
+    assert!(bp.modules_near(&query, 0.0).is_empty());
+    assert_eq!(bp.modules_near(&query, 0.6), [0, 1]);
+
+    let all = bp.bounding_sphere().unwrap();
+    assert!(all.center.distance(Vec3::new(1.5, 0.0, 0.0)) < 1e-5);
+    assert!((all.radius - (1.5 + radius)).abs() < 1e-5);
+    assert!(RobotBlueprint::new().bounding_sphere().is_none());
+
+    // Recentering moves the spheres with the modules.
+    bp.recenter(RecenterOrigin::BoundsCenter, false);
+    assert!(
+        bp.modules[&1]
+            .bounds
+            .center
+            .distance(Vec3::new(1.5, 0.0, 0.0))
+            < 1e-5
+    );
+
+    // Bounds missing from older files are recomputed on load.
+    let mut json = serde_json::to_value(&bp).unwrap();
+    for module in json["modules"].as_object_mut().unwrap().values_mut() {
+        module.as_object_mut().unwrap().remove("bounds");
+    }
+    let bytes = serde_json::to_vec(&json).unwrap();
+    let loaded = RobotBlueprint::decode(&bytes, BlueprintFormat::Json).unwrap();
+    assert_eq!(loaded.modules[&1].bounds, bp.modules[&1].bounds);
+}
+
+#[test]
+fn test_spatial_index() {
+    let mut bp = RobotBlueprint::new();
+    for i in 0..20 {
+        bp.add_module(i, unit_box(Vec3::new(i as f32 * 0.3, 0.0, 0.0)));
+    }
+    bp.add_module(
+        20,
+        RobotModule::new(
+            ShapePrimitive::Sphere(10.0),
+            100.0,
+            (Vec3::new(0.0, 30.0, 0.0), Quat::IDENTITY),
+        ),
+    );
+    let index = bp.spatial_index();
+    assert_eq!(index.len(), 21);
+
+    for query in [
+        ModuleBounds {
+            center: Vec3::new(1.0, 0.2, 0.0),
+            radius: 0.3,
+        },
+        ModuleBounds {
+            center: Vec3::new(0.0, 25.0, 0.0),
+            radius: 1.0,
+        },
+        ModuleBounds {
+            center: Vec3::ZERO,
+            radius: 100.0,
+        },
+    ] {
+        for margin in [0.0, 0.5] {
+            assert_eq!(
+                index.overlapping(&query, margin),
+                bp.modules_near(&query, margin)
+            );
+        }
+    }
+
+    let (id, distance) = index.nearest(Vec3::new(0.95, 0.0, 0.0)).unwrap();
+    assert_eq!(id, 3);
+    assert!(distance.abs() < 1e-6);
+    let (id, distance) = index.nearest(Vec3::new(5.7, 0.0, 2.0)).unwrap();
+    assert_eq!(id, 19);
+    assert!((distance - 1.9).abs() < 1e-5);
+    let (id, distance) = index.nearest(Vec3::new(0.0, 45.0, 0.0)).unwrap();
+    assert_eq!(id, 20);
+    assert!((distance - 5.0).abs() < 1e-4);
+    let distance = index.distance_to(19, Vec3::new(5.7, 0.0, 2.0)).unwrap();
+    assert!((distance - 1.9).abs() < 1e-5);
+    assert!(index.distance_to(21, Vec3::ZERO).is_none());
+    assert!(
+        RobotBlueprint::new()
+            .spatial_index()
+            .nearest(Vec3::ZERO)
+            .is_none()
+    );
+}
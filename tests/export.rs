@@ -0,0 +1,1240 @@
+// tests/export.rs
+use glam::{Quat, Vec3};
+use std::collections::HashMap;
+use symbios_robot::backends::JOINT_FRAME_AXIS;
+use symbios_robot::{
+    BINARY_FORMAT_VERSION, BINARY_MAGIC, BinaryError, BlueprintFormat, ConeLimit, ContactPair,
+    ContactSurface, ExportGeometry, JointDefinition, JointFrame, JointLimit, JointType,
+    MjcfOptions, ModuleId, PhysicsBackend, ProjectionPlane, RobotBlueprint, RobotConfig,
+    RobotModule, SdfOptions, SensorAttachment, SensorMount, SensorTiming, SensorType, ShapeKind,
+    ShapePrimitive, StlFormat, StlOptions, Transmission, TriMesh, UpAxis, UrdfOptions, WorldJoint,
+    population_batches,
+};
+
+const SHAPES: [ShapePrimitive; 4] = [
+    ShapePrimitive::Box(Vec3::new(0.1, 0.5, 0.2)),
+    ShapePrimitive::Cylinder {
+        radius: 0.1,
+        height: 1.0,
+    },
+    ShapePrimitive::Sphere(0.3),
+    ShapePrimitive::Capsule {
+        radius: 0.1,
+        height: 0.6,
+    },
+];
+
+/// Every directed edge must be matched by exactly one opposite edge.
+fn assert_watertight(mesh: &TriMesh) {
+    let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+    for t in &mesh.triangles {
+        for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+            *edges.entry((a, b)).or_default() += 1;
+        }
+    }
+    for (&(a, b), &n) in &edges {
+        assert_eq!(n, 1, "edge {a}-{b} used {n} times");
+        assert_eq!(edges.get(&(b, a)), Some(&1), "edge {a}-{b} is open");
+    }
+}
+
+fn two_boxes() -> RobotBlueprint {
+    let shape = ShapePrimitive::Box(Vec3::new(0.1, 0.5, 0.1));
+    let mut bp = RobotBlueprint::new();
+    bp.add_module(
+        0,
+        RobotModule::new(shape, 100.0, (Vec3::Y * 0.5, Quat::IDENTITY)),
+    );
+    bp.add_module(
+        1,
+        RobotModule::new(shape, 100.0, (Vec3::Y * 1.5, Quat::IDENTITY)),
+    );
+    bp.add_joint(JointDefinition {
+        parent_id: 0,
+        child_id: 1,
+        anchor_parent: Vec3::new(0.0, 0.5, 0.0),
+        anchor_child: Vec3::new(0.0, -0.5, 0.0),
+        joint_type: JointType::Hinge,
+        axis: Vec3::X,
+        child_axis: Vec3::X,
+        rest_displacement: 0.0,
+        limits: None,
+        transmission: None,
+        actuator: None,
+    });
+    bp
+}
+
+#[test]
+fn test_tessellation_is_closed_and_outward() {
+    for shape in SHAPES {
+        let mesh = shape.tessellate(48);
+        assert_watertight(&mesh);
+        let exact = shape.volume();
+        let volume = mesh.signed_volume();
+        assert!(volume > 0.0, "{shape} is inside out");
+        assert!(
+            (volume - exact).abs() / exact < 0.02,
+            "{shape}: {volume} vs {exact}"
+        );
+    }
+}
+
+#[test]
+fn test_shape_accessors_agree_with_tessellation() {
+    for shape in SHAPES {
+        let mesh = shape.tessellate(48);
+        let max = mesh.vertices.iter().fold(Vec3::ZERO, |m, v| m.max(v.abs()));
+        assert!(max.distance(shape.half_extents()) < 1e-4, "{shape}");
+        assert_eq!(shape.base_pivot().y, -shape.height() / 2.0);
+
+        let mut taller = shape;
+        taller.set_height(shape.height() * 2.0);
+        assert!((taller.height() - shape.height() * 2.0).abs() < 1e-6);
+        let mut wider = shape;
+        wider.set_radius(0.5);
+        assert_eq!(wider.radius(), shape.radius().map(|_| 0.5));
+    }
+    assert_eq!(SHAPES[0].radius(), None);
+    assert_eq!(SHAPES[3].half_extents(), Vec3::new(0.1, 0.4, 0.1));
+}
+
+#[test]
+fn test_stl_parts_and_manifest() {
+    let bp = two_boxes();
+    let parts = bp.stl_parts(&StlOptions::default());
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].entry.module_id, 0);
+    assert_eq!(parts[0].entry.file_name, "module_0000.stl");
+    assert_eq!(parts[1].entry.anchors[0].other_module, 0);
+    assert_eq!(
+        parts[1].entry.anchors[0].position,
+        Vec3::new(0.0, -0.5, 0.0)
+    );
+
+    // Binary STL: 80-byte header, triangle count, 50 bytes per triangle.
+    let data = &parts[0].data;
+    let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+    assert_eq!(count, 12);
+    assert_eq!(data.len(), 84 + 50 * count);
+
+    // Marked anchors add an inside-out cavity that keeps the part closed.
+    let marked = bp.stl_parts(&StlOptions {
+        format: StlFormat::Ascii,
+        mark_anchors: true,
+        ..Default::default()
+    });
+    let text = String::from_utf8(marked[0].data.clone()).unwrap();
+    assert!(text.starts_with("solid module_0"));
+    assert_eq!(text.matches("facet normal").count(), 12 + 8);
+}
+
+#[test]
+fn test_whole_robot_mesh_stl_and_obj() {
+    let bp = two_boxes();
+    let mesh = bp.to_trimesh();
+    assert_eq!(mesh.vertices.len(), 16);
+    assert_eq!(mesh.triangles.len(), 24);
+    assert!((mesh.signed_volume() - 0.08).abs() < 1e-5);
+    // The second box sits at its world position.
+    let top = mesh.vertices.iter().map(|v| v.y).fold(f32::MIN, f32::max);
+    assert!((top - 2.0).abs() < 1e-5);
+
+    let stl = bp.to_stl(&StlOptions::default());
+    assert_eq!(stl.len(), 84 + 50 * 24);
+
+    let obj = bp.to_obj();
+    assert_eq!(obj.matches("\no module_").count(), 2);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 16);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 24);
+    // Faces of the second object index past the first object's vertices.
+    let last = obj.lines().rfind(|l| l.starts_with("f ")).unwrap();
+    assert!(
+        last.split_whitespace()
+            .skip(1)
+            .all(|i| i.parse::<u32>().unwrap() > 8)
+    );
+}
+
+#[test]
+fn test_to_scad_lists_parts_and_assembly() {
+    let mut bp = two_boxes();
+    bp.add_module(
+        2,
+        RobotModule::new(SHAPES[1], 100.0, (Vec3::ZERO, Quat::from_rotation_z(1.0))),
+    );
+    let scad = bp.to_scad();
+    assert!(scad.contains("part_0_size = [0.200000, 1.000000, 0.200000];"));
+    assert!(scad.contains("module part_1() {"));
+    assert!(scad.contains("cylinder(h = part_2_height, r = part_2_radius, center = true)"));
+    assert!(scad.contains("translate([0.000000, 1.500000, 0.000000])"));
+    assert!(scad.contains("rotate(a = 57.2958, v = [0.000000, 0.000000, 1.000000]) part_2();"));
+    assert!(scad.trim_end().ends_with("scale(unit) robot();"));
+}
+
+#[test]
+fn test_to_sdf_maps_links_joints_and_sensors() {
+    let mut bp = two_boxes();
+    let sensors = &mut bp.modules.get_mut(&1).unwrap().sensors;
+    for (sensor_type, attachment) in [
+        (SensorType::IMU, SensorAttachment::Module),
+        (SensorType::Encoder, SensorAttachment::Joint),
+    ] {
+        sensors.push(SensorMount {
+            sensor_type,
+            local_position: Vec3::ZERO,
+            local_rotation: Quat::IDENTITY,
+            attachment,
+            timing: Default::default(),
+        });
+    }
+
+    let sdf = bp.to_sdf("walker");
+    assert!(sdf.contains("<sdf version=\"1.9\""));
+    assert!(sdf.contains("<model name=\"walker\">"));
+    assert!(sdf.contains("<link name=\"module_1\">"));
+    assert!(sdf.contains("<pose>0.000000 1.500000 0.000000 0.000000 0.000000 0.000000</pose>"));
+    assert!(sdf.contains("<box><size>0.200000 1.000000 0.200000</size></box>"));
+    assert!(sdf.contains("<mass>4.000000</mass>"));
+    assert!(sdf.contains("<xyz>1.000000 0.000000 0.000000</xyz>"));
+    assert!(sdf.contains("<sensor name=\"imu_0\" type=\"imu\">"));
+
+    // The encoder measures the joint, so it is written inside it.
+    let joint = sdf.split("<joint name=\"joint_0_1\"").nth(1).unwrap();
+    let joint = joint.split("</joint>").next().unwrap();
+    assert!(joint.starts_with(" type=\"revolute\">"));
+    assert!(joint.contains("type=\"custom\" gz:type=\"encoder\""));
+    assert!(sdf.trim_end().ends_with("</sdf>"));
+}
+
+#[test]
+fn test_to_xacro_exposes_dimensions_as_properties() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 1.0,
+        effort: 5.0,
+        velocity: 2.0,
+        cone: None,
+    });
+    let xacro = bp.to_xacro("two boxes", &RobotConfig::default());
+    assert!(xacro.contains("<robot name=\"two boxes\""));
+    assert!(xacro.contains("<xacro:property name=\"default_length\" value=\"1.000000\"/>"));
+    // Box extents are multiples of the defaults (length 1.0, width 0.2).
+    assert!(xacro.contains(
+        "<xacro:property name=\"module_1_length\" value=\"${1.000000 * default_length}\"/>"
+    ));
+    assert!(xacro.contains(
+        "<xacro:property name=\"module_1_width\" value=\"${1.000000 * default_width}\"/>"
+    ));
+    assert!(
+        xacro.contains("<box size=\"${module_0_width} ${module_0_length} ${module_0_depth}\"/>")
+    );
+    // The joint sits on the top face of module 0, whose link frame is its center.
+    assert!(xacro.contains("<origin xyz=\"${0} ${0.500000 * (module_0_length)} ${0}\" rpy=\"0.000000 0.000000 0.000000\"/>"));
+    assert!(xacro.contains("<joint name=\"joint_0_1\" type=\"revolute\">"));
+    assert!(xacro.contains("<limit lower=\"-1.000000\" upper=\"1.000000\""));
+    // Module 1's link frame is the joint anchor, half a length below its center.
+    assert!(
+        xacro.contains("<origin xyz=\"${0} ${0.500000 * (module_1_length)} ${0}\" rpy=\"0 0 0\"/>")
+    );
+    assert_eq!(xacro.matches("<link name=").count(), 3);
+}
+
+#[test]
+fn test_ros_description_plain_urdf_and_joint_manifest() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 1.0,
+        effort: 5.0,
+        velocity: 2.0,
+        cone: None,
+    });
+    let description = bp.ros_description("two boxes");
+    let urdf = &description.urdf;
+    assert!(!urdf.contains("xacro") && !urdf.contains("${"));
+    assert_eq!(urdf, &bp.to_urdf("two boxes"));
+    assert!(urdf.contains("<box size=\"0.200000 1.000000 0.200000\"/>"));
+    // The joint sits on the top face of module 0, whose link frame is its center.
+    assert!(urdf.contains(
+        "<origin xyz=\"0.000000 0.500000 0.000000\" rpy=\"0.000000 0.000000 0.000000\"/>"
+    ));
+    assert_eq!(urdf.matches("<link name=").count(), 3);
+
+    assert_eq!(description.joint_names(), ["joint_0_1"]);
+    let joint = &description.joints[0];
+    assert_eq!(joint.joint_type, "revolute");
+    assert_eq!(
+        (joint.parent_link.as_str(), joint.child_link.as_str()),
+        ("module_0", "module_1")
+    );
+    assert_eq!(joint.limits, Some((-1.0, 1.0)));
+
+    let yaml = description.params_yaml("robot_state_publisher");
+    assert!(
+        yaml.starts_with("robot_state_publisher:\n  ros__parameters:\n    robot_description: |\n")
+    );
+    assert!(yaml.contains("\n      <robot name=\"two boxes\">\n"));
+
+    // Fixed joints are static transforms, not joint states.
+    bp.joints[0].joint_type = JointType::Fixed;
+    assert!(bp.ros_description("two boxes").joints.is_empty());
+}
+
+#[test]
+fn test_urdf_writes_ball_joints_as_three_revolutes() {
+    let mut bp = two_boxes();
+    bp.joints[0].joint_type = JointType::Ball;
+    bp.joints[0].limits = Some(JointLimit {
+        min: -0.2,
+        max: 0.2,
+        effort: 5.0,
+        velocity: 10.0,
+        cone: Some(ConeLimit {
+            swing1: 0.5,
+            swing2: 0.3,
+            twist: 0.2,
+        }),
+    });
+    let urdf = bp.to_urdf("robot");
+    let joint = |name: &str| {
+        let start = urdf.find(&format!("<joint name=\"{name}\"")).unwrap();
+        let end = start + urdf[start..].find("</joint>").unwrap();
+        urdf[start..end].to_owned()
+    };
+    // Swings about the perpendicular axes, then the twist about the joint axis.
+    let swing1 = joint("joint_0_1_swing1");
+    assert!(swing1.contains("type=\"revolute\""));
+    assert!(swing1.contains("<origin xyz=\"0.000000 0.500000 0.000000\""));
+    assert!(swing1.contains("<parent link=\"module_0\"/>"));
+    assert!(swing1.contains("<child link=\"module_1_swing1\"/>"));
+    assert!(swing1.contains("<axis xyz=\"0.000000 1.000000 0.000000\"/>"));
+    assert!(swing1.contains(
+        "<limit lower=\"-0.500000\" upper=\"0.500000\" effort=\"5.000000\" velocity=\"10.000000\"/>"
+    ));
+    let swing2 = joint("joint_0_1_swing2");
+    assert!(swing2.contains("<origin xyz=\"0 0 0\" rpy=\"0 0 0\"/>"));
+    assert!(swing2.contains("<child link=\"module_1_swing2\"/>"));
+    assert!(swing2.contains("<axis xyz=\"0.000000 0.000000 1.000000\"/>"));
+    assert!(swing2.contains("<limit lower=\"-0.300000\" upper=\"0.300000\""));
+    let twist = joint("joint_0_1_twist");
+    assert!(twist.contains("<parent link=\"module_1_swing2\"/>"));
+    assert!(twist.contains("<child link=\"module_1\"/>"));
+    assert!(twist.contains("<axis xyz=\"1.000000 0.000000 0.000000\"/>"));
+    assert!(twist.contains("<limit lower=\"-0.200000\" upper=\"0.200000\""));
+    // The links in between carry no mass.
+    assert!(urdf.contains("<link name=\"module_1_swing1\"/>"));
+    assert!(urdf.contains("<link name=\"module_1_swing2\"/>"));
+    assert_eq!(urdf.matches("<link name=").count(), 5);
+    assert_eq!(urdf.matches("<inertial>").count(), 2);
+    assert!(!urdf.contains("<joint name=\"joint_0_1\" "));
+
+    let names = bp.ros_description("robot").joint_names().join(" ");
+    assert_eq!(names, "joint_0_1_swing1 joint_0_1_swing2 joint_0_1_twist");
+
+    // Without a cone every stage turns freely.
+    bp.joints[0].limits = None;
+    let urdf = bp.to_urdf("robot");
+    assert_eq!(urdf.matches("type=\"continuous\"").count(), 3);
+    assert!(!urdf.contains("<limit"));
+}
+
+#[test]
+fn test_urdf_transmissions_carry_the_gear_ratio() {
+    let mut bp = two_boxes();
+    assert!(!bp.to_urdf("robot").contains("<transmission"));
+
+    bp.joints[0].transmission = Some(Transmission {
+        gear_ratio: 50.0,
+        ..Default::default()
+    });
+    let urdf = bp.to_urdf("robot");
+    assert!(urdf.contains(
+        "  <transmission name=\"joint_0_1_transmission\">\n    \
+         <type>transmission_interface/SimpleTransmission</type>\n    \
+         <joint name=\"joint_0_1\">"
+    ));
+    assert!(urdf.contains("<mechanicalReduction>50.000000</mechanicalReduction>"));
+    assert!(
+        urdf.find("<transmission").unwrap() > urdf.find("<joint name=\"joint_0_1\" type=").unwrap()
+    );
+
+    // A fixed joint has nothing to drive.
+    bp.joints[0].joint_type = JointType::Fixed;
+    assert!(!bp.to_urdf("robot").contains("<transmission"));
+}
+
+#[test]
+fn test_to_mjcf_nests_bodies_in_parent_frames() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 1.0,
+        effort: 5.0,
+        velocity: 2.0,
+        cone: None,
+    });
+    bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+        sensor_type: SensorType::IMU,
+        local_position: Vec3::ZERO,
+        local_rotation: Quat::IDENTITY,
+        attachment: SensorAttachment::Module,
+        timing: Default::default(),
+    });
+
+    let mjcf = bp.to_mjcf("walker");
+    assert!(mjcf.contains("<mujoco model=\"walker\">"));
+    // The child opens before its parent closes, one metre up in the parent's frame.
+    let child = mjcf.find("<body name=\"module_1\" pos=\"0.000000 1.000000 0.000000\"");
+    assert!(child.unwrap() < mjcf.find("</body>").unwrap());
+    assert!(mjcf.contains("<freejoint name=\"root_0\"/>"));
+    assert!(mjcf.contains(
+        "<joint name=\"joint_0_1\" type=\"hinge\" pos=\"0.000000 -0.500000 0.000000\" \
+         axis=\"1.000000 0.000000 0.000000\" limited=\"true\" range=\"-1.000000 1.000000\"/>"
+    ));
+    assert!(
+        mjcf.contains("<geom name=\"module_0\" type=\"box\" size=\"0.100000 0.500000 0.100000\"/>")
+    );
+    assert!(mjcf.contains("ctrlrange=\"-5.000000 5.000000\""));
+    assert!(mjcf.contains("<gyro name=\"module_1_imu_0_gyro\" site=\"module_1_imu_0\"/>"));
+}
+
+#[test]
+fn test_exporters_place_joints_at_the_pivot() {
+    let mut bp = two_boxes();
+    // Pivot offsets missing from older files are restored on load.
+    let mut json = serde_json::to_value(&bp).unwrap();
+    for module in json["modules"].as_object_mut().unwrap().values_mut() {
+        module.as_object_mut().unwrap().remove("pivot_offset");
+    }
+    let bytes = serde_json::to_vec(&json).unwrap();
+    let loaded = RobotBlueprint::decode(&bytes, BlueprintFormat::Json).unwrap();
+    for id in [0, 1] {
+        assert_eq!(loaded.modules[&id].pivot_offset, Vec3::new(0.0, 0.5, 0.0));
+    }
+
+    bp.modules.get_mut(&1).unwrap().pivot_offset = Vec3::new(0.0, 0.3, 0.0);
+    assert!(bp.to_sdf("robot").contains(
+        "<joint name=\"joint_0_1\" type=\"revolute\">\n      \
+         <pose>0.000000 -0.300000 0.000000 0.000000 0.000000 0.000000</pose>"
+    ));
+    assert!(
+        bp.to_mjcf("robot").contains(
+            "<joint name=\"joint_0_1\" type=\"hinge\" pos=\"0.000000 -0.300000 0.000000\""
+        )
+    );
+    // The child link's frame sits at the pivot, so its box is 0.3 m up from there.
+    let urdf = bp.to_urdf("robot");
+    let child = urdf.split("<link name=\"module_1\">").nth(1).unwrap();
+    assert!(child.contains("<origin xyz=\"0.000000 0.300000 0.000000\""));
+}
+
+#[test]
+fn test_from_mjcf_round_trips_and_reads_capsules() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 0.5,
+        effort: 5.0,
+        velocity: 10.0,
+        cone: None,
+    });
+    let back = RobotBlueprint::from_mjcf(&bp.to_mjcf("two_boxes")).unwrap();
+    assert_eq!(back.modules.len(), 2);
+    assert_eq!(back.roots, [0]);
+    for id in [0, 1] {
+        let (pos, rot) = back.modules[&id].transform;
+        assert!(pos.distance(bp.modules[&id].transform.0) < 1e-4);
+        assert!(rot.angle_between(Quat::IDENTITY) < 1e-4);
+        assert!((back.modules[&id].mass - bp.modules[&id].mass).abs() < 1e-3);
+    }
+    let joint = &back.joints[0];
+    assert_eq!(joint.joint_type, JointType::Hinge);
+    assert!(joint.anchor_child.distance(Vec3::new(0.0, -0.5, 0.0)) < 1e-4);
+    assert!(back.modules[&1].pivot_offset.distance(-joint.anchor_child) < 1e-4);
+    assert!(joint.axis.distance(Vec3::X) < 1e-4);
+    let limits = joint.limits.unwrap();
+    assert!((limits.min + 1.0).abs() < 1e-5 && (limits.effort - 5.0).abs() < 1e-5);
+
+    // A Z-up leg in degrees, hanging below a welded torso.
+    let xml = r#"
+        <mujoco>
+          <default><joint limited="true"/></default>
+          <worldbody>
+            <geom type="plane" size="10 10 0.1"/>
+            <body name="torso" pos="0 0 1">
+              <geom type="sphere" size="0.25"/>
+              <body name="leg">
+                <joint name="hip" type="hinge" axis="0 1 0" range="-30 30"/>
+                <geom type="capsule" fromto="0 0 0 0 0 -0.4" size="0.05"/>
+              </body>
+            </body>
+          </worldbody>
+        </mujoco>"#;
+    let leg = RobotBlueprint::from_mjcf(xml).unwrap();
+    assert_eq!(leg.modules.len(), 2);
+    assert!(leg.is_welded(0));
+    assert!(leg.modules[&0].transform.0.distance(Vec3::Y) < 1e-5);
+    assert!(matches!(
+        leg.modules[&1].shape,
+        ShapePrimitive::Capsule { height, .. } if (height - 0.4).abs() < 1e-5
+    ));
+    // The capsule runs down the world Y axis, centred 0.2 below the torso.
+    let (pos, rot) = leg.modules[&1].transform;
+    assert!(pos.distance(Vec3::new(0.0, 0.8, 0.0)) < 1e-5);
+    assert!((rot * Vec3::Y).abs().distance(Vec3::Y) < 1e-5);
+    let limits = leg.joints[0].limits.unwrap();
+    assert!((limits.max - 30f32.to_radians()).abs() < 1e-5);
+
+    assert!(RobotBlueprint::from_mjcf("<robot/>").is_err());
+}
+
+/// A sink that accepts a few bytes and then fails.
+struct FailingWriter(usize);
+
+impl std::io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.0 == 0 {
+            return Err(std::io::Error::other("disk full"));
+        }
+        let n = buf.len().min(self.0);
+        self.0 -= n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_exports_match_string_exports() {
+    let bp = two_boxes();
+    let config = RobotConfig::default();
+    let streamed = |write: &dyn Fn(&mut Vec<u8>) -> std::io::Result<()>| {
+        let mut buf = Vec::new();
+        write(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    assert_eq!(
+        streamed(&|w| bp.write_urdf(w, "robot")),
+        bp.to_urdf("robot")
+    );
+    assert_eq!(
+        streamed(&|w| bp.write_xacro(w, "robot", &config)),
+        bp.to_xacro("robot", &config)
+    );
+    assert_eq!(streamed(&|w| bp.write_sdf(w, "robot")), bp.to_sdf("robot"));
+    assert_eq!(
+        streamed(&|w| bp.write_mjcf(w, "robot")),
+        bp.to_mjcf("robot")
+    );
+
+    let err = bp.write_mjcf(FailingWriter(64), "robot").unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+    assert!(bp.write_urdf(FailingWriter(0), "robot").is_err());
+}
+
+#[test]
+fn test_export_options_per_format() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 1.0,
+        effort: 5.0,
+        velocity: 2.0,
+        cone: None,
+    });
+    bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+        sensor_type: SensorType::IMU,
+        local_position: Vec3::ZERO,
+        local_rotation: Quat::IDENTITY,
+        attachment: SensorAttachment::Module,
+        timing: Default::default(),
+    });
+    let config = RobotConfig::default();
+    assert_eq!(
+        bp.to_urdf_with("robot", &UrdfOptions::default()),
+        bp.to_urdf("robot")
+    );
+    assert_eq!(
+        bp.to_xacro_with("robot", &config, &UrdfOptions::default()),
+        bp.to_xacro("robot", &config)
+    );
+    assert_eq!(
+        bp.to_sdf_with("robot", &SdfOptions::default()),
+        bp.to_sdf("robot")
+    );
+    assert_eq!(
+        bp.to_mjcf_with("robot", &MjcfOptions::default()),
+        bp.to_mjcf("robot")
+    );
+
+    let mesh = ExportGeometry::Mesh {
+        directory: "package://robot/meshes".into(),
+    };
+    let urdf = bp.to_urdf_with(
+        "robot",
+        &UrdfOptions {
+            geometry: mesh.clone(),
+            collision_only: true,
+            up_axis: UpAxis::Y,
+            prefix: "left_".into(),
+        },
+    );
+    assert!(urdf.contains("<link name=\"left_base_link\"/>"));
+    assert!(urdf.contains("<joint name=\"left_joint_0_1\" type=\"revolute\">"));
+    assert!(urdf.contains("<child link=\"left_module_1\"/>"));
+    assert!(urdf.contains("<mesh filename=\"package://robot/meshes/module_0001.stl\"/>"));
+    assert!(!urdf.contains("<visual>") && !urdf.contains("<box"));
+    // Y-up keeps the root at its blueprint pose.
+    let root = urdf.split("<joint name=\"left_root_0\"").nth(1).unwrap();
+    let root = root.split("</joint>").next().unwrap();
+    assert!(root.contains("<origin xyz=\"0.000000 0.500000 0.000000\""));
+
+    let sdf = bp.to_sdf_with(
+        "robot",
+        &SdfOptions {
+            sensors: false,
+            prefix: "left_".into(),
+            ..Default::default()
+        },
+    );
+    assert!(sdf.contains("<link name=\"left_module_1\">"));
+    assert!(sdf.contains("<parent>left_module_0</parent>"));
+    assert!(sdf.contains("<visual name=\"visual\">"));
+    assert!(!sdf.contains("<sensor"));
+
+    let mjcf = bp.to_mjcf_with(
+        "robot",
+        &MjcfOptions {
+            geometry: mesh,
+            sensors: false,
+            actuators: false,
+            prefix: "left_".into(),
+            ..Default::default()
+        },
+    );
+    assert!(mjcf.contains(
+        "<mesh name=\"left_module_0\" file=\"package://robot/meshes/module_0000.stl\"/>"
+    ));
+    assert!(mjcf.contains("<geom name=\"left_module_1\" type=\"mesh\" mesh=\"left_module_1\"/>"));
+    assert!(mjcf.contains("<joint name=\"left_joint_0_1\" type=\"hinge\""));
+    assert!(!mjcf.contains("<actuator>") && !mjcf.contains("<sensor>"));
+}
+
+#[cfg(feature = "usd")]
+#[test]
+fn test_usd_stage_and_package() {
+    let bp = two_boxes();
+    let usda = bp.to_usda("my walker");
+    assert!(usda.starts_with("#usda 1.0"));
+    assert!(usda.contains("defaultPrim = \"my_walker\""));
+    assert!(usda.contains("def Xform \"module_1\" ("));
+    assert!(usda.contains("double3 xformOp:translate = (0.000000, 1.500000, 0.000000)"));
+    assert!(usda.contains("def PhysicsRevoluteJoint \"joint_0_1\""));
+    assert!(usda.contains("rel physics:body1 = </my_walker/module_1>"));
+
+    let usdz = bp.to_usdz("my walker");
+    assert_eq!(&usdz[..4], b"PK\x03\x04");
+    let data = usdz.windows(9).position(|w| w == b"#usda 1.0").unwrap();
+    assert_eq!(data % 64, 0);
+    assert_eq!(&usdz[data..data + usda.len()], usda.as_bytes());
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_robot_plugin_spawns_parts_and_joints() {
+    use bevy::prelude::*;
+    use symbios_robot::{RobotJoint, RobotPart, RobotPlugin, RobotRoot, SpawnRobot};
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, RobotPlugin))
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Assets<StandardMaterial>>();
+    let root = app.world_mut().spawn(SpawnRobot(two_boxes())).id();
+    app.update();
+
+    let world = app.world_mut();
+    assert!(world.get::<SpawnRobot>(root).is_none());
+    assert!(world.get::<RobotRoot>(root).is_some());
+    let mut parts = world.query::<(&RobotPart, &Transform, &ChildOf)>();
+    let mut ids: Vec<u32> = parts
+        .iter(world)
+        .map(|(part, transform, parent)| {
+            assert_eq!(parent.parent(), root);
+            assert_eq!(transform.translation.y, part.module_id as f32 + 0.5);
+            part.module_id as u32
+        })
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(ids, [0, 1]);
+    let mut joints = world.query::<(&RobotJoint, &Transform)>();
+    let (joint, transform) = joints.single(world).unwrap();
+    assert_eq!(joint.0.child_id, 1);
+    assert_eq!(transform.translation, Vec3::new(0.0, 0.5, 0.0));
+    // Both boxes share one mesh.
+    assert_eq!(world.resource::<Assets<Mesh>>().len(), 1);
+}
+
+#[test]
+fn test_instantiate_drives_backend_with_matching_frames() {
+    #[derive(Default)]
+    struct Recorder {
+        modules: Vec<(ModuleId, bool)>,
+        sensors: Vec<(usize, Vec3)>,
+        joints: Vec<((usize, JointFrame), (usize, JointFrame))>,
+    }
+    impl PhysicsBackend for Recorder {
+        type Body = usize;
+        fn spawn_module(&mut self, id: ModuleId, _: &RobotModule, welded: bool) -> usize {
+            self.modules.push((id, welded));
+            self.modules.len() - 1
+        }
+        fn spawn_joint(
+            &mut self,
+            _: &JointDefinition,
+            parent: (usize, JointFrame),
+            child: (usize, JointFrame),
+        ) {
+            self.joints.push((parent, child));
+        }
+        fn mount_sensor(&mut self, body: usize, _: &SensorMount, pose: (Vec3, Quat)) {
+            self.sensors.push((body, pose.0));
+        }
+    }
+
+    // Turn the child a quarter about Z so the two frames differ.
+    let mut bp = two_boxes();
+    let turn = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    bp.modules.get_mut(&1).unwrap().transform.1 = turn;
+    bp.joints[0].child_axis = turn.inverse() * Vec3::X;
+    bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+        sensor_type: SensorType::IMU,
+        local_position: Vec3::X * 0.1,
+        local_rotation: Quat::IDENTITY,
+        attachment: SensorAttachment::Joint,
+        timing: Default::default(),
+    });
+    bp.add_world_joint(WorldJoint {
+        child_id: 0,
+        anchor_child: Vec3::ZERO,
+        anchor_world: Vec3::new(0.0, 0.5, 0.0),
+    });
+
+    let mut recorder = Recorder::default();
+    let bodies = bp.instantiate(&mut recorder);
+    assert_eq!(bodies, HashMap::from([(0, 0), (1, 1)]));
+    assert_eq!(recorder.modules, [(0, true), (1, false)]);
+    assert_eq!(recorder.sensors.len(), 1);
+    assert_eq!(recorder.sensors[0].0, 1);
+    assert!((recorder.sensors[0].1 - Vec3::new(0.1, -0.5, 0.0)).length() < 1e-6);
+
+    let [((0, frame1), (1, frame2))] = recorder.joints[..] else {
+        panic!("expected one joint from body 0 to body 1");
+    };
+    assert_eq!(frame1.anchor, bp.joints[0].anchor_parent);
+    assert_eq!(frame2.anchor, bp.joints[0].anchor_child);
+    // Both frames put the hinge axis along X and agree in world space.
+    let world1 = bp.modules[&0].transform.1 * frame1.basis;
+    let world2 = bp.modules[&1].transform.1 * frame2.basis;
+    assert!(world1.angle_between(world2) < 1e-5);
+    assert!((world1 * JOINT_FRAME_AXIS - Vec3::X).length() < 1e-5);
+}
+
+#[test]
+fn test_binary_round_trip_and_header_checks() {
+    let mut bp = two_boxes();
+    bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+        sensor_type: SensorType::Custom("whisker".into()),
+        local_position: Vec3::new(0.0, 0.5, 0.0),
+        local_rotation: Quat::from_rotation_z(0.3),
+        attachment: SensorAttachment::Module,
+        timing: Default::default(),
+    });
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 1.0,
+        effort: 2.0,
+        velocity: 3.0,
+        cone: None,
+    });
+
+    let bytes = bp.to_bytes();
+    assert_eq!(&bytes[..4], &BINARY_MAGIC);
+    assert_eq!(
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        BINARY_FORMAT_VERSION
+    );
+    let json = serde_json::to_vec(&bp).unwrap();
+    assert!(
+        bytes.len() * 3 < json.len(),
+        "{} vs {}",
+        bytes.len(),
+        json.len()
+    );
+    let restored = RobotBlueprint::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        serde_json::to_value(&restored).unwrap(),
+        serde_json::to_value(&bp).unwrap()
+    );
+
+    assert_eq!(
+        RobotBlueprint::from_bytes(&json).unwrap_err(),
+        BinaryError::BadMagic
+    );
+    assert_eq!(
+        RobotBlueprint::from_bytes(b"SRB").unwrap_err(),
+        BinaryError::BadMagic
+    );
+    let mut newer = bytes.clone();
+    newer[4..6].copy_from_slice(&(BINARY_FORMAT_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        RobotBlueprint::from_bytes(&newer).unwrap_err(),
+        BinaryError::UnsupportedVersion(BINARY_FORMAT_VERSION + 1)
+    );
+    assert!(matches!(
+        RobotBlueprint::from_bytes(&bytes[..bytes.len() / 2]),
+        Err(BinaryError::Decode(_))
+    ));
+}
+
+#[test]
+fn test_save_load_by_extension() {
+    let mut bp = two_boxes();
+    bp.add_marker(1, "LTOE", Vec3::new(0.0, -0.5, 0.1)).unwrap();
+    let expected = serde_json::to_value(&bp).unwrap();
+    let dir = std::env::temp_dir();
+    let pid = std::process::id();
+
+    for (ext, format) in [
+        ("json", BlueprintFormat::Json),
+        ("srbp", BlueprintFormat::Binary),
+        #[cfg(feature = "ron")]
+        ("RON", BlueprintFormat::Ron),
+    ] {
+        let path = dir.join(format!("symbios-robot-blueprint-{pid}.{ext}"));
+        assert_eq!(BlueprintFormat::from_path(&path), Some(format));
+        bp.save(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        let loaded = RobotBlueprint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), expected, "{ext}");
+
+        // Without a known extension the contents decide.
+        assert_eq!(BlueprintFormat::detect(&data), format);
+        let path = dir.join(format!("symbios-robot-blueprint-{pid}-{ext}.dat"));
+        std::fs::write(&path, &data).unwrap();
+        let detected = RobotBlueprint::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(serde_json::to_value(&detected.unwrap()).unwrap(), expected);
+    }
+
+    let unknown = dir.join(format!("symbios-robot-blueprint-{pid}.yaml"));
+    assert_eq!(
+        bp.save(&unknown).unwrap_err().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    assert!(!unknown.exists());
+    #[cfg(not(feature = "ron"))]
+    assert_eq!(
+        bp.encode(BlueprintFormat::Ron).unwrap_err().kind(),
+        std::io::ErrorKind::Unsupported
+    );
+    assert_eq!(
+        RobotBlueprint::decode(b"{ \"roots\": 3 }", BlueprintFormat::Json)
+            .unwrap_err()
+            .kind(),
+        std::io::ErrorKind::InvalidData
+    );
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn test_json_schema_covers_serialized_blueprint() {
+    let schema = RobotBlueprint::json_schema();
+    let properties = schema["properties"].as_object().unwrap();
+    let json = serde_json::to_value(two_boxes()).unwrap();
+    for key in json.as_object().unwrap().keys() {
+        assert!(
+            properties.contains_key(key),
+            "{key} missing from the schema"
+        );
+    }
+    for name in [
+        "RobotModule",
+        "JointDefinition",
+        "ShapePrimitive",
+        "SensorType",
+    ] {
+        assert!(
+            schema["$defs"].get(name).is_some(),
+            "{name} missing from $defs"
+        );
+    }
+    // Vectors are fixed-length arrays, as glam serializes them.
+    let anchor = &schema["$defs"]["JointDefinition"]["properties"]["anchor_parent"];
+    assert_eq!(anchor["type"], "array");
+    assert_eq!(anchor["minItems"], 3);
+    assert_eq!(anchor["maxItems"], 3);
+}
+
+#[cfg(feature = "avian")]
+#[test]
+fn test_avian_backend_spawns_bodies_and_joints() {
+    use avian3d::prelude::*;
+    use bevy::prelude::*;
+    use symbios_robot::backends::avian::spawn_blueprint;
+
+    let mut bp = two_boxes();
+    bp.add_world_joint(WorldJoint {
+        child_id: 0,
+        anchor_child: Vec3::ZERO,
+        anchor_world: Vec3::new(0.0, 0.5, 0.0),
+    });
+    let mut world = World::new();
+    let bodies = spawn_blueprint(&mut world.commands(), &bp);
+    world.flush();
+
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(world.get::<RigidBody>(bodies[&0]), Some(&RigidBody::Static));
+    assert_eq!(
+        world.get::<RigidBody>(bodies[&1]),
+        Some(&RigidBody::Dynamic)
+    );
+    assert_eq!(world.get::<Mass>(bodies[&1]).map(|m| m.0), Some(4.0));
+    let mut hinges = world.query::<(&RevoluteJoint, &JointCollisionDisabled)>();
+    assert_eq!(hinges.iter(&world).count(), 1);
+}
+
+#[cfg(feature = "rapier")]
+#[test]
+fn test_rapier_backend_uses_both_joint_axes() {
+    use rapier3d::prelude::*;
+    use symbios_robot::backends::rapier::build_blueprint;
+
+    // Turn the child a quarter about Z: the hinge axis is X for the parent, -Y for it.
+    let mut bp = two_boxes();
+    let turn = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    bp.modules.get_mut(&1).unwrap().transform.1 = turn;
+    bp.joints[0].child_axis = turn.inverse() * Vec3::X;
+
+    let mut bodies = RigidBodySet::new();
+    let mut colliders = ColliderSet::new();
+    let mut joints = ImpulseJointSet::new();
+    let handles = build_blueprint(&bp, &mut bodies, &mut colliders, &mut joints);
+    assert_eq!((handles.bodies.len(), handles.colliders.len()), (2, 2));
+    assert!(bodies[handles.bodies[&1]].is_dynamic());
+
+    let joint = &joints.get(handles.joints[&1]).unwrap().data;
+    assert_eq!(joint.locked_axes, JointAxesMask::LOCKED_REVOLUTE_AXES);
+    let axis2 = joint.local_axis2();
+    assert!((axis2.y + 1.0).abs() < 1e-5 && axis2.x.abs() < 1e-5);
+    assert!((joint.local_axis1().x - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_glb_nests_nodes_along_joints() {
+    let glb = two_boxes().to_glb();
+    assert_eq!(&glb[..4], b"glTF");
+    let word = |at: usize| u32::from_le_bytes(glb[at..at + 4].try_into().unwrap()) as usize;
+    assert_eq!(word(8), glb.len());
+    assert_eq!(word(16), 0x4E4F_534A); // JSON chunk
+    let json: serde_json::Value = serde_json::from_slice(&glb[20..20 + word(12)]).unwrap();
+
+    assert_eq!(json["scenes"][0]["nodes"], serde_json::json!([0]));
+    let nodes = json["nodes"].as_array().unwrap();
+    assert_eq!(nodes[0]["children"], serde_json::json!([1]));
+    // The child is posed relative to its parent.
+    assert_eq!(nodes[1]["translation"], serde_json::json!([0.0, 1.0, 0.0]));
+    assert_eq!(nodes[1]["extras"]["joint"]["joint_type"], "Hinge");
+    assert_eq!(json["accessors"].as_array().unwrap().len(), 4);
+    assert_eq!(
+        json["buffers"][0]["byteLength"],
+        glb.len() - 20 - word(12) - 8
+    );
+}
+
+#[test]
+fn test_glb_materials_match_their_indices() {
+    // Material 3 is met before material 1.
+    let mut bp = two_boxes();
+    bp.modules.get_mut(&0).unwrap().material_id = 3;
+    bp.modules.get_mut(&1).unwrap().material_id = 1;
+    let glb = bp.to_glb();
+    let len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    let json: serde_json::Value = serde_json::from_slice(&glb[20..20 + len]).unwrap();
+
+    let material_of = |mesh: usize| {
+        let index = json["meshes"][mesh]["primitives"][0]["material"]
+            .as_u64()
+            .unwrap() as usize;
+        json["materials"][index]["name"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(material_of(0), "material_3");
+    assert_eq!(material_of(1), "material_1");
+}
+
+#[test]
+fn test_control_interface_names_observations_and_actions() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 1.0,
+        effort: 5.0,
+        velocity: 2.0,
+        cone: None,
+    });
+    for sensor_type in [SensorType::IMU, SensorType::Camera] {
+        bp.modules.get_mut(&1).unwrap().sensors.push(SensorMount {
+            sensor_type,
+            local_position: Vec3::ZERO,
+            local_rotation: Quat::IDENTITY,
+            attachment: SensorAttachment::Module,
+            timing: Default::default(),
+        });
+    }
+    bp.modules.get_mut(&1).unwrap().sensors[0].timing = SensorTiming {
+        update_rate: Some(100.0),
+        latency: 0.01,
+        noise: 0.05,
+    };
+    let interface = bp.control_interface();
+    let names: Vec<&str> = interface
+        .observations
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "joint_0_1.position",
+            "joint_0_1.velocity",
+            "imu_1_0.orientation",
+            "imu_1_0.angular_velocity",
+            "imu_1_0.linear_acceleration",
+            "camera_1_1",
+        ]
+    );
+    assert_eq!(interface.observations[0].bounds, Some((-1.0, 1.0)));
+    assert_eq!(interface.observations[0].update_rate, None);
+    assert_eq!(interface.observations[3].update_rate, Some(100.0));
+    assert_eq!(interface.observations[3].latency, 0.01);
+    assert_eq!(interface.observations[3].noise, 0.05);
+    assert_eq!(interface.observations[5].noise, 0.0);
+    assert_eq!(interface.observations[4].offset, Some(9));
+    // The camera has no fixed size, so it stays out of the flat vector.
+    assert_eq!(interface.observations[5].offset, None);
+    assert_eq!(interface.observation_size, 12);
+    assert_eq!(interface.action_size, 1);
+    assert_eq!(interface.actions[0].bounds, Some((-5.0, 5.0)));
+
+    let json: serde_json::Value = serde_json::from_str(&interface.to_json()).unwrap();
+    assert_eq!(json["actions"][0]["name"], "joint_0_1.effort");
+    let python = interface.python_stub();
+    assert!(python.contains("OBSERVATION_SIZE = 12"));
+    assert!(python.contains("\"imu_1_0.angular_velocity\": (6, 3),"));
+    assert!(python.contains("\"camera_1_1\": None,"));
+    let rust = interface.rust_stub();
+    assert!(rust.contains("pub const ACTION_SIZE: usize = 1;"));
+    assert!(rust.contains("(\"joint_0_1.effort\", 0, 1),"));
+
+    let spec = interface.policy_spec();
+    assert_eq!(spec.observation.shape, [-1, 12]);
+    assert_eq!(spec.observation.names.len(), 12);
+    assert_eq!(spec.observation.names[0], "joint_0_1.position");
+    assert_eq!(spec.observation.names[4], "imu_1_0.orientation[2]");
+    assert_eq!(spec.observation.low[0], Some(-1.0));
+    assert_eq!(spec.observation.high[2], None);
+    assert_eq!(spec.action.names, ["joint_0_1.effort"]);
+    assert_eq!(spec.action.high, [Some(5.0)]);
+    assert_eq!(spec.extra_observations.len(), 1);
+    assert_eq!(spec.extra_observations[0].name, "camera_1_1");
+    assert_eq!(spec.extra_observations[0].shape, [-1, -1]);
+    let json: serde_json::Value = serde_json::from_str(&spec.to_json()).unwrap();
+    assert_eq!(json["action"]["dtype"], "float32");
+    assert!(json["observation"]["low"][2].is_null());
+}
+
+#[test]
+fn test_to_dot_draws_modules_and_joints() {
+    let mut bp = two_boxes();
+    bp.joints[0].limits = Some(JointLimit {
+        min: -1.0,
+        max: 0.5,
+        effort: 5.0,
+        velocity: 2.0,
+        cone: None,
+    });
+    let dot = bp.to_dot();
+    assert!(dot.starts_with("digraph robot {"));
+    assert!(dot.contains("m0 [label=\"#0\\nBox(0.20 x 1.00 x 0.20)\\n4.00 kg\", peripheries=2];"));
+    assert!(dot.contains("m1 [label=\"#1\\nBox(0.20 x 1.00 x 0.20)\\n4.00 kg\"];"));
+    assert!(dot.contains("m0 -> m1 [label=\"Hinge\\n[-1.00, 0.50]\"];"));
+    assert!(!dot.contains("world"));
+}
+
+#[test]
+fn test_to_svg_projects_outlines_and_joints() {
+    let bp = two_boxes();
+    let front = bp.to_svg(ProjectionPlane::XY, 64);
+    assert!(front.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"64\""));
+    assert_eq!(front.matches("<polygon").count(), 2);
+    // Each box projects to a rectangle.
+    let first = front.lines().find(|l| l.contains("<polygon")).unwrap();
+    let points = first.split('"').nth(1).unwrap();
+    assert_eq!(points.split(' ').count(), 4);
+    // One hinge marker, at the top of module 0 (screen Y points down).
+    assert_eq!(front.matches("<circle").count(), 1);
+    assert!(front.contains("cx=\"0.0000\" cy=\"-1.0000\""));
+
+    // From the top, both boxes overlap in the same square.
+    let top = bp.to_svg(ProjectionPlane::XZ, 64);
+    assert!(top.contains("viewBox=\"-0.1100 -0.1100 0.2200 0.2200\""));
+}
+
+#[test]
+fn test_instancing_batches_group_by_shape() {
+    let mut bp = two_boxes();
+    bp.add_module(
+        2,
+        RobotModule::new(SHAPES[2], 100.0, (Vec3::Y * 3.0, Quat::IDENTITY)),
+    );
+
+    let batches = bp.instancing_batches();
+    assert_eq!(batches.len(), 2);
+    let boxes = &batches[0];
+    assert_eq!(boxes.kind, ShapeKind::Box);
+    assert_eq!(boxes.modules, [0, 1]);
+    assert_eq!(boxes.scales[0], Vec3::new(0.2, 1.0, 0.2));
+    assert_eq!(batches[1].kind, ShapeKind::Sphere);
+    assert_eq!(batches[1].scales[0], Vec3::splat(0.6));
+
+    // The model matrix maps the unit cube's corner onto the module's corner.
+    let corner = boxes.model_matrices()[1].transform_point3(Vec3::splat(0.5));
+    assert!(corner.abs_diff_eq(Vec3::new(0.1, 2.0, 0.1), 1e-6));
+
+    let population = population_batches(&[bp.clone(), bp]);
+    assert_eq!(population[0].robots, [0, 0, 1, 1]);
+    assert_eq!(population[1].len(), 2);
+}
+
+#[test]
+fn test_contact_table_exports_to_sdf_and_mjcf() {
+    let mut bp = two_boxes();
+    // A loose rubber pad beside the arm, plus a rubber upper segment.
+    let mut pad = RobotModule::new(
+        ShapePrimitive::Sphere(0.1),
+        100.0,
+        (Vec3::new(1.0, 0.1, 0.0), Quat::IDENTITY),
+    );
+    pad.material_id = 2;
+    bp.add_module(2, pad.clone());
+    bp.add_root(2);
+    // A second pad far out of reach of the arm.
+    pad.transform.0 = Vec3::new(10.0, 0.0, 0.0);
+    pad.update_bounds();
+    bp.add_module(3, pad);
+    bp.add_root(3);
+    bp.modules.get_mut(&1).unwrap().material_id = 2;
+    let rubber = ContactSurface::Material(2);
+    bp.contacts.set(ContactPair {
+        stiffness: Some(5000.0),
+        damping: Some(20.0),
+        ..ContactPair::new(rubber, ContactSurface::Ground, 1.1, 0.0)
+    });
+    bp.contacts.set(ContactPair::new(
+        ContactSurface::Material(0),
+        rubber,
+        0.6,
+        0.5,
+    ));
+
+    // The rubber collisions take the rubber-ground entry; plastic has none.
+    let sdf = bp.to_sdf("robot");
+    assert_eq!(sdf.matches("<surface>").count(), 3);
+    assert_eq!(
+        sdf.matches("<mu>1.100000</mu><mu2>1.100000</mu2>").count(),
+        3
+    );
+    assert_eq!(
+        sdf.matches("<kp>5000.000000</kp><kd>20.000000</kd>")
+            .count(),
+        3
+    );
+
+    // Modules 0 and 1 are jointed and the far pad is out of reach, so only the plastic
+    // base and the near pad make a pair, and only their geoms leave the default collisions.
+    let mjcf = bp.to_mjcf("robot");
+    assert_eq!(mjcf.matches("<pair ").count(), 1);
+    assert!(mjcf.contains(
+        "<pair geom1=\"module_0\" geom2=\"module_2\" friction=\"0.600000 0.600000 0.005 0.0001 0.0001\" solref=\"0.02 0.215"
+    ));
+    assert_eq!(
+        mjcf.matches("friction=\"1.100000 0.005 0.0001\" solref=\"-5000.000000 -20.000000\"")
+            .count(),
+        3
+    );
+    let geom = |id: ModuleId| {
+        mjcf.lines()
+            .find(|l| l.contains(&format!("<geom name=\"module_{id}\"")))
+            .unwrap()
+            .to_owned()
+    };
+    assert!(geom(0).contains("contype=\"0\" conaffinity=\"1\""));
+    assert!(geom(2).contains("contype=\"0\" conaffinity=\"1\""));
+    assert!(!geom(1).contains("contype") && !geom(3).contains("contype"));
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn test_ron_fixture() {
+    let bp = RobotBlueprint::from_ron(
+        r#"(
+            roots: [0],
+            modules: {
+                0: (
+                    shape: Box((0.1, 0.5, 0.1)),
+                    mass: 1.0,
+                    density: 100.0,
+                    material_id: 0,
+                    sensors: [],
+                    transform: ((0.0, 0.5, 0.0), (0.0, 0.0, 0.0, 1.0)),
+                ),
+                1: (
+                    shape: Sphere(0.2),
+                    mass: 0.5,
+                    density: 100.0,
+                    material_id: 1,
+                    sensors: [],
+                    transform: ((0.0, 1.2, 0.0), (0.0, 0.0, 0.0, 1.0)),
+                ),
+            },
+            joints: [(
+                parent_id: 0,
+                child_id: 1,
+                anchor_parent: (0.0, 0.5, 0.0),
+                anchor_child: (0.0, -0.2, 0.0),
+                joint_type: Hinge,
+                axis: (1.0, 0.0, 0.0),
+                limits: (min: -1.0, max: 1.0, effort: 2.0, velocity: 3.0),
+            )],
+        )"#,
+    )
+    .unwrap();
+    assert_eq!(bp.modules.len(), 2);
+    assert_eq!(bp.modules[&1].shape, ShapePrimitive::Sphere(0.2));
+    assert_eq!(bp.joints[0].limits.unwrap().max, 1.0);
+
+    let text = bp.to_ron();
+    assert!(text.contains("Hinge"));
+    let restored = RobotBlueprint::from_ron(&text).unwrap();
+    assert_eq!(
+        serde_json::to_value(&restored).unwrap(),
+        serde_json::to_value(&bp).unwrap()
+    );
+}
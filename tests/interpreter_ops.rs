@@ -0,0 +1,1524 @@
+// tests/interpreter_ops.rs
+use glam::{Quat, Vec3};
+use symbios::{SymbiosState, SymbolTable};
+use symbios_robot::{
+    BomCategory, BuildWarning, CapsuleLength, ComTarget, ContactPair, ContactSurface, CostCatalog,
+    CustomOpContext, Extent, InterpretProblem, InterpreterSetup, JointType, Lineage,
+    MaterialPalette, RobotConfig, RobotInterpreter, RobotOp, RobotTurtleState, SensorAttachment,
+    SensorType, SphereAdvance, TraceStep, TurtleCheckpoint, metrics,
+};
+
+const STANDARD: &[&str] = &[
+    "f", "+", "-", "&", "^", "\\", "/", "|", "R", "~", "Rs", "B", "C", "O", "K", "!", "'", "M",
+    "D", "Fa", "G", "J", "Jf", "Jb", "Jp", "Jx", "Jy", "Jz", "Jl", "Jt", "Js", "Ja", "Jd", "Jw",
+    "S", "Si", "St", "Sl", "Sd", "Sh", "Sr", "Sc", "Se", "Sf", "Mk", "[", "]", "{", "}",
+];
+
+fn setup() -> (RobotInterpreter, SymbolTable) {
+    let mut interner = SymbolTable::new();
+    for sym in STANDARD {
+        interner.intern(sym).unwrap();
+    }
+    let mut interpreter = RobotInterpreter::new(RobotConfig::default());
+    interpreter.populate_standard_symbols(&interner);
+    (interpreter, interner)
+}
+
+fn state(interner: &SymbolTable, symbols: &[(&str, &[f64])]) -> SymbiosState {
+    let mut state = SymbiosState::new();
+    for (sym, params) in symbols {
+        state
+            .push(interner.resolve_id(sym).unwrap(), 0.0, params)
+            .unwrap();
+    }
+    state
+}
+
+#[test]
+fn test_analyze_matches_full_build() {
+    let (interpreter, interner) = setup();
+    let state = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("S", &[]),
+            ("[", &[]),
+            ("J", &[]),
+            ("+", &[]),
+            ("C", &[0.5]),
+            ("[", &[]),
+            ("Jb", &[]),
+            ("O", &[]),
+            ("]", &[]),
+            ("]", &[]),
+            ("B", &[1.0]),
+        ],
+    );
+
+    let stats = interpreter.analyze(&state);
+    let blueprint = interpreter.build_blueprint(&state);
+
+    assert_eq!(stats.module_count, blueprint.modules.len());
+    assert_eq!(stats.joint_count(), blueprint.joints.len());
+    assert_eq!(stats.joints_by_type[&JointType::Hinge], 1);
+    assert_eq!(stats.joints_by_type[&JointType::Ball], 1);
+    assert_eq!(stats.sensor_count, 1);
+    assert_eq!(stats.max_stack_depth, 2);
+    assert_eq!(stats.max_chain_depth, 2);
+
+    let total: f32 = blueprint.modules.values().map(|m| m.mass).sum();
+    assert!((stats.estimated_mass - total).abs() < 1e-5);
+    let aabb = stats.aabb.unwrap();
+    let full = blueprint.aabb(glam::Quat::IDENTITY);
+    assert!((aabb.min - full.min).length() < 1e-5);
+    assert!((aabb.max - full.max).length() < 1e-5);
+}
+
+#[test]
+fn test_session_steps_one_symbol_at_a_time() {
+    let (interpreter, interner) = setup();
+    let state = state(&interner, &[("B", &[1.0]), ("J", &[]), ("B", &[2.0])]);
+
+    let mut session = interpreter.session(&state);
+    assert_eq!(session.step(), Some(RobotOp::SpawnBox));
+    assert_eq!(session.blueprint().modules.len(), 1);
+    assert_eq!(session.turtle().position, Vec3::new(0.0, 1.0, 0.0));
+
+    assert_eq!(
+        session.step(),
+        Some(RobotOp::SetJointType(JointType::Hinge))
+    );
+    assert_eq!(session.turtle().joint_config.joint_type, JointType::Hinge);
+    assert!(session.blueprint().joints.is_empty());
+
+    session.run_to_end();
+    assert!(session.is_finished());
+    assert_eq!(session.step(), None);
+    assert_eq!(session.turtle().position, Vec3::new(0.0, 3.0, 0.0));
+    assert_eq!(session.into_blueprint().joints.len(), 1);
+}
+
+#[test]
+fn test_compiled_program_matches_state_build() {
+    let (interpreter, interner) = setup();
+    let state = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("[", &[]),
+            ("+", &[30.0]),
+            ("C", &[]),
+            ("]", &[]),
+            ("K", &[]),
+        ],
+    );
+
+    let program = interpreter.compile(&state);
+    assert_eq!(program.len(), 6);
+    assert_eq!(program.get(2), Some((&RobotOp::Yaw(1.0), &[30.0][..])));
+
+    let direct = interpreter.build_blueprint(&state);
+    let compiled = interpreter.build_from_program(&program);
+    assert_eq!(direct.modules.len(), compiled.modules.len());
+    for (id, module) in &direct.modules {
+        assert_eq!(module.transform, compiled.modules[id].transform);
+    }
+
+    // A differently configured interpreter reuses the program without recompiling.
+    let wide = RobotInterpreter::new(RobotConfig {
+        default_width: 1.0,
+        ..Default::default()
+    });
+    assert_eq!(wide.build_from_program(&program).modules.len(), 3);
+}
+
+#[test]
+fn test_bare_prismatic_limits_take_next_segment_length() {
+    let (interpreter, mut interner) = setup();
+
+    // Jp Jl B(0.5): the unmapped "A" between them must not break the lookahead.
+    interner.intern("A").unwrap();
+    let state = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Jp", &[]),
+            ("Jl", &[]),
+            ("A", &[]),
+            ("B", &[0.5]),
+        ],
+    );
+
+    for bp in [
+        interpreter.build_blueprint(&state),
+        interpreter.build_from_program(&interpreter.compile(&state)),
+    ] {
+        let limits = bp.joints[0].limits.unwrap();
+        assert_eq!(bp.joints[0].joint_type, JointType::Prismatic);
+        assert_eq!((limits.min, limits.max), (0.0, 0.5));
+    }
+}
+
+#[test]
+fn test_transmission_is_attached_to_next_joint() {
+    let (interpreter, interner) = setup();
+    let state = state(
+        &interner,
+        &[
+            ("B", &[]),
+            ("Jt", &[50.0, 0.8, 0.01]),
+            ("B", &[]),
+            ("Jt", &[0.0]),
+            ("B", &[]),
+        ],
+    );
+    let bp = interpreter.build_blueprint(&state);
+
+    let t = bp.joints[0].transmission.unwrap();
+    assert_eq!((t.gear_ratio, t.efficiency, t.backlash), (50.0, 0.8, 0.01));
+    assert_eq!(t.joint_effort(2.0), 80.0);
+    assert!(bp.joints[1].transmission.is_none());
+}
+
+#[test]
+fn test_servo_preset_sets_limits_and_adds_mass() {
+    let (interpreter, interner) = setup();
+    let mg996r = symbios_robot::servo::preset_by_name("mg996r").unwrap();
+    let index = symbios_robot::servo::SERVO_PRESETS
+        .iter()
+        .position(|p| p.name == mg996r.name)
+        .unwrap() as f64;
+
+    let plain =
+        interpreter.build_blueprint(&state(&interner, &[("B", &[]), ("J", &[]), ("B", &[])]));
+    let servoed = interpreter.build_blueprint(&state(
+        &interner,
+        &[("B", &[]), ("J", &[]), ("Js", &[index]), ("B", &[])],
+    ));
+
+    let joint = &servoed.joints[0];
+    let limits = joint.limits.unwrap();
+    assert_eq!(limits.effort, mg996r.stall_torque);
+    assert_eq!(limits.velocity, mg996r.no_load_speed);
+    assert_eq!(
+        joint.actuator.as_ref().unwrap().model.as_deref(),
+        Some("MG996R")
+    );
+
+    let added = servoed.modules[&0].mass - plain.modules[&0].mass;
+    assert!((added - mg996r.mass).abs() < 1e-6);
+    assert_eq!(servoed.modules[&1].mass, plain.modules[&1].mass);
+}
+
+#[test]
+fn test_actuator_mass_shifts_parent_center_of_mass() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("J", &[]),
+            ("Ja", &[0.5, 0.04, 0.04, 0.04]),
+            ("B", &[1.0]),
+        ],
+    ));
+
+    let base = &bp.modules[&0];
+    let shape_mass = base.mass - 0.5;
+    // Point mass sits on the top face (anchor at y = +0.5).
+    let expected_y = 0.5 * 0.5 / base.mass;
+    assert!((base.center_of_mass.y - expected_y).abs() < 1e-6);
+    assert!(shape_mass > 0.0);
+    assert!(bp.joints[0].actuator.as_ref().unwrap().housing.is_some());
+}
+
+#[test]
+fn test_bom_counts_and_prices_parts() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Si", &[]),
+            ("[", &[]),
+            ("J", &[]),
+            ("Js", &[2.0]),
+            ("B", &[0.5]),
+            ("]", &[]),
+            ("[", &[]),
+            ("J", &[]),
+            ("Js", &[2.0]),
+            ("B", &[0.5]),
+            ("]", &[]),
+        ],
+    ));
+
+    let catalog = CostCatalog::new()
+        .with_item("MG996R", 10.0)
+        .with_category(BomCategory::Segment, 1.0);
+    let bom = bp.bom_with_costs(&catalog);
+
+    assert_eq!(bom.count(BomCategory::Segment), 3);
+    assert_eq!(bom.count(BomCategory::Actuator), 2);
+    assert_eq!(bom.count(BomCategory::Sensor), 1);
+    let legs = bom
+        .lines
+        .iter()
+        .find(|l| l.item == "Box(0.20 x 0.50 x 0.20)")
+        .unwrap();
+    assert_eq!(legs.quantity, 2);
+    assert_eq!(bom.total_cost(), 3.0 + 20.0);
+    assert_eq!(bom.unpriced().count(), 2); // hinge joints and the IMU
+}
+
+#[test]
+fn test_lineage_annotations() {
+    let (interpreter, interner) = setup();
+    let parent = state(&interner, &[("B", &[1.0])]);
+    let child = state(&interner, &[("B", &[1.5])]);
+    let parent_hash = symbios_robot::genotype_hash(&parent);
+    assert_eq!(parent_hash, symbios_robot::genotype_hash(&parent));
+    assert_ne!(parent_hash, symbios_robot::genotype_hash(&child));
+
+    let bp = interpreter.build_blueprint(&child);
+    assert!(bp.lineage().is_none());
+    assert_eq!(bp.generation(), None);
+
+    let bp = bp.with_lineage(Lineage {
+        parent_genotype_hash: Some(parent_hash),
+        generation: Some(12),
+        mutation_operator: Some("param_jitter".into()),
+    });
+    assert_eq!(bp.parent_genotype_hash(), Some(parent_hash));
+    assert_eq!(bp.generation(), Some(12));
+    assert_eq!(bp.mutation_operator(), Some("param_jitter"));
+}
+
+#[test]
+fn test_joint_mounted_sensors() {
+    let (interpreter, interner) = setup();
+    let symbols: &[(&str, &[f64])] = &[
+        ("B", &[1.0]),
+        ("Se", &[]), // root has no joint: ignored
+        ("J", &[]),
+        ("B", &[1.0]),
+        ("Se", &[]),
+        ("Sf", &[]),
+        ("Si", &[]),
+    ];
+    let s = state(&interner, symbols);
+    let bp = interpreter.build_blueprint(&s);
+    assert_eq!(interpreter.analyze(&s).sensor_count, 3);
+
+    assert!(bp.modules[&0].sensors.is_empty());
+    let sensors = &bp.modules[&1].sensors;
+    assert_eq!(sensors.len(), 3);
+    assert_eq!(sensors[0].sensor_type, SensorType::Encoder);
+    assert_eq!(sensors[0].attachment, SensorAttachment::Joint);
+    assert_eq!(sensors[2].attachment, SensorAttachment::Module);
+
+    // The encoder sits on the joint, at the top of the first segment.
+    let (pos, _) = bp.sensor_pose(1, &sensors[0]).unwrap();
+    assert!(pos.abs_diff_eq(Vec3::Y, 1e-5), "{pos}");
+    let (pos, _) = bp.sensor_pose(1, &sensors[2]).unwrap();
+    assert!(pos.abs_diff_eq(Vec3::Y * 2.0, 1e-5), "{pos}");
+}
+
+#[test]
+fn test_reroot_moves_joint_sensors_with_their_joint() {
+    let (interpreter, interner) = setup();
+    let mut bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("J", &[]),
+            ("B", &[1.0]),
+            ("Se", &[]),
+            ("J", &[]),
+            ("B", &[1.0]),
+            ("Se", &[]),
+        ],
+    ));
+    let before: Vec<Vec3> = [1, 2]
+        .iter()
+        .map(|&id| bp.sensor_pose(id, &bp.modules[&id].sensors[0]).unwrap().0)
+        .collect();
+
+    bp.reroot(2).unwrap();
+    assert!(bp.modules[&2].sensors.is_empty());
+    assert_eq!(bp.modules[&1].sensors.len(), 1);
+    assert_eq!(bp.modules[&0].sensors.len(), 1);
+    let (p1, _) = bp.sensor_pose(1, &bp.modules[&1].sensors[0]).unwrap();
+    let (p0, _) = bp.sensor_pose(0, &bp.modules[&0].sensors[0]).unwrap();
+    assert!(p1.abs_diff_eq(before[1], 1e-5), "{p1} vs {}", before[1]);
+    assert!(p0.abs_diff_eq(before[0], 1e-5), "{p0} vs {}", before[0]);
+}
+
+#[test]
+fn test_imu_at_center_of_mass() {
+    let (interpreter, interner) = setup();
+    let s = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("J", &[]),
+            ("B", &[0.5]),
+            ("Sc", &[]),
+            ("Sc", &[1.0]),
+        ],
+    );
+    assert_eq!(interpreter.analyze(&s).sensor_count, 2);
+    let bp = interpreter.build_blueprint(&s);
+
+    // Module scope: at the centre of module 1.
+    let imu = &bp.modules[&1].sensors[0];
+    assert_eq!(imu.sensor_type, SensorType::IMU);
+    assert!(imu.local_position.abs_diff_eq(Vec3::ZERO, 1e-5));
+
+    // Robot scope: the heavier base pulls the COM into module 0.
+    let com = bp.center_of_mass().unwrap();
+    assert!((com.y - 0.75).abs() < 1e-5, "{com}");
+    let imu = bp.modules[&0].sensors.last().unwrap();
+    let (pos, _) = bp.sensor_pose(0, imu).unwrap();
+    assert!(pos.abs_diff_eq(com, 1e-5));
+
+    let mut empty = symbios_robot::RobotBlueprint::new();
+    assert!(empty.mount_imu_at_com(ComTarget::Robot).is_err());
+}
+
+#[test]
+fn test_depth_thermal_and_custom_sensors() {
+    let (mut interpreter, mut interner) = setup();
+    let sonar = interner.get_or_intern("Sx").unwrap();
+    interpreter.set_op(
+        sonar,
+        RobotOp::MountSensor(SensorType::Custom("sonar_array".into())),
+    );
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[("B", &[1.0]), ("Sd", &[]), ("Sh", &[]), ("Sx", &[])],
+    ));
+
+    let sensors = &bp.modules[&0].sensors;
+    let SensorType::DepthCamera(depth) = &sensors[0].sensor_type else {
+        panic!("expected a depth camera, got {:?}", sensors[0].sensor_type);
+    };
+    assert_eq!(depth.resolution, [640, 480]);
+    assert!(matches!(sensors[1].sensor_type, SensorType::Thermal(_)));
+    assert_eq!(sensors[2].sensor_type.name(), "sonar_array");
+
+    let bom = bp.bom();
+    assert!(bom.lines.iter().any(|l| l.item == "DepthCamera"));
+    assert!(bom.lines.iter().any(|l| l.item == "sonar_array"));
+}
+
+#[test]
+fn test_sensor_timing() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Si", &[]),
+            ("Sr", &[30.0, 20.0, 0.1]),
+            ("S", &[]),
+            ("Sc", &[]),
+        ],
+    ));
+    let sensors = &bp.modules[&0].sensors;
+    assert_eq!(sensors[0].timing.update_rate, None);
+    assert_eq!(sensors[1].timing.update_rate, Some(30.0));
+    assert!((sensors[1].timing.latency - 0.02).abs() < 1e-6);
+    assert_eq!(sensors[0].timing.noise, 0.0);
+    assert_eq!(sensors[1].timing.noise, 0.1);
+    assert_eq!(sensors[2].timing, sensors[1].timing);
+
+    // Every step samples without a rate; at 30 Hz on a 240 Hz sim, every 8th step.
+    let dt = 1.0 / 240.0;
+    assert!((0..10).all(|s| sensors[0].timing.samples_on_step(s, dt)));
+    let ticks: Vec<u64> = (0..40)
+        .filter(|&s| sensors[1].timing.samples_on_step(s, dt))
+        .collect();
+    assert_eq!(ticks, vec![0, 8, 16, 24, 32]);
+    assert_eq!(sensors[1].timing.latency_steps(dt), 5);
+}
+
+#[test]
+fn test_actuator_latency_and_backlash() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Jd", &[15.0, 0.01]), // no actuator yet: ignored
+            ("J", &[]),
+            ("Js", &[2.0]),
+            ("Jd", &[15.0, 0.01]),
+            ("Jt", &[10.0, 0.9, 0.002]),
+            ("B", &[1.0]),
+        ],
+    ));
+    let joint = &bp.joints[0];
+    let actuator = joint.actuator.as_ref().unwrap();
+    assert!((actuator.latency.unwrap() - 0.015).abs() < 1e-6);
+    assert_eq!(actuator.backlash, Some(0.01));
+    assert!((joint.actuation_latency() - 0.015).abs() < 1e-6);
+    // 0.002 rad in the gearbox plus 0.01 rad at the servo horn reduced 10:1.
+    assert!((joint.effective_backlash() - 0.003).abs() < 1e-6);
+
+    let action = &bp.control_interface().actions[0];
+    assert_eq!(action.latency, joint.actuation_latency());
+    assert_eq!(action.backlash, joint.effective_backlash());
+}
+
+#[test]
+fn test_ball_joint_cone_limits() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Jb", &[]),
+            ("Jl", &[0.5, 0.25, 0.1, 20.0]),
+            ("B", &[1.0]),
+        ],
+    ));
+    let limits = bp.joints[0].limits.unwrap();
+    let cone = limits.cone.unwrap();
+    assert_eq!((cone.swing1, cone.swing2, cone.twist), (0.5, 0.25, 0.1));
+    assert_eq!((limits.min, limits.max, limits.effort), (-0.1, 0.1, 20.0));
+
+    assert!(cone.allows(0.3, 0.1, 0.05));
+    assert!(!cone.allows(0.45, 0.2, 0.0)); // outside the ellipse
+    assert!(!cone.allows(0.0, 0.0, 0.2)); // over-twisted
+
+    // Hinges keep plain min/max limits.
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("J", &[]),
+            ("Jl", &[-0.5, 0.25]),
+            ("B", &[1.0]),
+        ],
+    ));
+    let limits = bp.joints[0].limits.unwrap();
+    assert!(limits.cone.is_none());
+    assert_eq!((limits.min, limits.max), (-0.5, 0.25));
+}
+
+#[test]
+fn test_anchor_to_world() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("Jw", &[]), // no module yet: ignored
+            ("B", &[1.0]),
+            ("Jw", &[]),
+            ("Jw", &[]), // already welded
+            ("J", &[]),
+            ("B", &[1.0]),
+        ],
+    ));
+    assert_eq!(bp.world_joints.len(), 1);
+    let weld = bp.world_joints[0];
+    assert_eq!(weld.child_id, 0);
+    assert!(bp.is_welded(0));
+    assert!(!bp.is_welded(1));
+
+    let (pos, rot) = bp.modules[&0].transform;
+    assert!((pos + rot * weld.anchor_child - weld.anchor_world).length() < 1e-5);
+}
+
+#[test]
+fn test_detached_parts_become_roots() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("[", &[]),
+            ("B", &[1.0]),
+            ("B", &[1.0]),
+            ("]", &[]),
+            ("[", &[]),
+            ("B", &[0.5]), // no module on the turtle: a detached part
+            ("]", &[]),
+        ],
+    ));
+    assert_eq!(bp.roots, vec![0, 2]);
+    assert_eq!(bp.root_module(), Some(0));
+    assert_eq!(bp.tree_root(1), Some(0));
+    assert_eq!(bp.tree_root(2), Some(2));
+    assert_eq!(bp.tree_root(42), None);
+}
+
+#[test]
+fn test_set_joint_axis() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("J", &[]),
+            ("Jz", &[]),
+            ("B", &[1.0]),
+            ("Jx", &[0.0, 0.0, 0.0]), // zero-length: ignored
+            ("B", &[1.0]),
+            ("Jy", &[1.0, 1.0, 0.0]),
+            ("B", &[1.0]),
+        ],
+    ));
+    assert!((bp.joints[0].axis - Vec3::Z).length() < 1e-6);
+    assert!((bp.joints[1].axis - Vec3::Z).length() < 1e-6);
+    let diagonal = Vec3::new(1.0, 1.0, 0.0).normalize();
+    assert!((bp.joints[2].axis - diagonal).length() < 1e-6);
+}
+
+#[test]
+fn test_prismatic_rest_displacement() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Jp", &[0.3]),
+            ("Jy", &[]),
+            ("Jl", &[-0.5, 0.5]),
+            ("B", &[1.0]),
+            ("Jp", &[2.0]), // beyond the limits: clamped to 0.5
+            ("Jl", &[0.0, 0.5]),
+            ("B", &[1.0]),
+        ],
+    ));
+    assert!((bp.joints[0].rest_displacement - 0.3).abs() < 1e-6);
+    assert!((bp.joints[1].rest_displacement - 0.5).abs() < 1e-6);
+    // Each segment starts slid out along Y, and the next one grows from its tip.
+    assert!((bp.modules[&1].transform.0.y - 1.8).abs() < 1e-5);
+    assert!((bp.modules[&2].transform.0.y - 3.3).abs() < 1e-5);
+    // Anchors stay at the joint's zero position.
+    assert!((bp.joints[0].anchor_parent - Vec3::new(0.0, 0.5, 0.0)).length() < 1e-5);
+}
+
+#[test]
+fn test_joint_axis_in_both_frames() {
+    let (interpreter, interner) = setup();
+    let mut bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[("B", &[1.0]), ("J", &[]), ("+", &[90.0]), ("B", &[1.0])],
+    ));
+    // The child is yawed a quarter turn, so its X axis is the parent's Y axis.
+    let joint = &bp.joints[0];
+    assert!((joint.axis - Vec3::Y).length() < 1e-5);
+    assert!((joint.child_axis - Vec3::X).length() < 1e-5);
+    let parent_rot = bp.modules[&0].transform.1;
+    let child_rot = bp.modules[&1].transform.1;
+    assert!((parent_rot * joint.axis - child_rot * joint.child_axis).length() < 1e-5);
+
+    // Rerooting swaps the roles of the two frames.
+    bp.reroot(1).unwrap();
+    assert!((bp.joints[0].axis - Vec3::X).length() < 1e-5);
+    assert!((bp.joints[0].child_axis - Vec3::Y).length() < 1e-5);
+}
+
+#[test]
+fn test_config_scope_restores_style_not_position() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("{", &[]),
+            ("'", &[3.0]),
+            ("J", &[]),
+            ("B", &[1.0]),
+            ("}", &[]),
+            ("B", &[1.0]),
+        ],
+    ));
+    assert_eq!(bp.modules[&1].material_id, 3);
+    assert_eq!(bp.joints[0].joint_type, JointType::Hinge);
+    // The style is back to the defaults, but the segment still grows from module 1.
+    assert_eq!(bp.modules[&2].material_id, 0);
+    assert_eq!(bp.joints[1].joint_type, JointType::Fixed);
+    assert_eq!(bp.joints[1].parent_id, 1);
+    assert!((bp.modules[&2].transform.0.y - 2.5).abs() < 1e-5);
+}
+
+#[test]
+fn test_material_theme_lasts_until_pop() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("[", &[]),
+            ("M", &[5.0]),
+            ("B", &[1.0]),
+            ("'", &[2.0]),
+            ("B", &[1.0]),
+            ("]", &[]),
+            ("B", &[1.0]),
+        ],
+    ));
+    assert_eq!(bp.modules[&0].material_id, 0);
+    // The theme overrides `'` inside the branch...
+    assert_eq!(bp.modules[&1].material_id, 5);
+    assert_eq!(bp.modules[&2].material_id, 5);
+    // ...and is gone after the pop.
+    assert_eq!(bp.modules[&3].material_id, 0);
+}
+
+#[test]
+fn test_turtle_orientation_is_renormalized() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        renormalize_interval: 1,
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let mut symbols: Vec<(&str, &[f64])> = Vec::new();
+    for _ in 0..5000 {
+        symbols.extend([("+", &[0.7][..]), ("&", &[1.3][..]), ("\\", &[0.3][..])]);
+    }
+    symbols.push(("B", &[1.0]));
+    let bp = interpreter.build_blueprint(&state(&interner, &symbols));
+    let rotation = bp.modules[&0].transform.1;
+    assert!((rotation.length() - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_every_rotation_counts_toward_renormalization() {
+    let mut turtle = RobotTurtleState {
+        renormalize_interval: 3,
+        ..Default::default()
+    };
+    turtle.rotate_local_x(0.1);
+    turtle.rotate_local_z(0.1);
+    assert_eq!(turtle.rotations_since_normalize, 2);
+    // An absolute rotation counts too, and triggers the renormalization.
+    turtle.set_rotation(Quat::from_rotation_y(0.3) * 1.01);
+    assert_eq!(turtle.rotations_since_normalize, 0);
+    assert!((turtle.rotation.length() - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_auto_hinge_axis_follows_bend() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        auto_hinge_axis: true,
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("J", &[]),
+            ("+", &[]),
+            ("B", &[1.0]), // bent in the XY plane: axis along Z
+            ("B", &[1.0]), // collinear: configured X axis
+        ],
+    ));
+    assert!((bp.joints[0].axis.dot(Vec3::Z).abs() - 1.0).abs() < 1e-5);
+    assert!((bp.joints[1].axis - Vec3::X).length() < 1e-5);
+}
+
+#[test]
+fn test_radial_branch_spreads_limbs() {
+    let (interpreter, interner) = setup();
+    const LIMB: [(&str, &[f64]); 4] = [("[", &[]), ("&", &[90.0]), ("B", &[1.0]), ("]", &[])];
+    let mut symbols: Vec<(&str, &[f64])> = vec![("B", &[1.0][..]), ("R", &[4.0][..])];
+    for _ in 0..4 {
+        symbols.extend(LIMB);
+    }
+    symbols.push(("B", &[1.0])); // the trunk carries on unrotated
+    let bp = interpreter.build_blueprint(&state(&interner, &symbols));
+
+    let top = Vec3::new(0.0, 1.0, 0.0);
+    let tips: Vec<Vec3> = (1..=4)
+        .map(|id| (bp.modules[&id].transform.0 - top).normalize())
+        .collect();
+    for (i, tip) in tips.iter().enumerate() {
+        assert!(tip.y.abs() < 1e-5);
+        let next = tips[(i + 1) % 4];
+        assert!(tip.dot(next).abs() < 1e-5); // a quarter turn apart
+    }
+    assert!((bp.modules[&5].transform.0 - Vec3::new(0.0, 1.5, 0.0)).length() < 1e-5);
+}
+
+#[test]
+fn test_align_to_surface_normal() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        align_to_surface_normal: true,
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("f", &[-0.5]),
+            ("+", &[90.0]),
+            ("f", &[0.1]), // now on a side face, pointing outwards
+            ("&", &[30.0]),
+            ("B", &[0.5]),
+            ("B", &[0.5]), // off the end cap: the turtle's heading is kept
+        ],
+    ));
+    let side = Vec3::new(bp.modules[&1].transform.0.x.signum(), 0.0, 0.0);
+    let (pos, rot) = bp.modules[&1].transform;
+    assert!((rot * Vec3::Y - side).length() < 1e-5);
+    assert!((pos - Vec3::new(0.35 * side.x, 0.5, 0.0)).length() < 1e-5);
+    assert!((bp.modules[&2].transform.1 * Vec3::Y - side).length() < 1e-5);
+}
+
+#[test]
+fn test_max_extent_skips_modules() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        max_extent: Some(Extent::Sphere(2.5)),
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let symbols: &[(&str, &[f64])] = &[
+        ("B", &[1.0]),
+        ("B", &[1.0]),
+        ("B", &[1.0]), // would reach y = 3
+        ("[", &[]),
+        ("+", &[90.0]),
+        ("B", &[0.4]), // sideways from the top of module 1: still inside
+        ("]", &[]),
+    ];
+    let bp = interpreter.build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.modules.len(), 3);
+    assert_eq!(bp.joints[1].parent_id, 1);
+    assert!(matches!(
+        bp.metadata.warnings.as_slice(),
+        [BuildWarning::ModuleOutOfBounds { center }] if (center.y - 2.5).abs() < 1e-5
+    ));
+    let stats = interpreter.analyze(&state(&interner, symbols));
+    assert_eq!((stats.module_count, stats.skipped_modules), (3, 1));
+
+    let mut boxed = RobotInterpreter::new(RobotConfig {
+        max_extent: Some(Extent::Box(Vec3::new(1.0, 10.0, 1.0))),
+        ..Default::default()
+    });
+    boxed.populate_standard_symbols(&interner);
+    let bp = boxed.build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.modules.len(), 4);
+    assert!(bp.metadata.warnings.is_empty());
+}
+
+#[test]
+fn test_stack_overflow_and_underflow_are_counted() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        max_stack_depth: 1,
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let symbols: &[(&str, &[f64])] = &[
+        ("]", &[]),
+        ("[", &[]),
+        ("[", &[]), // dropped: the stack is full
+        ("B", &[1.0]),
+        ("]", &[]),
+        ("]", &[]),
+        ("]", &[]),
+    ];
+    let bp = interpreter.build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.metadata.dropped_pushes, 1);
+    // The pop meant for the dropped push empties the stack early, so the last two pops
+    // are unmatched as well as the leading one.
+    assert_eq!(bp.metadata.unmatched_pops, 3);
+    let stats = interpreter.analyze(&state(&interner, symbols));
+    assert_eq!((stats.dropped_pushes, stats.unmatched_pops), (1, 3));
+
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(&interner, &[("[", &[]), ("]", &[])]));
+    assert_eq!(
+        (bp.metadata.dropped_pushes, bp.metadata.unmatched_pops),
+        (0, 0)
+    );
+}
+
+#[test]
+fn test_dof_budget_fixes_excess_joints() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        max_dof: Some(2),
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let symbols: &[(&str, &[f64])] = &[
+        ("B", &[1.0]),
+        ("J", &[]),
+        ("B", &[1.0]),
+        ("Jb", &[]),
+        ("B", &[1.0]), // a ball joint would bring the total to 4
+        ("J", &[]),
+        ("B", &[1.0]), // one hinge still fits
+    ];
+    let bp = interpreter.build_blueprint(&state(&interner, symbols));
+    let types: Vec<JointType> = bp.joints.iter().map(|j| j.joint_type).collect();
+    assert_eq!(
+        types,
+        [JointType::Hinge, JointType::Fixed, JointType::Hinge]
+    );
+    assert_eq!(metrics::dof_count(&bp), 2);
+    assert_eq!(
+        bp.metadata.warnings,
+        [BuildWarning::DofLimitReached { child_id: 2 }]
+    );
+    let stats = interpreter.analyze(&state(&interner, symbols));
+    assert_eq!(stats.joints_by_type.get(&JointType::Fixed), Some(&1));
+
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(&interner, symbols));
+    assert_eq!(metrics::dof_count(&bp), 5);
+}
+
+#[test]
+fn test_density_from_material_palette() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        material_palette: Some(MaterialPalette::standard()),
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("'", &[1.0]),
+            ("B", &[1.0]),
+            ("D", &[500.0]), // explicit override beats the palette
+            ("B", &[1.0]),
+            ("D", &[]),
+            ("'", &[9.0]), // not in the palette
+            ("B", &[1.0]),
+        ],
+    ));
+    let densities: Vec<f32> = (0..4).map(|id| bp.modules[&id].density).collect();
+    assert_eq!(densities, vec![1240.0, 2700.0, 500.0, 100.0]);
+}
+
+#[test]
+fn test_target_density_scales_robot() {
+    let (_, interner) = setup();
+    let symbols: &[(&str, &[f64])] = &[
+        ("B", &[1.0]),
+        ("'", &[1.0]),
+        ("J", &[]),
+        ("Js", &[2.0]),
+        ("B", &[1.0]),
+    ];
+    let interpreter = |target| {
+        let mut interpreter = RobotInterpreter::new(RobotConfig {
+            material_palette: Some(MaterialPalette::standard()),
+            target_density: Some(target),
+            ..Default::default()
+        });
+        interpreter.populate_standard_symbols(&interner);
+        interpreter
+    };
+
+    let swimmer = interpreter(1000.0);
+    let bp = swimmer.build_blueprint(&state(&interner, symbols));
+    let mass: f32 = bp.modules.values().map(|m| m.mass).sum();
+    // Two 0.2 x 1.0 x 0.2 boxes.
+    assert!((mass / 0.08 - 1000.0).abs() < 0.1);
+    let scale = bp.metadata.density_scale.unwrap();
+    // Aluminum stays denser than plastic.
+    assert!((bp.modules[&0].density - 1240.0 * scale).abs() < 1e-2);
+    assert!((bp.modules[&1].density - 2700.0 * scale).abs() < 1e-2);
+    let stats = swimmer.analyze(&state(&interner, symbols));
+    assert!((stats.estimated_mass - mass).abs() < 1e-3);
+
+    // The servo alone outweighs the target: nothing is scaled.
+    let bp = interpreter(0.01).build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.metadata.density_scale, None);
+    assert_eq!(bp.modules[&1].density, 2700.0);
+}
+
+#[test]
+fn test_inertia_scaled_limits() {
+    let (plain, interner) = setup();
+    let mut scaled = RobotInterpreter::new(RobotConfig {
+        inertia_scaled_limits: true,
+        ..Default::default()
+    });
+    scaled.populate_standard_symbols(&interner);
+    let symbols: &[(&str, &[f64])] = &[
+        ("B", &[1.0]),
+        ("J", &[]),
+        ("Jl", &[-1.0, 1.0]),
+        ("B", &[1.0]),
+        ("B", &[1.0]),
+        ("Jl", &[-1.0, 1.0, 50.0]), // explicit effort is kept
+        ("B", &[1.0]),
+    ];
+    let bp = scaled.build_blueprint(&state(&interner, symbols));
+    // Three 4 kg boxes hang off the first joint, centered 1.5 m above it.
+    let load = bp.joint_load(0).unwrap();
+    assert!((load.mass - 12.0).abs() < 1e-4);
+    assert!((load.lever - 1.5).abs() < 1e-4);
+    let efforts: Vec<f32> = bp.joints.iter().map(|j| j.limits.unwrap().effort).collect();
+    assert!((efforts[0] - 2.0 * 12.0 * 9.81 * 1.5).abs() < 1e-2);
+    assert!((efforts[1] - 2.0 * 8.0 * 9.81 * 1.0).abs() < 1e-2);
+    assert_eq!(efforts[2], 50.0);
+    // The shorter limb moves faster.
+    let velocity = |i: usize| bp.joints[i].limits.unwrap().velocity;
+    assert!(velocity(1) > velocity(0));
+    assert_ne!(velocity(2), 10.0);
+
+    let bp = plain.build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.joints[0].limits.unwrap().effort, 100.0);
+}
+
+#[test]
+fn test_module_pivot_offset_matches_joint_anchor() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[("B", &[1.0]), ("J", &[]), ("O", &[0.3]), ("C", &[0.4, 0.1])],
+    ));
+    assert_eq!(bp.modules[&0].pivot_offset, Vec3::new(0.0, 0.5, 0.0));
+    assert_eq!(bp.modules[&1].pivot_offset, Vec3::new(0.0, 0.3, 0.0));
+    for joint in &bp.joints {
+        assert_eq!(
+            joint.anchor_child,
+            -bp.modules[&joint.child_id].pivot_offset
+        );
+    }
+    let (pos, rot) = bp.modules[&2].transform;
+    let pivot = pos - rot * bp.modules[&2].pivot_offset;
+    assert!((pivot - Vec3::new(0.0, 1.6, 0.0)).length() < 1e-5);
+}
+
+#[test]
+fn test_capsule_length_convention() {
+    let (_, interner) = setup();
+    let symbols: &[(&str, &[f64])] = &[("K", &[1.0, 0.1]), ("B", &[1.0])];
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        capsule_length: CapsuleLength::TipToTip,
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let bp = interpreter.build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.metadata.capsule_length, CapsuleLength::TipToTip);
+    let capsule = &bp.modules[&0];
+    assert!((capsule.shape.height() - 0.8).abs() < 1e-6);
+    assert!((capsule.shape.half_extents().y - 0.5).abs() < 1e-6);
+    assert!((capsule.transform.0.y - 0.5).abs() < 1e-6);
+    assert!((capsule.pivot_offset.y - 0.5).abs() < 1e-6);
+    // The box starts where the capsule's tip ends.
+    assert!((bp.modules[&1].transform.0.y - 1.5).abs() < 1e-6);
+    assert!((bp.joints[0].anchor_parent.y - 0.5).abs() < 1e-6);
+
+    // Under the default convention the length is the section and the caps overhang it.
+    let (interpreter, _) = setup();
+    let bp = interpreter.build_blueprint(&state(&interner, symbols));
+    assert_eq!(bp.metadata.capsule_length, CapsuleLength::Section);
+    assert!((bp.modules[&0].shape.height() - 1.0).abs() < 1e-6);
+    assert!((bp.modules[&1].transform.0.y - 1.5).abs() < 1e-6);
+
+    // A tip-to-tip length shorter than the diameter leaves no section.
+    let section = CapsuleLength::TipToTip.section(0.1, 0.1);
+    assert_eq!(section, 0.0);
+    assert!((CapsuleLength::TipToTip.length(section, 0.1) - 0.2).abs() < 1e-6);
+}
+
+#[test]
+fn test_sphere_advance_policy() {
+    let (_, interner) = setup();
+    let symbols: &[(&str, &[f64])] = &[("B", &[1.0]), ("O", &[0.2]), ("B", &[1.0])];
+    let build = |sphere_advance| {
+        let mut interpreter = RobotInterpreter::new(RobotConfig {
+            sphere_advance,
+            ..Default::default()
+        });
+        interpreter.populate_standard_symbols(&interner);
+        interpreter.build_blueprint(&state(&interner, symbols))
+    };
+
+    let beads = build(SphereAdvance::Diameter);
+    assert!((beads.modules[&1].transform.0.y - 1.2).abs() < 1e-6);
+    assert!((beads.modules[&2].transform.0.y - 1.9).abs() < 1e-6);
+
+    // The next segment is jointed at the sphere's centre.
+    let hub = build(SphereAdvance::Radius);
+    assert!((hub.modules[&1].transform.0.y - 1.2).abs() < 1e-6);
+    assert!((hub.modules[&2].transform.0.y - 1.7).abs() < 1e-6);
+    assert!(hub.joints[1].anchor_parent.length() < 1e-6);
+
+    // The sphere is centred on the parent's tip, and both joints sit at its centre.
+    let knuckle = build(SphereAdvance::Zero);
+    assert!((knuckle.modules[&1].transform.0.y - 1.0).abs() < 1e-6);
+    assert_eq!(knuckle.modules[&1].pivot_offset, Vec3::ZERO);
+    assert_eq!(knuckle.joints[0].anchor_child, Vec3::ZERO);
+    assert!(knuckle.joints[1].anchor_parent.length() < 1e-6);
+    assert!((knuckle.modules[&2].transform.0.y - 1.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_anisotropic_friction() {
+    let (interpreter, interner) = setup();
+    let mut bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("Fa", &[0.25]),
+            ("B", &[1.0]),
+            ("{", &[]),
+            ("Fa", &[0.0]),
+            ("B", &[1.0]),
+            ("}", &[]),
+            ("C", &[1.0, 0.1]),
+        ],
+    ));
+    let friction = bp.modules[&0].anisotropic_friction.unwrap();
+    assert_eq!((friction.direction, friction.ratio), (Vec3::Y, 0.25));
+    assert!(bp.modules[&1].anisotropic_friction.is_none());
+    assert!(bp.modules[&2].anisotropic_friction.is_some());
+    assert_eq!(friction.coefficients(0.8), (0.2, 0.8));
+    assert!((friction.isotropic(0.8) - 0.5).abs() < 1e-6);
+
+    bp.contacts.set(ContactPair::new(
+        ContactSurface::Material(0),
+        ContactSurface::Ground,
+        0.8,
+        0.0,
+    ));
+    let sdf = bp.to_sdf("snake");
+    assert_eq!(sdf.matches("<fdir1>").count(), 2);
+    assert!(sdf.contains("<mu>0.200000</mu><mu2>0.800000</mu2><fdir1>0.000000 1.000000 0.000000"));
+    // The cylinder's collision frame stands it along Z.
+    assert!(sdf.contains(" 1.000000</fdir1>"));
+    let mjcf = bp.to_mjcf("snake");
+    assert_eq!(mjcf.matches("friction=\"0.500000 ").count(), 2);
+}
+
+#[test]
+fn test_export_import_map_by_name() {
+    let (interpreter, interner) = setup();
+    let map = interpreter.export_map(&interner);
+    assert_eq!(map.len(), STANDARD.len());
+    assert!(map.contains(&("B".to_string(), RobotOp::SpawnBox)));
+
+    // Same symbols interned in a different order get different IDs.
+    let mut reordered = SymbolTable::new();
+    reordered.intern("X").unwrap();
+    for sym in STANDARD.iter().rev().filter(|&&s| s != "Jw") {
+        reordered.intern(sym).unwrap();
+    }
+    let mut restored = RobotInterpreter::new(RobotConfig::default());
+    assert_eq!(restored.import_map(&reordered, &map), ["Jw"]);
+
+    let symbols: &[(&str, &[f64])] = &[("B", &[1.0]), ("J", &[]), ("+", &[]), ("B", &[0.5])];
+    let expected = interpreter.build_blueprint(&state(&interner, symbols));
+    let actual = restored.build_blueprint(&state(&reordered, symbols));
+    assert_eq!(actual.modules.len(), 2);
+    assert_eq!(actual.joints[0].joint_type, expected.joints[0].joint_type);
+    assert_eq!(actual.modules[&1].transform, expected.modules[&1].transform);
+}
+
+#[test]
+fn test_interpreter_setup_save_and_load() {
+    let (_, interner) = setup();
+    let mut interpreter = RobotInterpreter::new(RobotConfig {
+        default_length: 0.5,
+        max_extent: Some(Extent::Sphere(3.0)),
+        material_palette: Some(MaterialPalette::standard()),
+        ..Default::default()
+    });
+    interpreter.populate_standard_symbols(&interner);
+    let setup = interpreter.setup(&interner);
+
+    let path =
+        std::env::temp_dir().join(format!("symbios-robot-setup-{}.json", std::process::id()));
+    setup.save(&path).unwrap();
+    let loaded = InterpreterSetup::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, setup);
+
+    let restored = RobotInterpreter::from_setup(&loaded, &interner);
+    let bp = restored.build_blueprint(&state(&interner, &[("B", &[]), ("Jy", &[]), ("B", &[])]));
+    assert_eq!(bp.modules.len(), 2);
+    assert!((bp.modules[&1].transform.0.y - 0.75).abs() < 1e-6);
+
+    // Missing fields fall back to their defaults.
+    let partial: RobotConfig = serde_json::from_str(r#"{ "default_width": 0.5 }"#).unwrap();
+    assert_eq!(partial.default_width, 0.5);
+    assert_eq!(
+        partial.default_length,
+        RobotConfig::default().default_length
+    );
+}
+
+#[test]
+fn test_layout_then_annotate() {
+    let (interpreter, interner) = setup();
+    let s = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("Sc", &[1.0]), // robot built so far: just module 0
+            ("J", &[]),
+            ("Ja", &[0.5]),
+            ("B", &[1.0]),
+        ],
+    );
+
+    let layout = interpreter.layout(&s);
+    let geometry = layout.blueprint();
+    assert_eq!(geometry.modules.len(), 2);
+    assert_eq!(geometry.joints.len(), 1);
+    assert!(geometry.modules.values().all(|m| m.mass == 0.0));
+    assert!(geometry.modules[&0].sensors.is_empty());
+
+    let bp = interpreter.annotate(layout);
+    let direct = interpreter.build_blueprint(&s);
+    for id in [0, 1] {
+        assert_eq!(bp.modules[&id].mass, direct.modules[&id].mass);
+        assert_eq!(
+            bp.modules[&id].center_of_mass,
+            direct.modules[&id].center_of_mass
+        );
+    }
+    assert!(bp.modules[&0].mass > bp.modules[&1].mass); // carries the actuator
+    let imu = &bp.modules[&0].sensors[0];
+    assert_eq!(imu.sensor_type, SensorType::IMU);
+    assert!(imu.local_position.abs_diff_eq(Vec3::ZERO, 1e-5));
+}
+
+#[test]
+fn test_derive_seed_varies_per_branch() {
+    let build = |seed: u64| {
+        let (_, interner) = setup();
+        let mut interpreter = RobotInterpreter::new(RobotConfig {
+            seed,
+            ..Default::default()
+        });
+        interpreter.populate_standard_symbols(&interner);
+        // The same limb rule twice: B [ Rs ~ B ] [ Rs ~ B ]
+        let limb: &[(&str, &[f64])] = &[
+            ("[", &[]),
+            ("Rs", &[]),
+            ("~", &[30.0]),
+            ("B", &[1.0]),
+            ("]", &[]),
+        ];
+        let mut symbols: Vec<(&str, &[f64])> = vec![("B", &[1.0])];
+        symbols.extend_from_slice(limb);
+        symbols.extend_from_slice(limb);
+        let bp = interpreter.build_blueprint(&state(&interner, &symbols));
+        [bp.modules[&1].transform.1, bp.modules[&2].transform.1]
+    };
+    let [a, b] = build(7);
+    assert!(a.angle_between(b) > 1e-3, "limbs should differ");
+    assert_eq!(build(7), [a, b], "reproducible");
+    assert_ne!(build(8), [a, b], "depends on the global seed");
+}
+
+#[test]
+fn test_growth_stage_tagging() {
+    let (interpreter, interner) = setup();
+    let bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("G", &[1.0]),
+            ("[", &[]),
+            ("B", &[0.5]),
+            ("]", &[]),
+            ("G", &[2.0]),
+            ("B", &[0.5]),
+        ],
+    ));
+    let stages: Vec<u32> = (0..3).map(|id| bp.modules[&id].growth_stage).collect();
+    assert_eq!(stages, [0, 1, 2]);
+
+    let early = bp.at_growth_stage(1);
+    assert_eq!(early.modules.len(), 2);
+    assert_eq!(early.joints.len(), 1);
+    assert_eq!(bp.at_growth_stage(0).modules.len(), 1);
+}
+
+#[test]
+fn test_build_sequence_matches_individual_builds() {
+    let (interpreter, interner) = setup();
+    let states = [
+        state(&interner, &[("B", &[1.0])]),
+        state(&interner, &[("B", &[1.0]), ("Jp", &[]), ("Jl", &[])]),
+        // `Jl` now looks ahead at a segment, so it must not come from the snapshot.
+        state(
+            &interner,
+            &[("B", &[1.0]), ("Jp", &[]), ("Jl", &[]), ("B", &[0.5])],
+        ),
+        state(&interner, &[("B", &[2.0])]),
+    ];
+    let sequence = interpreter.build_sequence(&states);
+    assert_eq!(sequence.len(), states.len());
+    for (bp, s) in sequence.iter().zip(&states) {
+        let direct = interpreter.build_blueprint(s);
+        assert_eq!(bp.modules.len(), direct.modules.len());
+        assert_eq!(
+            bp.joints
+                .iter()
+                .map(|j| j.limits.map(|l| (l.min, l.max)))
+                .collect::<Vec<_>>(),
+            direct
+                .joints
+                .iter()
+                .map(|j| j.limits.map(|l| (l.min, l.max)))
+                .collect::<Vec<_>>()
+        );
+        for (id, module) in &direct.modules {
+            assert_eq!(bp.modules[id].transform, module.transform);
+            assert_eq!(bp.modules[id].mass, module.mass);
+        }
+    }
+    assert_eq!(sequence[2].joints[0].limits.unwrap().max, 0.5);
+}
+
+#[test]
+fn test_markers() {
+    let (interpreter, interner) = setup();
+    let mut bp = interpreter.build_blueprint(&state(
+        &interner,
+        &[("B", &[1.0]), ("Mk", &[3.0]), ("B", &[1.0]), ("Mk", &[])],
+    ));
+    assert!(bp.add_marker(1, "LTOE", Vec3::new(0.1, -0.5, 0.0)).is_ok());
+    assert!(bp.add_marker(9, "RTOE", Vec3::ZERO).is_err());
+
+    let positions = bp.marker_positions();
+    let names: Vec<&str> = positions.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["marker3", "marker", "LTOE"]);
+    for ((_, actual), expected) in positions.iter().zip([
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Vec3::new(0.1, 1.0, 0.0),
+    ]) {
+        assert!(actual.distance(expected) < 1e-5, "{actual} != {expected}");
+    }
+
+    let mjcf = bp.to_mjcf("walker");
+    assert!(mjcf.contains("<site name=\"LTOE\""));
+    assert!(mjcf.contains("<framepos name=\"LTOE\" objtype=\"site\" objname=\"LTOE\"/>"));
+    let sdf = bp.to_sdf("walker");
+    assert!(sdf.contains("<frame name=\"marker3\" attached_to=\"module_0\">"));
+
+    // Merging the fixed joint keeps every marker where it was.
+    let merged = bp.merge_fixed_joints();
+    assert_eq!(merged.modules.len(), 1);
+    for ((name, before), (merged_name, after)) in positions.iter().zip(merged.marker_positions()) {
+        assert_eq!(*name, merged_name);
+        assert!(before.distance(after) < 1e-5, "{before} != {after}");
+    }
+}
+
+#[test]
+fn test_try_build_blueprint_reports_problems() {
+    let (interpreter, mut interner) = setup();
+    let clean = state(&interner, &[("B", &[]), ("[", &[]), ("B", &[]), ("]", &[])]);
+    let bp = interpreter.try_build_blueprint(&clean).unwrap();
+    assert_eq!(bp.modules.len(), 2);
+
+    let a = interner.intern("A").unwrap();
+    let broken = state(
+        &interner,
+        &[
+            ("B", &[0.0]),
+            ("A", &[]),
+            ("]", &[]),
+            ("O", &[f64::NAN]),
+            ("B", &[1.0]),
+        ],
+    );
+    let err = interpreter.try_build_blueprint(&broken).unwrap_err();
+    assert_eq!(err.problems.len(), 4);
+    assert!(matches!(
+        err.problems[0],
+        InterpretProblem::DegenerateShape { index: 0, .. }
+    ));
+    assert_eq!(
+        err.problems[1],
+        InterpretProblem::UnmappedSymbol {
+            index: 1,
+            symbol: a
+        }
+    );
+    assert_eq!(
+        err.problems[2],
+        InterpretProblem::StackUnderflow { index: 2 }
+    );
+    assert!(matches!(
+        err.problems[3],
+        InterpretProblem::DegenerateShape { index: 3, .. }
+    ));
+    assert_eq!(
+        err.to_string(),
+        format!("4 interpretation problem(s), first: {}", err.problems[0])
+    );
+
+    let shallow = RobotInterpreter::new(RobotConfig {
+        max_stack_depth: 0,
+        ..Default::default()
+    })
+    .with_map(vec![RobotOp::Push]);
+    let mut pushes = SymbiosState::new();
+    pushes.push(0, 0.0, &[]).unwrap();
+    assert_eq!(
+        shallow.try_build_blueprint(&pushes).unwrap_err().problems,
+        [InterpretProblem::DroppedPush { index: 0 }]
+    );
+}
+
+#[test]
+fn test_build_blueprint_traced() {
+    let (interpreter, mut interner) = setup();
+    interner.intern("A").unwrap();
+    let s = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("A", &[]),
+            ("J", &[]),
+            ("+", &[90.0]),
+            ("B", &[0.5]),
+        ],
+    );
+    let (bp, trace) = interpreter.build_blueprint_traced(&s);
+    assert_eq!(
+        serde_json::to_value(&bp).unwrap(),
+        serde_json::to_value(&interpreter.build_blueprint(&s)).unwrap()
+    );
+
+    // The unmapped `A` is skipped.
+    let indices: Vec<usize> = trace.iter().map(|step| step.index).collect();
+    assert_eq!(indices, [0, 2, 3, 4]);
+    let TraceStep {
+        op,
+        before,
+        after,
+        modules,
+        joints,
+        ..
+    } = &trace[0];
+    assert_eq!(*op, RobotOp::SpawnBox);
+    assert_eq!(modules, &[0]);
+    assert!(joints.is_empty());
+    assert!((after.position - before.position - Vec3::Y).length() < 1e-6);
+
+    let yaw = &trace[2];
+    assert_eq!(yaw.params, [90.0]);
+    assert!(yaw.modules.is_empty());
+    assert!(yaw.after.rotation.angle_between(yaw.before.rotation) > 1.5);
+
+    assert_eq!(trace[3].modules, [1]);
+    assert_eq!(trace[3].joints, [0]);
+    assert_eq!(bp.joints[0].joint_type, JointType::Hinge);
+}
+
+#[test]
+fn test_custom_op() {
+    let (mut interpreter, mut interner) = setup();
+    let gripper = interner.intern("Gr").unwrap();
+    interpreter.set_custom_op(
+        gripper,
+        "gripper",
+        |ctx: &mut CustomOpContext<'_>, params: &[f64]| {
+            let spread = params.first().copied().unwrap_or(30.0);
+            for side in [1.0, -1.0] {
+                ctx.run(&RobotOp::Push, &[]);
+                ctx.run(&RobotOp::Yaw(side), &[spread]);
+                ctx.run(&RobotOp::SpawnBox, &[0.2, 0.02, 0.02]);
+                ctx.run(&RobotOp::Pop, &[]);
+            }
+            assert_eq!(ctx.turtle().current_module_id, Some(0));
+        },
+    );
+    let s = state(&interner, &[("B", &[]), ("J", &[]), ("Gr", &[45.0])]);
+    let bp = interpreter.build_blueprint(&s);
+    assert_eq!(bp.modules.len(), 3);
+    assert_eq!(bp.joints.len(), 2);
+    assert!(
+        bp.joints
+            .iter()
+            .all(|j| j.parent_id == 0 && j.joint_type == JointType::Hinge)
+    );
+    assert_eq!(interpreter.analyze(&s).module_count, 3);
+
+    // The name is what gets saved; without a registered op the symbol does nothing.
+    let map = interpreter.export_map(&interner);
+    assert!(map.contains(&("Gr".to_string(), RobotOp::Custom("gripper".into()))));
+    let mut restored = RobotInterpreter::new(RobotConfig::default());
+    assert!(restored.import_map(&interner, &map).is_empty());
+    assert_eq!(restored.build_blueprint(&s).modules.len(), 1);
+}
+
+#[test]
+fn test_extend_blueprint() {
+    let (interpreter, interner) = setup();
+    let first: &[(&str, &[f64])] = &[("B", &[1.0]), ("J", &[]), ("B", &[0.5])];
+    let grown: &[(&str, &[f64])] = &[
+        ("B", &[1.0]),
+        ("J", &[]),
+        ("B", &[0.5]),
+        ("[", &[]),
+        ("+", &[]),
+        ("Jp", &[]),
+        ("Jl", &[]),
+        ("B", &[0.4]),
+        ("]", &[]),
+    ];
+    let rewritten: &[(&str, &[f64])] = &[("C", &[0.3]), ("J", &[]), ("O", &[])];
+
+    let mut bp = symbios_robot::RobotBlueprint::new();
+    let mut checkpoint = TurtleCheckpoint::default();
+    for (symbols, cursor) in [(first, 2), (grown, 8), (rewritten, 2)] {
+        let s = state(&interner, symbols);
+        (bp, checkpoint) = interpreter.extend_blueprint(&s, checkpoint);
+        assert_eq!(checkpoint.cursor(), cursor);
+        assert_eq!(
+            serde_json::to_value(&bp).unwrap(),
+            serde_json::to_value(interpreter.build_blueprint(&s)).unwrap()
+        );
+    }
+    assert_eq!(bp.modules.len(), 2);
+}
+
+#[test]
+fn test_build_blueprint_from_iter() {
+    let (interpreter, mut interner) = setup();
+    interner.intern("A").unwrap();
+    // `Jl` looks ahead past the unmapped `A` to the segment it limits.
+    let s = state(
+        &interner,
+        &[
+            ("B", &[1.0]),
+            ("[", &[]),
+            ("+", &[30.0]),
+            ("Jp", &[]),
+            ("Jl", &[]),
+            ("A", &[]),
+            ("B", &[0.5]),
+            ("]", &[]),
+            ("S", &[]),
+        ],
+    );
+    let streamed = interpreter.build_blueprint_from_iter((0..s.len()).map_while(|i| s.get_view(i)));
+    assert_eq!(
+        serde_json::to_value(&streamed).unwrap(),
+        serde_json::to_value(interpreter.build_blueprint(&s)).unwrap()
+    );
+    assert_eq!(streamed.modules.len(), 2);
+    assert!(
+        interpreter
+            .build_blueprint_from_iter(std::iter::empty())
+            .modules
+            .is_empty()
+    );
+}
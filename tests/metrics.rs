@@ -0,0 +1,120 @@
+// tests/metrics.rs
+use symbios::{SymbiosState, SymbolTable};
+use symbios_robot::metrics::{self, Feature, FeatureRange, FeatureSpec};
+use symbios_robot::{RobotConfig, RobotInterpreter, SampleMode};
+
+fn build(symbols: &[(&str, &[f64])]) -> symbios_robot::RobotBlueprint {
+    let mut interner = SymbolTable::new();
+    for (sym, _) in symbols {
+        interner.get_or_intern(sym).unwrap();
+    }
+    let mut interpreter = RobotInterpreter::new(RobotConfig::default());
+    interpreter.populate_standard_symbols(&interner);
+
+    let mut state = SymbiosState::new();
+    for (sym, params) in symbols {
+        state
+            .push(interner.resolve_id(sym).unwrap(), 0.0, params)
+            .unwrap();
+    }
+    interpreter.build_blueprint(&state)
+}
+
+#[test]
+fn test_feature_vector_counts_and_normalizes() {
+    // Torso with two hinged legs: B [ J B ] [ J B ]
+    let bp = build(&[
+        ("B", &[1.0, 0.2, 0.2]),
+        ("[", &[]),
+        ("J", &[]),
+        ("B", &[0.5]),
+        ("]", &[]),
+        ("[", &[]),
+        ("J", &[]),
+        ("B", &[0.5]),
+        ("]", &[]),
+    ]);
+
+    assert_eq!(metrics::limb_count(&bp), 2);
+    assert_eq!(metrics::actuator_count(&bp), 2);
+    assert_eq!(metrics::max_depth(&bp), 1);
+
+    let spec = FeatureSpec::new(vec![
+        FeatureRange::new(Feature::LimbCount, 0.0, 4.0),
+        FeatureRange::new(Feature::ModuleCount, 0.0, 2.0),
+    ]);
+    let fv = metrics::feature_vector(&bp, &spec);
+    assert_eq!(fv, vec![0.5, 1.0]);
+    assert_eq!(
+        metrics::feature_vector(&bp, &FeatureSpec::default()).len(),
+        4
+    );
+}
+
+#[test]
+fn test_edit_distance_identity_and_growth() {
+    let arm = build(&[("B", &[1.0]), ("J", &[]), ("B", &[1.0])]);
+    let longer = build(&[("B", &[1.0]), ("J", &[]), ("B", &[1.0]), ("B", &[1.0])]);
+
+    assert_eq!(metrics::edit_distance(&arm, &arm), 0.0);
+    // One extra module: a single insertion.
+    assert_eq!(metrics::edit_distance(&arm, &longer), 1.0);
+    assert_eq!(
+        metrics::edit_distance(&arm, &longer),
+        metrics::edit_distance(&longer, &arm)
+    );
+}
+
+#[test]
+fn test_shape_distance_is_zero_for_identical_poses() {
+    let a = build(&[("B", &[1.0]), ("+", &[]), ("C", &[0.5])]);
+    let b = build(&[("B", &[1.0]), ("+", &[]), ("C", &[0.5])]);
+    let c = build(&[("B", &[2.0])]);
+
+    let points = a.sample_points(200, SampleMode::Surface);
+    assert_eq!(points.len(), 200);
+    assert_eq!(a.sample_points(50, SampleMode::Volume).len(), 50);
+
+    assert_eq!(metrics::shape_distance(&a, &b, 200), 0.0);
+    assert!(metrics::shape_distance(&a, &c, 200) > 0.0);
+    assert!(metrics::hausdorff_distance(&points, &c.sample_points(200, SampleMode::Surface)) > 0.0);
+}
+
+#[test]
+fn test_sample_surface_tags_points_with_modules() {
+    let bp = build(&[("B", &[1.0]), ("O", &[0.5])]);
+    let cloud = bp.sample_surface(100);
+    assert_eq!(cloud.len(), 100);
+    assert_eq!(cloud.module_ids.len(), 100);
+
+    for (id, p) in cloud.iter() {
+        let (pos, rot) = bp.modules[&id].transform;
+        let local = rot.inverse() * (p - pos);
+        let grown = bp.modules[&id].shape.contains_local(local * 0.999);
+        assert!(grown, "point {p} is not on module {id}");
+    }
+    assert!(cloud.points_of(0).count() > 0);
+    assert!(cloud.points_of(1).count() > 0);
+}
+
+#[test]
+fn test_population_stats_summarizes_features() {
+    let population = [
+        build(&[("B", &[1.0])]),
+        build(&[("B", &[1.0]), ("B", &[1.0])]),
+        build(&[("B", &[1.0]), ("B", &[1.0]), ("B", &[1.0])]),
+    ];
+    let stats = metrics::PopulationStats::new(&population);
+    assert_eq!(stats.size, 3);
+
+    let modules = stats.get(Feature::ModuleCount).unwrap();
+    assert_eq!((modules.min, modules.median, modules.max), (1.0, 2.0, 3.0));
+    assert!((modules.mean - 2.0).abs() < 1e-6);
+    assert!((modules.std_dev - (2.0f32 / 3.0).sqrt()).abs() < 1e-6);
+    assert!(stats.get(Feature::TotalMass).unwrap().max > 0.0);
+    assert!(stats.get(Feature::SensorCount).is_none());
+
+    let empty = metrics::PopulationStats::new(&[]);
+    assert_eq!(empty.size, 0);
+    assert_eq!(empty.get(Feature::LimbCount).unwrap().mean, 0.0);
+}
@@ -0,0 +1,143 @@
+// tests/validation.rs
+use glam::{Quat, Vec3};
+use std::f32::consts::PI;
+use symbios_robot::{
+    AnchorSide, BlueprintError, ConeLimit, JointDefinition, JointLimit, JointType, LimitIssueKind,
+    LimitValidation, RAGDOLL_LIMIT_SLACK, RobotBlueprint, RobotModule, ShapePrimitive,
+    Transmission,
+};
+
+fn limits(min: f32, max: f32) -> Option<JointLimit> {
+    Some(JointLimit {
+        min,
+        max,
+        effort: 10.0,
+        velocity: 1.0,
+        cone: None,
+    })
+}
+
+/// A root box with one child per joint type, each given `±π` limits.
+fn robot() -> RobotBlueprint {
+    let shape = ShapePrimitive::Box(Vec3::new(0.1, 0.5, 0.1));
+    let mut bp = RobotBlueprint::new();
+    for id in 0..5 {
+        bp.add_module(
+            id,
+            RobotModule::new(shape, 100.0, (Vec3::ZERO, Quat::IDENTITY)),
+        );
+    }
+    for (child, joint_type) in [
+        (1, JointType::Fixed),
+        (2, JointType::Hinge),
+        (3, JointType::Prismatic),
+        (4, JointType::Ball),
+    ] {
+        bp.add_joint(JointDefinition {
+            parent_id: 0,
+            child_id: child,
+            anchor_parent: Vec3::ZERO,
+            anchor_child: Vec3::ZERO,
+            joint_type,
+            axis: Vec3::X,
+            child_axis: Vec3::X,
+            rest_displacement: 0.0,
+            limits: limits(-PI, PI),
+            transmission: None,
+            actuator: None,
+        });
+    }
+    bp
+}
+
+#[test]
+fn test_limit_issues_per_joint_type() {
+    let bp = robot();
+    let issues = bp.limit_issues();
+    let kinds: Vec<(usize, LimitIssueKind)> =
+        issues.iter().map(|i| (i.joint_index, i.kind)).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (0, LimitIssueKind::LimitsOnFixed),
+            (
+                2,
+                LimitIssueKind::TravelExceedsSegment {
+                    travel: PI,
+                    length: 1.0
+                }
+            ),
+            (3, LimitIssueKind::MissingCone),
+        ]
+    );
+}
+
+#[test]
+fn test_strict_rejects_and_repair_fixes() {
+    let mut bp = robot();
+    bp.joints[1].limits = limits(2.0, -4.0);
+
+    let err = bp.validate_limits(LimitValidation::Strict).unwrap_err();
+    assert!(matches!(err, BlueprintError::InvalidLimits(i) if i.joint_index == 0));
+    assert!(bp.joints[0].limits.is_some(), "strict mode must not modify");
+
+    let fixed = bp.validate_limits(LimitValidation::Repair).unwrap();
+    assert_eq!(fixed.len(), 5); // fixed, inverted + out-of-range hinge, prismatic, ball
+    assert!(bp.joints[0].limits.is_none());
+    let hinge = bp.joints[1].limits.unwrap();
+    assert_eq!((hinge.min, hinge.max), (-PI, 2.0));
+    let slider = bp.joints[2].limits.unwrap();
+    assert_eq!((slider.min, slider.max), (-1.0, 1.0));
+    assert_eq!(bp.joints[3].limits.unwrap().cone.unwrap().twist, PI);
+
+    assert!(bp.limit_issues().is_empty());
+    assert!(bp.validate_limits(LimitValidation::Strict).is_ok());
+}
+
+#[test]
+fn test_floating_anchors_are_reported_and_snapped() {
+    let mut bp = robot();
+    bp.joints[1].anchor_parent = Vec3::new(0.0, 2.0, 0.0);
+    bp.joints[2].anchor_child = Vec3::new(0.3, 0.0, 0.0);
+    bp.joints[3].anchor_child = Vec3::new(0.105, 0.0, 0.0); // within tolerance
+
+    let issues = bp.anchor_issues(0.01);
+    let found: Vec<(usize, AnchorSide)> = issues.iter().map(|i| (i.joint_index, i.side)).collect();
+    assert_eq!(found, vec![(1, AnchorSide::Parent), (2, AnchorSide::Child)]);
+    assert!((issues[0].distance - 1.5).abs() < 1e-6);
+
+    assert_eq!(bp.snap_anchors(0.01), issues);
+    assert_eq!(bp.joints[1].anchor_parent, Vec3::new(0.0, 0.5, 0.0));
+    assert_eq!(bp.joints[2].anchor_child, Vec3::new(0.1, 0.0, 0.0));
+    assert!(bp.anchor_issues(0.01).is_empty());
+}
+
+#[test]
+fn test_ragdoll_is_passive_with_wider_limits() {
+    let mut bp = robot();
+    bp.joints[1].transmission = Some(Transmission::default());
+    bp.joints[3].limits = Some(JointLimit {
+        cone: Some(ConeLimit {
+            swing1: 0.5,
+            swing2: 3.1,
+            twist: 0.2,
+        }),
+        ..limits(0.0, 0.0).unwrap()
+    });
+    let ragdoll = bp.ragdoll();
+    for (joint, original) in ragdoll.joints.iter().zip(&bp.joints) {
+        assert_eq!(joint.joint_type, original.joint_type);
+        assert!(joint.transmission.is_none() && joint.actuator.is_none());
+        let limits = joint.limits.unwrap();
+        assert_eq!(limits.effort, 0.0);
+        assert_eq!(
+            limits.min,
+            original.limits.unwrap().min - RAGDOLL_LIMIT_SLACK
+        );
+    }
+    let cone = ragdoll.joints[3].limits.unwrap().cone.unwrap();
+    assert!((cone.swing1 - 0.6).abs() < 1e-6 && (cone.twist - 0.3).abs() < 1e-6);
+    assert_eq!(cone.swing2, PI);
+    assert!(!ragdoll.to_mjcf("ragdoll").contains("<motor"));
+    assert!(bp.to_mjcf("robot").contains("<motor"));
+}